@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+
+/// How urgent an alert is. Ordered so filters can use "at least" comparisons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum AlertSeverity {
+    Info,
+    /// Pattern forming, e.g. a double-top early warning.
+    Warning,
+    /// Pattern confirmed, e.g. a neckline breakdown.
+    Critical,
+}
+
+/// Chart pattern an alert refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum PatternKind {
+    DoubleBottom,
+    DoubleTop,
+    HeadAndShoulders,
+    TripleTop,
+    TripleBottom,
+    AscendingTriangle,
+    DescendingTriangle,
+    CupAndHandle,
+    /// Close above a consolidation range.
+    RangeBreakoutUp,
+    /// Close below a consolidation range.
+    RangeBreakoutDown,
+    /// Close below a rising trendline through swing lows.
+    TrendlineBreakDown,
+    /// Close above a falling trendline through swing highs.
+    TrendlineBreakUp,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum AlertStage {
+    /// Price is approaching the level that would complete the pattern.
+    EarlyWarning,
+    /// Price broke the pattern's trigger level.
+    Confirmation,
+    /// After a confirmation, price rallied back to the broken level and was
+    /// rejected.
+    Retest,
+}
+
+/// What has to cross a pattern's break level to confirm it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ConfirmationMode {
+    /// The candle's close.
+    #[default]
+    Close,
+    /// Its low: a wick through the level is enough.
+    Low,
+}
+
+/// Trend on a higher timeframe, as alerts are checked against it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum TrendDirection {
+    Up,
+    Down,
+}
+
+/// Alert raised by a pattern detector.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct PatternAlert {
+    pub coin: String,
+    pub pattern: PatternKind,
+    pub stage: AlertStage,
+    /// Open time of the candle that raised the alert (epoch ms).
+    pub open_time: u64,
+    /// Close of that candle.
+    pub price: f64,
+    /// Level the alert is about: the one approached for an early warning,
+    /// the one broken for a confirmation.
+    pub level: f64,
+    /// Measured-move target of a confirmation: the broken level, less the
+    /// pattern's height. Absent for patterns that don't project one.
+    pub target_price: Option<f64>,
+    /// Height of the confirmed pattern as a % of its top.
+    pub pattern_height_pct: Option<f64>,
+    /// Highest high of the rally back to the broken level, on a retest.
+    pub retest_high: Option<f64>,
+    /// Whether a close or a wick triggered a confirmation, for detectors
+    /// that can be set to either.
+    pub confirmation_mode: Option<ConfirmationMode>,
+    /// Higher-timeframe trend the alert was checked against, for detectors
+    /// with a higher-timeframe filter.
+    pub htf_trend: Option<TrendDirection>,
+}
+
+impl PatternAlert {
+    pub fn severity(&self) -> AlertSeverity {
+        match self.stage {
+            AlertStage::EarlyWarning => AlertSeverity::Warning,
+            AlertStage::Confirmation | AlertStage::Retest => AlertSeverity::Critical,
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! Candles with abnormal structure: long wicks, outsized ranges and closes
+//! far outside the recent envelope (the typical stop-hunt signature).
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::business_logic::indicators::AtrCalculator;
+use crate::models::candle::Candle;
+
+/// Bodies are floored at this fraction of the candle range, so a doji gets a
+/// large but finite wick-to-body ratio.
+const MIN_BODY_FRACTION: f64 = 0.05;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct AnomalyConfig {
+    pub atr_period: usize,
+    /// Longest wick over body at or above which a candle is flagged.
+    pub wick_body_ratio: f64,
+    /// Wick ratios only count on candles whose range is at least this many ATRs,
+    /// so quiet candles with a tick of wick aren't flagged.
+    pub min_wick_range_atr: f64,
+    /// Range (high - low) in ATRs at or above which a candle is flagged.
+    pub range_atr: f64,
+    /// Prior candles forming the high/low envelope.
+    pub envelope_candles: usize,
+    /// How far beyond the envelope (in ATRs) a close must land to be flagged.
+    pub envelope_margin_atr: f64,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            atr_period: 14,
+            wick_body_ratio: 3.0,
+            min_wick_range_atr: 1.0,
+            range_atr: 3.0,
+            envelope_candles: 20,
+            envelope_margin_atr: 1.0,
+        }
+    }
+}
+
+impl AnomalyConfig {
+    /// Problems that would make the config unusable, one message each.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let mut check = |ok: bool, message: &str| {
+            if !ok {
+                errors.push(message.to_string());
+            }
+        };
+        let positive = |v: f64| v > 0.0 && v.is_finite();
+
+        check(self.atr_period > 0, "atr_period must be positive");
+        check(
+            positive(self.wick_body_ratio),
+            "wick_body_ratio must be positive",
+        );
+        check(
+            self.min_wick_range_atr >= 0.0 && self.min_wick_range_atr.is_finite(),
+            "min_wick_range_atr must not be negative",
+        );
+        check(positive(self.range_atr), "range_atr must be positive");
+        check(
+            self.envelope_candles > 0,
+            "envelope_candles must be positive",
+        );
+        check(
+            self.envelope_margin_atr >= 0.0 && self.envelope_margin_atr.is_finite(),
+            "envelope_margin_atr must not be negative",
+        );
+        errors
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyKind {
+    LongWick,
+    WideRange,
+    OutsideEnvelope,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct CandleAnomaly {
+    pub coin: String,
+    /// Open time of the flagged candle (epoch ms).
+    pub open_time: u64,
+    pub kinds: Vec<AnomalyKind>,
+    /// Longest wick over the (floored) body.
+    pub wick_body_ratio: f64,
+    /// Candle range in ATRs.
+    pub range_atr: f64,
+    /// ATR of the candles before this one.
+    pub atr: f64,
+}
+
+/// Per-coin anomaly detector fed one closed candle at a time.
+#[derive(Debug, Clone)]
+pub struct CandleAnomalyDetector {
+    coin: String,
+    config: AnomalyConfig,
+    atr: AtrCalculator,
+    recent: VecDeque<Candle>,
+    last_open_time: Option<u64>,
+}
+
+impl CandleAnomalyDetector {
+    pub fn new(coin: impl Into<String>, config: AnomalyConfig) -> Self {
+        let config = AnomalyConfig {
+            envelope_candles: config.envelope_candles.max(1),
+            ..config
+        };
+        Self {
+            coin: coin.into(),
+            atr: AtrCalculator::new(config.atr_period),
+            config,
+            recent: VecDeque::new(),
+            last_open_time: None,
+        }
+    }
+
+    /// Check `candle` against the ATR and envelope of the candles before it,
+    /// then fold it in.
+    ///
+    /// Nothing is flagged until the ATR has warmed up and the envelope is full.
+    /// Candles at or before the last one seen are ignored.
+    pub fn update(&mut self, candle: &Candle) -> Option<CandleAnomaly> {
+        if self
+            .last_open_time
+            .is_some_and(|last| candle.open_time <= last)
+        {
+            return None;
+        }
+        self.last_open_time = Some(candle.open_time);
+
+        let anomaly = self.check(candle);
+
+        self.atr.update(candle);
+        self.recent.push_back(*candle);
+        if self.recent.len() > self.config.envelope_candles {
+            self.recent.pop_front();
+        }
+        anomaly
+    }
+
+    fn check(&self, candle: &Candle) -> Option<CandleAnomaly> {
+        let atr = self.atr.value().filter(|atr| *atr > 0.0)?;
+        if self.recent.len() < self.config.envelope_candles {
+            return None;
+        }
+
+        let range = candle.high - candle.low;
+        let range_atr = range / atr;
+        let wick_body_ratio = wick_body_ratio(candle);
+        let envelope_high = self.recent.iter().map(|c| c.high).fold(f64::MIN, f64::max);
+        let envelope_low = self.recent.iter().map(|c| c.low).fold(f64::MAX, f64::min);
+        let margin = self.config.envelope_margin_atr * atr;
+
+        let mut kinds = Vec::new();
+        if range_atr >= self.config.min_wick_range_atr
+            && wick_body_ratio >= self.config.wick_body_ratio
+        {
+            kinds.push(AnomalyKind::LongWick);
+        }
+        if range_atr >= self.config.range_atr {
+            kinds.push(AnomalyKind::WideRange);
+        }
+        if candle.close > envelope_high + margin || candle.close < envelope_low - margin {
+            kinds.push(AnomalyKind::OutsideEnvelope);
+        }
+
+        if kinds.is_empty() {
+            return None;
+        }
+        Some(CandleAnomaly {
+            coin: self.coin.clone(),
+            open_time: candle.open_time,
+            kinds,
+            wick_body_ratio,
+            range_atr,
+            atr,
+        })
+    }
+}
+
+/// Longest wick over the body, with the body floored at a small fraction of
+/// the range. Zero for a candle with no range.
+pub fn wick_body_ratio(candle: &Candle) -> f64 {
+    let range = candle.high - candle.low;
+    if range <= 0.0 {
+        return 0.0;
+    }
+    let upper = candle.high - candle.open.max(candle.close);
+    let lower = candle.open.min(candle.close) - candle.low;
+    let body = (candle.close - candle.open)
+        .abs()
+        .max(range * MIN_BODY_FRACTION);
+    upper.max(lower) / body
+}
@@ -0,0 +1,303 @@
+//! Ascending triangle detection: flat resistance tested repeatedly while the
+//! swing lows rise into it.
+
+use serde::{Deserialize, Serialize};
+
+use crate::business_logic::alerts::{AlertStage, PatternAlert, PatternKind};
+use crate::business_logic::indicators::AtrCalculator;
+use crate::business_logic::swing::SwingDetector;
+use crate::models::candle::Candle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct AscendingTriangleConfig {
+    pub atr_period: usize,
+    /// Swing reversal size in ATRs.
+    pub rev_atr: f64,
+    /// Max % a swing high may sit from the resistance and still count as a touch.
+    pub level_tolerance_pct: f64,
+    /// Resistance touches needed before the triangle qualifies.
+    pub min_touches: usize,
+    /// Min % each swing low must rise over the previous one.
+    pub min_low_rise_pct: f64,
+    /// The early warning fires once the latest higher low is within this %
+    /// of the resistance.
+    pub apex_threshold_pct: f64,
+    /// ATRs above the resistance a close must reach to confirm.
+    pub breakout_buffer: f64,
+}
+
+impl Default for AscendingTriangleConfig {
+    fn default() -> Self {
+        Self {
+            atr_period: 14,
+            rev_atr: 1.0,
+            level_tolerance_pct: 0.5,
+            min_touches: 2,
+            min_low_rise_pct: 0.2,
+            apex_threshold_pct: 2.5,
+            breakout_buffer: 0.3,
+        }
+    }
+}
+
+impl AscendingTriangleConfig {
+    /// Problems that would make the config unusable, one message each.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let mut check = |ok: bool, message: &str| {
+            if !ok {
+                errors.push(message.to_string());
+            }
+        };
+        check(self.atr_period > 0, "atr_period must be positive");
+        check(
+            self.rev_atr > 0.0 && self.rev_atr.is_finite(),
+            "rev_atr must be positive",
+        );
+        check(
+            self.level_tolerance_pct > 0.0 && self.level_tolerance_pct <= 100.0,
+            "level_tolerance_pct must be in (0, 100]",
+        );
+        check(self.min_touches >= 2, "min_touches must be at least 2");
+        check(
+            self.min_low_rise_pct >= 0.0 && self.min_low_rise_pct <= 100.0,
+            "min_low_rise_pct must be in [0, 100]",
+        );
+        check(
+            self.apex_threshold_pct > 0.0 && self.apex_threshold_pct <= 100.0,
+            "apex_threshold_pct must be in (0, 100]",
+        );
+        check(
+            self.breakout_buffer >= 0.0 && self.breakout_buffer.is_finite(),
+            "breakout_buffer must not be negative",
+        );
+        errors
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AscendingTriangleState {
+    Watching,
+    /// Resistance touched, not yet enough touches and higher lows.
+    Building,
+    /// Enough touches and at least two rising lows.
+    Qualified,
+    /// Compressed near the apex (early warning raised).
+    Forming,
+    Confirmed,
+    Invalidated,
+}
+
+impl AscendingTriangleState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AscendingTriangleState::Watching => "WATCHING",
+            AscendingTriangleState::Building => "BUILDING",
+            AscendingTriangleState::Qualified => "QUALIFIED",
+            AscendingTriangleState::Forming => "FORMING",
+            AscendingTriangleState::Confirmed => "CONFIRMED",
+            AscendingTriangleState::Invalidated => "INVALIDATED",
+        }
+    }
+}
+
+/// Where a coin's ascending triangle stands, as published to the pattern
+/// state.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct AscendingTriangleStatus {
+    pub coin: String,
+    /// `WATCHING`, `BUILDING`, `QUALIFIED`, `FORMING`, `CONFIRMED` or
+    /// `INVALIDATED`.
+    pub state: String,
+    pub resistance_price: Option<f64>,
+    pub latest_higher_low: Option<f64>,
+    /// Swing highs that have tested the resistance.
+    pub touches: usize,
+}
+
+/// Per-coin ascending triangle state machine fed one closed candle at a time.
+#[derive(Debug, Clone)]
+pub struct AscendingTriangleDetector {
+    coin: String,
+    config: AscendingTriangleConfig,
+    atr: AtrCalculator,
+    swings: SwingDetector,
+    state: AscendingTriangleState,
+    /// Swing highs counted as resistance touches.
+    touches: Vec<f64>,
+    /// Swing lows since the first touch, each higher than the last.
+    lows: Vec<f64>,
+}
+
+impl AscendingTriangleDetector {
+    pub fn new(coin: impl Into<String>, config: AscendingTriangleConfig) -> Self {
+        Self {
+            coin: coin.into(),
+            atr: AtrCalculator::new(config.atr_period),
+            swings: SwingDetector::new(config.rev_atr),
+            config,
+            state: AscendingTriangleState::Watching,
+            touches: Vec::new(),
+            lows: Vec::new(),
+        }
+    }
+
+    pub fn state(&self) -> AscendingTriangleState {
+        self.state
+    }
+
+    /// Highest resistance touch.
+    pub fn resistance_price(&self) -> Option<f64> {
+        self.touches.iter().copied().reduce(f64::max)
+    }
+
+    pub fn latest_higher_low(&self) -> Option<f64> {
+        self.lows.last().copied()
+    }
+
+    pub fn touches(&self) -> usize {
+        self.touches.len()
+    }
+
+    pub fn status(&self) -> AscendingTriangleStatus {
+        AscendingTriangleStatus {
+            coin: self.coin.clone(),
+            state: self.state.as_str().to_string(),
+            resistance_price: self.resistance_price(),
+            latest_higher_low: self.latest_higher_low(),
+            touches: self.touches(),
+        }
+    }
+
+    /// Feed the next closed candle, returning an alert if it raised one.
+    ///
+    /// Nothing happens until the ATR has warmed up.
+    pub fn update(&mut self, candle: &Candle) -> Option<PatternAlert> {
+        let atr = self.atr.update(candle)?;
+
+        let swing_alert = match self.swings.update(candle, atr) {
+            Some(swing) if swing.is_peak => {
+                self.on_peak(swing.price);
+                None
+            }
+            Some(swing) => self.on_trough(swing.price, candle),
+            None => None,
+        };
+        swing_alert.or_else(|| self.check_breakout(candle, atr))
+    }
+
+    fn restart(&mut self, touch: Option<f64>) {
+        self.touches = touch.into_iter().collect();
+        self.lows.clear();
+        self.state = if touch.is_some() {
+            AscendingTriangleState::Building
+        } else {
+            AscendingTriangleState::Watching
+        };
+    }
+
+    fn on_peak(&mut self, price: f64) {
+        if matches!(
+            self.state,
+            AscendingTriangleState::Confirmed | AscendingTriangleState::Invalidated
+        ) {
+            self.restart(Some(price));
+            return;
+        }
+        let Some(resistance) = self.resistance_price() else {
+            self.restart(Some(price));
+            return;
+        };
+        if (price - resistance).abs() / resistance * 100.0 <= self.config.level_tolerance_pct {
+            self.touches.push(price);
+            self.qualify();
+        } else {
+            // A clearly higher or lower high means the top isn't flat.
+            self.restart(Some(price));
+        }
+    }
+
+    fn on_trough(&mut self, price: f64, candle: &Candle) -> Option<PatternAlert> {
+        if self.touches.is_empty()
+            || matches!(
+                self.state,
+                AscendingTriangleState::Confirmed | AscendingTriangleState::Invalidated
+            )
+        {
+            return None;
+        }
+        if let Some(&previous) = self.lows.last() {
+            let rise_pct = (price - previous) / previous * 100.0;
+            if rise_pct < self.config.min_low_rise_pct {
+                self.touches.clear();
+                self.lows.clear();
+                self.state = AscendingTriangleState::Invalidated;
+                return None;
+            }
+        }
+        self.lows.push(price);
+        self.qualify();
+        self.check_compression(candle)
+    }
+
+    fn qualify(&mut self) {
+        if self.state == AscendingTriangleState::Building
+            && self.touches.len() >= self.config.min_touches
+            && self.lows.len() >= 2
+        {
+            self.state = AscendingTriangleState::Qualified;
+        }
+    }
+
+    fn check_compression(&mut self, candle: &Candle) -> Option<PatternAlert> {
+        if self.state != AscendingTriangleState::Qualified {
+            return None;
+        }
+        let resistance = self.resistance_price()?;
+        let low = self.latest_higher_low()?;
+        if (resistance - low) / resistance * 100.0 > self.config.apex_threshold_pct {
+            return None;
+        }
+        self.state = AscendingTriangleState::Forming;
+        Some(self.alert(AlertStage::EarlyWarning, candle, resistance))
+    }
+
+    /// A breakout from a qualified triangle confirms; one before it
+    /// qualifies just starts over.
+    fn check_breakout(&mut self, candle: &Candle, atr: f64) -> Option<PatternAlert> {
+        let resistance = self.resistance_price()?;
+        if candle.close <= resistance + self.config.breakout_buffer * atr {
+            return None;
+        }
+        match self.state {
+            AscendingTriangleState::Qualified | AscendingTriangleState::Forming => {
+                self.state = AscendingTriangleState::Confirmed;
+                Some(self.alert(AlertStage::Confirmation, candle, resistance))
+            }
+            AscendingTriangleState::Building => {
+                self.restart(None);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn alert(&self, stage: AlertStage, candle: &Candle, level: f64) -> PatternAlert {
+        PatternAlert {
+            coin: self.coin.clone(),
+            pattern: PatternKind::AscendingTriangle,
+            stage,
+            open_time: candle.open_time,
+            price: candle.close,
+            level,
+            target_price: None,
+            pattern_height_pct: None,
+            retest_high: None,
+            confirmation_mode: None,
+            htf_trend: None,
+        }
+    }
+}
@@ -0,0 +1,336 @@
+//! Cup and handle detection over a rolling candle window.
+//!
+//! Unlike the swing-based detectors this looks at the raw window: a rim high,
+//! a rounded bottom, a recovery back to the rim, then a shallow handle,
+//! confirmed by a close above the rim. Meant for higher timeframes.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::business_logic::alerts::{AlertStage, PatternAlert, PatternKind};
+use crate::business_logic::indicators::AtrCalculator;
+use crate::models::candle::Candle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct CupAndHandleConfig {
+    pub atr_period: usize,
+    /// Candles kept in the rolling window, and the warmup the detector needs.
+    pub window: usize,
+    /// Min candles from the left rim to the right rim.
+    pub min_cup_candles: usize,
+    /// Max candles from the left rim to the right rim.
+    pub max_cup_candles: usize,
+    /// Min % drop from the rim to the bottom of the cup.
+    pub min_depth_pct: f64,
+    /// Max % drop from the rim to the bottom of the cup.
+    pub max_depth_pct: f64,
+    /// Min share of cup candles closing in the lower third of the cup. Keeps
+    /// V-shaped drops and recoveries out.
+    pub min_bottom_share: f64,
+    /// Max % the right rim may sit from the left rim.
+    pub rim_tolerance_pct: f64,
+    /// Max candles the handle may run before it breaks out.
+    pub max_handle_candles: usize,
+    /// Max handle pullback as a fraction of the cup depth.
+    pub handle_max_retrace: f64,
+    /// ATRs above the rim a close must reach to confirm.
+    pub breakout_buffer: f64,
+}
+
+impl Default for CupAndHandleConfig {
+    fn default() -> Self {
+        Self {
+            atr_period: 14,
+            window: 200,
+            min_cup_candles: 30,
+            max_cup_candles: 150,
+            min_depth_pct: 8.0,
+            max_depth_pct: 35.0,
+            min_bottom_share: 0.4,
+            rim_tolerance_pct: 3.0,
+            max_handle_candles: 20,
+            handle_max_retrace: 0.5,
+            breakout_buffer: 0.3,
+        }
+    }
+}
+
+impl CupAndHandleConfig {
+    /// Problems that would make the config unusable, one message each.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let mut check = |ok: bool, message: &str| {
+            if !ok {
+                errors.push(message.to_string());
+            }
+        };
+        check(self.atr_period > 0, "atr_period must be positive");
+        check(
+            self.window > self.min_cup_candles,
+            "window must be more than min_cup_candles",
+        );
+        check(
+            self.min_cup_candles >= 2 && self.min_cup_candles <= self.max_cup_candles,
+            "min_cup_candles must be at least 2 and at most max_cup_candles",
+        );
+        check(
+            self.min_depth_pct > 0.0 && self.min_depth_pct <= self.max_depth_pct,
+            "min_depth_pct must be positive and at most max_depth_pct",
+        );
+        check(
+            self.max_depth_pct < 100.0,
+            "max_depth_pct must be below 100",
+        );
+        check(
+            (0.0..=1.0).contains(&self.min_bottom_share),
+            "min_bottom_share must be in [0, 1]",
+        );
+        check(
+            self.rim_tolerance_pct >= 0.0 && self.rim_tolerance_pct <= 100.0,
+            "rim_tolerance_pct must be in [0, 100]",
+        );
+        check(
+            self.max_handle_candles > 0,
+            "max_handle_candles must be positive",
+        );
+        check(
+            self.handle_max_retrace > 0.0 && self.handle_max_retrace <= 1.0,
+            "handle_max_retrace must be in (0, 1]",
+        );
+        check(
+            self.breakout_buffer >= 0.0 && self.breakout_buffer.is_finite(),
+            "breakout_buffer must not be negative",
+        );
+        errors
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CupAndHandleState {
+    Watching,
+    /// Cup complete and a handle pulling back (early warning raised).
+    HandleForming,
+    Confirmed,
+    Invalidated,
+}
+
+impl CupAndHandleState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CupAndHandleState::Watching => "WATCHING",
+            CupAndHandleState::HandleForming => "HANDLE_FORMING",
+            CupAndHandleState::Confirmed => "CONFIRMED",
+            CupAndHandleState::Invalidated => "INVALIDATED",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Cup {
+    /// Left rim high.
+    rim: f64,
+    bottom: f64,
+    right_rim: f64,
+    handle_low: f64,
+    handle_candles: usize,
+}
+
+impl Cup {
+    fn depth(&self) -> f64 {
+        self.rim - self.bottom
+    }
+}
+
+/// Where a coin's cup and handle stands, as published to the pattern state.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct CupAndHandleStatus {
+    pub coin: String,
+    /// `WATCHING`, `HANDLE_FORMING`, `CONFIRMED` or `INVALIDATED`.
+    pub state: String,
+    /// Left rim high.
+    pub rim_price: Option<f64>,
+    /// Cup depth as a % of the rim.
+    pub cup_depth_pct: Option<f64>,
+    pub handle_low: Option<f64>,
+}
+
+/// Per-coin cup and handle state machine fed one closed candle at a time.
+#[derive(Debug, Clone)]
+pub struct CupAndHandleDetector {
+    coin: String,
+    config: CupAndHandleConfig,
+    atr: AtrCalculator,
+    candles: VecDeque<Candle>,
+    state: CupAndHandleState,
+    cup: Option<Cup>,
+}
+
+impl CupAndHandleDetector {
+    pub fn new(coin: impl Into<String>, config: CupAndHandleConfig) -> Self {
+        Self {
+            coin: coin.into(),
+            atr: AtrCalculator::new(config.atr_period),
+            candles: VecDeque::with_capacity(config.window),
+            config,
+            state: CupAndHandleState::Watching,
+            cup: None,
+        }
+    }
+
+    /// Candles of history to backfill before the detector can see a full cup.
+    pub fn warmup_candles(&self) -> usize {
+        self.config.window.max(self.config.atr_period)
+    }
+
+    pub fn state(&self) -> CupAndHandleState {
+        self.state
+    }
+
+    pub fn rim_price(&self) -> Option<f64> {
+        self.cup.map(|c| c.rim)
+    }
+
+    /// Cup depth as a % of the rim.
+    pub fn cup_depth_pct(&self) -> Option<f64> {
+        self.cup.map(|c| c.depth() / c.rim * 100.0)
+    }
+
+    pub fn handle_low(&self) -> Option<f64> {
+        self.cup.map(|c| c.handle_low)
+    }
+
+    pub fn status(&self) -> CupAndHandleStatus {
+        CupAndHandleStatus {
+            coin: self.coin.clone(),
+            state: self.state.as_str().to_string(),
+            rim_price: self.rim_price(),
+            cup_depth_pct: self.cup_depth_pct(),
+            handle_low: self.handle_low(),
+        }
+    }
+
+    /// Feed the next closed candle, returning an alert if it raised one.
+    ///
+    /// Nothing happens until the ATR has warmed up.
+    pub fn update(&mut self, candle: &Candle) -> Option<PatternAlert> {
+        let atr = self.atr.update(candle);
+        self.candles.push_back(*candle);
+        if self.candles.len() > self.config.window {
+            self.candles.pop_front();
+        }
+        let atr = atr?;
+
+        match self.state {
+            CupAndHandleState::HandleForming => self.on_handle(candle, atr),
+            _ => {
+                let cup = self.find_cup()?;
+                self.cup = Some(cup);
+                self.state = CupAndHandleState::HandleForming;
+                Some(self.alert(AlertStage::EarlyWarning, candle, cup.rim))
+            }
+        }
+    }
+
+    fn on_handle(&mut self, candle: &Candle, atr: f64) -> Option<PatternAlert> {
+        let mut cup = self.cup?;
+        if candle.close > cup.rim + self.config.breakout_buffer * atr {
+            self.finish(CupAndHandleState::Confirmed);
+            return Some(self.alert(AlertStage::Confirmation, candle, cup.rim));
+        }
+        cup.handle_low = cup.handle_low.min(candle.low);
+        cup.handle_candles += 1;
+        self.cup = Some(cup);
+        let too_deep =
+            cup.right_rim - cup.handle_low > self.config.handle_max_retrace * cup.depth();
+        if too_deep || cup.handle_candles > self.config.max_handle_candles {
+            self.finish(CupAndHandleState::Invalidated);
+        }
+        None
+    }
+
+    /// Settle the pattern and drop the history it was built from so the
+    /// next cup has to form from scratch.
+    fn finish(&mut self, state: CupAndHandleState) {
+        let last = self.candles.pop_back();
+        self.candles.clear();
+        self.candles.extend(last);
+        self.state = state;
+    }
+
+    /// Look for a completed cup with a handle just starting, anchored on the
+    /// highest high in the last `max_handle_candles` before the latest candle.
+    fn find_cup(&mut self) -> Option<Cup> {
+        let candles = self.candles.make_contiguous();
+        let latest = candles.len().checked_sub(1)?;
+        let handle_start = latest.saturating_sub(self.config.max_handle_candles);
+        let right = argmax(candles, handle_start, latest)?;
+        // The latest candle is the first one of the handle.
+        if right + 1 != latest || candles[latest].close >= candles[right].close {
+            return None;
+        }
+        // The left rim is the highest high far enough back to leave room for
+        // the cup.
+        let cup_start = right.saturating_sub(self.config.max_cup_candles);
+        let cup_end = (right + 1).checked_sub(self.config.min_cup_candles)?;
+        let left = argmax(candles, cup_start, cup_end)?;
+        let bottom = argmin(candles, left + 1, right)?;
+        let cup_candles = right - left;
+        let rim = candles[left].high;
+        let right_rim = candles[right].high;
+        let low = candles[bottom].low;
+        let depth_pct = (rim - low) / rim * 100.0;
+        if depth_pct < self.config.min_depth_pct || depth_pct > self.config.max_depth_pct {
+            return None;
+        }
+        if (right_rim - rim).abs() / rim * 100.0 > self.config.rim_tolerance_pct {
+            return None;
+        }
+        let lower_third = low + (rim - low) / 3.0;
+        let in_bottom = candles[left..=right]
+            .iter()
+            .filter(|c| c.close <= lower_third)
+            .count();
+        if (in_bottom as f64) / ((cup_candles + 1) as f64) < self.config.min_bottom_share {
+            return None;
+        }
+
+        let cup = Cup {
+            rim,
+            bottom: low,
+            right_rim,
+            handle_low: candles[latest].low,
+            handle_candles: 1,
+        };
+        (right_rim - cup.handle_low <= self.config.handle_max_retrace * cup.depth()).then_some(cup)
+    }
+
+    fn alert(&self, stage: AlertStage, candle: &Candle, level: f64) -> PatternAlert {
+        PatternAlert {
+            coin: self.coin.clone(),
+            pattern: PatternKind::CupAndHandle,
+            stage,
+            open_time: candle.open_time,
+            price: candle.close,
+            level,
+            target_price: None,
+            pattern_height_pct: None,
+            retest_high: None,
+            confirmation_mode: None,
+            htf_trend: None,
+        }
+    }
+}
+
+/// Index of the highest high in `candles[from..to]`.
+fn argmax(candles: &[Candle], from: usize, to: usize) -> Option<usize> {
+    (from..to).max_by(|&a, &b| candles[a].high.total_cmp(&candles[b].high))
+}
+
+/// Index of the lowest low in `candles[from..to]`.
+fn argmin(candles: &[Candle], from: usize, to: usize) -> Option<usize> {
+    (from..to).min_by(|&a, &b| candles[a].low.total_cmp(&candles[b].low))
+}
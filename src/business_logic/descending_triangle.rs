@@ -0,0 +1,303 @@
+//! Descending triangle detection: flat support tested repeatedly while the
+//! swing highs fall into it.
+
+use serde::{Deserialize, Serialize};
+
+use crate::business_logic::alerts::{AlertStage, PatternAlert, PatternKind};
+use crate::business_logic::indicators::AtrCalculator;
+use crate::business_logic::swing::SwingDetector;
+use crate::models::candle::Candle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct DescendingTriangleConfig {
+    pub atr_period: usize,
+    /// Swing reversal size in ATRs.
+    pub rev_atr: f64,
+    /// Max % a swing low may sit from the support and still count as a touch.
+    pub level_tolerance_pct: f64,
+    /// Support touches needed before the triangle qualifies.
+    pub min_touches: usize,
+    /// Min % each swing high must fall below the previous one.
+    pub min_high_drop_pct: f64,
+    /// The early warning fires once the latest lower high is within this %
+    /// of the support.
+    pub apex_threshold_pct: f64,
+    /// ATRs below the support a close must reach to confirm.
+    pub breakdown_buffer: f64,
+}
+
+impl Default for DescendingTriangleConfig {
+    fn default() -> Self {
+        Self {
+            atr_period: 14,
+            rev_atr: 1.0,
+            level_tolerance_pct: 0.5,
+            min_touches: 2,
+            min_high_drop_pct: 0.2,
+            apex_threshold_pct: 2.5,
+            breakdown_buffer: 0.3,
+        }
+    }
+}
+
+impl DescendingTriangleConfig {
+    /// Problems that would make the config unusable, one message each.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let mut check = |ok: bool, message: &str| {
+            if !ok {
+                errors.push(message.to_string());
+            }
+        };
+        check(self.atr_period > 0, "atr_period must be positive");
+        check(
+            self.rev_atr > 0.0 && self.rev_atr.is_finite(),
+            "rev_atr must be positive",
+        );
+        check(
+            self.level_tolerance_pct > 0.0 && self.level_tolerance_pct <= 100.0,
+            "level_tolerance_pct must be in (0, 100]",
+        );
+        check(self.min_touches >= 2, "min_touches must be at least 2");
+        check(
+            self.min_high_drop_pct >= 0.0 && self.min_high_drop_pct <= 100.0,
+            "min_high_drop_pct must be in [0, 100]",
+        );
+        check(
+            self.apex_threshold_pct > 0.0 && self.apex_threshold_pct <= 100.0,
+            "apex_threshold_pct must be in (0, 100]",
+        );
+        check(
+            self.breakdown_buffer >= 0.0 && self.breakdown_buffer.is_finite(),
+            "breakdown_buffer must not be negative",
+        );
+        errors
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DescendingTriangleState {
+    Watching,
+    /// Support touched, not yet enough touches and lower highs.
+    Building,
+    /// Enough touches and at least two falling highs.
+    Qualified,
+    /// Compressed near the apex (early warning raised).
+    Forming,
+    Confirmed,
+    Invalidated,
+}
+
+impl DescendingTriangleState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DescendingTriangleState::Watching => "WATCHING",
+            DescendingTriangleState::Building => "BUILDING",
+            DescendingTriangleState::Qualified => "QUALIFIED",
+            DescendingTriangleState::Forming => "FORMING",
+            DescendingTriangleState::Confirmed => "CONFIRMED",
+            DescendingTriangleState::Invalidated => "INVALIDATED",
+        }
+    }
+}
+
+/// Where a coin's descending triangle stands, as published to the pattern
+/// state.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct DescendingTriangleStatus {
+    pub coin: String,
+    /// `WATCHING`, `BUILDING`, `QUALIFIED`, `FORMING`, `CONFIRMED` or
+    /// `INVALIDATED`.
+    pub state: String,
+    pub support_price: Option<f64>,
+    pub latest_lower_high: Option<f64>,
+    /// Swing lows that have tested the support.
+    pub touches: usize,
+}
+
+/// Per-coin descending triangle state machine fed one closed candle at a time.
+#[derive(Debug, Clone)]
+pub struct DescendingTriangleDetector {
+    coin: String,
+    config: DescendingTriangleConfig,
+    atr: AtrCalculator,
+    swings: SwingDetector,
+    state: DescendingTriangleState,
+    /// Swing lows counted as support touches.
+    touches: Vec<f64>,
+    /// Swing highs since the first touch, each lower than the last.
+    highs: Vec<f64>,
+}
+
+impl DescendingTriangleDetector {
+    pub fn new(coin: impl Into<String>, config: DescendingTriangleConfig) -> Self {
+        Self {
+            coin: coin.into(),
+            atr: AtrCalculator::new(config.atr_period),
+            swings: SwingDetector::new(config.rev_atr),
+            config,
+            state: DescendingTriangleState::Watching,
+            touches: Vec::new(),
+            highs: Vec::new(),
+        }
+    }
+
+    pub fn state(&self) -> DescendingTriangleState {
+        self.state
+    }
+
+    /// Lowest support touch.
+    pub fn support_price(&self) -> Option<f64> {
+        self.touches.iter().copied().reduce(f64::min)
+    }
+
+    pub fn latest_lower_high(&self) -> Option<f64> {
+        self.highs.last().copied()
+    }
+
+    pub fn touches(&self) -> usize {
+        self.touches.len()
+    }
+
+    pub fn status(&self) -> DescendingTriangleStatus {
+        DescendingTriangleStatus {
+            coin: self.coin.clone(),
+            state: self.state.as_str().to_string(),
+            support_price: self.support_price(),
+            latest_lower_high: self.latest_lower_high(),
+            touches: self.touches(),
+        }
+    }
+
+    /// Feed the next closed candle, returning an alert if it raised one.
+    ///
+    /// Nothing happens until the ATR has warmed up.
+    pub fn update(&mut self, candle: &Candle) -> Option<PatternAlert> {
+        let atr = self.atr.update(candle)?;
+
+        let swing_alert = match self.swings.update(candle, atr) {
+            Some(swing) if swing.is_peak => self.on_peak(swing.price, candle),
+            Some(swing) => {
+                self.on_trough(swing.price);
+                None
+            }
+            None => None,
+        };
+        swing_alert.or_else(|| self.check_breakdown(candle, atr))
+    }
+
+    fn restart(&mut self, touch: Option<f64>) {
+        self.touches = touch.into_iter().collect();
+        self.highs.clear();
+        self.state = if touch.is_some() {
+            DescendingTriangleState::Building
+        } else {
+            DescendingTriangleState::Watching
+        };
+    }
+
+    fn on_trough(&mut self, price: f64) {
+        if matches!(
+            self.state,
+            DescendingTriangleState::Confirmed | DescendingTriangleState::Invalidated
+        ) {
+            self.restart(Some(price));
+            return;
+        }
+        let Some(support) = self.support_price() else {
+            self.restart(Some(price));
+            return;
+        };
+        if (price - support).abs() / support * 100.0 <= self.config.level_tolerance_pct {
+            self.touches.push(price);
+            self.qualify();
+        } else {
+            // A clearly lower or higher low means the bottom isn't flat.
+            self.restart(Some(price));
+        }
+    }
+
+    fn on_peak(&mut self, price: f64, candle: &Candle) -> Option<PatternAlert> {
+        if self.touches.is_empty()
+            || matches!(
+                self.state,
+                DescendingTriangleState::Confirmed | DescendingTriangleState::Invalidated
+            )
+        {
+            return None;
+        }
+        if let Some(&previous) = self.highs.last() {
+            let drop_pct = (previous - price) / previous * 100.0;
+            if drop_pct < self.config.min_high_drop_pct {
+                self.touches.clear();
+                self.highs.clear();
+                self.state = DescendingTriangleState::Invalidated;
+                return None;
+            }
+        }
+        self.highs.push(price);
+        self.qualify();
+        self.check_compression(candle)
+    }
+
+    fn qualify(&mut self) {
+        if self.state == DescendingTriangleState::Building
+            && self.touches.len() >= self.config.min_touches
+            && self.highs.len() >= 2
+        {
+            self.state = DescendingTriangleState::Qualified;
+        }
+    }
+
+    fn check_compression(&mut self, candle: &Candle) -> Option<PatternAlert> {
+        if self.state != DescendingTriangleState::Qualified {
+            return None;
+        }
+        let support = self.support_price()?;
+        let high = self.latest_lower_high()?;
+        if (high - support) / support * 100.0 > self.config.apex_threshold_pct {
+            return None;
+        }
+        self.state = DescendingTriangleState::Forming;
+        Some(self.alert(AlertStage::EarlyWarning, candle, support))
+    }
+
+    /// A breakdown from a qualified triangle confirms; one before it
+    /// qualifies just starts over.
+    fn check_breakdown(&mut self, candle: &Candle, atr: f64) -> Option<PatternAlert> {
+        let support = self.support_price()?;
+        if candle.close >= support - self.config.breakdown_buffer * atr {
+            return None;
+        }
+        match self.state {
+            DescendingTriangleState::Qualified | DescendingTriangleState::Forming => {
+                self.state = DescendingTriangleState::Confirmed;
+                Some(self.alert(AlertStage::Confirmation, candle, support))
+            }
+            DescendingTriangleState::Building => {
+                self.restart(None);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn alert(&self, stage: AlertStage, candle: &Candle, level: f64) -> PatternAlert {
+        PatternAlert {
+            coin: self.coin.clone(),
+            pattern: PatternKind::DescendingTriangle,
+            stage,
+            open_time: candle.open_time,
+            price: candle.close,
+            level,
+            target_price: None,
+            pattern_height_pct: None,
+            retest_high: None,
+            confirmation_mode: None,
+            htf_trend: None,
+        }
+    }
+}
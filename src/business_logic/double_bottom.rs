@@ -0,0 +1,573 @@
+//! Double bottom detection: the bullish mirror of the double top in
+//! `spec/double_top_detection.md`.
+//!
+//! Trough 1 -> peak (the neckline) -> trough 2 at about the same level, confirmed
+//! by a close above the neckline.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::business_logic::alerts::{AlertStage, PatternAlert, PatternKind};
+use crate::business_logic::fibonacci::FibLevels;
+use crate::business_logic::indicators::{
+    AdxCalculator, AtrCalculator, EmaCalculator, ObvCalculator, RsiCalculator,
+};
+use crate::business_logic::swing::SwingDetector;
+use crate::models::candle::Candle;
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct DoubleBottomConfig {
+    pub atr_period: usize,
+    /// Swing reversal size in ATRs.
+    pub rev_atr: f64,
+    /// Candles after trough 1 within which the pattern must confirm.
+    pub max_trough_distance: usize,
+    /// Max % difference between the two troughs.
+    pub trough_tolerance_pct: f64,
+    /// Min % bounce from trough 1 to the neckline.
+    pub min_bounce_pct: f64,
+    /// % distance above trough 1 at which a falling price raises the early warning.
+    pub approach_threshold_pct: f64,
+    /// ATRs above the neckline a close must reach to confirm.
+    pub breakout_buffer: f64,
+    /// % below trough 1 that invalidates the pattern.
+    pub trough_fail_pct: f64,
+    /// The early warning needs the close below the close this many candles back.
+    pub trend_lookback: usize,
+    /// Replaces the `trend_lookback` check when set.
+    pub ema_filter: Option<EmaTrendFilter>,
+    pub rsi_period: usize,
+    /// Min RSI points trough 2 must sit above trough 1 to count as a bullish divergence.
+    pub rsi_divergence_delta: f64,
+    /// Hold the confirmation unless the troughs show an RSI divergence.
+    pub require_rsi_divergence: bool,
+    pub adx_period: usize,
+    /// Only alert on patterns whose first trough formed with at least this
+    /// ADX, i.e. at the end of a real trend rather than in chop.
+    pub min_adx_at_trough1: Option<f64>,
+    /// Min OBV rise from trough 1 to trough 2 (in volume units) that counts
+    /// as a bullish OBV divergence: buyers absorbing the retest.
+    pub obv_divergence_margin: f64,
+    /// Hold the confirmation unless the troughs show an OBV divergence.
+    pub require_obv_divergence: bool,
+}
+
+/// Early-warning trend filter that a single spike can't fool: the close must
+/// be below an EMA that has itself been falling, or for a double top above
+/// one that has been rising.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct EmaTrendFilter {
+    pub period: usize,
+    /// The EMA must have moved the trend's way since this many candles back.
+    pub slope_lookback: usize,
+}
+
+impl Default for EmaTrendFilter {
+    fn default() -> Self {
+        Self {
+            period: 20,
+            slope_lookback: 3,
+        }
+    }
+}
+
+impl Default for DoubleBottomConfig {
+    fn default() -> Self {
+        Self {
+            atr_period: 14,
+            rev_atr: 1.0,
+            max_trough_distance: 60,
+            trough_tolerance_pct: 1.5,
+            min_bounce_pct: 2.0,
+            approach_threshold_pct: 1.0,
+            breakout_buffer: 0.3,
+            trough_fail_pct: 1.5,
+            trend_lookback: 3,
+            ema_filter: None,
+            rsi_period: 14,
+            rsi_divergence_delta: 5.0,
+            require_rsi_divergence: false,
+            adx_period: 14,
+            min_adx_at_trough1: None,
+            obv_divergence_margin: 0.0,
+            require_obv_divergence: false,
+        }
+    }
+}
+
+impl DoubleBottomConfig {
+    /// Every setting that would make the detector misbehave, as one
+    /// human-readable message each; empty when the config is usable.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let mut check = |ok: bool, message: &str| {
+            if !ok {
+                errors.push(message.to_string());
+            }
+        };
+        let pct = |v: f64| v > 0.0 && v <= 100.0;
+
+        check(self.atr_period > 0, "atr_period must be positive");
+        check(self.rsi_period > 0, "rsi_period must be positive");
+        check(self.adx_period > 0, "adx_period must be positive");
+        check(self.trend_lookback > 0, "trend_lookback must be positive");
+        check(
+            self.max_trough_distance > self.trend_lookback,
+            "max_trough_distance must be greater than trend_lookback",
+        );
+        check(
+            self.rev_atr > 0.0 && self.rev_atr.is_finite(),
+            "rev_atr must be positive",
+        );
+        check(
+            self.breakout_buffer >= 0.0 && self.breakout_buffer.is_finite(),
+            "breakout_buffer must not be negative",
+        );
+        check(
+            pct(self.trough_tolerance_pct),
+            "trough_tolerance_pct must be in (0, 100]",
+        );
+        check(
+            pct(self.min_bounce_pct),
+            "min_bounce_pct must be in (0, 100]",
+        );
+        check(
+            pct(self.approach_threshold_pct),
+            "approach_threshold_pct must be in (0, 100]",
+        );
+        check(
+            pct(self.trough_fail_pct),
+            "trough_fail_pct must be in (0, 100]",
+        );
+        check(
+            (0.0..100.0).contains(&self.rsi_divergence_delta),
+            "rsi_divergence_delta must be in [0, 100)",
+        );
+        check(
+            self.min_adx_at_trough1
+                .is_none_or(|adx| (0.0..=100.0).contains(&adx)),
+            "min_adx_at_trough1 must be in [0, 100]",
+        );
+        check(
+            self.obv_divergence_margin >= 0.0 && self.obv_divergence_margin.is_finite(),
+            "obv_divergence_margin must not be negative",
+        );
+        if let Some(filter) = self.ema_filter {
+            check(filter.period > 0, "ema_filter.period must be positive");
+            check(
+                filter.slope_lookback > 0,
+                "ema_filter.slope_lookback must be positive",
+            );
+        }
+        errors
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DoubleBottomState {
+    /// Looking for a first trough.
+    Watching,
+    /// Trough 1 confirmed, waiting for a big enough bounce.
+    TroughFound,
+    /// Neckline confirmed, waiting for price to come back down.
+    PeakFound,
+    /// Price is retesting trough 1 (early warning raised).
+    Forming,
+    Confirmed,
+    Invalidated,
+}
+
+impl DoubleBottomState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DoubleBottomState::Watching => "WATCHING",
+            DoubleBottomState::TroughFound => "TROUGH_FOUND",
+            DoubleBottomState::PeakFound => "PEAK_FOUND",
+            DoubleBottomState::Forming => "FORMING",
+            DoubleBottomState::Confirmed => "CONFIRMED",
+            DoubleBottomState::Invalidated => "INVALIDATED",
+        }
+    }
+}
+
+/// Where a coin's double bottom stands, as published to the pattern state.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct DoubleBottomStatus {
+    pub coin: String,
+    /// `WATCHING`, `TROUGH_FOUND`, `PEAK_FOUND`, `FORMING`, `CONFIRMED` or
+    /// `INVALIDATED`.
+    pub state: String,
+    pub trough1_price: Option<f64>,
+    pub neckline_price: Option<f64>,
+    pub trough2_price: Option<f64>,
+    pub rsi_divergence: bool,
+    pub obv_divergence: bool,
+    pub fib_levels: Option<FibLevels>,
+    pub adx_at_trough1: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Trough {
+    price: f64,
+    /// Candle index at which the trough was confirmed.
+    index: usize,
+    readings: Readings,
+}
+
+/// Indicator values on the candle that made a trough's low.
+#[derive(Debug, Clone, Copy, Default)]
+struct Readings {
+    rsi: Option<f64>,
+    adx: Option<f64>,
+    obv: f64,
+}
+
+/// Per-coin double bottom state machine fed one closed candle at a time.
+#[derive(Debug, Clone)]
+pub struct DoubleBottomDetector {
+    coin: String,
+    config: DoubleBottomConfig,
+    atr: AtrCalculator,
+    swings: SwingDetector,
+    closes: VecDeque<f64>,
+    ema: Option<EmaCalculator>,
+    /// Recent EMA values, enough to measure its slope.
+    emas: VecDeque<f64>,
+    rsi: RsiCalculator,
+    adx: AdxCalculator,
+    obv: ObvCalculator,
+    /// Lowest low since the last peak and the readings on that candle, so a
+    /// trough's indicators are read at the extreme rather than at confirmation.
+    run_low: Option<(f64, Readings)>,
+    index: usize,
+    state: DoubleBottomState,
+    trough1: Option<Trough>,
+    neckline: Option<f64>,
+    trough2: Option<f64>,
+    rsi_divergence: bool,
+    obv_divergence: bool,
+    /// Highest high since trough 1 was confirmed: the end of the bounce leg.
+    leg_high: Option<f64>,
+    warned: bool,
+}
+
+impl DoubleBottomDetector {
+    pub fn new(coin: impl Into<String>, config: DoubleBottomConfig) -> Self {
+        Self {
+            coin: coin.into(),
+            atr: AtrCalculator::new(config.atr_period),
+            swings: SwingDetector::new(config.rev_atr),
+            config,
+            closes: VecDeque::new(),
+            ema: config.ema_filter.map(|f| EmaCalculator::new(f.period)),
+            emas: VecDeque::new(),
+            rsi: RsiCalculator::new(config.rsi_period),
+            adx: AdxCalculator::new(config.adx_period),
+            obv: ObvCalculator::new(),
+            run_low: None,
+            index: 0,
+            state: DoubleBottomState::Watching,
+            trough1: None,
+            neckline: None,
+            trough2: None,
+            rsi_divergence: false,
+            obv_divergence: false,
+            leg_high: None,
+            warned: false,
+        }
+    }
+
+    pub fn state(&self) -> DoubleBottomState {
+        self.state
+    }
+
+    pub fn trough1_price(&self) -> Option<f64> {
+        self.trough1.map(|t| t.price)
+    }
+
+    pub fn neckline_price(&self) -> Option<f64> {
+        self.neckline
+    }
+
+    pub fn trough2_price(&self) -> Option<f64> {
+        self.trough2
+    }
+
+    /// Whether trough 2 came in with a materially higher RSI than trough 1.
+    pub fn rsi_divergence(&self) -> bool {
+        self.rsi_divergence
+    }
+
+    /// Whether OBV rose from trough 1 to trough 2 by at least the configured margin.
+    pub fn obv_divergence(&self) -> bool {
+        self.obv_divergence
+    }
+
+    /// Retracements of the trough 1 -> bounce high leg, measured at the
+    /// latest close, while a pattern is in progress.
+    pub fn fib_levels(&self) -> Option<FibLevels> {
+        if !matches!(
+            self.state,
+            DoubleBottomState::TroughFound
+                | DoubleBottomState::PeakFound
+                | DoubleBottomState::Forming
+        ) {
+            return None;
+        }
+        FibLevels::new(self.trough1?.price, self.leg_high?, *self.closes.back()?)
+    }
+
+    /// ADX on the candle that made trough 1.
+    pub fn adx_at_trough1(&self) -> Option<f64> {
+        self.trough1.and_then(|t| t.readings.adx)
+    }
+
+    pub fn status(&self) -> DoubleBottomStatus {
+        DoubleBottomStatus {
+            coin: self.coin.clone(),
+            state: self.state.as_str().to_string(),
+            trough1_price: self.trough1_price(),
+            neckline_price: self.neckline_price(),
+            trough2_price: self.trough2_price(),
+            rsi_divergence: self.rsi_divergence,
+            obv_divergence: self.obv_divergence,
+            fib_levels: self.fib_levels(),
+            adx_at_trough1: self.adx_at_trough1(),
+        }
+    }
+
+    /// Feed the next closed candle, returning an alert if it raised one.
+    ///
+    /// Nothing happens until the ATR has warmed up.
+    pub fn update(&mut self, candle: &Candle) -> Option<PatternAlert> {
+        self.index += 1;
+        self.closes.push_back(candle.close);
+        if self.closes.len() > self.config.trend_lookback + 1 {
+            self.closes.pop_front();
+        }
+        self.update_ema(candle);
+        let readings = Readings {
+            rsi: self.rsi.update(candle),
+            adx: self.adx.update(candle).map(|v| v.adx),
+            obv: self.obv.update(candle),
+        };
+        if self.run_low.is_none_or(|(low, _)| candle.low < low) {
+            self.run_low = Some((candle.low, readings));
+        }
+        let atr = self.atr.update(candle)?;
+
+        if self.check_invalidation(candle) {
+            return None;
+        }
+
+        if let Some(swing) = self.swings.update(candle, atr) {
+            if swing.is_peak {
+                self.run_low = Some((candle.low, readings));
+                self.on_peak(swing.price);
+            } else {
+                let readings = self.run_low.map(|(_, r)| r).unwrap_or_default();
+                self.on_trough(swing.price, readings);
+            }
+        }
+
+        if self.trough1.is_some() && self.leg_high.is_none_or(|high| candle.high > high) {
+            self.leg_high = Some(candle.high);
+        }
+
+        match self.state {
+            DoubleBottomState::PeakFound | DoubleBottomState::Forming if self.trough1_adx_ok() => {
+                self.check_confirmation(candle, atr)
+                    .or_else(|| self.check_early_warning(candle))
+            }
+            _ => None,
+        }
+    }
+
+    fn trough1_adx_ok(&self) -> bool {
+        self.config
+            .min_adx_at_trough1
+            .is_none_or(|min| self.adx_at_trough1().is_some_and(|adx| adx >= min))
+    }
+
+    fn update_ema(&mut self, candle: &Candle) {
+        let (Some(ema), Some(filter)) = (self.ema.as_mut(), self.config.ema_filter) else {
+            return;
+        };
+        if let Some(value) = ema.update(candle) {
+            self.emas.push_back(value);
+            if self.emas.len() > filter.slope_lookback + 1 {
+                self.emas.pop_front();
+            }
+        }
+    }
+
+    /// Whether price is trending down into trough 1, by the EMA filter when
+    /// configured and the `trend_lookback` close comparison otherwise.
+    fn is_falling(&self, candle: &Candle) -> bool {
+        match self.config.ema_filter {
+            Some(filter) => {
+                let (Some(&oldest), Some(&ema)) = (self.emas.front(), self.emas.back()) else {
+                    return false;
+                };
+                self.emas.len() > filter.slope_lookback && candle.close < ema && ema < oldest
+            }
+            None => self.closes.front().is_some_and(|&front| {
+                self.closes.len() > self.config.trend_lookback && candle.close < front
+            }),
+        }
+    }
+
+    fn start_pattern(&mut self, price: f64, readings: Readings) {
+        self.trough1 = Some(Trough {
+            price,
+            index: self.index,
+            readings,
+        });
+        self.neckline = None;
+        self.trough2 = None;
+        self.rsi_divergence = false;
+        self.obv_divergence = false;
+        self.leg_high = None;
+        self.warned = false;
+        self.state = DoubleBottomState::TroughFound;
+    }
+
+    fn reset(&mut self, state: DoubleBottomState) {
+        self.trough1 = None;
+        self.neckline = None;
+        self.trough2 = None;
+        self.rsi_divergence = false;
+        self.obv_divergence = false;
+        self.leg_high = None;
+        self.warned = false;
+        self.state = state;
+    }
+
+    fn on_trough(&mut self, price: f64, readings: Readings) {
+        let Some(trough1) = self.trough1 else {
+            self.start_pattern(price, readings);
+            return;
+        };
+        match self.state {
+            // The bounce was too small; re-anchor on a lower low.
+            DoubleBottomState::TroughFound => {
+                if price < trough1.price {
+                    self.start_pattern(price, readings);
+                }
+            }
+            DoubleBottomState::PeakFound | DoubleBottomState::Forming => {
+                let average = (trough1.price + price) / 2.0;
+                let diff_pct = (trough1.price - price).abs() / average * 100.0;
+                if diff_pct <= self.config.trough_tolerance_pct {
+                    self.trough2 = Some(price);
+                    self.rsi_divergence =
+                        trough1
+                            .readings
+                            .rsi
+                            .zip(readings.rsi)
+                            .is_some_and(|(rsi1, rsi2)| {
+                                rsi2 - rsi1 >= self.config.rsi_divergence_delta
+                            });
+                    self.obv_divergence =
+                        readings.obv - trough1.readings.obv > self.config.obv_divergence_margin;
+                    self.state = DoubleBottomState::Forming;
+                } else {
+                    // Didn't come back to trough 1: this low starts a new pattern.
+                    self.start_pattern(price, readings);
+                }
+            }
+            _ => self.start_pattern(price, readings),
+        }
+    }
+
+    fn on_peak(&mut self, price: f64) {
+        let Some(trough1) = self.trough1 else {
+            return;
+        };
+        // Only the first qualifying bounce sets the neckline. Later rallies
+        // either stall below it or confirm on the way through.
+        if self.state == DoubleBottomState::TroughFound {
+            let bounce_pct = (price - trough1.price) / trough1.price * 100.0;
+            if bounce_pct >= self.config.min_bounce_pct {
+                self.neckline = Some(price);
+                self.state = DoubleBottomState::PeakFound;
+            }
+        }
+    }
+
+    /// Invalidate on a break below trough 1 or once the pattern has taken
+    /// too long. Returns whether it did.
+    fn check_invalidation(&mut self, candle: &Candle) -> bool {
+        let Some(trough1) = self.trough1 else {
+            return false;
+        };
+        if matches!(
+            self.state,
+            DoubleBottomState::Confirmed | DoubleBottomState::Invalidated
+        ) {
+            return false;
+        }
+        let fail_level = trough1.price * (1.0 - self.config.trough_fail_pct / 100.0);
+        let expired = self.index - trough1.index > self.config.max_trough_distance;
+        // Before the bounce a lower low simply becomes the new trough 1.
+        let broke_down = self.state != DoubleBottomState::TroughFound && candle.low < fail_level;
+        if broke_down || expired {
+            self.reset(DoubleBottomState::Invalidated);
+            return true;
+        }
+        false
+    }
+
+    fn check_early_warning(&mut self, candle: &Candle) -> Option<PatternAlert> {
+        if self.warned {
+            return None;
+        }
+        let trough1 = self.trough1?;
+        let distance_pct = (candle.close - trough1.price).abs() / trough1.price * 100.0;
+        if distance_pct > self.config.approach_threshold_pct || !self.is_falling(candle) {
+            return None;
+        }
+        self.warned = true;
+        self.state = DoubleBottomState::Forming;
+        Some(self.alert(AlertStage::EarlyWarning, candle, trough1.price))
+    }
+
+    fn check_confirmation(&mut self, candle: &Candle, atr: f64) -> Option<PatternAlert> {
+        self.trough2?;
+        if self.config.require_rsi_divergence && !self.rsi_divergence {
+            return None;
+        }
+        if self.config.require_obv_divergence && !self.obv_divergence {
+            return None;
+        }
+        let neckline = self.neckline?;
+        let break_level = neckline + self.config.breakout_buffer * atr;
+        if candle.close <= break_level {
+            return None;
+        }
+        self.state = DoubleBottomState::Confirmed;
+        Some(self.alert(AlertStage::Confirmation, candle, neckline))
+    }
+
+    fn alert(&self, stage: AlertStage, candle: &Candle, level: f64) -> PatternAlert {
+        PatternAlert {
+            coin: self.coin.clone(),
+            pattern: PatternKind::DoubleBottom,
+            stage,
+            open_time: candle.open_time,
+            price: candle.close,
+            level,
+            target_price: None,
+            pattern_height_pct: None,
+            retest_high: None,
+            confirmation_mode: None,
+            htf_trend: None,
+        }
+    }
+}
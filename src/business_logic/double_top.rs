@@ -0,0 +1,1494 @@
+//! Double top detection as described in `spec/double_top_detection.md`.
+//!
+//! Peak 1 -> trough (the neckline) -> peak 2 at about the same level,
+//! confirmed by a close below the neckline.
+//!
+//! Distances are measured between candle open times on the detection
+//! interval rather than by counting candles fed, so a gap in the feed still
+//! ages a pattern by the time that actually passed.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::business_logic::alerts::{
+    AlertStage, ConfirmationMode, PatternAlert, PatternKind, TrendDirection,
+};
+use crate::business_logic::double_bottom::EmaTrendFilter;
+use crate::business_logic::indicators::{AtrCalculator, EmaCalculator};
+use crate::business_logic::intervals;
+use crate::business_logic::open_interest::OiRule;
+use crate::business_logic::patterns::Confluence;
+use crate::business_logic::premium::DEFAULT_PREMIUM_BAND_PCT;
+use crate::business_logic::swing::SwingDetector;
+use crate::models::candle::Candle;
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct DoubleTopConfig {
+    pub atr_period: usize,
+    /// Swing reversal size in ATRs.
+    pub rev_atr: f64,
+    /// Candles after peak 1 within which the pattern must confirm.
+    pub max_peak_distance: usize,
+    /// Min candles from peak 1 to peak 2.
+    pub min_peak_distance: usize,
+    /// When set, the longer of the peak 1 to trough and trough to peak 2
+    /// legs may be at most this many times the shorter, in candles.
+    pub max_leg_ratio: Option<f64>,
+    /// Max % difference between the two peaks.
+    pub peak_tolerance_pct: f64,
+    /// Min % drop from peak 1 to the trough for it to become the neckline.
+    pub min_pullback_pct: f64,
+    /// Min % from the peaks down to the neckline.
+    pub min_pattern_height_pct: f64,
+    /// When set, the min drop from peak 1 to the trough in ATRs.
+    pub min_pullback_atr: Option<f64>,
+    /// When set, the min height from the peaks down to the neckline in ATRs.
+    pub min_pattern_height_atr: Option<f64>,
+    /// Whether a set ATR minimum replaces its % one or applies as well.
+    pub atr_rule: AtrRule,
+    /// % distance below peak 1 at which a rising price raises the early warning.
+    pub approach_threshold_pct: f64,
+    /// ATRs below the neckline the break must reach to confirm.
+    pub breakdown_buffer: f64,
+    /// Whether the close or the low must reach the break level.
+    pub confirmation_mode: ConfirmationMode,
+    /// % above peak 1 that invalidates the pattern.
+    pub peak_fail_pct: f64,
+    /// The early warning needs the close above the close this many closed
+    /// candles back; 0 skips the check.
+    pub trend_lookback: usize,
+    /// Replaces the `trend_lookback` check when set.
+    pub ema_filter: Option<EmaTrendFilter>,
+    /// Candles kept per coin for the checks that look back.
+    pub history_window: usize,
+    /// Candles after confirmation before the live state goes back to
+    /// `WATCHING`. The confirmed prices stay in the status until the next
+    /// pattern starts.
+    pub confirmed_ttl_candles: usize,
+    /// Candles after confirmation during which new peaks don't start
+    /// patterns; 0 turns the cooldown off.
+    pub post_confirm_cooldown_candles: usize,
+    /// When set, a close this many ATRs from the neckline ends the cooldown
+    /// early.
+    pub cooldown_exit_atr: Option<f64>,
+    /// Candles after confirmation during which a retest of the neckline is
+    /// tracked; 0 turns retest tracking off.
+    pub retest_window_candles: usize,
+    /// A rally whose high gets within this many ATRs below the neckline
+    /// retests it; a close back below that band rejects it.
+    pub retest_band_atr: f64,
+    /// ATRs above the neckline a close must reach to fail the retest.
+    pub retest_fail_buffer: f64,
+    /// When set, the breakdown candle's volume must be at least this many
+    /// times the average of the `volume_lookback` candles before it to
+    /// confirm.
+    pub min_breakdown_volume_mult: Option<f64>,
+    /// Candles the breakdown volume is averaged over.
+    pub volume_lookback: usize,
+    /// Volume around a peak is summed over this many candles either side
+    /// of it.
+    pub peak_volume_window: usize,
+    /// Peak 2 diverges when its volume is below this fraction of peak 1's.
+    pub volume_divergence_ratio: f64,
+    /// Only raise the early warning when the rally into peak 2 diverges.
+    pub require_volume_divergence: bool,
+    /// Patterns tracked at once, each from its own peak 1.
+    pub max_candidates: usize,
+    /// How much each factor counts toward the confidence score.
+    pub confidence: ConfidenceWeights,
+    /// Points added to the confidence score when other screeners agree.
+    pub confluence: ConfluenceBonus,
+    /// When set, alerts are checked against the trend on a higher
+    /// timeframe.
+    pub htf_filter: Option<HigherTimeframeFilter>,
+}
+
+/// Checks double top alerts against the trend on a higher timeframe, which
+/// the monitor reads once per candle of that timeframe.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct HigherTimeframeFilter {
+    /// Interval the trend is read on.
+    pub interval: String,
+    /// The trend is up while the latest close is above an EMA of this many
+    /// candles, down otherwise.
+    pub ema_period: usize,
+    /// What happens to an alert raised in an up trend.
+    pub mode: HigherTimeframeMode,
+}
+
+impl Default for HigherTimeframeFilter {
+    fn default() -> Self {
+        Self {
+            interval: "1h".to_string(),
+            ema_period: 20,
+            mode: HigherTimeframeMode::Suppress,
+        }
+    }
+}
+
+impl HigherTimeframeFilter {
+    /// Closed candles to classify the trend from, enough for the EMA to
+    /// settle after its seed.
+    pub fn candles_needed(&self) -> usize {
+        3 * self.ema_period
+    }
+
+    /// The trend as of the last of `candles`, oldest first; `None` until
+    /// there are enough of them for the EMA.
+    pub fn classify(&self, candles: &[Candle]) -> Option<TrendDirection> {
+        let mut ema = EmaCalculator::new(self.ema_period);
+        let value = candles.iter().filter_map(|c| ema.update(c)).last()?;
+        let close = candles.last()?.close;
+        Some(if close > value {
+            TrendDirection::Up
+        } else {
+            TrendDirection::Down
+        })
+    }
+}
+
+/// What the higher-timeframe filter does with an alert raised against the
+/// trend. Either way the alert carries the trend as `htf_trend`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum HigherTimeframeMode {
+    /// Drop it; the status keeps the last one dropped.
+    #[default]
+    Suppress,
+    /// Send it anyway.
+    Annotate,
+}
+
+/// How `min_pullback_atr` and `min_pattern_height_atr` combine with the %
+/// minimums.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum AtrRule {
+    /// The ATR minimum is checked instead of the % one.
+    #[default]
+    Instead,
+    /// Both minimums must hold.
+    Both,
+}
+
+/// Relative weights of the factors behind a double top's 0-100 confidence
+/// score. Each factor scores 0 to 1; the score is their weighted mean over
+/// the factors known so far, times 100.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfidenceWeights {
+    /// How close the peaks are: 1 when equal, 0 at `peak_tolerance_pct`.
+    pub peak_similarity: f64,
+    /// Peak average to neckline in ATRs: 1 from 5 ATRs up.
+    pub height_atr: f64,
+    /// Pullback from peak 1 to the neckline: 1 from twice
+    /// `min_pullback_pct` up.
+    pub trough_depth: f64,
+    /// Breakdown candle volume over the average of the `volume_lookback`
+    /// candles before it: 1 from twice the average up.
+    pub breakdown_volume: f64,
+    /// Candles from the trough to peak 2 over those from peak 1 to the
+    /// trough, or the inverse, whichever is at most 1.
+    pub symmetry: f64,
+}
+
+impl Default for ConfidenceWeights {
+    fn default() -> Self {
+        Self {
+            peak_similarity: 25.0,
+            height_atr: 20.0,
+            trough_depth: 20.0,
+            breakdown_volume: 20.0,
+            symmetry: 15.0,
+        }
+    }
+}
+
+impl ConfidenceWeights {
+    fn all(&self) -> [f64; 5] {
+        [
+            self.peak_similarity,
+            self.height_atr,
+            self.trough_depth,
+            self.breakdown_volume,
+            self.symmetry,
+        ]
+    }
+}
+
+/// Points added to a double top's confidence score, up to 100, when the
+/// open-interest and premium screeners back it up.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfluenceBonus {
+    /// Added while the coin trips any open-interest screener rule.
+    pub open_interest: f64,
+    /// Added while the perp has traded more than `premium_band_pct` above
+    /// the oracle price for at least `premium_min_ms`: longs paying up
+    /// into the top.
+    pub premium: f64,
+    pub premium_band_pct: f64,
+    pub premium_min_ms: u64,
+}
+
+impl Default for ConfluenceBonus {
+    fn default() -> Self {
+        Self {
+            open_interest: 10.0,
+            premium: 10.0,
+            premium_band_pct: DEFAULT_PREMIUM_BAND_PCT,
+            premium_min_ms: 30 * 60 * 1000,
+        }
+    }
+}
+
+/// Pattern height, in ATRs, that scores full marks.
+const FULL_HEIGHT_ATR: f64 = 5.0;
+/// Breakdown volume, as a multiple of the average, that scores full marks.
+const FULL_VOLUME_RATIO: f64 = 2.0;
+
+impl Default for DoubleTopConfig {
+    fn default() -> Self {
+        Self {
+            atr_period: 14,
+            rev_atr: 1.0,
+            max_peak_distance: 60,
+            min_peak_distance: 5,
+            max_leg_ratio: None,
+            peak_tolerance_pct: 1.5,
+            min_pullback_pct: 2.0,
+            min_pattern_height_pct: 2.0,
+            min_pullback_atr: None,
+            min_pattern_height_atr: None,
+            atr_rule: AtrRule::Instead,
+            approach_threshold_pct: 1.0,
+            breakdown_buffer: 0.3,
+            confirmation_mode: ConfirmationMode::Close,
+            peak_fail_pct: 1.5,
+            trend_lookback: 3,
+            ema_filter: None,
+            history_window: 100,
+            confirmed_ttl_candles: 24,
+            post_confirm_cooldown_candles: 0,
+            cooldown_exit_atr: None,
+            retest_window_candles: 12,
+            retest_band_atr: 0.5,
+            retest_fail_buffer: 0.5,
+            min_breakdown_volume_mult: None,
+            volume_lookback: 20,
+            peak_volume_window: 2,
+            volume_divergence_ratio: 0.8,
+            require_volume_divergence: false,
+            max_candidates: 3,
+            confidence: ConfidenceWeights::default(),
+            confluence: ConfluenceBonus::default(),
+            htf_filter: None,
+        }
+    }
+}
+
+impl DoubleTopConfig {
+    /// Problems that would make the config unusable, one message each.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let mut check = |ok: bool, message: &str| {
+            if !ok {
+                errors.push(message.to_string());
+            }
+        };
+        check(self.atr_period > 0, "atr_period must be positive");
+        check(
+            self.max_peak_distance > self.trend_lookback,
+            "max_peak_distance must be greater than trend_lookback",
+        );
+        check(
+            self.min_peak_distance < self.max_peak_distance,
+            "min_peak_distance must be less than max_peak_distance",
+        );
+        check(
+            self.max_leg_ratio
+                .is_none_or(|ratio| ratio >= 1.0 && ratio.is_finite()),
+            "max_leg_ratio must be at least 1",
+        );
+        check(
+            self.history_window > self.trend_lookback,
+            "history_window must be greater than trend_lookback",
+        );
+        if let Some(filter) = self.ema_filter {
+            check(filter.period > 0, "ema_filter.period must be positive");
+            check(
+                filter.slope_lookback > 0,
+                "ema_filter.slope_lookback must be positive",
+            );
+        }
+        check(
+            self.confirmed_ttl_candles > 0,
+            "confirmed_ttl_candles must be positive",
+        );
+        check(
+            self.cooldown_exit_atr
+                .is_none_or(|atr| atr > 0.0 && atr.is_finite()),
+            "cooldown_exit_atr must be positive",
+        );
+        check(
+            self.retest_band_atr > 0.0 && self.retest_band_atr.is_finite(),
+            "retest_band_atr must be positive",
+        );
+        check(
+            self.retest_fail_buffer >= 0.0 && self.retest_fail_buffer.is_finite(),
+            "retest_fail_buffer must not be negative",
+        );
+        check(
+            self.volume_lookback > 0 && self.volume_lookback < self.history_window,
+            "volume_lookback must be positive and less than history_window",
+        );
+        check(
+            self.min_breakdown_volume_mult
+                .is_none_or(|mult| mult > 0.0 && mult.is_finite()),
+            "min_breakdown_volume_mult must be positive",
+        );
+        check(
+            self.history_window > 2 * self.peak_volume_window,
+            "history_window must be greater than twice peak_volume_window",
+        );
+        check(
+            self.volume_divergence_ratio > 0.0 && self.volume_divergence_ratio.is_finite(),
+            "volume_divergence_ratio must be positive",
+        );
+        check(self.max_candidates > 0, "max_candidates must be positive");
+        let weights = self.confidence.all();
+        check(
+            weights.iter().all(|w| *w >= 0.0 && w.is_finite()),
+            "confidence weights must not be negative",
+        );
+        check(
+            weights.iter().sum::<f64>() > 0.0,
+            "confidence weights must not all be zero",
+        );
+        check(
+            [self.confluence.open_interest, self.confluence.premium]
+                .iter()
+                .all(|b| *b >= 0.0 && b.is_finite()),
+            "confluence bonuses must not be negative",
+        );
+        check(
+            self.confluence.premium_band_pct > 0.0 && self.confluence.premium_band_pct.is_finite(),
+            "confluence.premium_band_pct must be positive",
+        );
+        check(
+            self.rev_atr > 0.0 && self.rev_atr.is_finite(),
+            "rev_atr must be positive",
+        );
+        check(
+            self.breakdown_buffer >= 0.0 && self.breakdown_buffer.is_finite(),
+            "breakdown_buffer must not be negative",
+        );
+        check(
+            self.peak_tolerance_pct > 0.0 && self.peak_tolerance_pct <= 100.0,
+            "peak_tolerance_pct must be in (0, 100]",
+        );
+        check(
+            self.min_pullback_pct > 0.0 && self.min_pullback_pct <= 100.0,
+            "min_pullback_pct must be in (0, 100]",
+        );
+        check(
+            self.min_pattern_height_pct > 0.0 && self.min_pattern_height_pct <= 100.0,
+            "min_pattern_height_pct must be in (0, 100]",
+        );
+        check(
+            self.min_pullback_atr
+                .is_none_or(|atr| atr > 0.0 && atr.is_finite()),
+            "min_pullback_atr must be positive",
+        );
+        check(
+            self.min_pattern_height_atr
+                .is_none_or(|atr| atr > 0.0 && atr.is_finite()),
+            "min_pattern_height_atr must be positive",
+        );
+        check(
+            self.approach_threshold_pct > 0.0 && self.approach_threshold_pct <= 100.0,
+            "approach_threshold_pct must be in (0, 100]",
+        );
+        check(
+            self.peak_fail_pct > 0.0 && self.peak_fail_pct <= 100.0,
+            "peak_fail_pct must be in (0, 100]",
+        );
+        if let Some(filter) = &self.htf_filter {
+            check(
+                intervals::is_supported(&filter.interval),
+                "htf_filter.interval must be a supported interval",
+            );
+            check(
+                filter.ema_period > 0,
+                "htf_filter.ema_period must be positive",
+            );
+        }
+        errors
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DoubleTopState {
+    /// Looking for a first peak.
+    Watching,
+    /// Peak 1 confirmed, waiting for a deep enough pullback.
+    PeakFound,
+    /// Neckline confirmed, waiting for price to come back up.
+    TroughFound,
+    /// Price is retesting peak 1 (early warning raised).
+    Forming,
+    Confirmed,
+    /// After confirmation, price rallied back up to the neckline.
+    RetestWatch,
+    /// The retest was rejected (retest alert raised).
+    Retested,
+    /// Past `confirmed_ttl_candles` but still in the post-confirmation
+    /// cooldown, so new peaks are ignored.
+    CoolingDown,
+    Invalidated,
+}
+
+impl DoubleTopState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DoubleTopState::Watching => "WATCHING",
+            DoubleTopState::PeakFound => "PEAK_FOUND",
+            DoubleTopState::TroughFound => "TROUGH_FOUND",
+            DoubleTopState::Forming => "FORMING",
+            DoubleTopState::Confirmed => "CONFIRMED",
+            DoubleTopState::RetestWatch => "RETEST_WATCH",
+            DoubleTopState::Retested => "RETESTED",
+            DoubleTopState::CoolingDown => "COOLING_DOWN",
+            DoubleTopState::Invalidated => "INVALIDATED",
+        }
+    }
+
+    fn in_pattern(&self) -> bool {
+        matches!(
+            self,
+            DoubleTopState::PeakFound | DoubleTopState::TroughFound | DoubleTopState::Forming
+        )
+    }
+
+    /// Whether a confirmed pattern is still on show.
+    fn after_confirmation(&self) -> bool {
+        matches!(
+            self,
+            DoubleTopState::Confirmed | DoubleTopState::RetestWatch | DoubleTopState::Retested
+        )
+    }
+}
+
+/// Why a pattern went to `INVALIDATED`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InvalidationReason {
+    /// A high broke the level `peak_fail_pct` above peak 1.
+    PeakExceeded { fail_level: f64, high: f64 },
+    /// Peak 1 fell more than `max_peak_distance` candles behind.
+    TimedOut { candles_since_peak1: u64 },
+    /// A close back above the neckline, `retest_fail_buffer` ATRs up, after
+    /// confirmation.
+    RetestFailed { fail_level: f64, close: f64 },
+    /// [`DoubleTopDetector::reset`] was called.
+    ManualReset,
+}
+
+impl InvalidationReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InvalidationReason::PeakExceeded { .. } => "peak_exceeded",
+            InvalidationReason::TimedOut { .. } => "timed_out",
+            InvalidationReason::RetestFailed { .. } => "retest_failed",
+            InvalidationReason::ManualReset => "manual_reset",
+        }
+    }
+}
+
+/// Where a coin's double top stands, as published to the pattern state.
+///
+/// The top-level fields describe the most advanced candidate pattern; every
+/// candidate is listed in `candidates`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct DoubleTopStatus {
+    pub coin: String,
+    /// `WATCHING`, `PEAK_FOUND`, `TROUGH_FOUND`, `FORMING`, `CONFIRMED`,
+    /// `RETEST_WATCH`, `RETESTED`, `COOLING_DOWN` or `INVALIDATED`.
+    pub state: String,
+    pub peak1_price: Option<f64>,
+    pub neckline_price: Option<f64>,
+    pub peak2_price: Option<f64>,
+    /// Open time of the candle that made peak 1 (epoch ms).
+    pub peak1_time_ms: Option<u64>,
+    /// Open time of the candle that made the neckline's trough (epoch ms).
+    pub trough_time_ms: Option<u64>,
+    /// Open time of the candle that made peak 2 (epoch ms).
+    pub peak2_time_ms: Option<u64>,
+    /// Candles from peak 1 to the latest candle, gaps included.
+    pub candles_since_peak1: Option<u64>,
+    /// Measured-move target, once the pattern has confirmed.
+    pub target_price: Option<f64>,
+    /// Height from the peak average to the neckline as a % of that
+    /// average, once the pattern has confirmed.
+    pub pattern_height_pct: Option<f64>,
+    /// Highest high of the rally back to the neckline, once one started.
+    pub retest_high: Option<f64>,
+    /// Volume around peak 2 over the volume around peak 1.
+    pub peak_volume_ratio: Option<f64>,
+    /// Whether peak 2 formed on lower volume, by `volume_divergence_ratio`.
+    pub volume_divergence: Option<bool>,
+    /// 0-100 score of how clean the pattern is, while `FORMING` and once
+    /// confirmed.
+    pub confidence: Option<f64>,
+    /// Latest mark premium over the oracle price (percent), once sampled.
+    pub premium_pct: Option<f64>,
+    /// Open-interest screener rules the coin trips.
+    pub oi_rules: Vec<OiRule>,
+    /// `peak_exceeded`, `timed_out`, `retest_failed` or `manual_reset`,
+    /// while `INVALIDATED`.
+    pub invalidation_reason: Option<String>,
+    /// The levels behind `invalidation_reason`.
+    pub invalidation: Option<InvalidationReason>,
+    /// Candles left of the post-confirmation cooldown, during which new
+    /// peaks are ignored.
+    pub cooldown_remaining_candles: Option<u64>,
+    /// Second peaks rejected so far, over every candidate.
+    pub diagnostics: DoubleTopDiagnostics,
+    /// Higher-timeframe trend alerts are checked against, when the filter
+    /// is on and a trend has been read.
+    pub htf_trend: Option<TrendDirection>,
+    /// Last alert the higher-timeframe filter dropped.
+    pub suppressed_alert: Option<Box<PatternAlert>>,
+    /// Every pattern being tracked, oldest peak 1 first.
+    pub candidates: Vec<DoubleTopCandidateStatus>,
+}
+
+/// Second peaks the detector turned down, by reason.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct DoubleTopDiagnostics {
+    /// Fewer than `min_peak_distance` candles after peak 1.
+    pub too_close: u64,
+    /// Legs further apart in length than `max_leg_ratio`.
+    pub asymmetric: u64,
+}
+
+/// One candidate pattern in a [`DoubleTopStatus`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct DoubleTopCandidateStatus {
+    pub state: String,
+    pub peak1_price: f64,
+    pub neckline_price: Option<f64>,
+    pub peak2_price: Option<f64>,
+    pub peak1_time_ms: u64,
+    pub trough_time_ms: Option<u64>,
+    pub peak2_time_ms: Option<u64>,
+    pub invalidation: Option<InvalidationReason>,
+}
+
+/// Why a second peak within tolerance was turned down.
+#[derive(Debug, Clone, Copy)]
+enum PeakRejection {
+    TooClose,
+    Asymmetric,
+}
+
+/// A swing high or low.
+#[derive(Debug, Clone, Copy)]
+struct Pivot {
+    price: f64,
+    /// Open time of the candle that made the extreme.
+    open_time: u64,
+}
+
+/// The post-confirmation cooldown.
+#[derive(Debug, Clone, Copy)]
+struct Cooldown {
+    /// Open time of the confirming candle.
+    since: u64,
+    neckline: f64,
+}
+
+/// One possible double top, from its first peak on.
+#[derive(Debug, Clone)]
+struct Candidate {
+    state: DoubleTopState,
+    peak1: Pivot,
+    neckline: Option<Pivot>,
+    peak2: Option<Pivot>,
+    warned: bool,
+    /// Open time of the candle that confirmed the pattern.
+    confirmed_at: Option<u64>,
+    retest_high: Option<f64>,
+    /// Breakdown candle volume over the average of the candles before it.
+    breakdown_volume_ratio: Option<f64>,
+    invalidation: Option<InvalidationReason>,
+}
+
+impl Candidate {
+    fn new(peak1: Pivot) -> Self {
+        Self {
+            state: DoubleTopState::PeakFound,
+            peak1,
+            neckline: None,
+            peak2: None,
+            warned: false,
+            confirmed_at: None,
+            retest_high: None,
+            breakdown_volume_ratio: None,
+            invalidation: None,
+        }
+    }
+
+    fn invalidate(&mut self, reason: InvalidationReason) {
+        self.state = DoubleTopState::Invalidated;
+        self.invalidation = Some(reason);
+    }
+
+    /// How far along the pattern is, for picking the one to report.
+    fn progress(&self) -> u8 {
+        match self.state {
+            DoubleTopState::Watching | DoubleTopState::Invalidated => 0,
+            DoubleTopState::CoolingDown => 1,
+            DoubleTopState::PeakFound => 2,
+            DoubleTopState::TroughFound => 3,
+            DoubleTopState::Forming => 4,
+            DoubleTopState::Confirmed | DoubleTopState::RetestWatch | DoubleTopState::Retested => 5,
+        }
+    }
+
+    fn neckline_price(&self) -> Option<f64> {
+        self.neckline.map(|p| p.price)
+    }
+
+    /// Average of the two peaks, once there are two.
+    fn peak_average(&self) -> Option<f64> {
+        Some((self.peak1.price + self.peak2?.price) / 2.0)
+    }
+
+    /// Height from `top` down to the neckline as a % of `top`.
+    fn height_pct(&self, top: f64) -> Option<f64> {
+        Some((top - self.neckline_price()?) / top * 100.0)
+    }
+
+    /// The neckline less the pattern height, measured from the peak
+    /// average.
+    fn target_price(&self) -> Option<f64> {
+        let neckline = self.neckline_price()?;
+        Some(neckline - (self.peak_average()? - neckline))
+    }
+
+    fn status(&self) -> DoubleTopCandidateStatus {
+        DoubleTopCandidateStatus {
+            state: self.state.as_str().to_string(),
+            peak1_price: self.peak1.price,
+            neckline_price: self.neckline_price(),
+            peak2_price: self.peak2.map(|p| p.price),
+            peak1_time_ms: self.peak1.open_time,
+            trough_time_ms: self.neckline.map(|p| p.open_time),
+            peak2_time_ms: self.peak2.map(|p| p.open_time),
+            invalidation: self.invalidation,
+        }
+    }
+}
+
+/// Per-coin double top state machine fed one closed candle at a time.
+///
+/// Up to `max_candidates` patterns are tracked at once, each from its own
+/// peak 1, so a newer setup isn't missed while an older one is still
+/// pending.
+#[derive(Debug, Clone)]
+pub struct DoubleTopDetector {
+    coin: String,
+    config: DoubleTopConfig,
+    /// Detection interval the candles are on; distances are counted in it.
+    interval: String,
+    atr: AtrCalculator,
+    swings: SwingDetector,
+    ema: Option<EmaCalculator>,
+    /// Recent EMA values, enough to measure its slope.
+    emas: VecDeque<f64>,
+    /// The last `history_window` candles.
+    candles: VecDeque<Candle>,
+    /// Oldest peak 1 first. Finished ones stay until the next peak 1.
+    candidates: Vec<Candidate>,
+    cooldown: Option<Cooldown>,
+    /// Latest higher-timeframe trend, once the monitor has read one.
+    htf_trend: Option<TrendDirection>,
+    /// Last alert the higher-timeframe filter dropped.
+    suppressed_alert: Option<PatternAlert>,
+    confluence: Confluence,
+    diagnostics: DoubleTopDiagnostics,
+}
+
+impl DoubleTopDetector {
+    /// `interval` must be supported; it is what candle distances are
+    /// measured in.
+    pub fn new(coin: impl Into<String>, config: DoubleTopConfig, interval: &str) -> Self {
+        Self {
+            coin: coin.into(),
+            atr: AtrCalculator::new(config.atr_period),
+            swings: SwingDetector::new(config.rev_atr),
+            interval: interval.to_string(),
+            ema: config.ema_filter.map(|f| EmaCalculator::new(f.period)),
+            emas: VecDeque::new(),
+            candles: VecDeque::with_capacity(config.history_window),
+            config,
+            candidates: Vec::new(),
+            cooldown: None,
+            diagnostics: DoubleTopDiagnostics::default(),
+            htf_trend: None,
+            suppressed_alert: None,
+            confluence: Confluence::default(),
+        }
+    }
+
+    /// Second peaks rejected so far for their spacing or symmetry.
+    pub fn diagnostics(&self) -> DoubleTopDiagnostics {
+        self.diagnostics
+    }
+
+    /// The most advanced candidate, the oldest of equals.
+    fn primary(&self) -> Option<&Candidate> {
+        self.candidates
+            .iter()
+            .rev()
+            .max_by_key(|candidate| candidate.progress())
+    }
+
+    pub fn state(&self) -> DoubleTopState {
+        self.primary()
+            .map_or(DoubleTopState::Watching, |candidate| candidate.state)
+    }
+
+    pub fn peak1_price(&self) -> Option<f64> {
+        self.primary().map(|candidate| candidate.peak1.price)
+    }
+
+    pub fn neckline_price(&self) -> Option<f64> {
+        self.primary()?.neckline_price()
+    }
+
+    pub fn peak2_price(&self) -> Option<f64> {
+        self.primary()?.peak2.map(|p| p.price)
+    }
+
+    /// Why the reported pattern was invalidated, until a new peak 1.
+    pub fn invalidation_reason(&self) -> Option<InvalidationReason> {
+        self.primary()?.invalidation
+    }
+
+    /// Invalidate every pattern in progress or on show, as
+    /// [`InvalidationReason::ManualReset`], and end any cooldown. The candle
+    /// history is kept.
+    pub fn reset(&mut self) {
+        for candidate in &mut self.candidates {
+            if candidate.state.in_pattern()
+                || candidate.state.after_confirmation()
+                || candidate.state == DoubleTopState::CoolingDown
+            {
+                candidate.invalidate(InvalidationReason::ManualReset);
+            }
+        }
+        self.cooldown = None;
+    }
+
+    /// Candles left of the post-confirmation cooldown, while it lasts.
+    pub fn cooldown_remaining_candles(&self) -> Option<u64> {
+        let cooldown = self.cooldown?;
+        let latest = self.candles.back()?;
+        let elapsed = intervals::candles_between(&self.interval, cooldown.since, latest.open_time)?;
+        Some((self.config.post_confirm_cooldown_candles as u64).saturating_sub(elapsed))
+    }
+
+    /// Status of every candidate, oldest peak 1 first.
+    pub fn candidates(&self) -> Vec<DoubleTopCandidateStatus> {
+        self.candidates.iter().map(Candidate::status).collect()
+    }
+
+    /// Candles from the one that made peak 1 to the latest one, counted on
+    /// the detection interval so missing candles still count.
+    pub fn candles_since_peak1(&self) -> Option<u64> {
+        self.since_peak1(self.primary()?)
+    }
+
+    fn since_peak1(&self, candidate: &Candidate) -> Option<u64> {
+        let latest = self.candles.back()?;
+        intervals::candles_between(&self.interval, candidate.peak1.open_time, latest.open_time)
+    }
+
+    pub fn status(&self) -> DoubleTopStatus {
+        let primary = self.primary();
+        let peak_volume_ratio = self.peak_volume_ratio();
+        DoubleTopStatus {
+            coin: self.coin.clone(),
+            state: self.state().as_str().to_string(),
+            peak1_price: self.peak1_price(),
+            neckline_price: self.neckline_price(),
+            peak2_price: self.peak2_price(),
+            peak1_time_ms: primary.map(|c| c.peak1.open_time),
+            trough_time_ms: primary.and_then(|c| c.neckline).map(|p| p.open_time),
+            peak2_time_ms: primary.and_then(|c| c.peak2).map(|p| p.open_time),
+            candles_since_peak1: self.candles_since_peak1(),
+            target_price: primary.and_then(|c| c.confirmed_at.and(c.target_price())),
+            pattern_height_pct: primary.and_then(|c| {
+                c.confirmed_at
+                    .and(c.peak_average())
+                    .and_then(|average| c.height_pct(average))
+            }),
+            retest_high: primary.and_then(|c| c.retest_high),
+            peak_volume_ratio,
+            volume_divergence: peak_volume_ratio
+                .map(|ratio| ratio < self.config.volume_divergence_ratio),
+            confidence: self.confidence(),
+            premium_pct: self.confluence.premium_pct,
+            oi_rules: self.confluence.oi_rules.clone(),
+            invalidation_reason: self
+                .invalidation_reason()
+                .map(|reason| reason.as_str().to_string()),
+            invalidation: self.invalidation_reason(),
+            cooldown_remaining_candles: self.cooldown_remaining_candles(),
+            diagnostics: self.diagnostics,
+            htf_trend: self.config.htf_filter.as_ref().and(self.htf_trend),
+            suppressed_alert: self.suppressed_alert.clone().map(Box::new),
+            candidates: self.candidates(),
+        }
+    }
+
+    /// Volume around peak 2 over the volume around peak 1, once both peaks
+    /// are known and still in the candle window.
+    pub fn peak_volume_ratio(&self) -> Option<f64> {
+        let primary = self.primary()?;
+        let peak1 = self.volume_around(primary.peak1.open_time)?;
+        let peak2 = self.volume_around(primary.peak2?.open_time)?;
+        (peak1 > 0.0).then(|| peak2 / peak1)
+    }
+
+    /// Volume of the `peak_volume_window` candles either side of the one
+    /// that opened at `open_time`, and of that candle.
+    fn volume_around(&self, open_time: u64) -> Option<f64> {
+        let index = self.candles.iter().position(|c| c.open_time == open_time)?;
+        let window = self.config.peak_volume_window;
+        Some(
+            self.candles
+                .iter()
+                .skip(index.saturating_sub(window))
+                .take(index.min(window) + window + 1)
+                .map(|c| c.volume)
+                .sum(),
+        )
+    }
+
+    /// Whether the rally into a second peak that hasn't formed yet is on
+    /// lower volume than peak 1: the latest candles, as many as a peak's
+    /// volume is summed over, against peak 1's.
+    fn rally_diverges(&self, candidate: &Candidate) -> bool {
+        let Some(peak1) = self.volume_around(candidate.peak1.open_time) else {
+            return false;
+        };
+        let recent: f64 = self
+            .candles
+            .iter()
+            .rev()
+            .take(2 * self.config.peak_volume_window + 1)
+            .map(|c| c.volume)
+            .sum();
+        peak1 > 0.0 && recent / peak1 < self.config.volume_divergence_ratio
+    }
+
+    /// 0-100 confidence in the pattern while it is forming or confirmed,
+    /// weighted by [`DoubleTopConfig::confidence`], plus any
+    /// [`DoubleTopConfig::confluence`] bonuses.
+    pub fn confidence(&self) -> Option<f64> {
+        let candidate = self.primary()?;
+        if candidate.state != DoubleTopState::Forming && !candidate.state.after_confirmation() {
+            return None;
+        }
+        let peak1 = candidate.peak1;
+        let neckline = candidate.neckline?;
+        let weights = self.config.confidence;
+        let top = candidate.peak_average().unwrap_or(peak1.price);
+        let pullback_pct = (peak1.price - neckline.price) / peak1.price * 100.0;
+        let mut factors = vec![
+            (
+                weights.trough_depth,
+                pullback_pct / (2.0 * self.config.min_pullback_pct),
+            ),
+            (
+                weights.height_atr,
+                (top - neckline.price) / self.atr.value()? / FULL_HEIGHT_ATR,
+            ),
+        ];
+        if let Some(peak2) = candidate.peak2 {
+            let diff_pct = (peak1.price - peak2.price).abs() / top * 100.0;
+            factors.push((
+                weights.peak_similarity,
+                1.0 - diff_pct / self.config.peak_tolerance_pct,
+            ));
+            let leg = |from: u64, to: u64| {
+                intervals::candles_between(&self.interval, from, to).unwrap_or(0) as f64
+            };
+            let (first, second) = (
+                leg(peak1.open_time, neckline.open_time),
+                leg(neckline.open_time, peak2.open_time),
+            );
+            if first > 0.0 && second > 0.0 {
+                factors.push((weights.symmetry, first.min(second) / first.max(second)));
+            }
+        }
+        if let Some(ratio) = candidate.breakdown_volume_ratio {
+            factors.push((weights.breakdown_volume, ratio / FULL_VOLUME_RATIO));
+        }
+
+        let total: f64 = factors.iter().map(|(weight, _)| weight).sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let score: f64 = factors
+            .iter()
+            .map(|(weight, score)| weight * score.clamp(0.0, 1.0))
+            .sum();
+        Some((score / total * 100.0 + self.confluence_bonus()).min(100.0))
+    }
+
+    fn confluence_bonus(&self) -> f64 {
+        let bonus = self.config.confluence;
+        let mut points = 0.0;
+        if !self.confluence.oi_rules.is_empty() {
+            points += bonus.open_interest;
+        }
+        if self
+            .confluence
+            .premium_pct
+            .is_some_and(|pct| pct > bonus.premium_band_pct)
+            && self.confluence.premium_extreme_ms >= bonus.premium_min_ms
+        {
+            points += bonus.premium;
+        }
+        points
+    }
+
+    /// Feed the next closed candle, returning an alert if it raised one.
+    ///
+    /// Candles at or before the latest one already fed are ignored. Nothing
+    /// happens until the ATR has warmed up. When several candidates raise
+    /// alerts on the same candle only the most significant is returned:
+    /// a confirmation, then a retest, then an early warning.
+    pub fn update(&mut self, candle: &Candle) -> Option<PatternAlert> {
+        if self
+            .candles
+            .back()
+            .is_some_and(|last| candle.open_time <= last.open_time)
+        {
+            return None;
+        }
+        self.candles.push_back(*candle);
+        if self.candles.len() > self.config.history_window {
+            self.candles.pop_front();
+        }
+        self.update_ema(candle);
+        let atr = self.atr.update(candle)?;
+        self.end_cooldown(candle, atr);
+
+        let mut alerts = Vec::new();
+        let mut candidates = std::mem::take(&mut self.candidates);
+        for candidate in &mut candidates {
+            self.expire_confirmation(candidate, candle);
+            self.check_invalidation(candidate, candle);
+            // Ahead of the swings, so a peak made by the retest rally can't
+            // start a new pattern before the retest resolves.
+            alerts.extend(self.check_retest(candidate, candle, atr));
+        }
+
+        if let Some(swing) = self.swings.update(candle, atr) {
+            let point = Pivot {
+                price: swing.price,
+                open_time: swing.open_time,
+            };
+            if swing.is_peak {
+                let mut used = false;
+                for candidate in &mut candidates {
+                    match self.on_peak(candidate, point) {
+                        Ok(consumed) => used |= consumed,
+                        Err(PeakRejection::TooClose) => self.diagnostics.too_close += 1,
+                        Err(PeakRejection::Asymmetric) => self.diagnostics.asymmetric += 1,
+                    }
+                }
+                if !used && self.cooldown.is_none() {
+                    self.start_candidate(&mut candidates, point);
+                }
+                // Re-anchoring can bring two candidates to the same peak 1.
+                let mut seen = Vec::new();
+                candidates.retain(|c| {
+                    let first = !seen.contains(&c.peak1.open_time);
+                    seen.push(c.peak1.open_time);
+                    first
+                });
+            } else {
+                for candidate in &mut candidates {
+                    self.on_trough(candidate, point, atr);
+                }
+            }
+        }
+
+        for candidate in &mut candidates {
+            if matches!(
+                candidate.state,
+                DoubleTopState::TroughFound | DoubleTopState::Forming
+            ) {
+                let alert = self
+                    .check_confirmation(candidate, candle, atr)
+                    .or_else(|| self.check_early_warning(candidate, candle, atr));
+                alerts.extend(alert);
+            }
+        }
+        if self.config.post_confirm_cooldown_candles > 0 {
+            let confirmed = candidates
+                .iter()
+                .filter(|c| c.confirmed_at == Some(candle.open_time))
+                .find_map(|c| c.neckline_price());
+            if let Some(neckline) = confirmed {
+                self.cooldown = Some(Cooldown {
+                    since: candle.open_time,
+                    neckline,
+                });
+            }
+        }
+        self.candidates = candidates;
+
+        [
+            AlertStage::Confirmation,
+            AlertStage::Retest,
+            AlertStage::EarlyWarning,
+        ]
+        .into_iter()
+        .find_map(|stage| alerts.iter().find(|a| a.stage == stage).cloned())
+        .and_then(|alert| self.check_htf(alert))
+    }
+
+    /// Set the higher-timeframe trend alerts are checked against.
+    pub fn set_htf_trend(&mut self, trend: TrendDirection) {
+        self.htf_trend = Some(trend);
+    }
+
+    /// Set the screener signals the confidence score's bonuses come from.
+    pub fn set_confluence(&mut self, confluence: &Confluence) {
+        self.confluence = confluence.clone();
+    }
+
+    /// Tag `alert` with the higher-timeframe trend and, in suppress mode,
+    /// drop it when that trend is up. Alerts pass untagged until a trend is
+    /// known.
+    fn check_htf(&mut self, alert: PatternAlert) -> Option<PatternAlert> {
+        let Some(filter) = &self.config.htf_filter else {
+            return Some(alert);
+        };
+        let alert = PatternAlert {
+            htf_trend: self.htf_trend,
+            ..alert
+        };
+        if filter.mode == HigherTimeframeMode::Suppress
+            && self.htf_trend == Some(TrendDirection::Up)
+        {
+            self.suppressed_alert = Some(alert);
+            return None;
+        }
+        Some(alert)
+    }
+
+    fn update_ema(&mut self, candle: &Candle) {
+        let (Some(ema), Some(filter)) = (self.ema.as_mut(), self.config.ema_filter) else {
+            return;
+        };
+        if let Some(value) = ema.update(candle) {
+            self.emas.push_back(value);
+            if self.emas.len() > filter.slope_lookback + 1 {
+                self.emas.pop_front();
+            }
+        }
+    }
+
+    /// Whether price is trending up into peak 1. With the EMA filter,
+    /// `candle` closed above an EMA that rose over `slope_lookback` candles.
+    /// Otherwise it closed above the close `trend_lookback` candles before
+    /// it: always true for a lookback of 0, and false until that many
+    /// candles have been seen.
+    fn is_rising(&self, candle: &Candle) -> bool {
+        if let Some(filter) = self.config.ema_filter {
+            let (Some(&oldest), Some(&ema)) = (self.emas.front(), self.emas.back()) else {
+                return false;
+            };
+            return self.emas.len() > filter.slope_lookback && candle.close > ema && ema > oldest;
+        }
+        match self.config.trend_lookback {
+            0 => true,
+            back => self
+                .close_before(back)
+                .is_some_and(|close| candle.close > close),
+        }
+    }
+
+    /// Close of the candle `back` candles before the latest one, if it is
+    /// still in the window.
+    fn close_before(&self, back: usize) -> Option<f64> {
+        let index = self.candles.len().checked_sub(back + 1)?;
+        self.candles.get(index).map(|c| c.close)
+    }
+
+    /// Track a new pattern from `peak`, dropping finished ones and, at the
+    /// cap, the oldest still in progress.
+    fn start_candidate(&self, candidates: &mut Vec<Candidate>, peak: Pivot) {
+        candidates.retain(|c| c.state.in_pattern() || c.state.after_confirmation());
+        if candidates.len() >= self.config.max_candidates {
+            let oldest = candidates
+                .iter()
+                .position(|c| c.state.in_pattern())
+                .unwrap_or(0);
+            candidates.remove(oldest);
+        }
+        candidates.push(Candidate::new(peak));
+    }
+
+    /// Apply a new swing high to `candidate`. Returns whether the candidate
+    /// used it; an unused or rejected peak starts a candidate of its own.
+    fn on_peak(&self, candidate: &mut Candidate, peak: Pivot) -> Result<bool, PeakRejection> {
+        let peak1 = candidate.peak1;
+        match candidate.state {
+            // The pullback was too shallow; re-anchor on a higher high.
+            DoubleTopState::PeakFound => {
+                if peak.price > peak1.price {
+                    *candidate = Candidate::new(peak);
+                    return Ok(true);
+                }
+                Ok(false)
+            }
+            DoubleTopState::TroughFound | DoubleTopState::Forming => {
+                let average = (peak1.price + peak.price) / 2.0;
+                let diff_pct = (peak1.price - peak.price).abs() / average * 100.0;
+                if diff_pct > self.config.peak_tolerance_pct {
+                    // Didn't come back to peak 1: leave this one waiting.
+                    return Ok(false);
+                }
+                self.check_spacing(candidate, peak)?;
+                candidate.peak2 = Some(peak);
+                candidate.state = DoubleTopState::Forming;
+                Ok(true)
+            }
+            // The retest rally's own high; the retest decides what's next.
+            DoubleTopState::RetestWatch => Ok(true),
+            _ => Ok(false),
+        }
+    }
+
+    /// Whether `peak` is far enough from peak 1, and the two legs close
+    /// enough in length, to be peak 2.
+    fn check_spacing(&self, candidate: &Candidate, peak: Pivot) -> Result<(), PeakRejection> {
+        let leg = |from: u64, to: u64| intervals::candles_between(&self.interval, from, to);
+        let distance = leg(candidate.peak1.open_time, peak.open_time).unwrap_or(0);
+        if distance < self.config.min_peak_distance as u64 {
+            return Err(PeakRejection::TooClose);
+        }
+        let (Some(max_ratio), Some(neckline)) = (self.config.max_leg_ratio, candidate.neckline)
+        else {
+            return Ok(());
+        };
+        let first = leg(candidate.peak1.open_time, neckline.open_time).unwrap_or(0) as f64;
+        let second = leg(neckline.open_time, peak.open_time).unwrap_or(0) as f64;
+        if first.max(second) > max_ratio * first.min(second) {
+            return Err(PeakRejection::Asymmetric);
+        }
+        Ok(())
+    }
+
+    /// Whether the drop from `top` to `bottom` is at least `min_pct` % and,
+    /// when set, `min_atr` ATRs, as `atr_rule` combines them.
+    fn deep_enough(
+        &self,
+        top: f64,
+        bottom: f64,
+        min_pct: f64,
+        min_atr: Option<f64>,
+        atr: f64,
+    ) -> bool {
+        let by_pct = (top - bottom) / top * 100.0 >= min_pct;
+        let Some(min_atr) = min_atr else {
+            return by_pct;
+        };
+        let by_atr = top - bottom >= min_atr * atr;
+        match self.config.atr_rule {
+            AtrRule::Instead => by_atr,
+            AtrRule::Both => by_pct && by_atr,
+        }
+    }
+
+    /// Whether the pattern is tall enough measured from `top`.
+    fn tall_enough(&self, candidate: &Candidate, top: f64, atr: f64) -> Option<bool> {
+        Some(self.deep_enough(
+            top,
+            candidate.neckline_price()?,
+            self.config.min_pattern_height_pct,
+            self.config.min_pattern_height_atr,
+            atr,
+        ))
+    }
+
+    fn on_trough(&self, candidate: &mut Candidate, trough: Pivot, atr: f64) {
+        let pulled_back = self.deep_enough(
+            candidate.peak1.price,
+            trough.price,
+            self.config.min_pullback_pct,
+            self.config.min_pullback_atr,
+            atr,
+        );
+        match candidate.state {
+            DoubleTopState::PeakFound if pulled_back => {
+                candidate.neckline = Some(trough);
+                candidate.state = DoubleTopState::TroughFound;
+            }
+            // The neckline is the lowest low between the peaks.
+            DoubleTopState::TroughFound
+                if candidate
+                    .neckline
+                    .is_some_and(|neckline| trough.price < neckline.price) =>
+            {
+                candidate.neckline = Some(trough);
+            }
+            _ => {}
+        }
+    }
+
+    /// End the cooldown once `post_confirm_cooldown_candles` have passed
+    /// since confirmation or a close gets `cooldown_exit_atr` ATRs from the
+    /// neckline.
+    fn end_cooldown(&mut self, candle: &Candle, atr: f64) {
+        let Some(cooldown) = self.cooldown else {
+            return;
+        };
+        let elapsed = intervals::candles_between(&self.interval, cooldown.since, candle.open_time);
+        let timed_out = elapsed
+            .is_some_and(|candles| candles >= self.config.post_confirm_cooldown_candles as u64);
+        let moved_away = self
+            .config
+            .cooldown_exit_atr
+            .is_some_and(|exit| (candle.close - cooldown.neckline).abs() >= exit * atr);
+        if timed_out || moved_away {
+            self.cooldown = None;
+        }
+    }
+
+    /// Send the live state back to `WATCHING` once `confirmed_ttl_candles`
+    /// have passed since confirmation, keeping the pattern's prices. During
+    /// a cooldown it goes to `COOLING_DOWN` until that ends.
+    fn expire_confirmation(&self, candidate: &mut Candidate, candle: &Candle) {
+        if candidate.state == DoubleTopState::CoolingDown && self.cooldown.is_none() {
+            candidate.state = DoubleTopState::Watching;
+        }
+        if !candidate.state.after_confirmation() {
+            return;
+        }
+        let Some(confirmed_at) = candidate.confirmed_at else {
+            return;
+        };
+        let elapsed = intervals::candles_between(&self.interval, confirmed_at, candle.open_time);
+        if elapsed.is_some_and(|candles| candles >= self.config.confirmed_ttl_candles as u64) {
+            candidate.state = if self.cooldown.is_some() {
+                DoubleTopState::CoolingDown
+            } else {
+                DoubleTopState::Watching
+            };
+        }
+    }
+
+    /// Within `retest_window_candles` of confirmation, watch for a rally from
+    /// below into the band under the neckline, then for a close back below
+    /// the band (the retest alert). A close far enough above the neckline
+    /// invalidates instead.
+    fn check_retest(
+        &self,
+        candidate: &mut Candidate,
+        candle: &Candle,
+        atr: f64,
+    ) -> Option<PatternAlert> {
+        if !matches!(
+            candidate.state,
+            DoubleTopState::Confirmed | DoubleTopState::RetestWatch
+        ) {
+            return None;
+        }
+        let neckline = candidate.neckline_price()?;
+        let elapsed =
+            intervals::candles_between(&self.interval, candidate.confirmed_at?, candle.open_time)?;
+        if elapsed > self.config.retest_window_candles as u64 {
+            // The window closed without a rejection.
+            candidate.state = DoubleTopState::Confirmed;
+            return None;
+        }
+        let fail_level = neckline + self.config.retest_fail_buffer * atr;
+        if candle.close > fail_level {
+            candidate.invalidate(InvalidationReason::RetestFailed {
+                fail_level,
+                close: candle.close,
+            });
+            return None;
+        }
+
+        let band_low = neckline - self.config.retest_band_atr * atr;
+        if candidate.state == DoubleTopState::Confirmed {
+            // Only a rally from below the band counts, not the breakdown's
+            // own follow-through.
+            let prev_high = self.candles.iter().rev().nth(1).map(|c| c.high);
+            if candle.high >= band_low && prev_high.is_some_and(|high| high < band_low) {
+                candidate.state = DoubleTopState::RetestWatch;
+                candidate.retest_high = Some(candle.high);
+            }
+            return None;
+        }
+
+        let high = candidate
+            .retest_high
+            .map_or(candle.high, |h| h.max(candle.high));
+        candidate.retest_high = Some(high);
+        if candle.close >= band_low {
+            return None;
+        }
+        candidate.state = DoubleTopState::Retested;
+        Some(PatternAlert {
+            retest_high: Some(high),
+            ..self.alert(AlertStage::Retest, candle, neckline)
+        })
+    }
+
+    /// Invalidate on a break above peak 1 or once the pattern has taken
+    /// too long.
+    fn check_invalidation(&self, candidate: &mut Candidate, candle: &Candle) {
+        if !candidate.state.in_pattern() {
+            return;
+        }
+        let fail_level = candidate.peak1.price * (1.0 + self.config.peak_fail_pct / 100.0);
+        // Before the pullback a higher high simply becomes the new peak 1.
+        if candidate.state != DoubleTopState::PeakFound && candle.high > fail_level {
+            candidate.invalidate(InvalidationReason::PeakExceeded {
+                fail_level,
+                high: candle.high,
+            });
+            return;
+        }
+        if let Some(candles) = self
+            .since_peak1(candidate)
+            .filter(|candles| *candles > self.config.max_peak_distance as u64)
+        {
+            candidate.invalidate(InvalidationReason::TimedOut {
+                candles_since_peak1: candles,
+            });
+        }
+    }
+
+    fn check_early_warning(
+        &self,
+        candidate: &mut Candidate,
+        candle: &Candle,
+        atr: f64,
+    ) -> Option<PatternAlert> {
+        if candidate.warned {
+            return None;
+        }
+        let peak1 = candidate.peak1;
+        let distance_pct = (peak1.price - candle.close).abs() / peak1.price * 100.0;
+        if distance_pct > self.config.approach_threshold_pct || !self.is_rising(candle) {
+            return None;
+        }
+        if self.config.require_volume_divergence && !self.rally_diverges(candidate) {
+            return None;
+        }
+        if !self.tall_enough(candidate, peak1.price, atr)? {
+            return None;
+        }
+        candidate.warned = true;
+        candidate.state = DoubleTopState::Forming;
+        Some(self.alert(AlertStage::EarlyWarning, candle, peak1.price))
+    }
+
+    /// `candle`'s volume over the average of the up to `volume_lookback`
+    /// candles before it.
+    fn volume_ratio(&self, candle: &Candle) -> Option<f64> {
+        let before: Vec<f64> = self
+            .candles
+            .iter()
+            .rev()
+            .skip(1)
+            .take(self.config.volume_lookback)
+            .map(|c| c.volume)
+            .collect();
+        if before.is_empty() {
+            return None;
+        }
+        let average = before.iter().sum::<f64>() / before.len() as f64;
+        (average > 0.0).then(|| candle.volume / average)
+    }
+
+    fn check_confirmation(
+        &self,
+        candidate: &mut Candidate,
+        candle: &Candle,
+        atr: f64,
+    ) -> Option<PatternAlert> {
+        let average = candidate.peak_average()?;
+        if !self.tall_enough(candidate, average, atr)? {
+            return None;
+        }
+        let height_pct = candidate.height_pct(average)?;
+        let neckline = candidate.neckline_price()?;
+        let break_level = neckline - self.config.breakdown_buffer * atr;
+        let broke = match self.config.confirmation_mode {
+            ConfirmationMode::Close => candle.close < break_level,
+            ConfirmationMode::Low => candle.low < break_level,
+        };
+        if !broke {
+            return None;
+        }
+        let volume_ratio = self.volume_ratio(candle);
+        if let Some(mult) = self.config.min_breakdown_volume_mult {
+            // A thin breakdown leaves the pattern forming.
+            if volume_ratio.is_none_or(|ratio| ratio < mult) {
+                return None;
+            }
+        }
+        candidate.state = DoubleTopState::Confirmed;
+        candidate.confirmed_at = Some(candle.open_time);
+        candidate.breakdown_volume_ratio = volume_ratio;
+        Some(PatternAlert {
+            target_price: candidate.target_price(),
+            pattern_height_pct: Some(height_pct),
+            confirmation_mode: Some(self.config.confirmation_mode),
+            ..self.alert(AlertStage::Confirmation, candle, neckline)
+        })
+    }
+
+    fn alert(&self, stage: AlertStage, candle: &Candle, level: f64) -> PatternAlert {
+        PatternAlert {
+            coin: self.coin.clone(),
+            pattern: PatternKind::DoubleTop,
+            stage,
+            open_time: candle.open_time,
+            price: candle.close,
+            level,
+            target_price: None,
+            pattern_height_pct: None,
+            retest_high: None,
+            confirmation_mode: None,
+            htf_trend: None,
+        }
+    }
+}
@@ -0,0 +1,41 @@
+//! Fibonacci retracements of a price leg.
+
+use serde::Serialize;
+
+/// Standard retracement levels of the leg `from -> to`, plus how far price
+/// has come back along it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct FibLevels {
+    /// Where the leg started.
+    pub from: f64,
+    /// Where the leg ended; retracements are measured back from here.
+    pub to: f64,
+    pub r382: f64,
+    pub r500: f64,
+    pub r618: f64,
+    pub r786: f64,
+    /// How much of the leg `price` has given back, in % (0 at `to`, 100 at
+    /// `from`; can exceed either end).
+    pub retracement_pct: f64,
+}
+
+impl FibLevels {
+    /// Levels for a leg in either direction. `None` for a zero-length leg.
+    pub fn new(from: f64, to: f64, price: f64) -> Option<Self> {
+        let leg = to - from;
+        if leg == 0.0 {
+            return None;
+        }
+        let level = |ratio: f64| to - leg * ratio;
+        Some(Self {
+            from,
+            to,
+            r382: level(0.382),
+            r500: level(0.5),
+            r618: level(0.618),
+            r786: level(0.786),
+            retracement_pct: (to - price) / leg * 100.0,
+        })
+    }
+}
@@ -0,0 +1,192 @@
+//! Funding-rate anomaly screening.
+//!
+//! Hyperliquid funding is paid hourly, so rates here are hourly fractions
+//! (`0.0001` = 0.01% per hour).
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+/// Funding payments per year at one payment per hour.
+pub const FUNDING_PERIODS_PER_YEAR: f64 = 24.0 * 365.0;
+
+const HOUR_MS: u64 = 3_600_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct FundingScreenerConfig {
+    /// Flag a rate above this percentile of the coin's own trailing history (0-100).
+    pub percentile: f64,
+    /// Flag any rate above this hourly rate regardless of history.
+    pub absolute_threshold: Option<f64>,
+    /// Samples required before the percentile rule applies.
+    pub min_history: usize,
+}
+
+impl Default for FundingScreenerConfig {
+    fn default() -> Self {
+        Self {
+            percentile: 95.0,
+            absolute_threshold: Some(0.0005),
+            // Three days of hourly samples.
+            min_history: 72,
+        }
+    }
+}
+
+impl FundingScreenerConfig {
+    /// Problems that would make the config unusable, one message each.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if !(0.0..=100.0).contains(&self.percentile) {
+            errors.push("percentile must be between 0 and 100".to_string());
+        }
+        if self
+            .absolute_threshold
+            .is_some_and(|t| !t.is_finite() || t < 0.0)
+        {
+            errors.push("absolute_threshold must not be negative".to_string());
+        }
+        if self.min_history == 0 {
+            errors.push("min_history must be positive".to_string());
+        }
+        errors
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum FundingTrigger {
+    /// Above the configured percentile of the coin's trailing distribution.
+    Percentile,
+    /// Above the absolute threshold.
+    Absolute,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct FundingAnomaly {
+    pub coin: String,
+    /// Current hourly funding rate.
+    pub current_rate: f64,
+    /// Share of the trailing history at or below the current rate (0-100).
+    pub percentile: Option<f64>,
+    pub annualized_rate: f64,
+    pub triggers: Vec<FundingTrigger>,
+}
+
+/// Simple (non-compounded) annual rate for an hourly funding rate.
+pub fn annualized(hourly_rate: f64) -> f64 {
+    hourly_rate * FUNDING_PERIODS_PER_YEAR
+}
+
+/// Value at percentile `p` (0-100) of `values`, linearly interpolated between
+/// closest ranks. Returns `None` for an empty slice.
+pub fn percentile(values: &[f64], p: f64) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let rank = p.clamp(0.0, 100.0) / 100.0 * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    let weight = rank - lower as f64;
+    Some(sorted[lower] + (sorted[upper] - sorted[lower]) * weight)
+}
+
+/// Share of `values` at or below `value`, as a percentage. `None` when empty.
+pub fn percentile_rank(values: &[f64], value: f64) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let at_or_below = values.iter().filter(|&&v| v <= value).count();
+    Some(at_or_below as f64 / values.len() as f64 * 100.0)
+}
+
+/// Flag `coin` if its current rate is anomalous against its own `history`.
+pub fn screen(
+    coin: &str,
+    current_rate: f64,
+    history: &[f64],
+    config: &FundingScreenerConfig,
+) -> Option<FundingAnomaly> {
+    let mut triggers = Vec::new();
+
+    if history.len() >= config.min_history.max(1) {
+        if let Some(cutoff) = percentile(history, config.percentile) {
+            if current_rate > cutoff {
+                triggers.push(FundingTrigger::Percentile);
+            }
+        }
+    }
+    if config
+        .absolute_threshold
+        .is_some_and(|threshold| current_rate > threshold)
+    {
+        triggers.push(FundingTrigger::Absolute);
+    }
+
+    if triggers.is_empty() {
+        return None;
+    }
+    Some(FundingAnomaly {
+        coin: coin.to_string(),
+        current_rate,
+        percentile: percentile_rank(history, current_rate),
+        annualized_rate: annualized(current_rate),
+        triggers,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct FundingSample {
+    pub time_ms: u64,
+    /// Hourly funding rate.
+    pub rate: f64,
+}
+
+/// One funding rate per hour for a coin, oldest first.
+///
+/// Sampling more often than hourly refines the current hour's rate instead
+/// of adding samples, so the history is a distribution of hourly rates
+/// whatever the poll interval.
+#[derive(Debug, Clone, Default)]
+pub struct FundingHistory {
+    samples: VecDeque<FundingSample>,
+}
+
+impl FundingHistory {
+    /// Record `sample` as its hour's rate, then drop hours older than
+    /// `retain_ms` before it. Samples not newer than the latest one are
+    /// ignored.
+    pub fn record(&mut self, sample: FundingSample, retain_ms: u64) {
+        match self.samples.back_mut() {
+            Some(last) if sample.time_ms <= last.time_ms => return,
+            Some(last) if last.time_ms / HOUR_MS == sample.time_ms / HOUR_MS => *last = sample,
+            _ => self.samples.push_back(sample),
+        }
+        let cutoff = sample.time_ms.saturating_sub(retain_ms);
+        while self.samples.front().is_some_and(|s| s.time_ms < cutoff) {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn latest(&self) -> Option<FundingSample> {
+        self.samples.back().copied()
+    }
+
+    /// Rates of the recorded hours before the latest one, newest first.
+    pub fn trailing_rates(&self) -> Vec<f64> {
+        self.samples.iter().rev().skip(1).map(|s| s.rate).collect()
+    }
+
+    /// Screen the latest rate against the hours before it.
+    pub fn screen(&self, coin: &str, config: &FundingScreenerConfig) -> Option<FundingAnomaly> {
+        let latest = self.latest()?;
+        screen(coin, latest.rate, &self.trailing_rates(), config)
+    }
+}
@@ -0,0 +1,135 @@
+//! Detection of missing candles in a per-coin candle feed.
+
+use serde::{Deserialize, Serialize};
+
+use crate::business_logic::intervals;
+use crate::models::candle::Candle;
+
+/// What the monitor should do with a coin's detector when a gap is found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum GapPolicy {
+    /// Keep the detector and flag its status with `data_gap` until the feed is clean again.
+    Flag,
+    /// Discard detector state and start over from the candle after the gap.
+    ResetDetector,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct GapConfig {
+    pub policy: GapPolicy,
+    /// Consecutive contiguous candles needed after a gap before `data_gap` clears.
+    pub clean_candles_to_clear: u32,
+}
+
+impl Default for GapConfig {
+    fn default() -> Self {
+        Self {
+            policy: GapPolicy::Flag,
+            clean_candles_to_clear: 20,
+        }
+    }
+}
+
+/// A hole between two consecutive processed candles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CandleGap {
+    /// Open time of the last candle before the gap.
+    pub after_open_time: u64,
+    /// Open time of the first candle after the gap.
+    pub before_open_time: u64,
+    pub missing_candles: u64,
+}
+
+/// Tracks feed continuity for one coin at one interval.
+#[derive(Debug, Clone)]
+pub struct GapTracker {
+    interval: String,
+    config: GapConfig,
+    last_open_time: Option<u64>,
+    gap_count: u64,
+    clean_streak: u32,
+    data_gap: bool,
+}
+
+impl GapTracker {
+    pub fn new(interval: impl Into<String>, config: GapConfig) -> Self {
+        Self {
+            interval: interval.into(),
+            config,
+            last_open_time: None,
+            gap_count: 0,
+            clean_streak: 0,
+            data_gap: false,
+        }
+    }
+
+    /// Record the next processed candle, returning the gap before it if any.
+    ///
+    /// Candles at or before the last observed open time are ignored; dedup is
+    /// the caller's job and they cannot open a gap.
+    pub fn observe(&mut self, candle: &Candle) -> Option<CandleGap> {
+        let Some(last) = self.last_open_time else {
+            self.last_open_time = Some(candle.open_time);
+            return None;
+        };
+        if candle.open_time <= last {
+            return None;
+        }
+        self.last_open_time = Some(candle.open_time);
+
+        let missing = self.missing_between(last, candle.open_time);
+        if missing == 0 {
+            if self.data_gap {
+                self.clean_streak += 1;
+                if self.clean_streak >= self.config.clean_candles_to_clear {
+                    self.data_gap = false;
+                }
+            }
+            return None;
+        }
+
+        self.gap_count += 1;
+        self.clean_streak = 0;
+        self.data_gap = self.config.policy == GapPolicy::Flag;
+        Some(CandleGap {
+            after_open_time: last,
+            before_open_time: candle.open_time,
+            missing_candles: missing,
+        })
+    }
+
+    /// Number of candle buckets strictly between two open times.
+    fn missing_between(&self, from: u64, to: u64) -> u64 {
+        if let Some(ms) = intervals::interval_ms(&self.interval) {
+            return ((to - from) / ms).saturating_sub(1);
+        }
+        let mut missing = 0;
+        let mut next = from;
+        while let Some(following) = intervals::next_bucket_start(&self.interval, next) {
+            if following >= to {
+                break;
+            }
+            missing += 1;
+            next = following;
+        }
+        missing
+    }
+
+    pub fn policy(&self) -> GapPolicy {
+        self.config.policy
+    }
+
+    /// Gaps seen since the tracker was created.
+    pub fn gap_count(&self) -> u64 {
+        self.gap_count
+    }
+
+    /// True from a gap (under [`GapPolicy::Flag`]) until enough clean candles follow.
+    pub fn has_data_gap(&self) -> bool {
+        self.data_gap
+    }
+}
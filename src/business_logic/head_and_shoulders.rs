@@ -0,0 +1,343 @@
+//! Head and shoulders detection from confirmed swing points.
+//!
+//! Left shoulder -> trough -> higher head -> trough -> right shoulder near the
+//! left one, confirmed by a close below the neckline through the two troughs.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::business_logic::alerts::{AlertStage, PatternAlert, PatternKind};
+use crate::business_logic::indicators::AtrCalculator;
+use crate::business_logic::swing::SwingDetector;
+use crate::models::candle::Candle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct HeadAndShouldersConfig {
+    pub atr_period: usize,
+    /// Swing reversal size in ATRs.
+    pub rev_atr: f64,
+    /// Max % difference between the two shoulders.
+    pub shoulder_tolerance_pct: f64,
+    /// Max candles from each shoulder to the head, and from the right
+    /// shoulder to the neckline break.
+    pub max_head_distance: usize,
+    /// ATRs below the neckline a close must reach to confirm.
+    pub breakdown_buffer: f64,
+}
+
+impl Default for HeadAndShouldersConfig {
+    fn default() -> Self {
+        Self {
+            atr_period: 14,
+            rev_atr: 1.0,
+            shoulder_tolerance_pct: 2.0,
+            max_head_distance: 60,
+            breakdown_buffer: 0.3,
+        }
+    }
+}
+
+impl HeadAndShouldersConfig {
+    /// Problems that would make the config unusable, one message each.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let mut check = |ok: bool, message: &str| {
+            if !ok {
+                errors.push(message.to_string());
+            }
+        };
+        check(self.atr_period > 0, "atr_period must be positive");
+        check(
+            self.rev_atr > 0.0 && self.rev_atr.is_finite(),
+            "rev_atr must be positive",
+        );
+        check(
+            self.shoulder_tolerance_pct > 0.0 && self.shoulder_tolerance_pct <= 100.0,
+            "shoulder_tolerance_pct must be in (0, 100]",
+        );
+        check(
+            self.max_head_distance > 0,
+            "max_head_distance must be positive",
+        );
+        check(
+            self.breakdown_buffer >= 0.0 && self.breakdown_buffer.is_finite(),
+            "breakdown_buffer must not be negative",
+        );
+        errors
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeadAndShouldersState {
+    Watching,
+    /// Left shoulder, head and the trough after it are in; waiting for the
+    /// right shoulder.
+    HeadFound,
+    /// Right shoulder in (early warning raised); waiting for the neckline break.
+    Forming,
+    Confirmed,
+    Invalidated,
+}
+
+impl HeadAndShouldersState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HeadAndShouldersState::Watching => "WATCHING",
+            HeadAndShouldersState::HeadFound => "HEAD_FOUND",
+            HeadAndShouldersState::Forming => "FORMING",
+            HeadAndShouldersState::Confirmed => "CONFIRMED",
+            HeadAndShouldersState::Invalidated => "INVALIDATED",
+        }
+    }
+}
+
+/// Where a coin's head and shoulders stands, as published to the pattern
+/// state.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct HeadAndShouldersStatus {
+    pub coin: String,
+    /// `WATCHING`, `HEAD_FOUND`, `FORMING`, `CONFIRMED` or `INVALIDATED`.
+    pub state: String,
+    pub left_shoulder_price: Option<f64>,
+    pub head_price: Option<f64>,
+    pub right_shoulder_price: Option<f64>,
+    /// Neckline projected to the latest candle.
+    pub neckline_price: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Swing {
+    price: f64,
+    is_peak: bool,
+    /// Candle index at which the swing was confirmed.
+    index: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Structure {
+    left_shoulder: Swing,
+    left_trough: Swing,
+    head: Swing,
+    right_trough: Swing,
+    right_shoulder: Option<Swing>,
+}
+
+impl Structure {
+    /// Neckline through the two troughs, projected to candle `index`.
+    fn neckline_at(&self, index: usize) -> f64 {
+        let (a, b) = (self.left_trough, self.right_trough);
+        let slope = (b.price - a.price) / (b.index - a.index).max(1) as f64;
+        a.price + slope * (index as f64 - a.index as f64)
+    }
+}
+
+/// Per-coin head and shoulders state machine fed one closed candle at a time.
+#[derive(Debug, Clone)]
+pub struct HeadAndShouldersDetector {
+    coin: String,
+    config: HeadAndShouldersConfig,
+    atr: AtrCalculator,
+    swings: SwingDetector,
+    recent: VecDeque<Swing>,
+    index: usize,
+    state: HeadAndShouldersState,
+    structure: Option<Structure>,
+}
+
+impl HeadAndShouldersDetector {
+    pub fn new(coin: impl Into<String>, config: HeadAndShouldersConfig) -> Self {
+        Self {
+            coin: coin.into(),
+            atr: AtrCalculator::new(config.atr_period),
+            swings: SwingDetector::new(config.rev_atr),
+            config,
+            recent: VecDeque::new(),
+            index: 0,
+            state: HeadAndShouldersState::Watching,
+            structure: None,
+        }
+    }
+
+    pub fn state(&self) -> HeadAndShouldersState {
+        self.state
+    }
+
+    pub fn left_shoulder_price(&self) -> Option<f64> {
+        self.structure.map(|s| s.left_shoulder.price)
+    }
+
+    pub fn head_price(&self) -> Option<f64> {
+        self.structure.map(|s| s.head.price)
+    }
+
+    pub fn right_shoulder_price(&self) -> Option<f64> {
+        self.structure
+            .and_then(|s| s.right_shoulder)
+            .map(|s| s.price)
+    }
+
+    /// Neckline projected to the latest candle.
+    pub fn neckline_price(&self) -> Option<f64> {
+        self.structure.map(|s| s.neckline_at(self.index))
+    }
+
+    pub fn status(&self) -> HeadAndShouldersStatus {
+        HeadAndShouldersStatus {
+            coin: self.coin.clone(),
+            state: self.state.as_str().to_string(),
+            left_shoulder_price: self.left_shoulder_price(),
+            head_price: self.head_price(),
+            right_shoulder_price: self.right_shoulder_price(),
+            neckline_price: self.neckline_price(),
+        }
+    }
+
+    /// Feed the next closed candle, returning an alert if it raised one.
+    ///
+    /// Nothing happens until the ATR has warmed up.
+    pub fn update(&mut self, candle: &Candle) -> Option<PatternAlert> {
+        self.index += 1;
+        let atr = self.atr.update(candle)?;
+
+        if self.check_invalidation(candle) {
+            return None;
+        }
+
+        if let Some(point) = self.swings.update(candle, atr) {
+            self.recent.push_back(Swing {
+                price: point.price,
+                is_peak: point.is_peak,
+                index: self.index,
+            });
+            if self.recent.len() > 5 {
+                self.recent.pop_front();
+            }
+            if let Some(alert) = self.on_swing(candle) {
+                return Some(alert);
+            }
+        }
+
+        self.check_confirmation(candle, atr)
+    }
+
+    fn on_swing(&mut self, candle: &Candle) -> Option<PatternAlert> {
+        match self.state {
+            HeadAndShouldersState::Forming => None,
+            HeadAndShouldersState::HeadFound => {
+                let right_shoulder = *self.recent.back()?;
+                let mut structure = self.structure?;
+                if right_shoulder.is_peak && self.shoulders_match(&structure, right_shoulder) {
+                    structure.right_shoulder = Some(right_shoulder);
+                    self.structure = Some(structure);
+                    self.state = HeadAndShouldersState::Forming;
+                    let neckline = structure.neckline_at(self.index);
+                    return Some(self.alert(AlertStage::EarlyWarning, candle, neckline));
+                }
+                self.find_head();
+                None
+            }
+            _ => {
+                self.find_head();
+                None
+            }
+        }
+    }
+
+    /// Look for left shoulder, trough, head, trough at the end of the swings.
+    fn find_head(&mut self) {
+        let n = self.recent.len();
+        let candidate = (n >= 4)
+            .then(|| {
+                let tail: Vec<Swing> = self.recent.iter().skip(n - 4).copied().collect();
+                Structure {
+                    left_shoulder: tail[0],
+                    left_trough: tail[1],
+                    head: tail[2],
+                    right_trough: tail[3],
+                    right_shoulder: None,
+                }
+            })
+            .filter(|s| {
+                s.left_shoulder.is_peak
+                    && s.head.is_peak
+                    && !s.right_trough.is_peak
+                    && s.head.price > s.left_shoulder.price
+                    && s.head.index - s.left_shoulder.index <= self.config.max_head_distance
+            });
+        match candidate {
+            Some(structure) => {
+                self.structure = Some(structure);
+                self.state = HeadAndShouldersState::HeadFound;
+            }
+            None if self.state == HeadAndShouldersState::HeadFound => {
+                self.structure = None;
+                self.state = HeadAndShouldersState::Watching;
+            }
+            None => {}
+        }
+    }
+
+    fn shoulders_match(&self, structure: &Structure, right_shoulder: Swing) -> bool {
+        let left = structure.left_shoulder.price;
+        let average = (left + right_shoulder.price) / 2.0;
+        let diff_pct = (left - right_shoulder.price).abs() / average * 100.0;
+        right_shoulder.price < structure.head.price
+            && diff_pct <= self.config.shoulder_tolerance_pct
+            && right_shoulder.index - structure.head.index <= self.config.max_head_distance
+    }
+
+    /// Invalidate when price takes out the head before the break, or the
+    /// break takes too long after the right shoulder. Returns whether it did.
+    fn check_invalidation(&mut self, candle: &Candle) -> bool {
+        if !matches!(
+            self.state,
+            HeadAndShouldersState::HeadFound | HeadAndShouldersState::Forming
+        ) {
+            return false;
+        }
+        let Some(structure) = self.structure else {
+            return false;
+        };
+        let above_head = candle.high > structure.head.price;
+        let expired = structure
+            .right_shoulder
+            .is_some_and(|rs| self.index - rs.index > self.config.max_head_distance);
+        if above_head || expired {
+            self.state = HeadAndShouldersState::Invalidated;
+            return true;
+        }
+        false
+    }
+
+    fn check_confirmation(&mut self, candle: &Candle, atr: f64) -> Option<PatternAlert> {
+        if self.state != HeadAndShouldersState::Forming {
+            return None;
+        }
+        let neckline = self.structure?.neckline_at(self.index);
+        if candle.close >= neckline - self.config.breakdown_buffer * atr {
+            return None;
+        }
+        self.state = HeadAndShouldersState::Confirmed;
+        Some(self.alert(AlertStage::Confirmation, candle, neckline))
+    }
+
+    fn alert(&self, stage: AlertStage, candle: &Candle, level: f64) -> PatternAlert {
+        PatternAlert {
+            coin: self.coin.clone(),
+            pattern: PatternKind::HeadAndShoulders,
+            stage,
+            open_time: candle.open_time,
+            price: candle.close,
+            level,
+            target_price: None,
+            pattern_height_pct: None,
+            retest_high: None,
+            confirmation_mode: None,
+            htf_trend: None,
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! Named single-line indicators, as requested by `/indicators?set=ema20,rsi14`.
+
+use crate::business_logic::indicators::{
+    AtrCalculator, EmaCalculator, RsiCalculator, SmaCalculator,
+};
+use crate::models::candle::Candle;
+
+/// Longest period a spec may ask for; more than a full snapshot is never warm.
+pub const MAX_PERIOD: usize = 500;
+
+/// An indicator kind plus its period, e.g. `ema20`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IndicatorSpec {
+    Ema(usize),
+    Sma(usize),
+    Rsi(usize),
+    Atr(usize),
+}
+
+impl IndicatorSpec {
+    /// Parse `<kind><period>` where kind is `ema`, `sma`, `rsi` or `atr` and
+    /// period is 1-500.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let split = value
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(value.len());
+        let (kind, digits) = value.split_at(split);
+        let build: fn(usize) -> IndicatorSpec = match kind {
+            "ema" => IndicatorSpec::Ema,
+            "sma" => IndicatorSpec::Sma,
+            "rsi" => IndicatorSpec::Rsi,
+            "atr" => IndicatorSpec::Atr,
+            _ => {
+                return Err(format!(
+                "unknown indicator {value:?}; expected ema, sma, rsi or atr followed by a period"
+            ))
+            }
+        };
+        match digits.parse::<usize>() {
+            Ok(period) if (1..=MAX_PERIOD).contains(&period) => Ok(build(period)),
+            _ => Err(format!(
+                "bad period in {value:?}; expected a whole number between 1 and {MAX_PERIOD}"
+            )),
+        }
+    }
+
+    /// Canonical name, e.g. `ema20`.
+    pub fn name(&self) -> String {
+        let (kind, period) = match self {
+            IndicatorSpec::Ema(p) => ("ema", p),
+            IndicatorSpec::Sma(p) => ("sma", p),
+            IndicatorSpec::Rsi(p) => ("rsi", p),
+            IndicatorSpec::Atr(p) => ("atr", p),
+        };
+        format!("{kind}{period}")
+    }
+
+    /// One value per candle, `None` while the indicator warms up.
+    pub fn series(&self, candles: &[Candle]) -> Vec<Option<f64>> {
+        match *self {
+            IndicatorSpec::Ema(p) => EmaCalculator::new(p).apply_series(candles),
+            IndicatorSpec::Sma(p) => SmaCalculator::new(p).apply_series(candles),
+            IndicatorSpec::Rsi(p) => RsiCalculator::new(p).apply_series(candles),
+            IndicatorSpec::Atr(p) => AtrCalculator::new(p).apply_series(candles),
+        }
+    }
+}
+
+/// Parse a comma-separated list of specs, dropping repeats but keeping order.
+pub fn parse_set(value: &str) -> Result<Vec<IndicatorSpec>, String> {
+    let mut specs = Vec::new();
+    for item in value.split(',').map(str::trim) {
+        if item.is_empty() {
+            continue;
+        }
+        let spec = IndicatorSpec::parse(&item.to_ascii_lowercase())?;
+        if !specs.contains(&spec) {
+            specs.push(spec);
+        }
+    }
+    if specs.is_empty() {
+        return Err("set must name at least one indicator".to_string());
+    }
+    Ok(specs)
+}
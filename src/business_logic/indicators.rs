@@ -0,0 +1,855 @@
+use std::collections::VecDeque;
+
+use crate::models::candle::Candle;
+
+/// The candle's range, widened to reach the previous close across a gap.
+pub fn true_range(candle: &Candle, prev_close: Option<f64>) -> f64 {
+    match prev_close {
+        Some(prev_close) => (candle.high - candle.low)
+            .max((candle.high - prev_close).abs())
+            .max((candle.low - prev_close).abs()),
+        None => candle.high - candle.low,
+    }
+}
+
+/// Average True Range with Wilder smoothing.
+///
+/// The first value is the simple average of the first `period` true ranges;
+/// after that `atr = (prev_atr * (period - 1) + tr) / period`.
+#[derive(Debug, Clone)]
+pub struct AtrCalculator {
+    period: usize,
+    prev_close: Option<f64>,
+    seed_sum: f64,
+    seen: usize,
+    atr: Option<f64>,
+}
+
+impl AtrCalculator {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "ATR period must be positive");
+        Self {
+            period,
+            prev_close: None,
+            seed_sum: 0.0,
+            seen: 0,
+            atr: None,
+        }
+    }
+
+    /// Feed the next closed candle. Returns `None` until `period` candles have been seen.
+    pub fn update(&mut self, candle: &Candle) -> Option<f64> {
+        let tr = true_range(candle, self.prev_close);
+        self.prev_close = Some(candle.close);
+
+        let period = self.period as f64;
+        self.atr = match self.atr {
+            Some(prev_atr) => Some((prev_atr * (period - 1.0) + tr) / period),
+            None => {
+                self.seed_sum += tr;
+                self.seen += 1;
+                (self.seen == self.period).then(|| self.seed_sum / period)
+            }
+        };
+        self.atr
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        self.atr
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    /// Feed a whole snapshot, returning the value after each candle.
+    pub fn apply_series(&mut self, candles: &[Candle]) -> Vec<Option<f64>> {
+        candles.iter().map(|c| self.update(c)).collect()
+    }
+}
+
+/// Fixed-capacity window of the most recent values with running sums, so
+/// mean and variance are O(1) per update.
+#[derive(Debug, Clone)]
+pub struct RollingWindow {
+    capacity: usize,
+    values: VecDeque<f64>,
+    sum: f64,
+    sum_squares: f64,
+}
+
+impl RollingWindow {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "window capacity must be positive");
+        Self {
+            capacity,
+            values: VecDeque::with_capacity(capacity + 1),
+            sum: 0.0,
+            sum_squares: 0.0,
+        }
+    }
+
+    /// Add a value, returning the one it evicted once the window is full.
+    pub fn push(&mut self, value: f64) -> Option<f64> {
+        self.values.push_back(value);
+        self.sum += value;
+        self.sum_squares += value * value;
+        if self.values.len() <= self.capacity {
+            return None;
+        }
+        let evicted = self.values.pop_front()?;
+        self.sum -= evicted;
+        self.sum_squares -= evicted * evicted;
+        Some(evicted)
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.values.len() == self.capacity
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        (!self.is_empty()).then(|| self.sum / self.len() as f64)
+    }
+
+    /// Population variance, clamped at zero against rounding in the running sums.
+    pub fn variance(&self) -> Option<f64> {
+        let mean = self.mean()?;
+        Some((self.sum_squares / self.len() as f64 - mean * mean).max(0.0))
+    }
+
+    /// Oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = f64> + '_ {
+        self.values.iter().copied()
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.sum = 0.0;
+        self.sum_squares = 0.0;
+    }
+}
+
+/// Simple moving average of the last `period` closes.
+#[derive(Debug, Clone)]
+pub struct SmaCalculator {
+    window: RollingWindow,
+}
+
+impl SmaCalculator {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "SMA period must be positive");
+        Self {
+            window: RollingWindow::new(period),
+        }
+    }
+
+    /// Feed the next closed candle. Returns `None` until `period` candles have been seen.
+    pub fn update(&mut self, candle: &Candle) -> Option<f64> {
+        self.window.push(candle.close);
+        self.value()
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        self.window.mean().filter(|_| self.window.is_full())
+    }
+
+    pub fn period(&self) -> usize {
+        self.window.capacity()
+    }
+
+    /// Feed a whole snapshot, returning the value after each candle.
+    pub fn apply_series(&mut self, candles: &[Candle]) -> Vec<Option<f64>> {
+        candles.iter().map(|c| self.update(c)).collect()
+    }
+}
+
+/// Exponential moving average of closes.
+///
+/// Seeded with the simple average of the first `period` closes; after that
+/// `ema = prev_ema + (close - prev_ema) * 2 / (period + 1)`.
+#[derive(Debug, Clone)]
+pub struct EmaCalculator {
+    period: usize,
+    seed_sum: f64,
+    seen: usize,
+    ema: Option<f64>,
+}
+
+impl EmaCalculator {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "EMA period must be positive");
+        Self {
+            period,
+            seed_sum: 0.0,
+            seen: 0,
+            ema: None,
+        }
+    }
+
+    /// Feed the next closed candle. Returns `None` until `period` candles have been seen.
+    pub fn update(&mut self, candle: &Candle) -> Option<f64> {
+        self.update_value(candle.close)
+    }
+
+    /// Feed a raw value instead of a close, for EMAs of derived series.
+    pub fn update_value(&mut self, value: f64) -> Option<f64> {
+        let period = self.period as f64;
+        self.ema = match self.ema {
+            Some(prev) => Some(prev + (value - prev) * 2.0 / (period + 1.0)),
+            None => {
+                self.seed_sum += value;
+                self.seen += 1;
+                (self.seen == self.period).then(|| self.seed_sum / period)
+            }
+        };
+        self.ema
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        self.ema
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    /// Feed a whole snapshot, returning the value after each candle.
+    pub fn apply_series(&mut self, candles: &[Candle]) -> Vec<Option<f64>> {
+        candles.iter().map(|c| self.update(c)).collect()
+    }
+}
+
+/// Relative Strength Index with Wilder smoothing.
+///
+/// The first average gain and loss are simple averages over the first
+/// `period` close-to-close changes; after that each is smoothed like the ATR.
+#[derive(Debug, Clone)]
+pub struct RsiCalculator {
+    period: usize,
+    prev_close: Option<f64>,
+    gain_sum: f64,
+    loss_sum: f64,
+    seen: usize,
+    averages: Option<(f64, f64)>,
+}
+
+impl RsiCalculator {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "RSI period must be positive");
+        Self {
+            period,
+            prev_close: None,
+            gain_sum: 0.0,
+            loss_sum: 0.0,
+            seen: 0,
+            averages: None,
+        }
+    }
+
+    /// Feed the next closed candle. Returns `None` until `period` changes
+    /// (`period + 1` candles) have been seen.
+    pub fn update(&mut self, candle: &Candle) -> Option<f64> {
+        let prev_close = self.prev_close.replace(candle.close)?;
+        let change = candle.close - prev_close;
+        let (gain, loss) = (change.max(0.0), (-change).max(0.0));
+
+        let period = self.period as f64;
+        self.averages = match self.averages {
+            Some((avg_gain, avg_loss)) => Some((
+                (avg_gain * (period - 1.0) + gain) / period,
+                (avg_loss * (period - 1.0) + loss) / period,
+            )),
+            None => {
+                self.gain_sum += gain;
+                self.loss_sum += loss;
+                self.seen += 1;
+                (self.seen == self.period).then(|| (self.gain_sum / period, self.loss_sum / period))
+            }
+        };
+        self.value()
+    }
+
+    /// 0-100; 100 when there have been no losses to average.
+    pub fn value(&self) -> Option<f64> {
+        let (avg_gain, avg_loss) = self.averages?;
+        if avg_loss == 0.0 {
+            return Some(100.0);
+        }
+        Some(100.0 - 100.0 / (1.0 + avg_gain / avg_loss))
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    /// Feed a whole snapshot, returning the value after each candle.
+    pub fn apply_series(&mut self, candles: &[Candle]) -> Vec<Option<f64>> {
+        candles.iter().map(|c| self.update(c)).collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MacdConfig {
+    pub fast_period: usize,
+    pub slow_period: usize,
+    /// EMA period of the MACD line that forms the signal line.
+    pub signal_period: usize,
+}
+
+impl Default for MacdConfig {
+    fn default() -> Self {
+        Self {
+            fast_period: 12,
+            slow_period: 26,
+            signal_period: 9,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MacdValue {
+    /// Fast EMA minus slow EMA.
+    pub macd: f64,
+    pub signal: f64,
+    /// MACD minus signal.
+    pub histogram: f64,
+}
+
+/// The MACD line crossing its signal line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MacdCross {
+    /// MACD crossed above the signal.
+    Bullish,
+    /// MACD crossed below the signal.
+    Bearish,
+}
+
+/// MACD with a signal line, fed one closed candle at a time.
+#[derive(Debug, Clone)]
+pub struct MacdCalculator {
+    fast: EmaCalculator,
+    slow: EmaCalculator,
+    signal: EmaCalculator,
+    value: Option<MacdValue>,
+    cross: Option<MacdCross>,
+}
+
+impl MacdCalculator {
+    pub fn new(config: MacdConfig) -> Self {
+        assert!(
+            config.fast_period < config.slow_period,
+            "MACD fast period must be shorter than the slow period"
+        );
+        Self {
+            fast: EmaCalculator::new(config.fast_period),
+            slow: EmaCalculator::new(config.slow_period),
+            signal: EmaCalculator::new(config.signal_period),
+            value: None,
+            cross: None,
+        }
+    }
+
+    /// Feed the next closed candle. Returns `None` until the signal line has
+    /// warmed up: `slow_period + signal_period - 1` candles.
+    pub fn update(&mut self, candle: &Candle) -> Option<MacdValue> {
+        let fast = self.fast.update(candle);
+        let slow = self.slow.update(candle);
+        let macd = fast? - slow?;
+        let signal = self.signal.update_value(macd)?;
+        let value = MacdValue {
+            macd,
+            signal,
+            histogram: macd - signal,
+        };
+        self.cross = self.value.and_then(|prev| cross(prev, value));
+        self.value = Some(value);
+        self.value
+    }
+
+    pub fn value(&self) -> Option<MacdValue> {
+        self.value
+    }
+
+    /// The cross made by the latest candle, if any.
+    pub fn crossover(&self) -> Option<MacdCross> {
+        self.cross
+    }
+
+    /// Feed a whole snapshot, returning the value after each candle.
+    pub fn apply_series(&mut self, candles: &[Candle]) -> Vec<Option<MacdValue>> {
+        candles.iter().map(|c| self.update(c)).collect()
+    }
+}
+
+/// Index of every signal-line cross in a batch of MACD values.
+pub fn macd_crossovers(values: &[Option<MacdValue>]) -> Vec<(usize, MacdCross)> {
+    values
+        .windows(2)
+        .enumerate()
+        .filter_map(|(i, pair)| cross(pair[0]?, pair[1]?).map(|c| (i + 1, c)))
+        .collect()
+}
+
+fn cross(prev: MacdValue, next: MacdValue) -> Option<MacdCross> {
+    if prev.histogram <= 0.0 && next.histogram > 0.0 {
+        Some(MacdCross::Bullish)
+    } else if prev.histogram >= 0.0 && next.histogram < 0.0 {
+        Some(MacdCross::Bearish)
+    } else {
+        None
+    }
+}
+
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// Where a VWAP starts accumulating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VwapAnchor {
+    /// Restart every day at this many minutes past 00:00 UTC.
+    Session { start_minute: u32 },
+    /// Accumulate from the candle opening at or after this time (epoch ms).
+    At(u64),
+}
+
+/// Volume-weighted average of each candle's typical price, `(high + low + close) / 3`.
+#[derive(Debug, Clone)]
+pub struct VwapCalculator {
+    anchor: VwapAnchor,
+    session_start: Option<u64>,
+    price_volume: f64,
+    volume: f64,
+}
+
+impl VwapCalculator {
+    pub fn new(anchor: VwapAnchor) -> Self {
+        if let VwapAnchor::Session { start_minute } = anchor {
+            assert!(
+                start_minute < 24 * 60,
+                "VWAP session start must be within the day"
+            );
+        }
+        Self {
+            anchor,
+            session_start: None,
+            price_volume: 0.0,
+            volume: 0.0,
+        }
+    }
+
+    /// Feed the next closed candle. Returns `None` before the anchor and
+    /// while the anchored window has traded no volume.
+    pub fn update(&mut self, candle: &Candle) -> Option<f64> {
+        match self.anchor {
+            VwapAnchor::At(start) if candle.open_time < start => return None,
+            VwapAnchor::At(_) => {}
+            VwapAnchor::Session { start_minute } => {
+                let offset = u64::from(start_minute) * 60_000;
+                let session = (candle.open_time + DAY_MS - offset) / DAY_MS;
+                if self.session_start != Some(session) {
+                    self.session_start = Some(session);
+                    self.price_volume = 0.0;
+                    self.volume = 0.0;
+                }
+            }
+        }
+        let typical = (candle.high + candle.low + candle.close) / 3.0;
+        self.price_volume += typical * candle.volume;
+        self.volume += candle.volume;
+        self.value()
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        (self.volume > 0.0).then(|| self.price_volume / self.volume)
+    }
+
+    pub fn anchor(&self) -> VwapAnchor {
+        self.anchor
+    }
+
+    /// Feed a whole snapshot, returning the value after each candle.
+    pub fn apply_series(&mut self, candles: &[Candle]) -> Vec<Option<f64>> {
+        candles.iter().map(|c| self.update(c)).collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdxValue {
+    /// Positive directional indicator, 0-100.
+    pub plus_di: f64,
+    /// Negative directional indicator, 0-100.
+    pub minus_di: f64,
+    /// Average directional index, 0-100: trend strength regardless of direction.
+    pub adx: f64,
+}
+
+/// Wilder's ADX with the +DI/-DI lines.
+///
+/// True range and directional movement are Wilder-summed over `period`
+/// candles (`sum = sum - sum / period + x`), giving the DIs from the
+/// `period + 1`th candle. ADX is the simple average of the first `period`
+/// DX values and Wilder-smoothed after that, so it needs `2 * period` candles.
+#[derive(Debug, Clone)]
+pub struct AdxCalculator {
+    period: usize,
+    prev: Option<Candle>,
+    /// Seed sums of true range, +DM and -DM, then their Wilder sums.
+    sums: (f64, f64, f64),
+    seen: usize,
+    dx_sum: f64,
+    dx_seen: usize,
+    adx: Option<f64>,
+    value: Option<AdxValue>,
+}
+
+impl AdxCalculator {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "ADX period must be positive");
+        Self {
+            period,
+            prev: None,
+            sums: (0.0, 0.0, 0.0),
+            seen: 0,
+            dx_sum: 0.0,
+            dx_seen: 0,
+            adx: None,
+            value: None,
+        }
+    }
+
+    /// Feed the next closed candle. Returns `None` until `2 * period` candles
+    /// have been seen.
+    pub fn update(&mut self, candle: &Candle) -> Option<AdxValue> {
+        let prev = self.prev.replace(*candle)?;
+        let tr = true_range(candle, Some(prev.close));
+        let up = candle.high - prev.high;
+        let down = prev.low - candle.low;
+        let plus_dm = if up > down && up > 0.0 { up } else { 0.0 };
+        let minus_dm = if down > up && down > 0.0 { down } else { 0.0 };
+
+        let period = self.period as f64;
+        let (tr_sum, plus_sum, minus_sum) = &mut self.sums;
+        if self.seen < self.period {
+            *tr_sum += tr;
+            *plus_sum += plus_dm;
+            *minus_sum += minus_dm;
+            self.seen += 1;
+            if self.seen < self.period {
+                return None;
+            }
+        } else {
+            *tr_sum += tr - *tr_sum / period;
+            *plus_sum += plus_dm - *plus_sum / period;
+            *minus_sum += minus_dm - *minus_sum / period;
+        }
+
+        let (plus_di, minus_di) = if *tr_sum > 0.0 {
+            (100.0 * *plus_sum / *tr_sum, 100.0 * *minus_sum / *tr_sum)
+        } else {
+            (0.0, 0.0)
+        };
+        let di_sum = plus_di + minus_di;
+        let dx = if di_sum > 0.0 {
+            100.0 * (plus_di - minus_di).abs() / di_sum
+        } else {
+            0.0
+        };
+        self.adx = match self.adx {
+            Some(prev_adx) => Some((prev_adx * (period - 1.0) + dx) / period),
+            None => {
+                self.dx_sum += dx;
+                self.dx_seen += 1;
+                (self.dx_seen == self.period).then(|| self.dx_sum / period)
+            }
+        };
+        self.value = self.adx.map(|adx| AdxValue {
+            plus_di,
+            minus_di,
+            adx,
+        });
+        self.value
+    }
+
+    pub fn value(&self) -> Option<AdxValue> {
+        self.value
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    /// Feed a whole snapshot, returning the value after each candle.
+    pub fn apply_series(&mut self, candles: &[Candle]) -> Vec<Option<AdxValue>> {
+        candles.iter().map(|c| self.update(c)).collect()
+    }
+}
+
+/// On-balance volume: a running total that adds each candle's volume on an
+/// up close and subtracts it on a down close. Starts at 0 on the first candle.
+#[derive(Debug, Clone, Default)]
+pub struct ObvCalculator {
+    prev_close: Option<f64>,
+    obv: f64,
+}
+
+impl ObvCalculator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed the next closed candle, returning the running total.
+    pub fn update(&mut self, candle: &Candle) -> f64 {
+        if let Some(prev_close) = self.prev_close.replace(candle.close) {
+            if candle.close > prev_close {
+                self.obv += candle.volume;
+            } else if candle.close < prev_close {
+                self.obv -= candle.volume;
+            }
+        }
+        self.obv
+    }
+
+    pub fn value(&self) -> f64 {
+        self.obv
+    }
+
+    /// Feed a whole snapshot, returning the total after each candle.
+    pub fn apply_series(&mut self, candles: &[Candle]) -> Vec<f64> {
+        candles.iter().map(|c| self.update(c)).collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DonchianChannel {
+    /// Highest high of the window.
+    pub upper: f64,
+    /// Lowest low of the window.
+    pub lower: f64,
+}
+
+impl DonchianChannel {
+    pub fn middle(&self) -> f64 {
+        (self.upper + self.lower) / 2.0
+    }
+}
+
+/// A close outside a Donchian channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelBreak {
+    Above,
+    Below,
+}
+
+/// Where `candle` closed relative to a channel it is not part of.
+pub fn channel_break(channel: DonchianChannel, candle: &Candle) -> Option<ChannelBreak> {
+    if candle.close > channel.upper {
+        Some(ChannelBreak::Above)
+    } else if candle.close < channel.lower {
+        Some(ChannelBreak::Below)
+    } else {
+        None
+    }
+}
+
+/// Rolling highest high and lowest low over the last `period` candles.
+///
+/// Each side keeps a monotonic deque of `(index, price)` candidates, so an
+/// update is O(1) amortized and the extreme is always at the front.
+#[derive(Debug, Clone)]
+pub struct DonchianCalculator {
+    period: usize,
+    index: usize,
+    highs: VecDeque<(usize, f64)>,
+    lows: VecDeque<(usize, f64)>,
+    channel: Option<DonchianChannel>,
+    last_break: Option<ChannelBreak>,
+}
+
+impl DonchianCalculator {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "Donchian period must be positive");
+        Self {
+            period,
+            index: 0,
+            highs: VecDeque::new(),
+            lows: VecDeque::new(),
+            channel: None,
+            last_break: None,
+        }
+    }
+
+    /// Feed the next closed candle. Returns `None` until `period` candles have been seen.
+    pub fn update(&mut self, candle: &Candle) -> Option<DonchianChannel> {
+        self.last_break = self.channel.and_then(|prior| channel_break(prior, candle));
+
+        while self
+            .highs
+            .back()
+            .is_some_and(|&(_, high)| high <= candle.high)
+        {
+            self.highs.pop_back();
+        }
+        self.highs.push_back((self.index, candle.high));
+        while self.lows.back().is_some_and(|&(_, low)| low >= candle.low) {
+            self.lows.pop_back();
+        }
+        self.lows.push_back((self.index, candle.low));
+
+        if self.index >= self.period {
+            let oldest = self.index + 1 - self.period;
+            while self.highs.front().is_some_and(|&(i, _)| i < oldest) {
+                self.highs.pop_front();
+            }
+            while self.lows.front().is_some_and(|&(i, _)| i < oldest) {
+                self.lows.pop_front();
+            }
+        }
+        self.index += 1;
+
+        self.channel = match (self.highs.front(), self.lows.front()) {
+            (Some(&(_, upper)), Some(&(_, lower))) if self.index >= self.period => {
+                Some(DonchianChannel { upper, lower })
+            }
+            _ => None,
+        };
+        self.channel
+    }
+
+    pub fn value(&self) -> Option<DonchianChannel> {
+        self.channel
+    }
+
+    /// How the latest candle closed relative to the channel of the `period`
+    /// candles before it.
+    pub fn last_break(&self) -> Option<ChannelBreak> {
+        self.last_break
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    /// Feed a whole snapshot, returning the channel after each candle.
+    pub fn apply_series(&mut self, candles: &[Candle]) -> Vec<Option<DonchianChannel>> {
+        candles.iter().map(|c| self.update(c)).collect()
+    }
+}
+
+/// Upper/middle/lower bands around a midline, as drawn by Bollinger and
+/// Keltner channels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bands {
+    pub upper: f64,
+    pub middle: f64,
+    pub lower: f64,
+}
+
+impl Bands {
+    fn around(middle: f64, width: f64) -> Self {
+        Self {
+            upper: middle + width,
+            middle,
+            lower: middle - width,
+        }
+    }
+
+    /// Whether both bands sit strictly inside `other`.
+    pub fn is_inside(&self, other: &Bands) -> bool {
+        self.upper < other.upper && self.lower > other.lower
+    }
+}
+
+/// Bollinger bands: SMA of closes plus/minus `k` population standard deviations.
+#[derive(Debug, Clone)]
+pub struct BollingerCalculator {
+    window: RollingWindow,
+    k: f64,
+}
+
+impl BollingerCalculator {
+    pub fn new(period: usize, k: f64) -> Self {
+        assert!(period > 0, "Bollinger period must be positive");
+        Self {
+            window: RollingWindow::new(period),
+            k,
+        }
+    }
+
+    /// Feed the next closed candle. Returns `None` until `period` candles have been seen.
+    pub fn update(&mut self, candle: &Candle) -> Option<Bands> {
+        self.window.push(candle.close);
+        self.value()
+    }
+
+    pub fn value(&self) -> Option<Bands> {
+        if !self.window.is_full() {
+            return None;
+        }
+        let (mean, variance) = (self.window.mean()?, self.window.variance()?);
+        Some(Bands::around(mean, self.k * variance.sqrt()))
+    }
+
+    /// Feed a whole snapshot, returning the bands after each candle.
+    pub fn apply_series(&mut self, candles: &[Candle]) -> Vec<Option<Bands>> {
+        candles.iter().map(|c| self.update(c)).collect()
+    }
+}
+
+/// Keltner channel: EMA of closes plus/minus `multiplier` ATRs.
+#[derive(Debug, Clone)]
+pub struct KeltnerCalculator {
+    ema: EmaCalculator,
+    atr: AtrCalculator,
+    multiplier: f64,
+}
+
+impl KeltnerCalculator {
+    pub fn new(ema_period: usize, atr_period: usize, multiplier: f64) -> Self {
+        Self {
+            ema: EmaCalculator::new(ema_period),
+            atr: AtrCalculator::new(atr_period),
+            multiplier,
+        }
+    }
+
+    /// Feed the next closed candle. Returns `None` until both the EMA and
+    /// the ATR have warmed up.
+    pub fn update(&mut self, candle: &Candle) -> Option<Bands> {
+        self.ema.update(candle);
+        self.atr.update(candle);
+        self.value()
+    }
+
+    pub fn value(&self) -> Option<Bands> {
+        Some(Bands::around(
+            self.ema.value()?,
+            self.multiplier * self.atr.value()?,
+        ))
+    }
+
+    /// Feed a whole snapshot, returning the bands after each candle.
+    pub fn apply_series(&mut self, candles: &[Candle]) -> Vec<Option<Bands>> {
+        candles.iter().map(|c| self.update(c)).collect()
+    }
+}
+
+/// The "squeeze": Bollinger bands contracted inside the Keltner channel,
+/// volatility coiling ahead of a range breakout.
+pub fn is_squeeze(bollinger: &Bands, keltner: &Bands) -> bool {
+    bollinger.is_inside(keltner)
+}
@@ -0,0 +1,137 @@
+//! Candle interval arithmetic.
+//!
+//! Every interval except `1M` has a fixed length and buckets aligned to the
+//! Unix epoch (so `1w` buckets open on Thursdays 00:00 UTC, the weekday of
+//! 1970-01-01). `1M` buckets are calendar months opening on the 1st at
+//! 00:00 UTC, so their length varies between 28 and 31 days and range math
+//! has to walk the calendar instead of multiplying a fixed duration.
+
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
+
+/// Intervals accepted by Hyperliquid's `candleSnapshot`.
+pub const SUPPORTED_INTERVALS: [&str; 14] = [
+    "1m", "3m", "5m", "15m", "30m", "1h", "2h", "4h", "8h", "12h", "1d", "3d", "1w", "1M",
+];
+
+const MINUTE_MS: u64 = 60_000;
+const HOUR_MS: u64 = 60 * MINUTE_MS;
+const DAY_MS: u64 = 24 * HOUR_MS;
+
+pub fn is_supported(interval: &str) -> bool {
+    SUPPORTED_INTERVALS.contains(&interval)
+}
+
+/// Length of one candle for fixed-length intervals.
+///
+/// Returns `None` for `1M`, whose length depends on the month, and for
+/// unsupported intervals.
+pub fn interval_ms(interval: &str) -> Option<u64> {
+    let ms = match interval {
+        "1m" => MINUTE_MS,
+        "3m" => 3 * MINUTE_MS,
+        "5m" => 5 * MINUTE_MS,
+        "15m" => 15 * MINUTE_MS,
+        "30m" => 30 * MINUTE_MS,
+        "1h" => HOUR_MS,
+        "2h" => 2 * HOUR_MS,
+        "4h" => 4 * HOUR_MS,
+        "8h" => 8 * HOUR_MS,
+        "12h" => 12 * HOUR_MS,
+        "1d" => DAY_MS,
+        "3d" => 3 * DAY_MS,
+        "1w" => 7 * DAY_MS,
+        _ => return None,
+    };
+    Some(ms)
+}
+
+fn is_monthly(interval: &str) -> bool {
+    interval == "1M"
+}
+
+fn month_start(ts_ms: u64) -> Option<NaiveDate> {
+    let date = DateTime::<Utc>::from_timestamp_millis(i64::try_from(ts_ms).ok()?)?.date_naive();
+    NaiveDate::from_ymd_opt(date.year(), date.month(), 1)
+}
+
+fn date_ms(date: NaiveDate) -> Option<u64> {
+    u64::try_from(date.and_hms_opt(0, 0, 0)?.and_utc().timestamp_millis()).ok()
+}
+
+/// Open time of the candle that contains `ts_ms`.
+pub fn bucket_start(interval: &str, ts_ms: u64) -> Option<u64> {
+    if is_monthly(interval) {
+        return date_ms(month_start(ts_ms)?);
+    }
+    let ms = interval_ms(interval)?;
+    Some(ts_ms - ts_ms % ms)
+}
+
+/// Open time of the candle after the one that contains `ts_ms`.
+pub fn next_bucket_start(interval: &str, ts_ms: u64) -> Option<u64> {
+    if is_monthly(interval) {
+        return date_ms(month_start(ts_ms)?.checked_add_months(Months::new(1))?);
+    }
+    Some(bucket_start(interval, ts_ms)? + interval_ms(interval)?)
+}
+
+/// Start of a window holding the `count` most recent candles as of `now_ms`,
+/// including the one still open at `now_ms`.
+///
+/// For `1M` this walks back calendar months, so `count = 12` always starts
+/// on the 1st of the month eleven months before the current one.
+pub fn window_start(interval: &str, count: u32, now_ms: u64) -> Option<u64> {
+    let back = count.saturating_sub(1);
+    if is_monthly(interval) {
+        return date_ms(month_start(now_ms)?.checked_sub_months(Months::new(back))?);
+    }
+    let ms = interval_ms(interval)?;
+    Some(bucket_start(interval, now_ms)?.saturating_sub(u64::from(back) * ms))
+}
+
+/// Time from `now_ms` until the next candle opens, i.e. how long a poller
+/// should wait before the current candle can have closed.
+pub fn ms_until_next_bucket(interval: &str, now_ms: u64) -> Option<u64> {
+    Some(next_bucket_start(interval, now_ms)? - now_ms)
+}
+
+/// Candles from the one containing `from_ms` to the one containing `to_ms`:
+/// 0 for the same candle, 1 for the next. Counts the candles in between
+/// whether or not they were ever seen, so gaps in a feed don't shrink it.
+pub fn candles_between(interval: &str, from_ms: u64, to_ms: u64) -> Option<u64> {
+    let from = bucket_start(interval, from_ms)?;
+    let to = bucket_start(interval, to_ms)?;
+    if to <= from {
+        return Some(0);
+    }
+    if !is_monthly(interval) {
+        return Some((to - from) / interval_ms(interval)?);
+    }
+    let mut count = 0;
+    let mut open = from;
+    while open < to {
+        count += 1;
+        open = next_bucket_start(interval, open)?;
+    }
+    Some(count)
+}
+
+/// Number of candles that opened after the one at `last_open_ms` and have
+/// closed by `now_ms`: the gap a poller has to backfill.
+pub fn closed_since(interval: &str, last_open_ms: u64, now_ms: u64) -> Option<u64> {
+    let forming = bucket_start(interval, now_ms)?;
+    let first_missing = next_bucket_start(interval, last_open_ms)?;
+    if first_missing >= forming {
+        return Some(0);
+    }
+    if !is_monthly(interval) {
+        return Some((forming - first_missing) / interval_ms(interval)?);
+    }
+    let mut count = 0;
+    let mut open = first_missing;
+    while open < forming {
+        count += 1;
+        open = next_bucket_start(interval, open)?;
+    }
+    Some(count)
+}
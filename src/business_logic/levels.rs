@@ -0,0 +1,168 @@
+//! Horizontal support and resistance levels clustered from swing points.
+
+use serde::Serialize;
+
+use crate::business_logic::indicators::AtrCalculator;
+use crate::business_logic::swing::SwingDetector;
+use crate::models::candle::Candle;
+
+#[derive(Debug, Clone, Copy)]
+pub struct LevelConfig {
+    pub atr_period: usize,
+    /// Swing reversal size in ATRs.
+    pub rev_atr: f64,
+    /// Swings within this many ATRs of a level count as a touch of it.
+    pub merge_atr: f64,
+    /// ATRs beyond a level a close must reach to break it.
+    pub break_atr: f64,
+    /// Levels kept per coin; the weakest, least recently touched go first.
+    pub max_levels: usize,
+}
+
+impl Default for LevelConfig {
+    fn default() -> Self {
+        Self {
+            atr_period: 14,
+            rev_atr: 1.0,
+            merge_atr: 0.5,
+            break_atr: 1.0,
+            max_levels: 20,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum LevelSide {
+    Support,
+    Resistance,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct Level {
+    /// Average of the swing prices that touched the level.
+    pub price: f64,
+    /// Swing touches; the level's strength.
+    pub touches: u32,
+    /// Open time of the candle that confirmed the latest touch (epoch ms).
+    pub last_touch_ms: u64,
+    pub side: LevelSide,
+    /// Whether the level has been broken once and changed side.
+    pub flipped: bool,
+}
+
+/// Per-coin level tracker fed one closed candle at a time.
+///
+/// Swing highs start resistance levels and swing lows support levels. A
+/// decisive close through a level flips its side; a flipped level that is
+/// broken again is dropped.
+#[derive(Debug, Clone)]
+pub struct LevelDetector {
+    config: LevelConfig,
+    atr: AtrCalculator,
+    swings: SwingDetector,
+    levels: Vec<Level>,
+}
+
+impl LevelDetector {
+    pub fn new(config: LevelConfig) -> Self {
+        Self {
+            atr: AtrCalculator::new(config.atr_period),
+            swings: SwingDetector::new(config.rev_atr),
+            config,
+            levels: Vec::new(),
+        }
+    }
+
+    /// Feed the next closed candle. Nothing happens until the ATR has warmed up.
+    pub fn update(&mut self, candle: &Candle) {
+        let Some(atr) = self.atr.update(candle) else {
+            return;
+        };
+        self.check_breaks(candle, atr);
+        if let Some(swing) = self.swings.update(candle, atr) {
+            self.touch(swing.price, swing.is_peak, candle.open_time, atr);
+        }
+    }
+
+    /// Up to `limit` levels, strongest first, most recently touched breaking ties.
+    pub fn top(&self, limit: usize) -> Vec<Level> {
+        let mut levels = self.levels.clone();
+        levels.sort_by(|a, b| {
+            b.touches
+                .cmp(&a.touches)
+                .then_with(|| b.last_touch_ms.cmp(&a.last_touch_ms))
+        });
+        levels.truncate(limit);
+        levels
+    }
+
+    fn touch(&mut self, price: f64, is_peak: bool, open_time: u64, atr: f64) {
+        let tolerance = self.config.merge_atr * atr;
+        let nearest = self
+            .levels
+            .iter_mut()
+            .filter(|level| (level.price - price).abs() <= tolerance)
+            .min_by(|a, b| (a.price - price).abs().total_cmp(&(b.price - price).abs()));
+        if let Some(level) = nearest {
+            let touches = f64::from(level.touches);
+            level.price = (level.price * touches + price) / (touches + 1.0);
+            level.touches += 1;
+            level.last_touch_ms = open_time;
+            return;
+        }
+
+        if self.levels.len() >= self.config.max_levels {
+            if let Some(weakest) = self.weakest() {
+                self.levels.swap_remove(weakest);
+            }
+        }
+        self.levels.push(Level {
+            price,
+            touches: 1,
+            last_touch_ms: open_time,
+            side: if is_peak {
+                LevelSide::Resistance
+            } else {
+                LevelSide::Support
+            },
+            flipped: false,
+        });
+    }
+
+    fn weakest(&self) -> Option<usize> {
+        self.levels
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                a.touches
+                    .cmp(&b.touches)
+                    .then_with(|| a.last_touch_ms.cmp(&b.last_touch_ms))
+            })
+            .map(|(i, _)| i)
+    }
+
+    fn check_breaks(&mut self, candle: &Candle, atr: f64) {
+        let margin = self.config.break_atr * atr;
+        self.levels.retain_mut(|level| {
+            let broken = match level.side {
+                LevelSide::Resistance => candle.close > level.price + margin,
+                LevelSide::Support => candle.close < level.price - margin,
+            };
+            if !broken {
+                return true;
+            }
+            if level.flipped {
+                return false;
+            }
+            level.side = match level.side {
+                LevelSide::Resistance => LevelSide::Support,
+                LevelSide::Support => LevelSide::Resistance,
+            };
+            level.flipped = true;
+            true
+        });
+    }
+}
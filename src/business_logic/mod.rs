@@ -0,0 +1,31 @@
+pub mod alerts;
+pub mod anomalies;
+pub mod ascending_triangle;
+pub mod cup_and_handle;
+pub mod descending_triangle;
+pub mod double_bottom;
+pub mod double_top;
+pub mod fibonacci;
+pub mod funding;
+pub mod gaps;
+pub mod head_and_shoulders;
+pub mod indicator_series;
+pub mod indicators;
+pub mod intervals;
+pub mod levels;
+pub mod movers;
+pub mod open_interest;
+pub mod paper;
+pub mod patterns;
+pub mod pivots;
+pub mod premium;
+pub mod quiet_hours;
+pub mod range_breakout;
+pub mod swing;
+pub mod trade_plan;
+pub mod transforms;
+pub mod trendline;
+pub mod triple_bottom;
+pub mod triple_top;
+pub mod volatility;
+pub mod volume;
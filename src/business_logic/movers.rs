@@ -0,0 +1,127 @@
+//! Ranking coins by price change over a trailing window.
+
+use serde::Serialize;
+
+use crate::business_logic::intervals;
+use crate::models::candle::Candle;
+
+/// Trailing windows `/movers` accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MoverWindow {
+    OneHour,
+    FourHours,
+    OneDay,
+}
+
+impl MoverWindow {
+    pub const ALL: [MoverWindow; 3] = [
+        MoverWindow::OneHour,
+        MoverWindow::FourHours,
+        MoverWindow::OneDay,
+    ];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|w| w.as_str() == value)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MoverWindow::OneHour => "1h",
+            MoverWindow::FourHours => "4h",
+            MoverWindow::OneDay => "24h",
+        }
+    }
+
+    /// Candle interval fine enough to measure this window.
+    pub fn candle_interval(self) -> &'static str {
+        match self {
+            MoverWindow::OneHour => "5m",
+            MoverWindow::FourHours => "15m",
+            MoverWindow::OneDay => "1h",
+        }
+    }
+
+    /// Whole candles of [`MoverWindow::candle_interval`] in the window.
+    pub fn candle_count(self) -> u32 {
+        match self {
+            MoverWindow::OneHour => 12,
+            MoverWindow::FourHours => 16,
+            MoverWindow::OneDay => 24,
+        }
+    }
+
+    /// Start of the candle range to fetch: the window's whole candles plus
+    /// the one still forming at `now_ms`.
+    pub fn fetch_start(self, now_ms: u64) -> u64 {
+        intervals::window_start(self.candle_interval(), self.candle_count() + 1, now_ms)
+            .expect("mover intervals are fixed-length")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct Mover {
+    pub coin: String,
+    /// Percent change from `open_price` to `last_price`.
+    pub change_pct: f64,
+    /// Open of the first candle in the window; for `24h`, the price a day ago.
+    pub open_price: f64,
+    /// Close of the latest (possibly still forming) candle; for `24h`, the
+    /// mark price.
+    pub last_price: f64,
+    /// Base-asset volume over the window.
+    pub volume: f64,
+    /// Volume valued at each candle's close; for `24h`, the exchange's
+    /// notional volume.
+    pub volume_usd: f64,
+}
+
+/// Change over `candles` (oldest first). `None` when empty or unpriced.
+pub fn mover_from_candles(coin: &str, candles: &[Candle]) -> Option<Mover> {
+    let first = candles.first()?;
+    let last = candles.last()?;
+    if first.open <= 0.0 {
+        return None;
+    }
+    Some(Mover {
+        coin: coin.to_string(),
+        change_pct: (last.close - first.open) / first.open * 100.0,
+        open_price: first.open,
+        last_price: last.close,
+        volume: candles.iter().map(|c| c.volume).sum(),
+        volume_usd: candles.iter().map(|c| c.volume * c.close).sum(),
+    })
+}
+
+/// Change over the last day from a perp's market context. `None` when
+/// unpriced a day ago.
+pub fn mover_from_day_context(
+    coin: &str,
+    prev_day_price: f64,
+    price: f64,
+    base_volume: f64,
+    notional_volume: f64,
+) -> Option<Mover> {
+    if prev_day_price <= 0.0 {
+        return None;
+    }
+    Some(Mover {
+        coin: coin.to_string(),
+        change_pct: (price - prev_day_price) / prev_day_price * 100.0,
+        open_price: prev_day_price,
+        last_price: price,
+        volume: base_volume,
+        volume_usd: notional_volume,
+    })
+}
+
+/// Largest moves first, in either direction; ties broken by coin name.
+pub fn rank(mut movers: Vec<Mover>) -> Vec<Mover> {
+    movers.sort_by(|a, b| {
+        b.change_pct
+            .abs()
+            .total_cmp(&a.change_pct.abs())
+            .then_with(|| a.coin.cmp(&b.coin))
+    });
+    movers
+}
@@ -0,0 +1,167 @@
+//! Open-interest change screening over periodic OI samples.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+const HOUR_MS: u64 = 3_600_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct OiScreenerConfig {
+    /// Window the OI and price deltas are measured over.
+    pub window_ms: u64,
+    /// OI rise (percent) over the window that counts as unusual.
+    pub rise_pct: f64,
+    /// Price change (percent) at or below which price counts as flat or down.
+    pub max_price_change_pct: f64,
+    /// OI drop (percent, positive) over the window that counts as a collapse.
+    pub collapse_pct: f64,
+}
+
+impl Default for OiScreenerConfig {
+    fn default() -> Self {
+        Self {
+            window_ms: 4 * HOUR_MS,
+            rise_pct: 15.0,
+            max_price_change_pct: 0.5,
+            collapse_pct: 20.0,
+        }
+    }
+}
+
+impl OiScreenerConfig {
+    /// Problems that would make the config unusable, one message each.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.window_ms == 0 {
+            errors.push("window_ms must be positive".to_string());
+        }
+        if !(self.rise_pct > 0.0 && self.rise_pct.is_finite()) {
+            errors.push("rise_pct must be positive".to_string());
+        }
+        if !self.max_price_change_pct.is_finite() {
+            errors.push("max_price_change_pct must be a number".to_string());
+        }
+        if !(self.collapse_pct > 0.0 && self.collapse_pct.is_finite()) {
+            errors.push("collapse_pct must be positive".to_string());
+        }
+        errors
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum OiRule {
+    /// OI rising while price is flat or down: positions building against the move.
+    SqueezeFuel,
+    /// OI falling sharply: positions being closed or liquidated.
+    Collapse,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OiSample {
+    pub time_ms: u64,
+    pub open_interest: f64,
+    pub price: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct OiMetrics {
+    pub coin: String,
+    pub open_interest: f64,
+    pub oi_change_pct: f64,
+    pub price_change_pct: f64,
+    /// Actual span between the baseline and latest samples.
+    pub window_ms: u64,
+    pub rules: Vec<OiRule>,
+}
+
+/// Trailing OI samples for one coin.
+#[derive(Debug, Clone, Default)]
+pub struct OiHistory {
+    samples: VecDeque<OiSample>,
+}
+
+impl OiHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a sample and drop those older than `retain_ms` before it.
+    ///
+    /// Samples not newer than the latest one are ignored.
+    pub fn record(&mut self, sample: OiSample, retain_ms: u64) {
+        if self
+            .samples
+            .back()
+            .is_some_and(|last| sample.time_ms <= last.time_ms)
+        {
+            return;
+        }
+        self.samples.push_back(sample);
+        let cutoff = sample.time_ms.saturating_sub(retain_ms);
+        while self.samples.front().is_some_and(|s| s.time_ms < cutoff) {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Deltas from the oldest sample inside `config.window_ms` to the latest.
+    ///
+    /// Returns `None` until the history spans at least half the window, so a
+    /// freshly started sampler doesn't report noise from two adjacent samples.
+    pub fn metrics(&self, coin: &str, config: &OiScreenerConfig) -> Option<OiMetrics> {
+        let latest = *self.samples.back()?;
+        let cutoff = latest.time_ms.saturating_sub(config.window_ms);
+        let baseline = *self.samples.iter().find(|s| s.time_ms >= cutoff)?;
+        let span = latest.time_ms - baseline.time_ms;
+        if span == 0 || span * 2 < config.window_ms {
+            return None;
+        }
+        if baseline.open_interest <= 0.0 || baseline.price <= 0.0 {
+            return None;
+        }
+
+        let oi_change_pct = pct_change(baseline.open_interest, latest.open_interest);
+        let price_change_pct = pct_change(baseline.price, latest.price);
+        Some(OiMetrics {
+            coin: coin.to_string(),
+            open_interest: latest.open_interest,
+            oi_change_pct,
+            price_change_pct,
+            window_ms: span,
+            rules: classify(oi_change_pct, price_change_pct, config),
+        })
+    }
+}
+
+/// Rules triggered by an OI and price change over the configured window.
+pub fn classify(
+    oi_change_pct: f64,
+    price_change_pct: f64,
+    config: &OiScreenerConfig,
+) -> Vec<OiRule> {
+    let mut rules = Vec::new();
+    if oi_change_pct >= config.rise_pct && price_change_pct <= config.max_price_change_pct {
+        rules.push(OiRule::SqueezeFuel);
+    }
+    if oi_change_pct <= -config.collapse_pct {
+        rules.push(OiRule::Collapse);
+    }
+    rules
+}
+
+fn pct_change(from: f64, to: f64) -> f64 {
+    (to - from) / from * 100.0
+}
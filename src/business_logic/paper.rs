@@ -0,0 +1,186 @@
+//! Paper trading of confirmed signals: virtual shorts tracked against
+//! candles and scored in R-multiples.
+
+use serde::Serialize;
+
+use crate::business_logic::trade_plan::TradePlan;
+use crate::models::candle::Candle;
+
+/// Which level is assumed hit first when one candle touches both.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BothTouched {
+    /// Assume the stop (pessimistic).
+    #[default]
+    StopFirst,
+    TargetFirst,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PaperConfig {
+    /// Quote amount lost when a trade hits its stop; sizes every position.
+    pub risk_per_trade: f64,
+    pub both_touched: BothTouched,
+}
+
+impl Default for PaperConfig {
+    fn default() -> Self {
+        Self {
+            risk_per_trade: 100.0,
+            both_touched: BothTouched::StopFirst,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ExitReason {
+    Stop,
+    Target,
+}
+
+/// Open virtual short.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct PaperPosition {
+    pub coin: String,
+    pub entry: f64,
+    pub stop: f64,
+    pub target: f64,
+    /// Base units, sized so hitting the stop loses `risk_per_trade`.
+    pub size: f64,
+    /// Open time of the confirming candle; only later candles can exit.
+    pub opened_at_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct ClosedTrade {
+    pub coin: String,
+    pub entry: f64,
+    pub exit: f64,
+    pub reason: ExitReason,
+    pub opened_at_ms: u64,
+    pub closed_at_ms: u64,
+    /// Profit in multiples of the initial risk; -1 at the stop (worse on a gap).
+    pub r_multiple: f64,
+    pub pnl: f64,
+}
+
+impl PaperPosition {
+    /// Open a short at the plan's entry. `None` if the plan carries no risk.
+    pub fn open(
+        coin: impl Into<String>,
+        plan: &TradePlan,
+        opened_at_ms: u64,
+        config: &PaperConfig,
+    ) -> Option<Self> {
+        let risk = plan.stop - plan.entry;
+        if risk <= 0.0 || config.risk_per_trade <= 0.0 {
+            return None;
+        }
+        Some(Self {
+            coin: coin.into(),
+            entry: plan.entry,
+            stop: plan.stop,
+            target: plan.target,
+            size: config.risk_per_trade / risk,
+            opened_at_ms,
+        })
+    }
+
+    /// Check the next candle, returning the closed trade if it exits.
+    ///
+    /// A candle that opens beyond a level fills at its open: worse than the
+    /// stop on a gap up, better than the target on a gap down.
+    pub fn update(&self, candle: &Candle, config: &PaperConfig) -> Option<ClosedTrade> {
+        if candle.open_time <= self.opened_at_ms {
+            return None;
+        }
+        let (exit, reason) = if candle.open >= self.stop {
+            (candle.open, ExitReason::Stop)
+        } else if candle.open <= self.target {
+            (candle.open, ExitReason::Target)
+        } else {
+            let stop_hit = candle.high >= self.stop;
+            let target_hit = candle.low <= self.target;
+            match (stop_hit, target_hit) {
+                (true, true) => match config.both_touched {
+                    BothTouched::StopFirst => (self.stop, ExitReason::Stop),
+                    BothTouched::TargetFirst => (self.target, ExitReason::Target),
+                },
+                (true, false) => (self.stop, ExitReason::Stop),
+                (false, true) => (self.target, ExitReason::Target),
+                (false, false) => return None,
+            }
+        };
+        Some(self.close(exit, reason, candle.open_time))
+    }
+
+    fn close(&self, exit: f64, reason: ExitReason, closed_at_ms: u64) -> ClosedTrade {
+        let r_multiple = (self.entry - exit) / (self.stop - self.entry);
+        ClosedTrade {
+            coin: self.coin.clone(),
+            entry: self.entry,
+            exit,
+            reason,
+            opened_at_ms: self.opened_at_ms,
+            closed_at_ms,
+            r_multiple,
+            pnl: (self.entry - exit) * self.size,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct EquityPoint {
+    pub time_ms: u64,
+    /// Cumulative PnL after the trade closing at `time_ms`.
+    pub equity: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct PaperPerformance {
+    pub trades: usize,
+    pub wins: usize,
+    /// `None` before the first closed trade.
+    pub win_rate: Option<f64>,
+    pub average_r: Option<f64>,
+    pub total_r: f64,
+    pub total_pnl: f64,
+    /// One point per closed trade, in closing order.
+    pub equity_curve: Vec<EquityPoint>,
+}
+
+/// Summarize closed trades, ordering them by close time.
+pub fn performance(trades: &[ClosedTrade]) -> PaperPerformance {
+    let mut ordered: Vec<&ClosedTrade> = trades.iter().collect();
+    ordered.sort_by_key(|t| t.closed_at_ms);
+
+    let mut equity = 0.0;
+    let equity_curve = ordered
+        .iter()
+        .map(|trade| {
+            equity += trade.pnl;
+            EquityPoint {
+                time_ms: trade.closed_at_ms,
+                equity,
+            }
+        })
+        .collect();
+
+    let count = ordered.len();
+    let wins = ordered.iter().filter(|t| t.r_multiple > 0.0).count();
+    let total_r: f64 = ordered.iter().map(|t| t.r_multiple).sum();
+    PaperPerformance {
+        trades: count,
+        wins,
+        win_rate: (count > 0).then(|| wins as f64 / count as f64),
+        average_r: (count > 0).then(|| total_r / count as f64),
+        total_r,
+        total_pnl: equity,
+        equity_curve,
+    }
+}
@@ -0,0 +1,251 @@
+//! The common face of the chart-pattern detectors, so the monitor can run
+//! any mix of them per coin and publish their progress in one place.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::business_logic::alerts::{PatternAlert, PatternKind, TrendDirection};
+use crate::business_logic::ascending_triangle::{
+    AscendingTriangleDetector, AscendingTriangleStatus,
+};
+use crate::business_logic::cup_and_handle::{CupAndHandleDetector, CupAndHandleStatus};
+use crate::business_logic::descending_triangle::{
+    DescendingTriangleDetector, DescendingTriangleStatus,
+};
+use crate::business_logic::double_bottom::{DoubleBottomDetector, DoubleBottomStatus};
+use crate::business_logic::double_top::{DoubleTopDetector, DoubleTopStatus};
+use crate::business_logic::head_and_shoulders::{HeadAndShouldersDetector, HeadAndShouldersStatus};
+use crate::business_logic::open_interest::OiRule;
+use crate::business_logic::range_breakout::{RangeBreakoutDetector, RangeBreakoutStatus};
+use crate::business_logic::trendline::{TrendlineDetector, TrendlinesStatus};
+use crate::business_logic::triple_bottom::{TripleBottomDetector, TripleBottomStatus};
+use crate::business_logic::triple_top::{TripleTopDetector, TripleTopStatus};
+use crate::models::candle::Candle;
+
+/// A per-coin pattern state machine fed one closed candle at a time.
+pub trait PatternDetector: fmt::Debug + Send {
+    /// Feed the next closed candle, returning an alert if it raised one.
+    fn update(&mut self, candle: &Candle) -> Option<PatternAlert>;
+
+    /// Where the pattern stands after the last candle.
+    fn status(&self) -> PatternStatus;
+
+    /// Closed candles to backfill before the detector can see a full
+    /// pattern, when it needs more than the swing-based detectors.
+    fn warmup_candles(&self) -> usize {
+        0
+    }
+
+    /// The latest higher-timeframe trend, for detectors that check their
+    /// alerts against it; the rest ignore it.
+    fn set_htf_trend(&mut self, _trend: TrendDirection) {}
+
+    /// What the other screeners currently say about the coin, for
+    /// detectors that score their patterns against it; the rest ignore it.
+    fn set_confluence(&mut self, _confluence: &Confluence) {}
+}
+
+/// Signals from the open-interest and premium screeners for one coin.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Confluence {
+    /// Open-interest screener rules the coin trips.
+    pub oi_rules: Vec<OiRule>,
+    /// Latest mark premium over the oracle price (percent).
+    pub premium_pct: Option<f64>,
+    /// How long the premium has stayed beyond the band on its current
+    /// side (ms).
+    pub premium_extreme_ms: u64,
+}
+
+/// One detector's progress on one coin, tagged by `pattern`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(tag = "pattern", rename_all = "snake_case")]
+pub enum PatternStatus {
+    DoubleBottom(DoubleBottomStatus),
+    DoubleTop(Box<DoubleTopStatus>),
+    HeadAndShoulders(HeadAndShouldersStatus),
+    TripleTop(TripleTopStatus),
+    TripleBottom(TripleBottomStatus),
+    AscendingTriangle(AscendingTriangleStatus),
+    DescendingTriangle(DescendingTriangleStatus),
+    CupAndHandle(CupAndHandleStatus),
+    RangeBreakout(RangeBreakoutStatus),
+    Trendline(TrendlinesStatus),
+}
+
+/// Detector names, as used for the `pattern` tag and in filters.
+pub const PATTERN_NAMES: &[&str] = &[
+    "double_bottom",
+    "double_top",
+    "head_and_shoulders",
+    "triple_top",
+    "triple_bottom",
+    "ascending_triangle",
+    "descending_triangle",
+    "cup_and_handle",
+    "range_breakout",
+    "trendline",
+];
+
+impl PatternStatus {
+    pub fn coin(&self) -> &str {
+        match self {
+            PatternStatus::DoubleBottom(status) => &status.coin,
+            PatternStatus::DoubleTop(status) => &status.coin,
+            PatternStatus::HeadAndShoulders(status) => &status.coin,
+            PatternStatus::TripleTop(status) => &status.coin,
+            PatternStatus::TripleBottom(status) => &status.coin,
+            PatternStatus::AscendingTriangle(status) => &status.coin,
+            PatternStatus::DescendingTriangle(status) => &status.coin,
+            PatternStatus::CupAndHandle(status) => &status.coin,
+            PatternStatus::RangeBreakout(status) => &status.coin,
+            PatternStatus::Trendline(status) => &status.coin,
+        }
+    }
+
+    /// The detector's name, as in [`PATTERN_NAMES`].
+    pub fn name(&self) -> &'static str {
+        match self {
+            PatternStatus::DoubleBottom(_) => "double_bottom",
+            PatternStatus::DoubleTop(_) => "double_top",
+            PatternStatus::HeadAndShoulders(_) => "head_and_shoulders",
+            PatternStatus::TripleTop(_) => "triple_top",
+            PatternStatus::TripleBottom(_) => "triple_bottom",
+            PatternStatus::AscendingTriangle(_) => "ascending_triangle",
+            PatternStatus::DescendingTriangle(_) => "descending_triangle",
+            PatternStatus::CupAndHandle(_) => "cup_and_handle",
+            PatternStatus::RangeBreakout(_) => "range_breakout",
+            PatternStatus::Trendline(_) => "trendline",
+        }
+    }
+}
+
+/// Name of the detector that raises alerts of `kind`.
+pub fn detector_name(kind: PatternKind) -> &'static str {
+    match kind {
+        PatternKind::DoubleBottom => "double_bottom",
+        PatternKind::DoubleTop => "double_top",
+        PatternKind::HeadAndShoulders => "head_and_shoulders",
+        PatternKind::TripleTop => "triple_top",
+        PatternKind::TripleBottom => "triple_bottom",
+        PatternKind::AscendingTriangle => "ascending_triangle",
+        PatternKind::DescendingTriangle => "descending_triangle",
+        PatternKind::CupAndHandle => "cup_and_handle",
+        PatternKind::RangeBreakoutUp | PatternKind::RangeBreakoutDown => "range_breakout",
+        PatternKind::TrendlineBreakDown | PatternKind::TrendlineBreakUp => "trendline",
+    }
+}
+
+impl PatternDetector for DoubleBottomDetector {
+    fn update(&mut self, candle: &Candle) -> Option<PatternAlert> {
+        DoubleBottomDetector::update(self, candle)
+    }
+
+    fn status(&self) -> PatternStatus {
+        PatternStatus::DoubleBottom(DoubleBottomDetector::status(self))
+    }
+}
+
+impl PatternDetector for DoubleTopDetector {
+    fn update(&mut self, candle: &Candle) -> Option<PatternAlert> {
+        DoubleTopDetector::update(self, candle)
+    }
+
+    fn status(&self) -> PatternStatus {
+        PatternStatus::DoubleTop(Box::new(DoubleTopDetector::status(self)))
+    }
+
+    fn set_htf_trend(&mut self, trend: TrendDirection) {
+        DoubleTopDetector::set_htf_trend(self, trend)
+    }
+
+    fn set_confluence(&mut self, confluence: &Confluence) {
+        DoubleTopDetector::set_confluence(self, confluence)
+    }
+}
+
+impl PatternDetector for HeadAndShouldersDetector {
+    fn update(&mut self, candle: &Candle) -> Option<PatternAlert> {
+        HeadAndShouldersDetector::update(self, candle)
+    }
+
+    fn status(&self) -> PatternStatus {
+        PatternStatus::HeadAndShoulders(HeadAndShouldersDetector::status(self))
+    }
+}
+
+impl PatternDetector for TripleTopDetector {
+    fn update(&mut self, candle: &Candle) -> Option<PatternAlert> {
+        TripleTopDetector::update(self, candle)
+    }
+
+    fn status(&self) -> PatternStatus {
+        PatternStatus::TripleTop(TripleTopDetector::status(self))
+    }
+}
+
+impl PatternDetector for TripleBottomDetector {
+    fn update(&mut self, candle: &Candle) -> Option<PatternAlert> {
+        TripleBottomDetector::update(self, candle)
+    }
+
+    fn status(&self) -> PatternStatus {
+        PatternStatus::TripleBottom(TripleBottomDetector::status(self))
+    }
+}
+
+impl PatternDetector for AscendingTriangleDetector {
+    fn update(&mut self, candle: &Candle) -> Option<PatternAlert> {
+        AscendingTriangleDetector::update(self, candle)
+    }
+
+    fn status(&self) -> PatternStatus {
+        PatternStatus::AscendingTriangle(AscendingTriangleDetector::status(self))
+    }
+}
+
+impl PatternDetector for DescendingTriangleDetector {
+    fn update(&mut self, candle: &Candle) -> Option<PatternAlert> {
+        DescendingTriangleDetector::update(self, candle)
+    }
+
+    fn status(&self) -> PatternStatus {
+        PatternStatus::DescendingTriangle(DescendingTriangleDetector::status(self))
+    }
+}
+
+impl PatternDetector for CupAndHandleDetector {
+    fn update(&mut self, candle: &Candle) -> Option<PatternAlert> {
+        CupAndHandleDetector::update(self, candle)
+    }
+
+    fn status(&self) -> PatternStatus {
+        PatternStatus::CupAndHandle(CupAndHandleDetector::status(self))
+    }
+
+    fn warmup_candles(&self) -> usize {
+        CupAndHandleDetector::warmup_candles(self)
+    }
+}
+
+impl PatternDetector for RangeBreakoutDetector {
+    fn update(&mut self, candle: &Candle) -> Option<PatternAlert> {
+        RangeBreakoutDetector::update(self, candle)
+    }
+
+    fn status(&self) -> PatternStatus {
+        PatternStatus::RangeBreakout(RangeBreakoutDetector::status(self))
+    }
+}
+
+impl PatternDetector for TrendlineDetector {
+    fn update(&mut self, candle: &Candle) -> Option<PatternAlert> {
+        TrendlineDetector::update(self, candle)
+    }
+
+    fn status(&self) -> PatternStatus {
+        PatternStatus::Trendline(TrendlineDetector::status(self))
+    }
+}
@@ -0,0 +1,42 @@
+//! Classic floor-trader pivot points from a prior session's OHLC.
+
+use chrono::{DateTime, NaiveDate};
+use serde::Serialize;
+
+use crate::models::candle::Candle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct PivotLevels {
+    /// `(high + low + close) / 3`.
+    pub pivot: f64,
+    pub r1: f64,
+    pub r2: f64,
+    pub r3: f64,
+    pub s1: f64,
+    pub s2: f64,
+    pub s3: f64,
+}
+
+impl PivotLevels {
+    /// Levels for the session after `candle`.
+    pub fn classic(candle: &Candle) -> Self {
+        let (high, low) = (candle.high, candle.low);
+        let pivot = (high + low + candle.close) / 3.0;
+        let range = high - low;
+        Self {
+            pivot,
+            r1: 2.0 * pivot - low,
+            r2: pivot + range,
+            r3: high + 2.0 * (pivot - low),
+            s1: 2.0 * pivot - high,
+            s2: pivot - range,
+            s3: low - 2.0 * (high - pivot),
+        }
+    }
+}
+
+/// UTC calendar date containing `ts_ms`.
+pub fn utc_date(ts_ms: u64) -> Option<NaiveDate> {
+    DateTime::from_timestamp_millis(i64::try_from(ts_ms).ok()?).map(|t| t.date_naive())
+}
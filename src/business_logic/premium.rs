@@ -0,0 +1,98 @@
+//! Perp mark price premium (or discount) versus the oracle price.
+
+use std::collections::VecDeque;
+
+use serde::Serialize;
+
+/// Premium beyond which a coin is listed by the premium screener (percent).
+pub const DEFAULT_PREMIUM_BAND_PCT: f64 = 0.5;
+
+/// `(mark - oracle) / oracle` in percent; negative is a discount.
+pub fn premium_pct(mark_price: f64, oracle_price: f64) -> Option<f64> {
+    (oracle_price > 0.0).then(|| (mark_price - oracle_price) / oracle_price * 100.0)
+}
+
+/// Whether `premium_pct` lies outside `±band_pct`.
+pub fn outside_band(premium_pct: f64, band_pct: f64) -> bool {
+    premium_pct.abs() > band_pct
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct PremiumSample {
+    pub time_ms: u64,
+    pub premium_pct: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct PremiumStats {
+    pub mean_pct: f64,
+    pub min_pct: f64,
+    pub max_pct: f64,
+}
+
+/// Rolling premium samples for one coin, oldest first.
+#[derive(Debug, Clone, Default)]
+pub struct PremiumHistory {
+    samples: VecDeque<PremiumSample>,
+}
+
+impl PremiumHistory {
+    /// Append a sample, then drop those older than `retain_ms` before it.
+    /// Samples not newer than the latest one are ignored.
+    pub fn record(&mut self, sample: PremiumSample, retain_ms: u64) {
+        if self
+            .samples
+            .back()
+            .is_some_and(|last| sample.time_ms <= last.time_ms)
+        {
+            return;
+        }
+        self.samples.push_back(sample);
+        let cutoff = sample.time_ms.saturating_sub(retain_ms);
+        while self.samples.front().is_some_and(|s| s.time_ms < cutoff) {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn samples(&self) -> impl Iterator<Item = &PremiumSample> {
+        self.samples.iter()
+    }
+
+    pub fn latest(&self) -> Option<PremiumSample> {
+        self.samples.back().copied()
+    }
+
+    pub fn stats(&self) -> Option<PremiumStats> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let values = self.samples.iter().map(|s| s.premium_pct);
+        Some(PremiumStats {
+            mean_pct: values.clone().sum::<f64>() / self.samples.len() as f64,
+            min_pct: values.clone().fold(f64::INFINITY, f64::min),
+            max_pct: values.fold(f64::NEG_INFINITY, f64::max),
+        })
+    }
+
+    /// How long the premium has stayed outside `±band_pct` on the same side,
+    /// measured back from the latest sample. Zero when it is inside the band.
+    pub fn persistent_extreme_ms(&self, band_pct: f64) -> u64 {
+        let Some(latest) = self.latest() else {
+            return 0;
+        };
+        if !outside_band(latest.premium_pct, band_pct) {
+            return 0;
+        }
+        let side = latest.premium_pct.signum();
+        let first = self
+            .samples
+            .iter()
+            .rev()
+            .take_while(|s| outside_band(s.premium_pct, band_pct) && s.premium_pct.signum() == side)
+            .last()
+            .map_or(latest.time_ms, |s| s.time_ms);
+        latest.time_ms - first
+    }
+}
@@ -0,0 +1,115 @@
+//! Daily quiet-hours windows for alert delivery.
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::business_logic::alerts::AlertSeverity;
+
+/// What happens to an alert that lands inside quiet hours.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum QuietMode {
+    /// The alert is discarded.
+    #[default]
+    Drop,
+    /// The alert is held and delivered when the window ends.
+    Defer,
+}
+
+/// A daily window, in local time, during which alerts are held back.
+///
+/// `start` and `end` are `HH:MM`; a window whose end is before its start
+/// wraps past midnight (`22:00`-`07:00`). Equal start and end is an empty
+/// window.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct QuietHours {
+    pub start: String,
+    pub end: String,
+    /// IANA timezone name, e.g. `Europe/London`; defaults to UTC.
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    /// Alerts at or above this severity are delivered even during quiet hours.
+    pub bypass_severity: Option<AlertSeverity>,
+    #[serde(default)]
+    pub mode: QuietMode,
+}
+
+fn default_timezone() -> String {
+    "UTC".to_string()
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value, "%H:%M")
+        .map_err(|_| format!("expected a HH:MM time, got {value:?}"))
+}
+
+impl QuietHours {
+    /// Check that the times and timezone parse.
+    pub fn validate(&self) -> Result<(), String> {
+        self.parsed().map(|_| ())
+    }
+
+    fn parsed(&self) -> Result<(NaiveTime, NaiveTime, Tz), String> {
+        let start = parse_time(&self.start)?;
+        let end = parse_time(&self.end)?;
+        let tz = self
+            .timezone
+            .parse::<Tz>()
+            .map_err(|_| format!("unknown timezone {:?}", self.timezone))?;
+        Ok((start, end, tz))
+    }
+
+    /// If `now_ms` is inside the window, when the window ends (epoch ms).
+    ///
+    /// An invalid schedule is never quiet, so a bad config can't silence a sink.
+    pub fn active_until(&self, now_ms: u64) -> Option<u64> {
+        let (start, end, tz) = self.parsed().ok()?;
+        let now = tz.timestamp_millis_opt(now_ms as i64).single()?;
+        let local = now.time().with_nanosecond(0)?;
+
+        let quiet = if start < end {
+            start <= local && local < end
+        } else if start > end {
+            local >= start || local < end
+        } else {
+            false
+        };
+        if !quiet {
+            return None;
+        }
+
+        let today = now.date_naive();
+        let end_date = if local < end {
+            today
+        } else {
+            today + Duration::days(1)
+        };
+        local_to_ms(&tz, end_date, end)
+    }
+
+    /// If an alert at `severity` should be held back at `now_ms`, when the
+    /// window ends.
+    pub fn suppresses(&self, severity: AlertSeverity, now_ms: u64) -> Option<u64> {
+        if self
+            .bypass_severity
+            .is_some_and(|bypass| severity >= bypass)
+        {
+            return None;
+        }
+        self.active_until(now_ms)
+    }
+}
+
+/// Epoch ms of `time` on `date` in `tz`. A time skipped by a DST jump resolves
+/// to the first valid instant after it.
+fn local_to_ms(tz: &Tz, date: NaiveDate, time: NaiveTime) -> Option<u64> {
+    let naive = NaiveDateTime::new(date, time);
+    let resolved: DateTime<Tz> = (0..=120).find_map(|minutes| {
+        tz.from_local_datetime(&(naive + Duration::minutes(minutes)))
+            .earliest()
+    })?;
+    u64::try_from(resolved.timestamp_millis()).ok()
+}
@@ -0,0 +1,253 @@
+//! Range / consolidation breakout detection.
+//!
+//! Tracks the run of recent candles that fit inside a `band_pct` band. Once
+//! that run is long enough the coin is in a range, and the first close
+//! beyond either bound by an ATR buffer is the breakout.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::business_logic::alerts::{AlertStage, PatternAlert, PatternKind};
+use crate::business_logic::indicators::AtrCalculator;
+use crate::models::candle::Candle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct RangeBreakoutConfig {
+    pub atr_period: usize,
+    /// Max range width, high to low, as a % of the low.
+    pub band_pct: f64,
+    /// Candles that must fit in the band before it counts as a range.
+    pub min_candles: usize,
+    /// ATRs beyond a bound a close must reach to break out.
+    pub breakout_buffer: f64,
+    /// % a close must move from the broken bound before a new range is
+    /// looked for.
+    pub reset_distance_pct: f64,
+}
+
+impl Default for RangeBreakoutConfig {
+    fn default() -> Self {
+        Self {
+            atr_period: 14,
+            band_pct: 3.0,
+            min_candles: 20,
+            breakout_buffer: 0.3,
+            reset_distance_pct: 2.0,
+        }
+    }
+}
+
+impl RangeBreakoutConfig {
+    /// Problems that would make the config unusable, one message each.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let mut check = |ok: bool, message: &str| {
+            if !ok {
+                errors.push(message.to_string());
+            }
+        };
+        check(self.atr_period > 0, "atr_period must be positive");
+        check(
+            self.band_pct > 0.0 && self.band_pct <= 100.0,
+            "band_pct must be in (0, 100]",
+        );
+        check(self.min_candles >= 2, "min_candles must be at least 2");
+        check(
+            self.breakout_buffer >= 0.0 && self.breakout_buffer.is_finite(),
+            "breakout_buffer must not be negative",
+        );
+        check(
+            self.reset_distance_pct >= 0.0 && self.reset_distance_pct <= 100.0,
+            "reset_distance_pct must be in [0, 100]",
+        );
+        errors
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeBreakoutState {
+    Watching,
+    InRange,
+    BrokeUp,
+    BrokeDown,
+}
+
+impl RangeBreakoutState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RangeBreakoutState::Watching => "WATCHING",
+            RangeBreakoutState::InRange => "IN_RANGE",
+            RangeBreakoutState::BrokeUp => "BROKE_UP",
+            RangeBreakoutState::BrokeDown => "BROKE_DOWN",
+        }
+    }
+}
+
+/// Where a coin's range breakout stands, as published to the pattern state.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct RangeBreakoutStatus {
+    pub coin: String,
+    /// `WATCHING`, `IN_RANGE`, `BROKE_UP` or `BROKE_DOWN`.
+    pub state: String,
+    /// Bounds of the current range, or of the one just broken.
+    pub range_high: Option<f64>,
+    pub range_low: Option<f64>,
+    pub candles_in_range: usize,
+}
+
+/// Per-coin range breakout state machine fed one closed candle at a time.
+#[derive(Debug, Clone)]
+pub struct RangeBreakoutDetector {
+    coin: String,
+    config: RangeBreakoutConfig,
+    atr: AtrCalculator,
+    /// (high, low) of the candles currently in the band.
+    range: VecDeque<(f64, f64)>,
+    state: RangeBreakoutState,
+    /// Bounds frozen at the breakout, kept for the status payload.
+    broken: Option<(f64, f64)>,
+}
+
+impl RangeBreakoutDetector {
+    pub fn new(coin: impl Into<String>, config: RangeBreakoutConfig) -> Self {
+        Self {
+            coin: coin.into(),
+            atr: AtrCalculator::new(config.atr_period),
+            config,
+            range: VecDeque::new(),
+            state: RangeBreakoutState::Watching,
+            broken: None,
+        }
+    }
+
+    pub fn state(&self) -> RangeBreakoutState {
+        self.state
+    }
+
+    pub fn range_high(&self) -> Option<f64> {
+        self.bounds().map(|(high, _)| high)
+    }
+
+    pub fn range_low(&self) -> Option<f64> {
+        self.bounds().map(|(_, low)| low)
+    }
+
+    pub fn candles_in_range(&self) -> usize {
+        self.range.len()
+    }
+
+    fn bounds(&self) -> Option<(f64, f64)> {
+        if self.broken.is_some() {
+            return self.broken;
+        }
+        let high = self.range.iter().map(|&(h, _)| h).reduce(f64::max)?;
+        let low = self.range.iter().map(|&(_, l)| l).reduce(f64::min)?;
+        Some((high, low))
+    }
+
+    pub fn status(&self) -> RangeBreakoutStatus {
+        RangeBreakoutStatus {
+            coin: self.coin.clone(),
+            state: self.state.as_str().to_string(),
+            range_high: self.range_high(),
+            range_low: self.range_low(),
+            candles_in_range: self.candles_in_range(),
+        }
+    }
+
+    /// Feed the next closed candle, returning an alert if it raised one.
+    ///
+    /// Nothing happens until the ATR has warmed up.
+    pub fn update(&mut self, candle: &Candle) -> Option<PatternAlert> {
+        let atr = self.atr.update(candle)?;
+
+        match self.state {
+            RangeBreakoutState::BrokeUp | RangeBreakoutState::BrokeDown => {
+                self.check_reset(candle);
+                None
+            }
+            RangeBreakoutState::InRange => self.check_breakout(candle, atr).or_else(|| {
+                self.push(candle);
+                None
+            }),
+            RangeBreakoutState::Watching => {
+                self.push(candle);
+                None
+            }
+        }
+    }
+
+    /// Add the candle to the run, dropping the oldest candles until the run
+    /// fits the band again.
+    fn push(&mut self, candle: &Candle) {
+        self.range.push_back((candle.high, candle.low));
+        while let Some((high, low)) = self.bounds() {
+            if (high - low) / low * 100.0 <= self.config.band_pct {
+                break;
+            }
+            self.range.pop_front();
+        }
+        self.state = if self.range.len() >= self.config.min_candles {
+            RangeBreakoutState::InRange
+        } else {
+            RangeBreakoutState::Watching
+        };
+    }
+
+    fn check_breakout(&mut self, candle: &Candle, atr: f64) -> Option<PatternAlert> {
+        let (high, low) = self.bounds()?;
+        let buffer = self.config.breakout_buffer * atr;
+        let (state, pattern, level) = if candle.close > high + buffer {
+            (
+                RangeBreakoutState::BrokeUp,
+                PatternKind::RangeBreakoutUp,
+                high,
+            )
+        } else if candle.close < low - buffer {
+            (
+                RangeBreakoutState::BrokeDown,
+                PatternKind::RangeBreakoutDown,
+                low,
+            )
+        } else {
+            return None;
+        };
+        self.broken = Some((high, low));
+        self.state = state;
+        Some(PatternAlert {
+            coin: self.coin.clone(),
+            pattern,
+            stage: AlertStage::Confirmation,
+            open_time: candle.open_time,
+            price: candle.close,
+            level,
+            target_price: None,
+            pattern_height_pct: None,
+            retest_high: None,
+            confirmation_mode: None,
+            htf_trend: None,
+        })
+    }
+
+    /// Start looking for a new range once price is far enough from the
+    /// broken bound, in either direction.
+    fn check_reset(&mut self, candle: &Candle) {
+        let Some((high, low)) = self.broken else {
+            return;
+        };
+        let level = if self.state == RangeBreakoutState::BrokeUp {
+            high
+        } else {
+            low
+        };
+        if (candle.close - level).abs() / level * 100.0 >= self.config.reset_distance_pct {
+            self.broken = None;
+            self.range.clear();
+            self.push(candle);
+        }
+    }
+}
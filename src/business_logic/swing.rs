@@ -0,0 +1,280 @@
+//! Real-time swing high/low detection without look-ahead.
+//!
+//! A swing is confirmed once price reverses by `rev_atr * atr` from the running
+//! extreme, as described in `spec/double_top_detection.md`.
+
+use serde::Serialize;
+
+use crate::business_logic::indicators::AtrCalculator;
+use crate::models::candle::Candle;
+
+/// Candles observed before the detector may commit to an initial trend.
+pub const DEFAULT_SEED_CANDLES: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trend {
+    Up,
+    Down,
+}
+
+/// A confirmed swing high (`is_peak`) or swing low.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SwingPoint {
+    pub price: f64,
+    pub is_peak: bool,
+    /// Open time of the candle that made the extreme (epoch ms), which is
+    /// earlier than the candle that confirmed it.
+    pub open_time: u64,
+}
+
+/// Extremes tracked before the initial trend is known.
+#[derive(Debug, Clone, Copy)]
+struct Seed {
+    high: f64,
+    high_time: u64,
+    low: f64,
+    low_time: u64,
+    /// Whether the low was set more recently than the high.
+    low_is_latest: bool,
+    seen: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum State {
+    Seeding(Seed),
+    Up { swing_high: f64, high_time: u64 },
+    Down { swing_low: f64, low_time: u64 },
+}
+
+/// Confirms swing highs and lows as candles arrive.
+///
+/// The initial trend is inferred rather than assumed: for the first
+/// `seed_candles` candles (and until price has moved a full reversal away from
+/// one of the seed extremes) the detector only tracks the highest high and
+/// lowest low and emits nothing. It then commits to the direction price moved
+/// in, so a series that starts mid-decline emits its first trough rather than a
+/// phantom peak at the opening candle.
+///
+/// ```
+/// use perpscreener::business_logic::indicators::AtrCalculator;
+/// use perpscreener::business_logic::swing::SwingDetector;
+/// # use perpscreener::models::candle::Candle;
+/// # let candles: Vec<Candle> = Vec::new();
+///
+/// let mut atr = AtrCalculator::new(14);
+/// let mut swings = SwingDetector::new(1.0);
+/// for candle in &candles {
+///     if let Some(atr) = atr.update(candle) {
+///         if let Some(point) = swings.update(candle, atr) {
+///             println!("swing {} at {}", if point.is_peak { "high" } else { "low" }, point.price);
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SwingDetector {
+    rev_atr: f64,
+    seed_candles: usize,
+    state: Option<State>,
+}
+
+impl SwingDetector {
+    pub fn new(rev_atr: f64) -> Self {
+        Self {
+            rev_atr,
+            seed_candles: DEFAULT_SEED_CANDLES,
+            state: None,
+        }
+    }
+
+    /// Minimum number of candles to observe before committing to a trend.
+    pub fn with_seed_candles(mut self, seed_candles: usize) -> Self {
+        self.seed_candles = seed_candles.max(1);
+        self
+    }
+
+    /// Current trend, or `None` while still seeding.
+    pub fn trend(&self) -> Option<Trend> {
+        match self.state {
+            Some(State::Up { .. }) => Some(Trend::Up),
+            Some(State::Down { .. }) => Some(Trend::Down),
+            Some(State::Seeding { .. }) | None => None,
+        }
+    }
+
+    /// Feed the next closed candle with the current ATR, returning a swing
+    /// point if this candle confirmed one.
+    ///
+    /// The candle that ends seeding is also evaluated against the newly
+    /// committed trend, so a sharp reversal on that candle is not lost.
+    pub fn update(&mut self, candle: &Candle, atr: f64) -> Option<SwingPoint> {
+        let rev = self.rev_atr * atr;
+        let state = match self.state {
+            None => self.seed(None, candle, rev),
+            Some(State::Seeding(seed)) => self.seed(Some(seed), candle, rev),
+            Some(trending) => trending,
+        };
+
+        let (next, swing) = match state {
+            State::Seeding(_) => (state, None),
+            State::Up {
+                swing_high,
+                high_time,
+            } => {
+                let (swing_high, high_time) = if candle.high > swing_high {
+                    (candle.high, candle.open_time)
+                } else {
+                    (swing_high, high_time)
+                };
+                if swing_high - candle.low >= rev {
+                    let peak = SwingPoint {
+                        price: swing_high,
+                        is_peak: true,
+                        open_time: high_time,
+                    };
+                    (
+                        State::Down {
+                            swing_low: candle.low,
+                            low_time: candle.open_time,
+                        },
+                        Some(peak),
+                    )
+                } else {
+                    (
+                        State::Up {
+                            swing_high,
+                            high_time,
+                        },
+                        None,
+                    )
+                }
+            }
+            State::Down {
+                swing_low,
+                low_time,
+            } => {
+                let (swing_low, low_time) = if candle.low < swing_low {
+                    (candle.low, candle.open_time)
+                } else {
+                    (swing_low, low_time)
+                };
+                if candle.high - swing_low >= rev {
+                    let trough = SwingPoint {
+                        price: swing_low,
+                        is_peak: false,
+                        open_time: low_time,
+                    };
+                    (
+                        State::Up {
+                            swing_high: candle.high,
+                            high_time: candle.open_time,
+                        },
+                        Some(trough),
+                    )
+                } else {
+                    (
+                        State::Down {
+                            swing_low,
+                            low_time,
+                        },
+                        None,
+                    )
+                }
+            }
+        };
+        self.state = Some(next);
+        swing
+    }
+
+    /// Extend the seed extremes with `candle` and commit to a trend once
+    /// enough candles are in and the extremes are a full reversal apart.
+    fn seed(&self, seed: Option<Seed>, candle: &Candle, rev: f64) -> State {
+        let seed = match seed {
+            None => Seed {
+                high: candle.high,
+                high_time: candle.open_time,
+                low: candle.low,
+                low_time: candle.open_time,
+                low_is_latest: candle.close < candle.open,
+                seen: 1,
+            },
+            Some(seed) => {
+                let new_high = candle.high > seed.high;
+                let new_low = candle.low < seed.low;
+                let low_is_latest = match (new_high, new_low) {
+                    (true, false) => false,
+                    (false, true) => true,
+                    // An outside candle: its close says which extreme came last.
+                    (true, true) => candle.close < candle.open,
+                    (false, false) => seed.low_is_latest,
+                };
+                Seed {
+                    high: seed.high.max(candle.high),
+                    high_time: if new_high {
+                        candle.open_time
+                    } else {
+                        seed.high_time
+                    },
+                    low: seed.low.min(candle.low),
+                    low_time: if new_low {
+                        candle.open_time
+                    } else {
+                        seed.low_time
+                    },
+                    low_is_latest,
+                    seen: seed.seen + 1,
+                }
+            }
+        };
+
+        if seed.seen < self.seed_candles || seed.high - seed.low < rev {
+            return State::Seeding(seed);
+        }
+        // Price has travelled a full reversal between the extremes; the trend
+        // runs towards whichever extreme was made last.
+        if seed.low_is_latest {
+            State::Down {
+                swing_low: seed.low,
+                low_time: seed.low_time,
+            }
+        } else {
+            State::Up {
+                swing_high: seed.high,
+                high_time: seed.high_time,
+            }
+        }
+    }
+}
+
+/// A confirmed swing with the ATR it was confirmed at; one vertex of the
+/// zigzag the detectors see.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct ZigzagPoint {
+    pub price: f64,
+    pub is_peak: bool,
+    /// Open time of the candle that made the extreme (epoch ms).
+    pub open_time: u64,
+    /// ATR when the swing was confirmed.
+    pub atr: f64,
+}
+
+/// Run closed `candles` (oldest first) through a fresh ATR and swing
+/// detector and return every swing confirmed along the way.
+pub fn zigzag(candles: &[Candle], atr_period: usize, rev_atr: f64) -> Vec<ZigzagPoint> {
+    let mut atr = AtrCalculator::new(atr_period);
+    let mut swings = SwingDetector::new(rev_atr);
+    candles
+        .iter()
+        .filter_map(|candle| {
+            let atr = atr.update(candle)?;
+            let point = swings.update(candle, atr)?;
+            Some(ZigzagPoint {
+                price: point.price,
+                is_peak: point.is_peak,
+                open_time: point.open_time,
+                atr,
+            })
+        })
+        .collect()
+}
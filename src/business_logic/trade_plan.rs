@@ -0,0 +1,80 @@
+//! Entry, stop and measured-move target for a confirmed double top.
+
+use serde::Serialize;
+
+/// Where the invalidation stop goes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StopRule {
+    /// Above the second peak, padded by this many ATRs.
+    AbovePeak { atr_buffer: f64 },
+    /// At the level whose reclaim fails the pattern.
+    PeakFail,
+}
+
+impl Default for StopRule {
+    fn default() -> Self {
+        StopRule::AbovePeak { atr_buffer: 0.5 }
+    }
+}
+
+/// Levels of a confirmed double top that a plan is derived from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DoubleTopLevels {
+    /// Price at which the neckline break confirmed.
+    pub break_price: f64,
+    pub neckline: f64,
+    /// High of the second peak.
+    pub peak2: f64,
+    /// Level whose reclaim fails the pattern.
+    pub peak_fail: f64,
+    /// ATR at confirmation; `None` pads [`StopRule::AbovePeak`] by nothing.
+    pub atr: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct TradePlan {
+    /// Short entry at the break price.
+    pub entry: f64,
+    pub stop: f64,
+    /// Neckline minus the pattern height (the measured move).
+    pub target: f64,
+    /// Reward over risk, `(entry - target) / (stop - entry)`.
+    pub risk_reward: f64,
+}
+
+impl TradePlan {
+    /// Short plan for a confirmed double top.
+    ///
+    /// Returns `None` when the levels can't form a sensible trade: a pattern
+    /// with no height, a stop at or below entry, or a target at or above entry
+    /// (the break already ran past the measured move).
+    pub fn for_double_top(levels: &DoubleTopLevels, rule: StopRule) -> Option<Self> {
+        let height = levels.peak2 - levels.neckline;
+        if height <= 0.0 {
+            return None;
+        }
+
+        let entry = levels.break_price;
+        let stop = match rule {
+            StopRule::AbovePeak { atr_buffer } => {
+                levels.peak2 + atr_buffer.max(0.0) * levels.atr.unwrap_or(0.0).max(0.0)
+            }
+            StopRule::PeakFail => levels.peak_fail,
+        };
+        let target = levels.neckline - height;
+
+        let risk = stop - entry;
+        let reward = entry - target;
+        if risk <= 0.0 || reward <= 0.0 {
+            return None;
+        }
+        let risk_reward = reward / risk;
+        risk_reward.is_finite().then_some(Self {
+            entry,
+            stop,
+            target,
+            risk_reward,
+        })
+    }
+}
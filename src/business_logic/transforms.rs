@@ -0,0 +1,31 @@
+//! Candle transforms applied after fetching, before display or detection.
+
+use crate::models::candle::Candle;
+
+/// Heikin-Ashi candles from raw `candles` (oldest first).
+///
+/// `close = (open + high + low + close) / 4`; the first open is the raw
+/// candle's `(open + close) / 2`, after which each open is the midpoint of
+/// the previous Heikin-Ashi open and close. Highs and lows stretch to cover
+/// the new body. Times, volume and trade counts are kept as they are.
+pub fn heikin_ashi(candles: &[Candle]) -> Vec<Candle> {
+    let mut prev: Option<(f64, f64)> = None;
+    candles
+        .iter()
+        .map(|candle| {
+            let close = (candle.open + candle.high + candle.low + candle.close) / 4.0;
+            let open = match prev {
+                Some((prev_open, prev_close)) => (prev_open + prev_close) / 2.0,
+                None => (candle.open + candle.close) / 2.0,
+            };
+            prev = Some((open, close));
+            Candle {
+                open,
+                high: candle.high.max(open).max(close),
+                low: candle.low.min(open).min(close),
+                close,
+                ..*candle
+            }
+        })
+        .collect()
+}
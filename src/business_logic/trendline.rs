@@ -0,0 +1,318 @@
+//! Trendline break detection.
+//!
+//! Fits a least-squares line through the last few swing lows (an uptrend's
+//! support) and swing highs (a downtrend's resistance), and alerts when a
+//! close cuts through a qualifying line by an ATR buffer.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::business_logic::alerts::{AlertStage, PatternAlert, PatternKind};
+use crate::business_logic::indicators::AtrCalculator;
+use crate::business_logic::swing::SwingDetector;
+use crate::models::candle::Candle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct TrendlineConfig {
+    pub atr_period: usize,
+    /// Swing reversal size in ATRs.
+    pub rev_atr: f64,
+    /// Swing points the line is fitted through.
+    pub anchor_swings: usize,
+    /// Anchors that must sit within `touch_tolerance_atr` of the line.
+    pub min_touches: usize,
+    /// Min R² of the fit.
+    pub min_r_squared: f64,
+    /// ATRs from the line within which an anchor counts as a touch.
+    pub touch_tolerance_atr: f64,
+    /// ATRs through the line a close must reach to break it.
+    pub break_buffer: f64,
+}
+
+impl Default for TrendlineConfig {
+    fn default() -> Self {
+        Self {
+            atr_period: 14,
+            rev_atr: 1.0,
+            anchor_swings: 3,
+            min_touches: 3,
+            min_r_squared: 0.9,
+            touch_tolerance_atr: 0.5,
+            break_buffer: 0.3,
+        }
+    }
+}
+
+impl TrendlineConfig {
+    /// Problems that would make the config unusable, one message each.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let mut check = |ok: bool, message: &str| {
+            if !ok {
+                errors.push(message.to_string());
+            }
+        };
+        check(self.atr_period > 0, "atr_period must be positive");
+        check(
+            self.rev_atr > 0.0 && self.rev_atr.is_finite(),
+            "rev_atr must be positive",
+        );
+        check(self.anchor_swings >= 2, "anchor_swings must be at least 2");
+        check(
+            self.min_touches <= self.anchor_swings,
+            "min_touches must be at most anchor_swings",
+        );
+        check(
+            (0.0..=1.0).contains(&self.min_r_squared),
+            "min_r_squared must be in [0, 1]",
+        );
+        check(
+            self.touch_tolerance_atr >= 0.0 && self.touch_tolerance_atr.is_finite(),
+            "touch_tolerance_atr must not be negative",
+        );
+        check(
+            self.break_buffer >= 0.0 && self.break_buffer.is_finite(),
+            "break_buffer must not be negative",
+        );
+        errors
+    }
+}
+
+/// A qualifying trendline as of the latest candle.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct TrendlineStatus {
+    /// Price change per candle.
+    pub slope: f64,
+    /// The line projected to the latest candle.
+    pub projected: f64,
+    pub touches: usize,
+    pub r_squared: f64,
+}
+
+/// A coin's qualifying trendlines, as published to the pattern state.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct TrendlinesStatus {
+    pub coin: String,
+    /// Rising line through the recent swing lows.
+    pub uptrend: Option<TrendlineStatus>,
+    /// Falling line through the recent swing highs.
+    pub downtrend: Option<TrendlineStatus>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Line {
+    slope: f64,
+    intercept: f64,
+    touches: usize,
+    r_squared: f64,
+}
+
+impl Line {
+    fn value_at(&self, index: usize) -> f64 {
+        self.intercept + self.slope * index as f64
+    }
+
+    /// Least-squares fit through `(index, price)` points.
+    fn fit(points: &VecDeque<(usize, f64)>, tolerance: f64) -> Option<Line> {
+        let n = points.len() as f64;
+        if points.len() < 2 {
+            return None;
+        }
+        let mean_x = points.iter().map(|&(x, _)| x as f64).sum::<f64>() / n;
+        let mean_y = points.iter().map(|&(_, y)| y).sum::<f64>() / n;
+        let sxx: f64 = points
+            .iter()
+            .map(|&(x, _)| (x as f64 - mean_x).powi(2))
+            .sum();
+        let sxy: f64 = points
+            .iter()
+            .map(|&(x, y)| (x as f64 - mean_x) * (y - mean_y))
+            .sum();
+        if sxx == 0.0 {
+            return None;
+        }
+        let slope = sxy / sxx;
+        let intercept = mean_y - slope * mean_x;
+        let line = |x: usize| intercept + slope * x as f64;
+        let ss_res: f64 = points.iter().map(|&(x, y)| (y - line(x)).powi(2)).sum();
+        let ss_tot: f64 = points.iter().map(|&(_, y)| (y - mean_y).powi(2)).sum();
+        let r_squared = if ss_tot == 0.0 {
+            1.0
+        } else {
+            1.0 - ss_res / ss_tot
+        };
+        let touches = points
+            .iter()
+            .filter(|&&(x, y)| (y - line(x)).abs() <= tolerance)
+            .count();
+        Some(Line {
+            slope,
+            intercept,
+            touches,
+            r_squared,
+        })
+    }
+}
+
+/// Per-coin trendline tracker fed one closed candle at a time.
+#[derive(Debug, Clone)]
+pub struct TrendlineDetector {
+    coin: String,
+    config: TrendlineConfig,
+    atr: AtrCalculator,
+    swings: SwingDetector,
+    index: usize,
+    /// Lowest low and highest high since the last opposite swing, with the
+    /// candle index they were made on, so anchors sit at the actual extreme
+    /// rather than where the swing was confirmed.
+    run_low: Option<(f64, usize)>,
+    run_high: Option<(f64, usize)>,
+    lows: VecDeque<(usize, f64)>,
+    highs: VecDeque<(usize, f64)>,
+    support: Option<Line>,
+    resistance: Option<Line>,
+}
+
+impl TrendlineDetector {
+    pub fn new(coin: impl Into<String>, config: TrendlineConfig) -> Self {
+        Self {
+            coin: coin.into(),
+            atr: AtrCalculator::new(config.atr_period),
+            swings: SwingDetector::new(config.rev_atr),
+            config,
+            index: 0,
+            run_low: None,
+            run_high: None,
+            lows: VecDeque::new(),
+            highs: VecDeque::new(),
+            support: None,
+            resistance: None,
+        }
+    }
+
+    /// Rising line through the recent swing lows, if one qualifies.
+    pub fn uptrend(&self) -> Option<TrendlineStatus> {
+        self.support.map(|line| self.line_status(line))
+    }
+
+    /// Falling line through the recent swing highs, if one qualifies.
+    pub fn downtrend(&self) -> Option<TrendlineStatus> {
+        self.resistance.map(|line| self.line_status(line))
+    }
+
+    pub fn status(&self) -> TrendlinesStatus {
+        TrendlinesStatus {
+            coin: self.coin.clone(),
+            uptrend: self.uptrend(),
+            downtrend: self.downtrend(),
+        }
+    }
+
+    fn line_status(&self, line: Line) -> TrendlineStatus {
+        TrendlineStatus {
+            slope: line.slope,
+            projected: line.value_at(self.index),
+            touches: line.touches,
+            r_squared: line.r_squared,
+        }
+    }
+
+    /// Feed the next closed candle, returning an alert if it raised one.
+    ///
+    /// Nothing happens until the ATR has warmed up.
+    pub fn update(&mut self, candle: &Candle) -> Option<PatternAlert> {
+        self.index += 1;
+        let atr = self.atr.update(candle)?;
+
+        if self.run_low.is_none_or(|(low, _)| candle.low < low) {
+            self.run_low = Some((candle.low, self.index));
+        }
+        if self.run_high.is_none_or(|(high, _)| candle.high > high) {
+            self.run_high = Some((candle.high, self.index));
+        }
+
+        if let Some(swing) = self.swings.update(candle, atr) {
+            if swing.is_peak {
+                let index = self.run_high.map_or(self.index, |(_, i)| i);
+                self.run_low = Some((candle.low, self.index));
+                push(
+                    &mut self.highs,
+                    (index, swing.price),
+                    self.config.anchor_swings,
+                );
+                self.resistance = self.fit(&self.highs, atr).filter(|l| l.slope < 0.0);
+            } else {
+                let index = self.run_low.map_or(self.index, |(_, i)| i);
+                self.run_high = Some((candle.high, self.index));
+                push(
+                    &mut self.lows,
+                    (index, swing.price),
+                    self.config.anchor_swings,
+                );
+                self.support = self.fit(&self.lows, atr).filter(|l| l.slope > 0.0);
+            }
+        }
+
+        self.check_break(candle, atr)
+    }
+
+    fn fit(&self, points: &VecDeque<(usize, f64)>, atr: f64) -> Option<Line> {
+        if points.len() < self.config.anchor_swings {
+            return None;
+        }
+        Line::fit(points, self.config.touch_tolerance_atr * atr).filter(|line| {
+            line.touches >= self.config.min_touches && line.r_squared >= self.config.min_r_squared
+        })
+    }
+
+    /// A broken line is dropped along with its anchors, so it can't fire
+    /// twice; a fresh line needs new swings.
+    fn check_break(&mut self, candle: &Candle, atr: f64) -> Option<PatternAlert> {
+        let buffer = self.config.break_buffer * atr;
+        if let Some(line) = self.support {
+            let level = line.value_at(self.index);
+            if candle.close < level - buffer {
+                self.support = None;
+                self.lows.clear();
+                return Some(self.alert(PatternKind::TrendlineBreakDown, candle, level));
+            }
+        }
+        if let Some(line) = self.resistance {
+            let level = line.value_at(self.index);
+            if candle.close > level + buffer {
+                self.resistance = None;
+                self.highs.clear();
+                return Some(self.alert(PatternKind::TrendlineBreakUp, candle, level));
+            }
+        }
+        None
+    }
+
+    fn alert(&self, pattern: PatternKind, candle: &Candle, level: f64) -> PatternAlert {
+        PatternAlert {
+            coin: self.coin.clone(),
+            pattern,
+            stage: AlertStage::Confirmation,
+            open_time: candle.open_time,
+            price: candle.close,
+            level,
+            target_price: None,
+            pattern_height_pct: None,
+            retest_high: None,
+            confirmation_mode: None,
+            htf_trend: None,
+        }
+    }
+}
+
+fn push(points: &mut VecDeque<(usize, f64)>, point: (usize, f64), cap: usize) {
+    points.push_back(point);
+    while points.len() > cap {
+        points.pop_front();
+    }
+}
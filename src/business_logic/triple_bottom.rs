@@ -0,0 +1,339 @@
+//! Triple bottom detection: three tests of the same low, confirmed by a
+//! close above the resistance set by the rallies between them.
+
+use serde::{Deserialize, Serialize};
+
+use crate::business_logic::alerts::{AlertStage, PatternAlert, PatternKind};
+use crate::business_logic::indicators::AtrCalculator;
+use crate::business_logic::swing::SwingDetector;
+use crate::models::candle::Candle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct TripleBottomConfig {
+    pub atr_period: usize,
+    /// Swing reversal size in ATRs.
+    pub rev_atr: f64,
+    /// Max % difference between trough 1 and each later trough, either side,
+    /// so a slight undercut still counts as a retest.
+    pub trough_tolerance_pct: f64,
+    /// Min % bounce from trough 1 to the resistance.
+    pub min_bounce_pct: f64,
+    /// ATRs above the resistance a close must reach to confirm.
+    pub breakout_buffer: f64,
+    /// % below trough 1 that invalidates the pattern.
+    pub trough_fail_pct: f64,
+    /// Candles after trough 1 within which the pattern must confirm.
+    pub max_pattern_candles: usize,
+}
+
+impl Default for TripleBottomConfig {
+    fn default() -> Self {
+        Self {
+            atr_period: 14,
+            rev_atr: 1.0,
+            trough_tolerance_pct: 1.5,
+            min_bounce_pct: 2.0,
+            breakout_buffer: 0.3,
+            trough_fail_pct: 1.5,
+            max_pattern_candles: 120,
+        }
+    }
+}
+
+impl TripleBottomConfig {
+    /// Problems that would make the config unusable, one message each.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let mut check = |ok: bool, message: &str| {
+            if !ok {
+                errors.push(message.to_string());
+            }
+        };
+        check(self.atr_period > 0, "atr_period must be positive");
+        check(
+            self.rev_atr > 0.0 && self.rev_atr.is_finite(),
+            "rev_atr must be positive",
+        );
+        check(
+            self.trough_tolerance_pct > 0.0 && self.trough_tolerance_pct <= 100.0,
+            "trough_tolerance_pct must be in (0, 100]",
+        );
+        check(
+            self.min_bounce_pct > 0.0 && self.min_bounce_pct <= 100.0,
+            "min_bounce_pct must be in (0, 100]",
+        );
+        check(
+            self.breakout_buffer >= 0.0 && self.breakout_buffer.is_finite(),
+            "breakout_buffer must not be negative",
+        );
+        check(
+            self.trough_fail_pct > 0.0 && self.trough_fail_pct <= 100.0,
+            "trough_fail_pct must be in (0, 100]",
+        );
+        check(
+            self.max_pattern_candles > 0,
+            "max_pattern_candles must be positive",
+        );
+        errors
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TripleBottomState {
+    Watching,
+    TroughFound,
+    /// Trough 2 matched trough 1; waiting for a third test.
+    DoubleForming,
+    /// Trough 3 matched (early warning raised); waiting for the breakout.
+    TripleForming,
+    Confirmed,
+    Invalidated,
+}
+
+impl TripleBottomState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TripleBottomState::Watching => "WATCHING",
+            TripleBottomState::TroughFound => "TROUGH_FOUND",
+            TripleBottomState::DoubleForming => "DOUBLE_FORMING",
+            TripleBottomState::TripleForming => "TRIPLE_FORMING",
+            TripleBottomState::Confirmed => "CONFIRMED",
+            TripleBottomState::Invalidated => "INVALIDATED",
+        }
+    }
+
+    fn in_pattern(&self) -> bool {
+        matches!(
+            self,
+            TripleBottomState::TroughFound
+                | TripleBottomState::DoubleForming
+                | TripleBottomState::TripleForming
+        )
+    }
+}
+
+/// Where a coin's triple bottom stands, as published to the pattern state.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct TripleBottomStatus {
+    pub coin: String,
+    /// `WATCHING`, `TROUGH_FOUND`, `DOUBLE_FORMING`, `TRIPLE_FORMING`,
+    /// `CONFIRMED` or `INVALIDATED`.
+    pub state: String,
+    pub trough1_price: Option<f64>,
+    pub trough2_price: Option<f64>,
+    pub trough3_price: Option<f64>,
+    /// Highest swing high between the troughs.
+    pub resistance_price: Option<f64>,
+}
+
+/// Per-coin triple top state machine fed one closed candle at a time.
+#[derive(Debug, Clone)]
+pub struct TripleBottomDetector {
+    coin: String,
+    config: TripleBottomConfig,
+    atr: AtrCalculator,
+    swings: SwingDetector,
+    index: usize,
+    state: TripleBottomState,
+    trough1: Option<(f64, usize)>,
+    trough2: Option<f64>,
+    trough3: Option<f64>,
+    /// Highest swing high between trough 1 and the latest matched trough.
+    resistance: Option<f64>,
+}
+
+impl TripleBottomDetector {
+    pub fn new(coin: impl Into<String>, config: TripleBottomConfig) -> Self {
+        Self {
+            coin: coin.into(),
+            atr: AtrCalculator::new(config.atr_period),
+            swings: SwingDetector::new(config.rev_atr),
+            config,
+            index: 0,
+            state: TripleBottomState::Watching,
+            trough1: None,
+            trough2: None,
+            trough3: None,
+            resistance: None,
+        }
+    }
+
+    pub fn state(&self) -> TripleBottomState {
+        self.state
+    }
+
+    pub fn trough1_price(&self) -> Option<f64> {
+        self.trough1.map(|(price, _)| price)
+    }
+
+    pub fn trough2_price(&self) -> Option<f64> {
+        self.trough2
+    }
+
+    pub fn trough3_price(&self) -> Option<f64> {
+        self.trough3
+    }
+
+    pub fn resistance_price(&self) -> Option<f64> {
+        self.resistance
+    }
+
+    pub fn status(&self) -> TripleBottomStatus {
+        TripleBottomStatus {
+            coin: self.coin.clone(),
+            state: self.state.as_str().to_string(),
+            trough1_price: self.trough1_price(),
+            trough2_price: self.trough2_price(),
+            trough3_price: self.trough3_price(),
+            resistance_price: self.resistance_price(),
+        }
+    }
+
+    /// Feed the next closed candle, returning an alert if it raised one.
+    ///
+    /// Nothing happens until the ATR has warmed up.
+    pub fn update(&mut self, candle: &Candle) -> Option<PatternAlert> {
+        self.index += 1;
+        let atr = self.atr.update(candle)?;
+
+        if self.check_invalidation(candle) {
+            return None;
+        }
+
+        let swing_alert = match self.swings.update(candle, atr) {
+            Some(swing) if !swing.is_peak => self.on_trough(swing.price, candle),
+            Some(swing) => {
+                self.on_peak(swing.price);
+                None
+            }
+            None => None,
+        };
+        swing_alert.or_else(|| self.check_breakout(candle, atr))
+    }
+
+    fn start_pattern(&mut self, price: f64) {
+        self.trough1 = Some((price, self.index));
+        self.trough2 = None;
+        self.trough3 = None;
+        self.resistance = None;
+        self.state = TripleBottomState::TroughFound;
+    }
+
+    fn reset(&mut self, state: TripleBottomState) {
+        self.trough1 = None;
+        self.trough2 = None;
+        self.trough3 = None;
+        self.resistance = None;
+        self.state = state;
+    }
+
+    fn matches_trough1(&self, price: f64) -> bool {
+        let Some((trough1, _)) = self.trough1 else {
+            return false;
+        };
+        let average = (trough1 + price) / 2.0;
+        (trough1 - price).abs() / average * 100.0 <= self.config.trough_tolerance_pct
+    }
+
+    fn on_trough(&mut self, price: f64, candle: &Candle) -> Option<PatternAlert> {
+        let (trough1, _) = match self.trough1 {
+            Some(trough1) if self.state.in_pattern() => trough1,
+            _ => {
+                self.start_pattern(price);
+                return None;
+            }
+        };
+        let bounced = self
+            .resistance
+            .is_some_and(|r| (r - trough1) / trough1 * 100.0 >= self.config.min_bounce_pct);
+
+        match self.state {
+            TripleBottomState::TroughFound if bounced && self.matches_trough1(price) => {
+                self.trough2 = Some(price);
+                self.state = TripleBottomState::DoubleForming;
+                None
+            }
+            TripleBottomState::TroughFound if price < trough1 => {
+                self.start_pattern(price);
+                None
+            }
+            TripleBottomState::DoubleForming if self.matches_trough1(price) => {
+                self.trough3 = Some(price);
+                self.state = TripleBottomState::TripleForming;
+                let resistance = self.resistance?;
+                Some(self.alert(AlertStage::EarlyWarning, candle, resistance))
+            }
+            // Higher lows leave the pattern in place.
+            _ => None,
+        }
+    }
+
+    fn on_peak(&mut self, price: f64) {
+        if matches!(
+            self.state,
+            TripleBottomState::TroughFound | TripleBottomState::DoubleForming
+        ) {
+            self.resistance = Some(self.resistance.map_or(price, |r| r.max(price)));
+        }
+    }
+
+    /// Invalidate when price falls through trough 1 by the fail margin or the
+    /// pattern runs too long. Returns whether it did.
+    fn check_invalidation(&mut self, candle: &Candle) -> bool {
+        if !self.state.in_pattern() {
+            return false;
+        }
+        let Some((trough1, trough1_index)) = self.trough1 else {
+            return false;
+        };
+        let fail_level = trough1 * (1.0 - self.config.trough_fail_pct / 100.0);
+        let expired = self.index - trough1_index > self.config.max_pattern_candles;
+        // Before a second trough, a new low simply becomes the next trough 1.
+        let failed = self.state != TripleBottomState::TroughFound && candle.low < fail_level;
+        if failed || expired {
+            self.reset(TripleBottomState::Invalidated);
+            return true;
+        }
+        false
+    }
+
+    /// A breakout with three troughs confirms. A breakout after only two
+    /// resolves the setup as a plain double bottom, which this detector
+    /// leaves alone.
+    fn check_breakout(&mut self, candle: &Candle, atr: f64) -> Option<PatternAlert> {
+        let resistance = self.resistance?;
+        if candle.close <= resistance + self.config.breakout_buffer * atr {
+            return None;
+        }
+        match self.state {
+            TripleBottomState::TripleForming => {
+                self.state = TripleBottomState::Confirmed;
+                Some(self.alert(AlertStage::Confirmation, candle, resistance))
+            }
+            TripleBottomState::DoubleForming => {
+                self.reset(TripleBottomState::Watching);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn alert(&self, stage: AlertStage, candle: &Candle, level: f64) -> PatternAlert {
+        PatternAlert {
+            coin: self.coin.clone(),
+            pattern: PatternKind::TripleBottom,
+            stage,
+            open_time: candle.open_time,
+            price: candle.close,
+            level,
+            target_price: None,
+            pattern_height_pct: None,
+            retest_high: None,
+            confirmation_mode: None,
+            htf_trend: None,
+        }
+    }
+}
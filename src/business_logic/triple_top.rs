@@ -0,0 +1,336 @@
+//! Triple top detection: a double top whose high gets a third test before
+//! the neckline breaks.
+
+use serde::{Deserialize, Serialize};
+
+use crate::business_logic::alerts::{AlertStage, PatternAlert, PatternKind};
+use crate::business_logic::indicators::AtrCalculator;
+use crate::business_logic::swing::SwingDetector;
+use crate::models::candle::Candle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct TripleTopConfig {
+    pub atr_period: usize,
+    /// Swing reversal size in ATRs.
+    pub rev_atr: f64,
+    /// Max % difference between peak 1 and each later peak.
+    pub peak_tolerance_pct: f64,
+    /// Min % drop from peak 1 to the neckline.
+    pub min_pullback_pct: f64,
+    /// ATRs below the neckline a close must reach to confirm.
+    pub breakdown_buffer: f64,
+    /// % above peak 1 that invalidates the pattern.
+    pub peak_fail_pct: f64,
+    /// Candles after peak 1 within which the pattern must confirm.
+    pub max_pattern_candles: usize,
+}
+
+impl Default for TripleTopConfig {
+    fn default() -> Self {
+        Self {
+            atr_period: 14,
+            rev_atr: 1.0,
+            peak_tolerance_pct: 1.5,
+            min_pullback_pct: 2.0,
+            breakdown_buffer: 0.3,
+            peak_fail_pct: 1.5,
+            max_pattern_candles: 120,
+        }
+    }
+}
+
+impl TripleTopConfig {
+    /// Problems that would make the config unusable, one message each.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let mut check = |ok: bool, message: &str| {
+            if !ok {
+                errors.push(message.to_string());
+            }
+        };
+        check(self.atr_period > 0, "atr_period must be positive");
+        check(
+            self.rev_atr > 0.0 && self.rev_atr.is_finite(),
+            "rev_atr must be positive",
+        );
+        check(
+            self.peak_tolerance_pct > 0.0 && self.peak_tolerance_pct <= 100.0,
+            "peak_tolerance_pct must be in (0, 100]",
+        );
+        check(
+            self.min_pullback_pct > 0.0 && self.min_pullback_pct <= 100.0,
+            "min_pullback_pct must be in (0, 100]",
+        );
+        check(
+            self.breakdown_buffer >= 0.0 && self.breakdown_buffer.is_finite(),
+            "breakdown_buffer must not be negative",
+        );
+        check(
+            self.peak_fail_pct > 0.0 && self.peak_fail_pct <= 100.0,
+            "peak_fail_pct must be in (0, 100]",
+        );
+        check(
+            self.max_pattern_candles > 0,
+            "max_pattern_candles must be positive",
+        );
+        errors
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TripleTopState {
+    Watching,
+    PeakFound,
+    /// Peak 2 matched peak 1; waiting for a third test.
+    DoubleForming,
+    /// Peak 3 matched (early warning raised); waiting for the neckline break.
+    TripleForming,
+    Confirmed,
+    Invalidated,
+}
+
+impl TripleTopState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TripleTopState::Watching => "WATCHING",
+            TripleTopState::PeakFound => "PEAK_FOUND",
+            TripleTopState::DoubleForming => "DOUBLE_FORMING",
+            TripleTopState::TripleForming => "TRIPLE_FORMING",
+            TripleTopState::Confirmed => "CONFIRMED",
+            TripleTopState::Invalidated => "INVALIDATED",
+        }
+    }
+
+    fn in_pattern(&self) -> bool {
+        matches!(
+            self,
+            TripleTopState::PeakFound
+                | TripleTopState::DoubleForming
+                | TripleTopState::TripleForming
+        )
+    }
+}
+
+/// Where a coin's triple top stands, as published to the pattern state.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct TripleTopStatus {
+    pub coin: String,
+    /// `WATCHING`, `PEAK_FOUND`, `DOUBLE_FORMING`, `TRIPLE_FORMING`, `CONFIRMED`
+    /// or `INVALIDATED`.
+    pub state: String,
+    pub peak1_price: Option<f64>,
+    pub peak2_price: Option<f64>,
+    pub peak3_price: Option<f64>,
+    pub neckline_price: Option<f64>,
+}
+
+/// Per-coin triple top state machine fed one closed candle at a time.
+#[derive(Debug, Clone)]
+pub struct TripleTopDetector {
+    coin: String,
+    config: TripleTopConfig,
+    atr: AtrCalculator,
+    swings: SwingDetector,
+    index: usize,
+    state: TripleTopState,
+    peak1: Option<(f64, usize)>,
+    peak2: Option<f64>,
+    peak3: Option<f64>,
+    /// Lowest swing low between peak 1 and the latest matched peak.
+    neckline: Option<f64>,
+}
+
+impl TripleTopDetector {
+    pub fn new(coin: impl Into<String>, config: TripleTopConfig) -> Self {
+        Self {
+            coin: coin.into(),
+            atr: AtrCalculator::new(config.atr_period),
+            swings: SwingDetector::new(config.rev_atr),
+            config,
+            index: 0,
+            state: TripleTopState::Watching,
+            peak1: None,
+            peak2: None,
+            peak3: None,
+            neckline: None,
+        }
+    }
+
+    pub fn state(&self) -> TripleTopState {
+        self.state
+    }
+
+    pub fn peak1_price(&self) -> Option<f64> {
+        self.peak1.map(|(price, _)| price)
+    }
+
+    pub fn peak2_price(&self) -> Option<f64> {
+        self.peak2
+    }
+
+    pub fn peak3_price(&self) -> Option<f64> {
+        self.peak3
+    }
+
+    pub fn neckline_price(&self) -> Option<f64> {
+        self.neckline
+    }
+
+    pub fn status(&self) -> TripleTopStatus {
+        TripleTopStatus {
+            coin: self.coin.clone(),
+            state: self.state.as_str().to_string(),
+            peak1_price: self.peak1_price(),
+            peak2_price: self.peak2_price(),
+            peak3_price: self.peak3_price(),
+            neckline_price: self.neckline_price(),
+        }
+    }
+
+    /// Feed the next closed candle, returning an alert if it raised one.
+    ///
+    /// Nothing happens until the ATR has warmed up.
+    pub fn update(&mut self, candle: &Candle) -> Option<PatternAlert> {
+        self.index += 1;
+        let atr = self.atr.update(candle)?;
+
+        if self.check_invalidation(candle) {
+            return None;
+        }
+
+        let swing_alert = match self.swings.update(candle, atr) {
+            Some(swing) if swing.is_peak => self.on_peak(swing.price, candle),
+            Some(swing) => {
+                self.on_trough(swing.price);
+                None
+            }
+            None => None,
+        };
+        swing_alert.or_else(|| self.check_breakdown(candle, atr))
+    }
+
+    fn start_pattern(&mut self, price: f64) {
+        self.peak1 = Some((price, self.index));
+        self.peak2 = None;
+        self.peak3 = None;
+        self.neckline = None;
+        self.state = TripleTopState::PeakFound;
+    }
+
+    fn reset(&mut self, state: TripleTopState) {
+        self.peak1 = None;
+        self.peak2 = None;
+        self.peak3 = None;
+        self.neckline = None;
+        self.state = state;
+    }
+
+    fn matches_peak1(&self, price: f64) -> bool {
+        let Some((peak1, _)) = self.peak1 else {
+            return false;
+        };
+        let average = (peak1 + price) / 2.0;
+        (peak1 - price).abs() / average * 100.0 <= self.config.peak_tolerance_pct
+    }
+
+    fn on_peak(&mut self, price: f64, candle: &Candle) -> Option<PatternAlert> {
+        let (peak1, _) = match self.peak1 {
+            Some(peak1) if self.state.in_pattern() => peak1,
+            _ => {
+                self.start_pattern(price);
+                return None;
+            }
+        };
+        let pulled_back = self
+            .neckline
+            .is_some_and(|n| (peak1 - n) / peak1 * 100.0 >= self.config.min_pullback_pct);
+
+        match self.state {
+            TripleTopState::PeakFound if pulled_back && self.matches_peak1(price) => {
+                self.peak2 = Some(price);
+                self.state = TripleTopState::DoubleForming;
+                None
+            }
+            TripleTopState::PeakFound if price > peak1 => {
+                self.start_pattern(price);
+                None
+            }
+            TripleTopState::DoubleForming if self.matches_peak1(price) => {
+                self.peak3 = Some(price);
+                self.state = TripleTopState::TripleForming;
+                let neckline = self.neckline?;
+                Some(self.alert(AlertStage::EarlyWarning, candle, neckline))
+            }
+            // Lower peaks leave the pattern in place.
+            _ => None,
+        }
+    }
+
+    fn on_trough(&mut self, price: f64) {
+        if matches!(
+            self.state,
+            TripleTopState::PeakFound | TripleTopState::DoubleForming
+        ) {
+            self.neckline = Some(self.neckline.map_or(price, |n| n.min(price)));
+        }
+    }
+
+    /// Invalidate when price clears peak 1 by the fail margin or the pattern
+    /// runs too long. Returns whether it did.
+    fn check_invalidation(&mut self, candle: &Candle) -> bool {
+        if !self.state.in_pattern() {
+            return false;
+        }
+        let Some((peak1, peak1_index)) = self.peak1 else {
+            return false;
+        };
+        let fail_level = peak1 * (1.0 + self.config.peak_fail_pct / 100.0);
+        let expired = self.index - peak1_index > self.config.max_pattern_candles;
+        // Before a second peak, a new high simply becomes the next peak 1.
+        let failed = self.state != TripleTopState::PeakFound && candle.high > fail_level;
+        if failed || expired {
+            self.reset(TripleTopState::Invalidated);
+            return true;
+        }
+        false
+    }
+
+    /// A break with three peaks confirms. A break after only two resolves
+    /// the setup as a plain double top, which this detector leaves alone.
+    fn check_breakdown(&mut self, candle: &Candle, atr: f64) -> Option<PatternAlert> {
+        let neckline = self.neckline?;
+        if candle.close >= neckline - self.config.breakdown_buffer * atr {
+            return None;
+        }
+        match self.state {
+            TripleTopState::TripleForming => {
+                self.state = TripleTopState::Confirmed;
+                Some(self.alert(AlertStage::Confirmation, candle, neckline))
+            }
+            TripleTopState::DoubleForming => {
+                self.reset(TripleTopState::Watching);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn alert(&self, stage: AlertStage, candle: &Candle, level: f64) -> PatternAlert {
+        PatternAlert {
+            coin: self.coin.clone(),
+            pattern: PatternKind::TripleTop,
+            stage,
+            open_time: candle.open_time,
+            price: candle.close,
+            level,
+            target_price: None,
+            pattern_height_pct: None,
+            retest_high: None,
+            confirmation_mode: None,
+            htf_trend: None,
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! Ranking coins by ATR as a percentage of price, and classifying a coin's
+//! current ATR% against its own history.
+
+use std::collections::VecDeque;
+
+use serde::Serialize;
+
+use crate::business_logic::indicators::AtrCalculator;
+use crate::models::candle::Candle;
+
+/// Lookback for [`VolatilityEntry::atr_pct_change_24h`].
+pub const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct VolatilityEntry {
+    pub coin: String,
+    /// ATR at the latest candle, in price units.
+    pub atr: f64,
+    /// `atr` as a percentage of the latest close.
+    pub atr_pct: f64,
+    /// `atr_pct` minus its value on the last candle at least 24h earlier;
+    /// absent when the candles don't reach back that far.
+    pub atr_pct_change_24h: Option<f64>,
+}
+
+/// ATR% of `coin` over closed `candles` (oldest first).
+///
+/// Returns `None` until `period` candles have been seen.
+pub fn volatility_entry(coin: &str, candles: &[Candle], period: usize) -> Option<VolatilityEntry> {
+    let mut atr = AtrCalculator::new(period);
+    let series: Vec<(u64, f64, f64)> = candles
+        .iter()
+        .filter_map(|candle| {
+            let value = atr.update(candle)?;
+            (candle.close > 0.0).then(|| (candle.open_time, value, value / candle.close * 100.0))
+        })
+        .collect();
+
+    let &(last_open_time, atr, atr_pct) = series.last()?;
+    let day_ago = last_open_time.checked_sub(DAY_MS);
+    let atr_pct_change_24h = day_ago.and_then(|day_ago| {
+        series
+            .iter()
+            .rev()
+            .find(|(open_time, _, _)| *open_time <= day_ago)
+            .map(|&(_, _, earlier_pct)| atr_pct - earlier_pct)
+    });
+
+    Some(VolatilityEntry {
+        coin: coin.to_string(),
+        atr,
+        atr_pct,
+        atr_pct_change_24h,
+    })
+}
+
+/// Most volatile first; ties broken by coin name.
+pub fn rank(mut entries: Vec<VolatilityEntry>) -> Vec<VolatilityEntry> {
+    entries.sort_by(|a, b| {
+        b.atr_pct
+            .total_cmp(&a.atr_pct)
+            .then_with(|| a.coin.cmp(&b.coin))
+    });
+    entries
+}
+
+/// Where a coin's current ATR% sits within its own recent history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum VolatilityRegime {
+    Low,
+    Normal,
+    High,
+}
+
+impl VolatilityRegime {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VolatilityRegime::Low => "LOW",
+            VolatilityRegime::Normal => "NORMAL",
+            VolatilityRegime::High => "HIGH",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RegimeConfig {
+    pub atr_period: usize,
+    /// ATR% samples kept; also the number needed before classifying.
+    pub window: usize,
+    /// At or below this percentile rank the regime is LOW.
+    pub low_percentile: f64,
+    /// At or above this percentile rank the regime is HIGH.
+    pub high_percentile: f64,
+}
+
+impl Default for RegimeConfig {
+    fn default() -> Self {
+        Self {
+            atr_period: 14,
+            window: 500,
+            low_percentile: 20.0,
+            high_percentile: 80.0,
+        }
+    }
+}
+
+impl RegimeConfig {
+    pub fn classify(&self, percentile_rank: f64) -> VolatilityRegime {
+        if percentile_rank <= self.low_percentile {
+            VolatilityRegime::Low
+        } else if percentile_rank >= self.high_percentile {
+            VolatilityRegime::High
+        } else {
+            VolatilityRegime::Normal
+        }
+    }
+}
+
+/// Share of `values` strictly below `value`, in %.
+pub fn percentile_rank(values: impl IntoIterator<Item = f64>, value: f64) -> f64 {
+    let (mut below, mut total) = (0usize, 0usize);
+    for v in values {
+        total += 1;
+        if v < value {
+            below += 1;
+        }
+    }
+    if total == 0 {
+        return 0.0;
+    }
+    below as f64 / total as f64 * 100.0
+}
+
+/// Per-coin ATR% history fed one closed candle at a time. Memory and work per
+/// candle are bounded by `window`.
+#[derive(Debug, Clone)]
+pub struct RegimeTracker {
+    config: RegimeConfig,
+    atr: AtrCalculator,
+    history: VecDeque<f64>,
+    regime: Option<VolatilityRegime>,
+}
+
+impl RegimeTracker {
+    pub fn new(config: RegimeConfig) -> Self {
+        assert!(config.window > 0, "regime window must be positive");
+        Self {
+            config,
+            atr: AtrCalculator::new(config.atr_period),
+            history: VecDeque::with_capacity(config.window),
+            regime: None,
+        }
+    }
+
+    /// Feed the next closed candle. Returns `None` until the window of ATR%
+    /// samples is full.
+    pub fn update(&mut self, candle: &Candle) -> Option<VolatilityRegime> {
+        let atr = self.atr.update(candle)?;
+        if candle.close <= 0.0 {
+            return self.regime;
+        }
+        if self.history.len() == self.config.window {
+            self.history.pop_front();
+        }
+        self.history.push_back(atr / candle.close * 100.0);
+        if self.history.len() < self.config.window {
+            return None;
+        }
+        let current = *self.history.back()?;
+        let rank = percentile_rank(self.history.iter().copied(), current);
+        self.regime = Some(self.config.classify(rank));
+        self.regime
+    }
+
+    pub fn regime(&self) -> Option<VolatilityRegime> {
+        self.regime
+    }
+
+    /// Latest ATR as a percentage of the close.
+    pub fn atr_pct(&self) -> Option<f64> {
+        self.history.back().copied()
+    }
+}
@@ -0,0 +1,180 @@
+//! Volume spike detection against a rolling average of prior candles.
+
+use serde::{Deserialize, Serialize};
+
+use crate::business_logic::indicators::RollingWindow;
+use crate::models::candle::Candle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct VolumeSpikeConfig {
+    /// Prior closed candles averaged to form the baseline.
+    pub window: usize,
+    /// A candle spikes when its volume is at least this multiple of the baseline.
+    pub multiple: f64,
+    /// When set, replaces `multiple`: a candle spikes when its volume exceeds
+    /// the baseline mean by this many standard deviations, so the bar follows
+    /// how lumpy the coin's volume naturally is.
+    pub min_stddevs: Option<f64>,
+    /// Floor on the standard deviation `min_stddevs` is measured in, as a
+    /// percentage of the baseline mean. Without it a flat window (stddev 0)
+    /// would flag a candle a hair above the mean.
+    pub min_stddev_pct: f64,
+}
+
+impl Default for VolumeSpikeConfig {
+    fn default() -> Self {
+        Self {
+            window: 20,
+            multiple: 3.0,
+            min_stddevs: None,
+            min_stddev_pct: 10.0,
+        }
+    }
+}
+
+impl VolumeSpikeConfig {
+    /// Problems that would make the config unusable, one message each.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.window == 0 {
+            errors.push("window must be positive".to_string());
+        }
+        if !(self.multiple > 0.0 && self.multiple.is_finite()) {
+            errors.push("multiple must be positive".to_string());
+        }
+        if self
+            .min_stddevs
+            .is_some_and(|k| !(k > 0.0 && k.is_finite()))
+        {
+            errors.push("min_stddevs must be positive".to_string());
+        }
+        if !(self.min_stddev_pct >= 0.0 && self.min_stddev_pct.is_finite()) {
+            errors.push("min_stddev_pct must not be negative".to_string());
+        }
+        errors
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum CandleDirection {
+    Up,
+    Down,
+    Flat,
+}
+
+impl CandleDirection {
+    pub fn of(candle: &Candle) -> Self {
+        if candle.close > candle.open {
+            CandleDirection::Up
+        } else if candle.close < candle.open {
+            CandleDirection::Down
+        } else {
+            CandleDirection::Flat
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct VolumeSpike {
+    pub coin: String,
+    /// Open time of the spiking candle (epoch ms).
+    pub open_time: u64,
+    pub volume: f64,
+    pub average_volume: f64,
+    /// `volume / average_volume`.
+    pub multiple: f64,
+    /// Open-to-close change of the spiking candle.
+    pub price_change_pct: f64,
+    pub direction: CandleDirection,
+}
+
+/// Rolling volume baseline for one coin.
+#[derive(Debug, Clone)]
+pub struct VolumeMonitor {
+    coin: String,
+    config: VolumeSpikeConfig,
+    volumes: RollingWindow,
+    last_open_time: Option<u64>,
+}
+
+impl VolumeMonitor {
+    pub fn new(coin: impl Into<String>, config: VolumeSpikeConfig) -> Self {
+        let window = config.window.max(1);
+        Self {
+            coin: coin.into(),
+            config: VolumeSpikeConfig { window, ..config },
+            volumes: RollingWindow::new(window),
+            last_open_time: None,
+        }
+    }
+
+    /// Feed the next closed candle, returning a spike if its volume beats the
+    /// baseline of the candles before it.
+    ///
+    /// Nothing is flagged until the window has filled, and a candle at or
+    /// before the last one seen is ignored so a refetch never re-alerts.
+    pub fn update(&mut self, candle: &Candle) -> Option<VolumeSpike> {
+        if self
+            .last_open_time
+            .is_some_and(|last| candle.open_time <= last)
+        {
+            return None;
+        }
+        self.last_open_time = Some(candle.open_time);
+
+        let spike = self.check(candle);
+
+        self.volumes.push(candle.volume);
+        spike
+    }
+
+    /// Mean volume of the window, once it has filled.
+    pub fn average(&self) -> Option<f64> {
+        self.volumes.mean().filter(|_| self.volumes.is_full())
+    }
+
+    /// Population standard deviation of the window's volumes, once it has filled.
+    pub fn stddev(&self) -> Option<f64> {
+        self.volumes
+            .variance()
+            .filter(|_| self.volumes.is_full())
+            .map(f64::sqrt)
+    }
+
+    fn check(&self, candle: &Candle) -> Option<VolumeSpike> {
+        let average = self.average()?;
+        if average <= 0.0 {
+            return None;
+        }
+        let multiple = candle.volume / average;
+        let spiked = match self.config.min_stddevs {
+            Some(k) => {
+                let floor = average * self.config.min_stddev_pct / 100.0;
+                candle.volume > average + k * self.stddev()?.max(floor)
+            }
+            None => multiple >= self.config.multiple,
+        };
+        if !spiked {
+            return None;
+        }
+        let price_change_pct = if candle.open > 0.0 {
+            (candle.close - candle.open) / candle.open * 100.0
+        } else {
+            0.0
+        };
+        Some(VolumeSpike {
+            coin: self.coin.clone(),
+            open_time: candle.open_time,
+            volume: candle.volume,
+            average_volume: average,
+            multiple,
+            price_change_pct,
+            direction: CandleDirection::of(candle),
+        })
+    }
+}
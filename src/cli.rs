@@ -0,0 +1,75 @@
+//! Command-line arguments of the `perpscreener` binary.
+//!
+//! Flags override the config file and environment (see [`crate::settings`]).
+//! Running without a subcommand is the same as `perpscreener serve`.
+
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+
+use crate::settings::{CoinSelection, Settings};
+
+#[derive(Debug, Parser)]
+#[command(name = "perpscreener", version, about = "Hyperliquid perp screener")]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    #[command(flatten)]
+    pub serve: ServeArgs,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run the HTTP server (the default).
+    Serve(ServeArgs),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Args)]
+pub struct ServeArgs {
+    /// Config file; defaults to config.toml, or PERPSCREENER_CONFIG if set.
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+    /// Port to listen on, keeping the host from server.bind.
+    #[arg(long)]
+    pub port: Option<u16>,
+    /// Comma-separated coins to monitor, e.g. BTC,ETH, or `all` for every perp.
+    #[arg(long, value_delimiter = ',', value_name = "COINS")]
+    pub coins: Option<Vec<String>>,
+    /// Candle interval the detectors run on, e.g. 1m or 15m.
+    #[arg(long)]
+    pub interval: Option<String>,
+}
+
+impl Cli {
+    /// The subcommand to run, with `serve` standing in when none was given.
+    pub fn into_command(self) -> Command {
+        self.command.unwrap_or(Command::Serve(self.serve))
+    }
+}
+
+impl ServeArgs {
+    /// Overwrite `settings` with every flag that was given.
+    pub fn apply(&self, settings: &mut Settings) {
+        if let Some(port) = self.port {
+            let host = match settings.server.bind.rsplit_once(':') {
+                Some((host, _)) => host,
+                None => &settings.server.bind,
+            };
+            settings.server.bind = format!("{host}:{port}");
+        }
+        if let Some(coins) = &self.coins {
+            settings.monitor.coins = CoinSelection::from_list(
+                coins
+                    .iter()
+                    .map(|c| c.trim())
+                    .filter(|c| !c.is_empty())
+                    .map(String::from)
+                    .collect(),
+            );
+        }
+        if let Some(interval) = &self.interval {
+            settings.monitor.detection_interval = interval.clone();
+        }
+    }
+}
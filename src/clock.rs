@@ -0,0 +1,115 @@
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Source of the current time in epoch milliseconds.
+///
+/// Anything that compares data timestamps against "now" takes a clock so tests
+/// can drive time explicitly instead of sleeping.
+pub trait Clock: Send + Sync {
+    fn now_ms(&self) -> u64;
+}
+
+/// Wall-clock time from the host.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or(0)
+    }
+}
+
+/// Weight given to each new sample in the skew moving average.
+const SKEW_SMOOTHING: f64 = 0.2;
+
+/// Smoothed estimate of `upstream time - local time`, in milliseconds.
+///
+/// Fed with pairs of (upstream-reported time, local time at receipt), e.g.
+/// from a response `Date` header. A positive estimate means the local clock is
+/// behind upstream.
+#[derive(Debug)]
+pub struct SkewEstimator {
+    warn_threshold_ms: u64,
+    estimate_ms: Mutex<Option<f64>>,
+}
+
+impl SkewEstimator {
+    pub fn new(warn_threshold_ms: u64) -> Self {
+        Self {
+            warn_threshold_ms,
+            estimate_ms: Mutex::new(None),
+        }
+    }
+
+    /// Fold in one sample and return the updated estimate.
+    ///
+    /// Logs a warning when the estimate crosses the threshold in either direction.
+    pub fn observe(&self, upstream_ms: u64, local_ms: u64) -> i64 {
+        let sample = upstream_ms as f64 - local_ms as f64;
+        let mut estimate = self.estimate_ms.lock().unwrap();
+        let was_exceeded = estimate.is_some_and(|e| self.exceeds(e));
+        let updated = match *estimate {
+            Some(previous) => previous + SKEW_SMOOTHING * (sample - previous),
+            None => sample,
+        };
+        *estimate = Some(updated);
+
+        let is_exceeded = self.exceeds(updated);
+        if is_exceeded && !was_exceeded {
+            eprintln!(
+                "Warning: local clock is off from upstream by {}ms (threshold {}ms)",
+                updated.round(),
+                self.warn_threshold_ms
+            );
+        } else if was_exceeded && !is_exceeded {
+            eprintln!("Local clock skew back within {}ms", self.warn_threshold_ms);
+        }
+        updated.round() as i64
+    }
+
+    /// Current estimate, or `None` before the first sample.
+    pub fn estimate_ms(&self) -> Option<i64> {
+        self.estimate_ms.lock().unwrap().map(|e| e.round() as i64)
+    }
+
+    pub fn warn_threshold_ms(&self) -> u64 {
+        self.warn_threshold_ms
+    }
+
+    pub fn exceeds_threshold(&self) -> bool {
+        self.estimate_ms
+            .lock()
+            .unwrap()
+            .is_some_and(|e| self.exceeds(e))
+    }
+
+    fn exceeds(&self, estimate: f64) -> bool {
+        estimate.abs() > self.warn_threshold_ms as f64
+    }
+}
+
+/// Clock that applies the current [`SkewEstimator`] offset to another clock,
+/// so closed-candle checks follow upstream time rather than the host's.
+pub struct SkewCorrectedClock {
+    inner: Arc<dyn Clock>,
+    skew: Arc<SkewEstimator>,
+}
+
+impl SkewCorrectedClock {
+    pub fn new(inner: Arc<dyn Clock>, skew: Arc<SkewEstimator>) -> Self {
+        Self { inner, skew }
+    }
+}
+
+impl Clock for SkewCorrectedClock {
+    fn now_ms(&self) -> u64 {
+        let now = self.inner.now_ms();
+        match self.skew.estimate_ms() {
+            Some(offset) => now.saturating_add_signed(offset),
+            None => now,
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Error returned by route handlers, rendered as a JSON [`ErrorResponse`].
+#[derive(Debug)]
+pub enum AppError {
+    /// The request was well-formed but its contents were rejected.
+    Validation(String),
+    NotFound(String),
+    /// The request body is larger than the server will process.
+    TooLarge(String),
+    Internal(String),
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ErrorResponse {
+    pub error: String,
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (status, error) = match self {
+            AppError::Validation(message) => (StatusCode::BAD_REQUEST, message),
+            AppError::NotFound(message) => (StatusCode::NOT_FOUND, message),
+            AppError::TooLarge(message) => (StatusCode::PAYLOAD_TOO_LARGE, message),
+            AppError::Internal(message) => (StatusCode::INTERNAL_SERVER_ERROR, message),
+        };
+        (status, Json(ErrorResponse { error })).into_response()
+    }
+}
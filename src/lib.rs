@@ -0,0 +1,304 @@
+//! Hyperliquid perp screener.
+//!
+//! The crate is usable both as the `perpscreener` server binary and as a
+//! library. The detection layer in `business_logic` is always available;
+//! the rest is behind cargo features:
+//!
+//! - `server` (default): the HTTP router ([`app`]), OpenAPI docs and Swagger UI.
+//!   Implies `client`.
+//! - `client`: Hyperliquid data fetching in `services`.
+//!
+//! Build with `--no-default-features` to depend on the detectors only, without
+//! axum, utoipa or tokio.
+
+pub mod business_logic;
+#[cfg(feature = "server")]
+pub mod cli;
+pub mod clock;
+#[cfg(feature = "server")]
+pub mod error;
+pub mod models;
+#[cfg(feature = "server")]
+pub mod naming;
+#[cfg(feature = "server")]
+pub mod routes;
+#[cfg(feature = "client")]
+pub mod services;
+#[cfg(feature = "server")]
+pub mod settings;
+#[cfg(feature = "server")]
+pub mod state;
+
+#[cfg(feature = "server")]
+pub use server::{app, openapi, serve, shutdown_signal, ApiDoc};
+
+#[cfg(feature = "server")]
+mod server {
+    use axum::{
+        middleware,
+        routing::{delete, get, post},
+        Router,
+    };
+    use tokio::net::TcpListener;
+    use tokio_util::sync::CancellationToken;
+    use utoipa::OpenApi;
+    use utoipa_swagger_ui::SwaggerUi;
+
+    use crate::naming::{self, ApiNaming};
+    use crate::routes;
+    use crate::state::AppState;
+
+    #[derive(OpenApi)]
+    #[openapi(
+        paths(
+            routes::monitor::pause,
+            routes::monitor::resume,
+            routes::config::config,
+            routes::dashboard::dashboard,
+            routes::patterns::double_bottom,
+            routes::patterns::double_bottom_stream,
+            routes::health::health,
+            routes::indicators::indicators,
+            routes::levels::levels,
+            routes::movers::movers,
+            routes::patterns::patterns,
+            routes::patterns::pattern_stream,
+            routes::pivots::pivots,
+            routes::premium::premium,
+            routes::schemas::list_schemas,
+            routes::schemas::get_schema,
+            routes::screeners::volume_spikes,
+            routes::screeners::anomalies,
+            routes::screeners::premium_outliers,
+            routes::screeners::funding,
+            routes::screeners::open_interest,
+            routes::swings::swings,
+            routes::volatility::volatility_ranking,
+            routes::webhooks::create_webhook,
+            routes::webhooks::list_webhooks,
+            routes::webhooks::delete_webhook,
+            routes::webhooks::webhook_deliveries
+        ),
+        components(schemas(
+            routes::config::RuntimeConfig,
+            routes::monitor::MonitorPauseResponse,
+            crate::settings::ServerSettings,
+            crate::settings::MonitorSettings,
+            crate::settings::VolatilitySettings,
+            crate::business_logic::gaps::GapConfig,
+            crate::business_logic::gaps::GapPolicy,
+            crate::naming::ApiNaming,
+            crate::business_logic::double_bottom::DoubleBottomConfig,
+            crate::business_logic::double_bottom::EmaTrendFilter,
+            crate::business_logic::double_top::DoubleTopConfig,
+            crate::business_logic::double_top::ConfidenceWeights,
+            crate::business_logic::double_top::ConfluenceBonus,
+            crate::business_logic::double_top::AtrRule,
+            crate::business_logic::double_top::HigherTimeframeFilter,
+            crate::business_logic::double_top::HigherTimeframeMode,
+            crate::business_logic::trendline::TrendlineConfig,
+            crate::business_logic::range_breakout::RangeBreakoutConfig,
+            crate::business_logic::cup_and_handle::CupAndHandleConfig,
+            crate::business_logic::descending_triangle::DescendingTriangleConfig,
+            crate::business_logic::ascending_triangle::AscendingTriangleConfig,
+            crate::business_logic::triple_bottom::TripleBottomConfig,
+            crate::business_logic::triple_top::TripleTopConfig,
+            crate::business_logic::head_and_shoulders::HeadAndShouldersConfig,
+            crate::business_logic::volume::VolumeSpikeConfig,
+            crate::business_logic::anomalies::AnomalyConfig,
+            crate::business_logic::funding::FundingScreenerConfig,
+            crate::business_logic::open_interest::OiScreenerConfig,
+            routes::dashboard::DashboardResponse,
+            routes::health::HealthResponse,
+            routes::health::HealthStatus,
+            routes::health::CoinFreshness,
+            routes::indicators::IndicatorsResponse,
+            routes::indicators::IndicatorValues,
+            routes::levels::LevelsResponse,
+            crate::business_logic::levels::Level,
+            crate::business_logic::levels::LevelSide,
+            routes::movers::MoversResponse,
+            crate::business_logic::movers::Mover,
+            routes::patterns::PatternsResponse,
+            routes::patterns::DoubleBottomResponse,
+            crate::business_logic::patterns::PatternStatus,
+            crate::business_logic::double_bottom::DoubleBottomStatus,
+            crate::business_logic::double_top::DoubleTopStatus,
+            crate::business_logic::double_top::DoubleTopCandidateStatus,
+            crate::business_logic::double_top::InvalidationReason,
+            crate::business_logic::double_top::DoubleTopDiagnostics,
+            crate::business_logic::trendline::TrendlinesStatus,
+            crate::business_logic::trendline::TrendlineStatus,
+            crate::business_logic::range_breakout::RangeBreakoutStatus,
+            crate::business_logic::cup_and_handle::CupAndHandleStatus,
+            crate::business_logic::descending_triangle::DescendingTriangleStatus,
+            crate::business_logic::ascending_triangle::AscendingTriangleStatus,
+            crate::business_logic::triple_bottom::TripleBottomStatus,
+            crate::business_logic::triple_top::TripleTopStatus,
+            crate::business_logic::head_and_shoulders::HeadAndShouldersStatus,
+            routes::pivots::PivotsResponse,
+            crate::business_logic::pivots::PivotLevels,
+            routes::premium::PremiumResponse,
+            crate::business_logic::premium::PremiumSample,
+            crate::business_logic::premium::PremiumStats,
+            routes::schemas::SchemaListResponse,
+            routes::screeners::VolumeSpikesResponse,
+            crate::business_logic::volume::VolumeSpike,
+            crate::business_logic::volume::CandleDirection,
+            routes::screeners::AnomaliesResponse,
+            crate::business_logic::anomalies::CandleAnomaly,
+            crate::business_logic::anomalies::AnomalyKind,
+            routes::screeners::PremiumScreenerResponse,
+            routes::screeners::PremiumOutlier,
+            routes::screeners::FundingScreenerResponse,
+            routes::screeners::OiScreenerResponse,
+            routes::swings::SwingsResponse,
+            crate::business_logic::swing::ZigzagPoint,
+            routes::volatility::VolatilityRankingResponse,
+            crate::business_logic::volatility::VolatilityEntry,
+            routes::webhooks::CreateWebhookRequest,
+            routes::webhooks::WebhookResponse,
+            routes::webhooks::WebhookStatus,
+            crate::business_logic::quiet_hours::QuietHours,
+            crate::business_logic::quiet_hours::QuietMode,
+            routes::webhooks::DeliveriesResponse,
+            crate::services::delivery_queue::QueuedDelivery,
+            crate::services::delivery_queue::FailedDelivery,
+            crate::business_logic::alerts::AlertSeverity,
+            crate::business_logic::alerts::PatternAlert,
+            crate::business_logic::alerts::PatternKind,
+            crate::business_logic::alerts::AlertStage,
+            crate::business_logic::alerts::ConfirmationMode,
+            crate::business_logic::alerts::TrendDirection,
+            crate::models::candle::Candle,
+            crate::business_logic::trade_plan::TradePlan,
+            crate::business_logic::fibonacci::FibLevels,
+            crate::business_logic::paper::PaperPosition,
+            crate::business_logic::paper::PaperPerformance,
+            crate::business_logic::paper::ClosedTrade,
+            crate::business_logic::paper::EquityPoint,
+            crate::business_logic::paper::ExitReason,
+            crate::business_logic::funding::FundingAnomaly,
+            crate::business_logic::funding::FundingTrigger,
+            crate::business_logic::open_interest::OiMetrics,
+            crate::business_logic::open_interest::OiRule,
+            crate::business_logic::volatility::VolatilityRegime,
+            crate::services::universe::UniverseFilter,
+            crate::services::monitor::MonitorAlert,
+            crate::error::ErrorResponse
+        ))
+    )]
+    pub struct ApiDoc;
+
+    /// OpenAPI document with property names in the given convention.
+    pub fn openapi(naming: ApiNaming) -> utoipa::openapi::OpenApi {
+        let mut doc = ApiDoc::openapi();
+        if naming == ApiNaming::Camel {
+            naming::rename_openapi(&mut doc, naming::snake_to_camel);
+        }
+        doc
+    }
+
+    /// Router with every API route plus Swagger UI mounted.
+    ///
+    /// ```
+    /// use perpscreener::state::AppState;
+    ///
+    /// let app: axum::Router = perpscreener::app(AppState::default());
+    /// # let _ = app;
+    /// ```
+    pub fn app(state: AppState) -> Router {
+        let naming = state.naming;
+        let json_naming = naming::JsonNaming::new(naming, &ApiDoc::openapi());
+        Router::new()
+            .route("/admin/monitor/pause", post(routes::monitor::pause))
+            .route("/admin/monitor/resume", post(routes::monitor::resume))
+            .route("/config", get(routes::config::config))
+            .route("/dashboard", get(routes::dashboard::dashboard))
+            .route("/double-bottom", get(routes::patterns::double_bottom))
+            .route(
+                "/double-bottom/stream",
+                get(routes::patterns::double_bottom_stream),
+            )
+            .route("/health", get(routes::health::health))
+            .route("/indicators", get(routes::indicators::indicators))
+            .route("/levels", get(routes::levels::levels))
+            .route("/movers", get(routes::movers::movers))
+            .route("/patterns", get(routes::patterns::patterns))
+            .route("/patterns/stream", get(routes::patterns::pattern_stream))
+            .route("/pivots", get(routes::pivots::pivots))
+            .route("/premium", get(routes::premium::premium))
+            .route("/schemas", get(routes::schemas::list_schemas))
+            .route("/schemas/{name}", get(routes::schemas::get_schema))
+            .route("/screeners/anomalies", get(routes::screeners::anomalies))
+            .route("/screeners/funding", get(routes::screeners::funding))
+            .route(
+                "/screeners/open-interest",
+                get(routes::screeners::open_interest),
+            )
+            .route(
+                "/screeners/premium",
+                get(routes::screeners::premium_outliers),
+            )
+            .route(
+                "/screeners/volume-spikes",
+                get(routes::screeners::volume_spikes),
+            )
+            .route("/swings", get(routes::swings::swings))
+            .route(
+                "/volatility-ranking",
+                get(routes::volatility::volatility_ranking),
+            )
+            .route(
+                "/webhooks",
+                get(routes::webhooks::list_webhooks).post(routes::webhooks::create_webhook),
+            )
+            .route("/webhooks/{id}", delete(routes::webhooks::delete_webhook))
+            .route(
+                "/webhooks/{id}/deliveries",
+                get(routes::webhooks::webhook_deliveries),
+            )
+            .with_state(state)
+            .layer(middleware::from_fn_with_state(
+                json_naming,
+                naming::rename_json,
+            ))
+            .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", openapi(naming)))
+    }
+
+    /// Serve `app` until `shutdown` is cancelled, then stop accepting
+    /// connections and return once in-flight requests have finished.
+    pub async fn serve(
+        listener: TcpListener,
+        app: Router,
+        shutdown: CancellationToken,
+    ) -> std::io::Result<()> {
+        axum::serve(listener, app)
+            .with_graceful_shutdown(shutdown.cancelled_owned())
+            .await
+    }
+
+    /// Resolve on ctrl-c, or on SIGTERM where there are signals.
+    pub async fn shutdown_signal() {
+        let ctrl_c = async {
+            if tokio::signal::ctrl_c().await.is_err() {
+                std::future::pending::<()>().await;
+            }
+        };
+        #[cfg(unix)]
+        let terminate = async {
+            match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+                Ok(mut sigterm) => {
+                    sigterm.recv().await;
+                }
+                Err(_) => std::future::pending::<()>().await,
+            }
+        };
+        #[cfg(not(unix))]
+        let terminate = std::future::pending::<()>();
+        tokio::select! {
+            _ = ctrl_c => {}
+            _ = terminate => {}
+        }
+    }
+}
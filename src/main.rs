@@ -1,26 +1,146 @@
-mod routes;
-mod services;
-mod business_logic;
+use std::sync::Arc;
+use std::time::Duration;
 
-use axum::{routing::get, Router};
-use utoipa::OpenApi;
-use utoipa_swagger_ui::SwaggerUi;
-
-#[derive(OpenApi)]
-#[openapi(
-    paths(routes::health::health),
-    components(schemas(routes::health::HealthResponse))
-)]
-struct ApiDoc;
+use clap::Parser;
+use perpscreener::cli::{Cli, Command, ServeArgs};
+use perpscreener::clock::{SkewCorrectedClock, SkewEstimator, SystemClock};
+use perpscreener::services::contexts::ContextSampler;
+use perpscreener::services::delivery_queue::DeliveryQueue;
+use perpscreener::services::hyperliquid::HyperliquidClient;
+use perpscreener::services::monitor::MarketMonitor;
+use perpscreener::services::universe::{self, UniverseRefresher};
+use perpscreener::services::webhooks::WebhookStore;
+use perpscreener::settings::{self, CoinSelection, Settings};
+use perpscreener::state::{AppState, DEFAULT_MAX_CLOCK_SKEW_MS};
+use tokio_util::sync::CancellationToken;
 
 #[tokio::main]
 async fn main() {
-    let app = Router::new()
-        .route("/health", get(routes::health::health))
-        .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()));
+    match Cli::parse().into_command() {
+        Command::Serve(args) => serve(args).await,
+    }
+}
+
+async fn serve(args: ServeArgs) {
+    let config_path = args
+        .config
+        .clone()
+        .or_else(|| std::env::var_os(settings::CONFIG_ENV).map(Into::into));
+    let mut settings =
+        Settings::load(config_path.as_deref(), std::env::vars()).unwrap_or_else(|e| {
+            eprintln!("Failed to load config: {e}");
+            std::process::exit(1);
+        });
+    args.apply(&mut settings);
+    let errors = settings.validate();
+    if !errors.is_empty() {
+        eprintln!("Invalid config:");
+        for error in errors {
+            eprintln!("  {error}");
+        }
+        std::process::exit(1);
+    }
+    println!("Effective config:\n{}", settings.to_toml());
+    let clock_skew = Arc::new(SkewEstimator::new(DEFAULT_MAX_CLOCK_SKEW_MS));
+    let hyperliquid = HyperliquidClient::new().with_clock_skew(clock_skew.clone());
+    let coins = match &settings.monitor.coins {
+        CoinSelection::Listed(coins) => coins.clone(),
+        CoinSelection::All => universe::discover(&hyperliquid, &settings.monitor.discovery)
+            .await
+            .unwrap_or_else(|e| {
+                eprintln!("Failed to discover perps: {e}");
+                std::process::exit(1);
+            }),
+    };
+    println!(
+        "Monitoring {} coins on {} candles: {}",
+        coins.len(),
+        settings.monitor.detection_interval,
+        coins.join(", ")
+    );
+    let server = settings.server.clone();
+    let webhooks = WebhookStore::open(&server.webhook_store_path).unwrap_or_else(|e| {
+        eprintln!(
+            "Failed to load {}: {e}",
+            server.webhook_store_path.display()
+        );
+        std::process::exit(1);
+    });
+    let webhooks = Arc::new(webhooks);
+    let webhook_queue = DeliveryQueue::open(&server.webhook_queue_path).unwrap_or_else(|e| {
+        eprintln!(
+            "Failed to load {}: {e}",
+            server.webhook_queue_path.display()
+        );
+        std::process::exit(1);
+    });
+    let webhook_queue = Arc::new(webhook_queue);
+    let clock = Arc::new(SkewCorrectedClock::new(
+        Arc::new(SystemClock),
+        clock_skew.clone(),
+    ));
+    let poll_every = Duration::from_secs(settings.monitor.poll_interval_secs);
+    let discover_every = (settings.monitor.coins == CoinSelection::All)
+        .then(|| Duration::from_secs(settings.monitor.discovery_refresh_secs));
+    let state = AppState::new(clock)
+        .with_naming(server.api_naming)
+        .with_settings(settings)
+        .with_clock_skew(clock_skew)
+        .with_hyperliquid(hyperliquid)
+        .with_monitored_coins(coins)
+        .with_webhooks(webhooks)
+        .with_webhook_queue(webhook_queue);
+    let shutdown = CancellationToken::new();
+    let retry_worker = tokio::spawn(state.webhook_dispatcher().run_retry_worker(
+        Duration::from_secs(server.webhook_retry_secs),
+        shutdown.clone(),
+    ));
+    let context_sampler =
+        tokio::spawn(ContextSampler::new(state.clone()).run(poll_every, shutdown.clone()));
+    let monitor = tokio::spawn(
+        MarketMonitor::new(
+            state.clone(),
+            state.settings.monitor.detection_interval.clone(),
+        )
+        .run(poll_every, shutdown.clone()),
+    );
+    let universe_refresher = discover_every.map(|every| {
+        tokio::spawn(
+            UniverseRefresher::new(
+                state.hyperliquid.clone(),
+                state.settings.monitor.discovery,
+                state.coins.clone(),
+            )
+            .run(every, shutdown.clone()),
+        )
+    });
+    let app = perpscreener::app(state);
 
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
-    println!("Server running on http://localhost:3000");
-    println!("Swagger UI: http://localhost:3000/swagger-ui");
-    axum::serve(listener, app).await.unwrap();
+    let listener = tokio::net::TcpListener::bind(&server.bind)
+        .await
+        .unwrap_or_else(|e| {
+            eprintln!("Failed to bind {}: {e}", server.bind);
+            std::process::exit(1);
+        });
+    let port = listener.local_addr().unwrap().port();
+    println!("Server running on http://localhost:{port}");
+    println!("Swagger UI: http://localhost:{port}/swagger-ui");
+    tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move {
+            perpscreener::shutdown_signal().await;
+            println!("Shutting down");
+            shutdown.cancel();
+        }
+    });
+    if let Err(e) = perpscreener::serve(listener, app, shutdown.clone()).await {
+        eprintln!("Server error: {e}");
+        std::process::exit(1);
+    }
+    let _ = retry_worker.await;
+    let _ = context_sampler.await;
+    let _ = monitor.await;
+    if let Some(universe_refresher) = universe_refresher {
+        let _ = universe_refresher.await;
+    }
 }
@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+use crate::business_logic::intervals;
+
+/// One OHLCV candle with prices parsed to floats.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct Candle {
+    /// Candle open time (epoch ms).
+    pub open_time: u64,
+    /// Last millisecond covered by the candle (epoch ms), as reported upstream.
+    pub close_time: u64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+    pub num_trades: u64,
+}
+
+impl Candle {
+    /// Whether the candle has finished forming as of `now_ms`.
+    ///
+    /// Uses the end of the candle's own `interval` bucket rather than the
+    /// upstream `close_time`, so a candle is closed from the moment the next one
+    /// opens. Unsupported intervals fall back to `close_time < now_ms`.
+    pub fn is_closed_at(&self, interval: &str, now_ms: u64) -> bool {
+        match intervals::next_bucket_start(interval, self.open_time) {
+            Some(end) => end <= now_ms,
+            None => self.close_time < now_ms,
+        }
+    }
+}
+
+/// Drop candles that are still forming as of `now_ms`, keeping order.
+pub fn closed_only(candles: Vec<Candle>, interval: &str, now_ms: u64) -> Vec<Candle> {
+    candles
+        .into_iter()
+        .filter(|candle| candle.is_closed_at(interval, now_ms))
+        .collect()
+}
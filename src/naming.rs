@@ -0,0 +1,378 @@
+//! JSON field naming for API requests and responses.
+//!
+//! Types serialize with snake_case field names. When the API runs with
+//! [`ApiNaming::Camel`], [`rename_json`] rewrites the field names of JSON
+//! bodies on the way in (camel → snake) and out (snake → camel), and the
+//! published OpenAPI and JSON Schema documents are rewritten to match, so a
+//! single set of types serves both conventions. Only keys the route's
+//! OpenAPI schema declares as properties are renamed; map keys (coins,
+//! indicator names), enum values, query parameter names and path segments
+//! are left untouched.
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use axum::{
+    body::{to_bytes, Body, HttpBody},
+    extract::{MatchedPath, Request, State},
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use utoipa::openapi::schema::{AdditionalProperties, ArrayItems, Schema};
+use utoipa::openapi::{OpenApi, RefOr};
+use utoipa::ToSchema;
+
+use crate::error::AppError;
+
+/// Largest JSON body renamed. Bigger requests are rejected with 413;
+/// bigger (or unsized) responses are passed through unrenamed.
+pub const MAX_RENAMED_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+/// Field naming convention for JSON bodies, chosen at startup.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiNaming {
+    #[default]
+    Snake,
+    Camel,
+}
+
+impl FromStr for ApiNaming {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "snake" => Ok(ApiNaming::Snake),
+            "camel" => Ok(ApiNaming::Camel),
+            other => Err(format!(
+                "api naming must be \"snake\" or \"camel\", got {other:?}"
+            )),
+        }
+    }
+}
+
+/// `as_of_ms` → `asOfMs`.
+pub fn snake_to_camel(key: &str) -> String {
+    let mut out = String::with_capacity(key.len());
+    let mut upper = false;
+    for ch in key.chars() {
+        if ch == '_' && !out.is_empty() {
+            upper = true;
+        } else if upper {
+            out.extend(ch.to_uppercase());
+            upper = false;
+        } else {
+            out.push(ch);
+        }
+    }
+    out
+}
+
+/// `asOfMs` → `as_of_ms`.
+pub fn camel_to_snake(key: &str) -> String {
+    let mut out = String::with_capacity(key.len() + 4);
+    for ch in key.chars() {
+        if ch.is_ascii_uppercase() && !out.is_empty() {
+            out.push('_');
+        }
+        out.push(ch.to_ascii_lowercase());
+    }
+    out
+}
+
+/// Rename the property names (and matching `required` entries) of every
+/// schema inside an OpenAPI or JSON Schema document.
+pub fn rename_schema_properties(value: Value, rename: fn(&str) -> String) -> Value {
+    match value {
+        Value::Object(map) => {
+            let is_schema = map.get("properties").is_some_and(Value::is_object);
+            Value::Object(
+                map.into_iter()
+                    .map(|(key, child)| {
+                        let child = match (is_schema, key.as_str(), child) {
+                            (true, "properties", Value::Object(properties)) => Value::Object(
+                                properties
+                                    .into_iter()
+                                    .map(|(name, schema)| {
+                                        (rename(&name), rename_schema_properties(schema, rename))
+                                    })
+                                    .collect::<Map<_, _>>(),
+                            ),
+                            (true, "required", Value::Array(names)) => Value::Array(
+                                names
+                                    .into_iter()
+                                    .map(|name| match name {
+                                        Value::String(name) => Value::String(rename(&name)),
+                                        other => other,
+                                    })
+                                    .collect(),
+                            ),
+                            (_, _, child) => rename_schema_properties(child, rename),
+                        };
+                        (key, child)
+                    })
+                    .collect(),
+            )
+        }
+        Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(|item| rename_schema_properties(item, rename))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+/// Rename the property names of every component schema in an OpenAPI document.
+pub fn rename_openapi(doc: &mut OpenApi, rename: fn(&str) -> String) {
+    if let Some(components) = &mut doc.components {
+        for schema in components.schemas.values_mut() {
+            rename_component(schema, rename);
+        }
+    }
+}
+
+fn rename_component(schema: &mut RefOr<Schema>, rename: fn(&str) -> String) {
+    let RefOr::T(schema) = schema else {
+        return;
+    };
+    match schema {
+        Schema::Object(object) => {
+            object.required = object.required.iter().map(|name| rename(name)).collect();
+            object.properties = std::mem::take(&mut object.properties)
+                .into_iter()
+                .map(|(name, mut property)| {
+                    rename_component(&mut property, rename);
+                    (rename(&name), property)
+                })
+                .collect();
+            if let Some(additional) = &mut object.additional_properties {
+                if let AdditionalProperties::RefOr(values) = additional.as_mut() {
+                    rename_component(values, rename);
+                }
+            }
+        }
+        Schema::Array(array) => {
+            if let ArrayItems::RefOrSchema(items) = &mut array.items {
+                rename_component(items, rename);
+            }
+        }
+        Schema::OneOf(one_of) => one_of
+            .items
+            .iter_mut()
+            .for_each(|item| rename_component(item, rename)),
+        Schema::AllOf(all_of) => all_of
+            .items
+            .iter_mut()
+            .for_each(|item| rename_component(item, rename)),
+        Schema::AnyOf(any_of) => any_of
+            .items
+            .iter_mut()
+            .for_each(|item| rename_component(item, rename)),
+        _ => {}
+    }
+}
+
+fn is_json(content_type: Option<&HeaderValue>) -> bool {
+    content_type
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"))
+}
+
+/// Field renaming for [`rename_json`], driven by the snake_case OpenAPI
+/// document of the routes it wraps.
+#[derive(Debug, Clone)]
+pub struct JsonNaming {
+    naming: ApiNaming,
+    /// The OpenAPI document as JSON; empty under [`ApiNaming::Snake`].
+    doc: Arc<Value>,
+}
+
+impl JsonNaming {
+    /// `doc` must describe the routes with their snake_case field names.
+    pub fn new(naming: ApiNaming, doc: &OpenApi) -> Self {
+        let doc = match naming {
+            ApiNaming::Snake => Value::Null,
+            ApiNaming::Camel => serde_json::to_value(doc).unwrap_or(Value::Null),
+        };
+        Self {
+            naming,
+            doc: Arc::new(doc),
+        }
+    }
+
+    fn operation(&self, method: &Method, path: &str) -> Option<&Value> {
+        self.doc
+            .get("paths")?
+            .get(path)?
+            .get(method.as_str().to_ascii_lowercase())
+    }
+
+    /// JSON schema of the request body of `method path`.
+    pub fn request_schema(&self, method: &Method, path: &str) -> Option<&Value> {
+        json_content_schema(self.operation(method, path)?.get("requestBody")?)
+    }
+
+    /// JSON schema of the `status` response of `method path`.
+    pub fn response_schema(
+        &self,
+        method: &Method,
+        path: &str,
+        status: StatusCode,
+    ) -> Option<&Value> {
+        let responses = self.operation(method, path)?.get("responses")?;
+        json_content_schema(responses.get(status.as_str())?)
+    }
+
+    /// Rename the properties `schema` declares in `value`, recursively,
+    /// with `rename`. Keys of maps (`additionalProperties`) and of free-form
+    /// values are kept as they are.
+    pub fn rename(&self, value: Value, schema: &Value, rename: fn(&str) -> String) -> Value {
+        let mut candidates = Vec::new();
+        self.collect_schemas(schema, &mut candidates, 0);
+        match value {
+            Value::Object(map) => {
+                let additional = candidates
+                    .iter()
+                    .find_map(|s| s.get("additionalProperties").filter(|a| a.is_object()));
+                Value::Object(
+                    map.into_iter()
+                        .map(|(key, child)| {
+                            let renamed = rename(&key);
+                            let property = candidates.iter().find_map(|s| {
+                                let properties = s.get("properties")?;
+                                properties.get(&key).or_else(|| properties.get(&renamed))
+                            });
+                            match (property, additional) {
+                                (Some(property), _) => {
+                                    (renamed, self.rename(child, property, rename))
+                                }
+                                (None, Some(values)) => (key, self.rename(child, values, rename)),
+                                (None, None) => (key, child),
+                            }
+                        })
+                        .collect(),
+                )
+            }
+            Value::Array(items) => match candidates.iter().find_map(|s| s.get("items")) {
+                Some(item_schema) => Value::Array(
+                    items
+                        .into_iter()
+                        .map(|item| self.rename(item, item_schema, rename))
+                        .collect(),
+                ),
+                None => Value::Array(items),
+            },
+            other => other,
+        }
+    }
+
+    /// `schema` with `$ref`s resolved and `oneOf`/`anyOf`/`allOf` flattened
+    /// into the schemas a value might match.
+    fn collect_schemas<'a>(&'a self, schema: &'a Value, out: &mut Vec<&'a Value>, depth: usize) {
+        // Component references can be recursive; a value never nests this deep.
+        if depth > 32 {
+            return;
+        }
+        if let Some(target) = schema.get("$ref").and_then(Value::as_str) {
+            if let Some(resolved) = target
+                .strip_prefix("#/components/schemas/")
+                .and_then(|name| self.doc.get("components")?.get("schemas")?.get(name))
+            {
+                self.collect_schemas(resolved, out, depth + 1);
+            }
+            return;
+        }
+        out.push(schema);
+        for key in ["oneOf", "anyOf", "allOf"] {
+            for variant in schema
+                .get(key)
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                self.collect_schemas(variant, out, depth + 1);
+            }
+        }
+    }
+}
+
+fn json_content_schema(body: &Value) -> Option<&Value> {
+    body.get("content")?.get("application/json")?.get("schema")
+}
+
+/// Middleware translating JSON bodies between snake_case and the configured
+/// naming. A no-op under [`ApiNaming::Snake`], and for routes or statuses
+/// without a JSON schema in the OpenAPI document.
+///
+/// `/schemas` responses are skipped: their keys are JSON Schema keywords,
+/// and the route renames the described properties itself.
+pub async fn rename_json(
+    State(naming): State<JsonNaming>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if naming.naming == ApiNaming::Snake || request.uri().path().starts_with("/schemas") {
+        return next.run(request).await;
+    }
+    let method = request.method().clone();
+    let Some(path) = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+    else {
+        return next.run(request).await;
+    };
+
+    let request = match naming.request_schema(&method, &path) {
+        Some(schema) if is_json(request.headers().get(header::CONTENT_TYPE)) => {
+            let (mut parts, body) = request.into_parts();
+            let Ok(bytes) = to_bytes(body, MAX_RENAMED_BODY_BYTES).await else {
+                return AppError::TooLarge(format!(
+                    "request body exceeds {MAX_RENAMED_BODY_BYTES} bytes"
+                ))
+                .into_response();
+            };
+            let body = rename_bytes(&naming, &bytes, schema, camel_to_snake)
+                .unwrap_or_else(|| bytes.to_vec());
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Request::from_parts(parts, Body::from(body))
+        }
+        _ => request,
+    };
+
+    let response = next.run(request).await;
+    let Some(schema) = naming.response_schema(&method, &path, response.status()) else {
+        return response;
+    };
+    let fits = response
+        .body()
+        .size_hint()
+        .upper()
+        .is_some_and(|len| len <= MAX_RENAMED_BODY_BYTES as u64);
+    if !fits || !is_json(response.headers().get(header::CONTENT_TYPE)) {
+        return response;
+    }
+    let (mut parts, body) = response.into_parts();
+    let Ok(bytes) = to_bytes(body, MAX_RENAMED_BODY_BYTES).await else {
+        return AppError::Internal("failed to read response body".to_string()).into_response();
+    };
+    let body =
+        rename_bytes(&naming, &bytes, schema, snake_to_camel).unwrap_or_else(|| bytes.to_vec());
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(body))
+}
+
+fn rename_bytes(
+    naming: &JsonNaming,
+    bytes: &[u8],
+    schema: &Value,
+    rename: fn(&str) -> String,
+) -> Option<Vec<u8>> {
+    let value: Value = serde_json::from_slice(bytes).ok()?;
+    serde_json::to_vec(&naming.rename(value, schema, rename)).ok()
+}
@@ -0,0 +1,79 @@
+use axum::{extract::State, Json};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::business_logic::anomalies::AnomalyConfig;
+use crate::business_logic::ascending_triangle::AscendingTriangleConfig;
+use crate::business_logic::cup_and_handle::CupAndHandleConfig;
+use crate::business_logic::descending_triangle::DescendingTriangleConfig;
+use crate::business_logic::double_bottom::DoubleBottomConfig;
+use crate::business_logic::double_top::DoubleTopConfig;
+use crate::business_logic::funding::FundingScreenerConfig;
+use crate::business_logic::head_and_shoulders::HeadAndShouldersConfig;
+use crate::business_logic::open_interest::OiScreenerConfig;
+use crate::business_logic::range_breakout::RangeBreakoutConfig;
+use crate::business_logic::trendline::TrendlineConfig;
+use crate::business_logic::triple_bottom::TripleBottomConfig;
+use crate::business_logic::triple_top::TripleTopConfig;
+use crate::business_logic::volume::VolumeSpikeConfig;
+use crate::settings::{MonitorSettings, ServerSettings, VolatilitySettings};
+use crate::state::AppState;
+
+/// The settings in effect after merging defaults, the config file, the
+/// environment and command-line flags.
+///
+/// Webhook secrets are not settings and never appear here.
+#[derive(Serialize, ToSchema)]
+pub struct RuntimeConfig {
+    pub server: ServerSettings,
+    /// Monitored coins and polling.
+    pub monitor: MonitorSettings,
+    pub double_bottom: DoubleBottomConfig,
+    pub double_top: DoubleTopConfig,
+    pub volume_spike: VolumeSpikeConfig,
+    pub anomalies: AnomalyConfig,
+    pub volatility: VolatilitySettings,
+    pub funding: FundingScreenerConfig,
+    pub open_interest: OiScreenerConfig,
+    pub head_and_shoulders: HeadAndShouldersConfig,
+    pub triple_top: TripleTopConfig,
+    pub triple_bottom: TripleBottomConfig,
+    pub ascending_triangle: AscendingTriangleConfig,
+    pub descending_triangle: DescendingTriangleConfig,
+    pub cup_and_handle: CupAndHandleConfig,
+    pub range_breakout: RangeBreakoutConfig,
+    pub trendline: TrendlineConfig,
+}
+
+#[utoipa::path(
+    get,
+    path = "/config",
+    responses(
+        (status = 200, description = "Effective runtime configuration", body = RuntimeConfig)
+    )
+)]
+pub async fn config(State(state): State<AppState>) -> Json<RuntimeConfig> {
+    let settings = &state.settings;
+    Json(RuntimeConfig {
+        server: ServerSettings {
+            api_naming: state.naming,
+            ..settings.server.clone()
+        },
+        monitor: settings.monitor.clone(),
+        double_bottom: settings.double_bottom,
+        double_top: settings.double_top.clone(),
+        volume_spike: settings.volume_spike,
+        anomalies: settings.anomalies,
+        volatility: settings.volatility.clone(),
+        funding: settings.funding,
+        open_interest: settings.open_interest,
+        head_and_shoulders: settings.head_and_shoulders,
+        triple_top: settings.triple_top,
+        triple_bottom: settings.triple_bottom,
+        ascending_triangle: settings.ascending_triangle,
+        descending_triangle: settings.descending_triangle,
+        cup_and_handle: settings.cup_and_handle,
+        range_breakout: settings.range_breakout,
+        trendline: settings.trendline,
+    })
+}
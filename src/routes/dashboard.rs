@@ -0,0 +1,37 @@
+use axum::{extract::State, Json};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::business_logic::anomalies::CandleAnomaly;
+use crate::business_logic::volume::VolumeSpike;
+use crate::routes::health::{self, HealthResponse};
+use crate::routes::volatility::VolatilityRankingResponse;
+use crate::state::AppState;
+
+/// Everything a dashboard polls, in one response. Built from what the
+/// monitor has already published, so it never waits on upstream.
+#[derive(Serialize, ToSchema)]
+pub struct DashboardResponse {
+    pub health: HealthResponse,
+    pub volatility: VolatilityRankingResponse,
+    /// Recent volume spikes, newest candle first.
+    pub volume_spikes: Vec<VolumeSpike>,
+    /// Recent candle anomalies, newest candle first.
+    pub anomalies: Vec<CandleAnomaly>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/dashboard",
+    responses(
+        (status = 200, description = "Health, volatility ranking and recent screener events", body = DashboardResponse)
+    )
+)]
+pub async fn dashboard(State(state): State<AppState>) -> Json<DashboardResponse> {
+    Json(DashboardResponse {
+        health: health::report(&state),
+        volatility: VolatilityRankingResponse::latest(&state),
+        volume_spikes: state.volume_spikes.snapshot(),
+        anomalies: state.anomalies.snapshot(),
+    })
+}
@@ -1,21 +1,136 @@
-use axum::Json;
+use std::collections::BTreeMap;
+
+use axum::{extract::State, http::StatusCode, Json};
 use serde::Serialize;
 use utoipa::ToSchema;
 
+use crate::state::AppState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    /// Every monitored coin has fresh data.
+    Healthy,
+    /// At least one, but not every, monitored coin is stale.
+    Degraded,
+    /// Every monitored coin is stale.
+    Unhealthy,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CoinFreshness {
+    pub coin: String,
+    /// Close time of the most recently processed candle (epoch ms); absent
+    /// until the monitor has processed one.
+    pub last_candle_close_ms: Option<u64>,
+    pub age_ms: Option<u64>,
+    /// True once past the threshold, or while no candle has been processed.
+    pub stale: bool,
+    /// How long the coin has been past the staleness threshold; 0 while
+    /// fresh, absent while no candle has been processed.
+    pub stale_for_ms: Option<u64>,
+    /// Candles went missing from the feed and could not be backfilled; clears
+    /// once enough contiguous candles follow.
+    pub data_gap: bool,
+    /// Gaps found in the coin's feed since the monitor picked it up.
+    pub gap_count: u64,
+    /// The monitor is paused for this coin; its data ages until it resumes.
+    pub paused: bool,
+}
+
 #[derive(Serialize, ToSchema)]
 pub struct HealthResponse {
-    pub status: String,
+    pub status: HealthStatus,
+    /// Age beyond which a coin's data counts as stale (ms).
+    pub max_age_ms: u64,
+    pub coins: Vec<CoinFreshness>,
+    /// Number of entries in `coins` that are stale.
+    pub stale_coins: usize,
+    /// Estimated upstream minus local time (ms); absent until measured.
+    pub clock_skew_ms: Option<i64>,
+    /// True when the estimated skew exceeds the configured threshold.
+    pub clock_skew_warning: bool,
+}
+
+/// Classify data freshness of each `monitored` coin from its last processed
+/// candle close time. A coin missing from `last_processed` counts as stale.
+pub fn evaluate(
+    monitored: &[String],
+    last_processed: &BTreeMap<String, u64>,
+    now_ms: u64,
+    max_age_ms: u64,
+) -> HealthResponse {
+    let mut monitored = monitored.to_vec();
+    monitored.sort();
+    monitored.dedup();
+    let coins: Vec<CoinFreshness> = monitored
+        .into_iter()
+        .map(|coin| {
+            let last_candle_close_ms = last_processed.get(&coin).copied();
+            let age_ms = last_candle_close_ms.map(|close| now_ms.saturating_sub(close));
+            CoinFreshness {
+                coin,
+                last_candle_close_ms,
+                age_ms,
+                stale: age_ms.is_none_or(|age| age > max_age_ms),
+                stale_for_ms: age_ms.map(|age| age.saturating_sub(max_age_ms)),
+                data_gap: false,
+                gap_count: 0,
+                paused: false,
+            }
+        })
+        .collect();
+
+    let stale = coins.iter().filter(|coin| coin.stale).count();
+    let status = if stale == 0 {
+        HealthStatus::Healthy
+    } else if stale < coins.len() {
+        HealthStatus::Degraded
+    } else {
+        HealthStatus::Unhealthy
+    };
+
+    HealthResponse {
+        status,
+        max_age_ms,
+        coins,
+        stale_coins: stale,
+        clock_skew_ms: None,
+        clock_skew_warning: false,
+    }
 }
 
 #[utoipa::path(
     get,
     path = "/health",
     responses(
-        (status = 200, description = "Health check", body = HealthResponse)
+        (status = 200, description = "Healthy, or degraded with some coins stale", body = HealthResponse),
+        (status = 503, description = "Every monitored coin is stale", body = HealthResponse)
     )
 )]
-pub async fn health() -> Json<HealthResponse> {
-    Json(HealthResponse {
-        status: "healthy".to_string(),
-    })
+pub async fn health(State(state): State<AppState>) -> (StatusCode, Json<HealthResponse>) {
+    let response = report(&state);
+    let code = match response.status {
+        HealthStatus::Unhealthy => StatusCode::SERVICE_UNAVAILABLE,
+        HealthStatus::Healthy | HealthStatus::Degraded => StatusCode::OK,
+    };
+    (code, Json(response))
+}
+
+/// Freshness of every monitored coin as of now, with the clock skew.
+pub fn report(state: &AppState) -> HealthResponse {
+    let mut response = evaluate(
+        &state.coins.get(),
+        &state.freshness.snapshot(),
+        state.clock.now_ms(),
+        state.health.max_age_ms(),
+    );
+    for coin in &mut response.coins {
+        coin.data_gap = state.freshness.has_data_gap(&coin.coin);
+        coin.gap_count = state.freshness.gap_count(&coin.coin);
+        coin.paused = state.coins.is_paused(&coin.coin);
+    }
+    response.clock_skew_ms = state.clock_skew.estimate_ms();
+    response.clock_skew_warning = state.clock_skew.exceeds_threshold();
+    response
 }
@@ -0,0 +1,97 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::business_logic::{indicator_series, intervals};
+use crate::error::AppError;
+use crate::services::indicators::IndicatorService;
+use crate::state::AppState;
+
+const DEFAULT_INTERVAL: &str = "15m";
+const DEFAULT_LIMIT: usize = 300;
+/// Ten snapshots' worth; anything past one snapshot is fetched in pages.
+const MAX_LIMIT: usize = 5000;
+
+#[derive(Deserialize, IntoParams)]
+pub struct IndicatorsQuery {
+    pub coin: String,
+    /// Candle interval (default `15m`).
+    pub interval: Option<String>,
+    /// Most recent closed candles to compute over, 1-5000 (default 300).
+    pub limit: Option<usize>,
+    /// Required. Comma-separated indicators, each a kind (`ema`, `sma`, `rsi`, `atr`)
+    /// followed by a period of 1-500, e.g. `ema20,ema50,rsi14,atr14`.
+    pub set: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct IndicatorsResponse {
+    pub coin: String,
+    pub interval: String,
+    /// Open time of each candle (epoch ms), oldest first.
+    pub open_times: Vec<u64>,
+    /// In request order.
+    pub indicators: Vec<IndicatorValues>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct IndicatorValues {
+    /// Canonical name, e.g. `ema20`.
+    pub name: String,
+    /// One value per entry in `open_times`; null while the indicator warms up.
+    pub values: Vec<Option<f64>>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/indicators",
+    params(IndicatorsQuery),
+    responses(
+        (status = 200, description = "Indicator series aligned with recent closed candles", body = IndicatorsResponse),
+        (status = 400, description = "Invalid interval, limit or indicator set", body = crate::error::ErrorResponse),
+        (status = 500, description = "Market data unavailable", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn indicators(
+    State(state): State<AppState>,
+    Query(query): Query<IndicatorsQuery>,
+) -> Result<Json<IndicatorsResponse>, AppError> {
+    let interval = query
+        .interval
+        .unwrap_or_else(|| DEFAULT_INTERVAL.to_string());
+    if !intervals::is_supported(&interval) {
+        return Err(AppError::Validation(format!(
+            "unsupported interval {interval:?}"
+        )));
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(AppError::Validation(format!(
+            "limit must be between 1 and {MAX_LIMIT}"
+        )));
+    }
+    let specs = indicator_series::parse_set(query.set.as_deref().unwrap_or_default())
+        .map_err(AppError::Validation)?;
+
+    let computed = IndicatorService::new(state.hyperliquid.clone())
+        .compute(&query.coin, &interval, limit, &specs, state.clock.now_ms())
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    Ok(Json(IndicatorsResponse {
+        coin: query.coin,
+        interval,
+        open_times: computed.open_times,
+        indicators: computed
+            .series
+            .into_iter()
+            .map(|(spec, values)| IndicatorValues {
+                name: spec.name(),
+                values,
+            })
+            .collect(),
+    }))
+}
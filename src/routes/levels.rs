@@ -0,0 +1,81 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::business_logic::intervals;
+use crate::business_logic::levels::{Level, LevelConfig, LevelDetector};
+use crate::error::AppError;
+use crate::state::AppState;
+
+const DEFAULT_INTERVAL: &str = "15m";
+const DEFAULT_LIMIT: usize = 10;
+/// Closed candles the detector is run over.
+const LOOKBACK: usize = 500;
+
+#[derive(Deserialize, IntoParams)]
+pub struct LevelsQuery {
+    pub coin: String,
+    /// Candle interval (default `15m`).
+    pub interval: Option<String>,
+    /// Max levels returned, strongest first (default 10).
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct LevelsResponse {
+    pub coin: String,
+    pub interval: String,
+    /// Closed candles the detector saw.
+    pub candles: usize,
+    /// Most touches first.
+    pub levels: Vec<Level>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/levels",
+    params(LevelsQuery),
+    responses(
+        (status = 200, description = "Support and resistance levels for the coin, strongest first", body = LevelsResponse),
+        (status = 400, description = "Invalid interval or limit", body = crate::error::ErrorResponse),
+        (status = 500, description = "Market data unavailable", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn levels(
+    State(state): State<AppState>,
+    Query(query): Query<LevelsQuery>,
+) -> Result<Json<LevelsResponse>, AppError> {
+    let coin = query.coin;
+    let interval = query
+        .interval
+        .unwrap_or_else(|| DEFAULT_INTERVAL.to_string());
+    if !intervals::is_supported(&interval) {
+        return Err(AppError::Validation(format!(
+            "unsupported interval {interval:?}"
+        )));
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+    if limit == 0 {
+        return Err(AppError::Validation("limit must be positive".to_string()));
+    }
+
+    let candles = state
+        .hyperliquid
+        .recent_closed_candles(&coin, &interval, LOOKBACK, state.clock.now_ms())
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let mut detector = LevelDetector::new(LevelConfig::default());
+    for candle in &candles {
+        detector.update(candle);
+    }
+
+    Ok(Json(LevelsResponse {
+        coin,
+        interval,
+        candles: candles.len(),
+        levels: detector.top(limit),
+    }))
+}
@@ -1 +1,15 @@
+pub mod config;
+pub mod dashboard;
 pub mod health;
+pub mod indicators;
+pub mod levels;
+pub mod monitor;
+pub mod movers;
+pub mod patterns;
+pub mod pivots;
+pub mod premium;
+pub mod schemas;
+pub mod screeners;
+pub mod swings;
+pub mod volatility;
+pub mod webhooks;
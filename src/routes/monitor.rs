@@ -0,0 +1,80 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::error::AppError;
+use crate::state::{AppState, PausedCoins};
+
+#[derive(Deserialize, IntoParams)]
+pub struct MonitorPauseQuery {
+    /// Monitored coin to pause or resume; omit for every coin.
+    pub coin: Option<String>,
+}
+
+impl MonitorPauseQuery {
+    fn coin(&self, state: &AppState) -> Result<Option<String>, AppError> {
+        let Some(coin) = &self.coin else {
+            return Ok(None);
+        };
+        if !state.coins.get().contains(coin) {
+            return Err(AppError::NotFound(format!("{coin} is not monitored")));
+        }
+        Ok(Some(coin.clone()))
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct MonitorPauseResponse {
+    /// Every coin is paused.
+    pub all: bool,
+    /// Coins paused on their own, whether or not `all` is set.
+    pub coins: Vec<String>,
+}
+
+impl From<PausedCoins> for MonitorPauseResponse {
+    fn from(paused: PausedCoins) -> Self {
+        Self {
+            all: paused.all,
+            coins: paused.coins.into_iter().collect(),
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/monitor/pause",
+    params(MonitorPauseQuery),
+    responses(
+        (status = 200, description = "Stop fetching and publishing for the coin, or every coin, keeping detector state", body = MonitorPauseResponse),
+        (status = 404, description = "Coin is not monitored", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn pause(
+    State(state): State<AppState>,
+    Query(query): Query<MonitorPauseQuery>,
+) -> Result<Json<MonitorPauseResponse>, AppError> {
+    let coin = query.coin(&state)?;
+    state.coins.pause(coin.as_deref());
+    Ok(Json(state.coins.paused().into()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/monitor/resume",
+    params(MonitorPauseQuery),
+    responses(
+        (status = 200, description = "Resume the coin, or lift every pause; the monitor backfills what was missed straight away", body = MonitorPauseResponse),
+        (status = 404, description = "Coin is not monitored", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn resume(
+    State(state): State<AppState>,
+    Query(query): Query<MonitorPauseQuery>,
+) -> Result<Json<MonitorPauseResponse>, AppError> {
+    let coin = query.coin(&state)?;
+    state.coins.resume(coin.as_deref());
+    Ok(Json(state.coins.paused().into()))
+}
@@ -0,0 +1,70 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::business_logic::movers::{Mover, MoverWindow};
+use crate::error::AppError;
+use crate::state::AppState;
+
+const DEFAULT_LIMIT: usize = 20;
+const MAX_LIMIT: usize = 200;
+
+#[derive(Deserialize, IntoParams)]
+pub struct MoversQuery {
+    /// Trailing window: `1h`, `4h` or `24h` (default).
+    pub window: Option<String>,
+    /// Number of coins to return, 1-200 (default 20).
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct MoversResponse {
+    pub window: String,
+    /// When the ranking was computed (epoch ms); rankings are cached briefly.
+    pub computed_at_ms: u64,
+    /// Largest absolute moves first.
+    pub movers: Vec<Mover>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/movers",
+    params(MoversQuery),
+    responses(
+        (status = 200, description = "Coins ranked by price change over the window", body = MoversResponse),
+        (status = 400, description = "Invalid window or limit", body = crate::error::ErrorResponse),
+        (status = 500, description = "Market data unavailable", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn movers(
+    State(state): State<AppState>,
+    Query(query): Query<MoversQuery>,
+) -> Result<Json<MoversResponse>, AppError> {
+    let window = match query.window.as_deref() {
+        None => MoverWindow::OneDay,
+        Some(value) => MoverWindow::parse(value).ok_or_else(|| {
+            AppError::Validation(format!("window must be 1h, 4h or 24h, got {value:?}"))
+        })?,
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(AppError::Validation(format!(
+            "limit must be between 1 and {MAX_LIMIT}"
+        )));
+    }
+
+    let (computed_at_ms, ranked) = state
+        .movers
+        .get_or_refresh(&state.hyperliquid, window, state.clock.now_ms())
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    Ok(Json(MoversResponse {
+        window: window.as_str().to_string(),
+        computed_at_ms,
+        movers: ranked.iter().take(limit).cloned().collect(),
+    }))
+}
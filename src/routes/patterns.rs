@@ -0,0 +1,228 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::{Query, State},
+    response::sse::{Event, KeepAlive, Sse},
+    Json,
+};
+use futures_util::stream::{self, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::business_logic::alerts::PatternAlert;
+use crate::business_logic::double_bottom::DoubleBottomStatus;
+use crate::business_logic::patterns::{self, PatternStatus, PATTERN_NAMES};
+use crate::error::AppError;
+use crate::state::{AppState, PatternEvent};
+
+#[derive(Clone, Deserialize, IntoParams)]
+pub struct PatternsQuery {
+    /// Only this coin.
+    pub coin: Option<String>,
+    /// Only this detector, e.g. `double_bottom`.
+    pub pattern: Option<String>,
+}
+
+impl PatternsQuery {
+    fn validated(self) -> Result<Self, AppError> {
+        if let Some(pattern) = &self.pattern {
+            if !PATTERN_NAMES.contains(&pattern.as_str()) {
+                return Err(AppError::Validation(format!(
+                    "unknown pattern {pattern:?}; expected one of {}",
+                    PATTERN_NAMES.join(", ")
+                )));
+            }
+        }
+        Ok(self)
+    }
+
+    fn wants_status(&self, status: &PatternStatus) -> bool {
+        self.coin
+            .as_deref()
+            .is_none_or(|coin| status.coin() == coin)
+            && self
+                .pattern
+                .as_deref()
+                .is_none_or(|pattern| status.name() == pattern)
+    }
+
+    fn wants_alert(&self, alert: &PatternAlert) -> bool {
+        self.coin.as_deref().is_none_or(|coin| alert.coin == coin)
+            && self
+                .pattern
+                .as_deref()
+                .is_none_or(|pattern| patterns::detector_name(alert.pattern) == pattern)
+    }
+
+    fn event(&self, event: &PatternEvent) -> Option<Event> {
+        match event {
+            PatternEvent::Status(status) if self.wants_status(status) => {
+                Event::default().event("status").json_data(status).ok()
+            }
+            PatternEvent::Alert(alert) if self.wants_alert(alert) => {
+                Event::default().event("alert").json_data(alert).ok()
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PatternsResponse {
+    /// By coin, then pattern.
+    pub statuses: Vec<PatternStatus>,
+    /// Recent alerts, newest candle first.
+    pub alerts: Vec<PatternAlert>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/patterns",
+    params(PatternsQuery),
+    responses(
+        (status = 200, description = "Every pattern detector's status on the monitored coins, with recent alerts", body = PatternsResponse),
+        (status = 400, description = "Unknown pattern", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn patterns(
+    State(state): State<AppState>,
+    Query(query): Query<PatternsQuery>,
+) -> Result<Json<PatternsResponse>, AppError> {
+    let query = query.validated()?;
+    Ok(Json(PatternsResponse {
+        statuses: state
+            .patterns
+            .statuses()
+            .into_iter()
+            .filter(|status| query.wants_status(status))
+            .collect(),
+        alerts: state
+            .patterns
+            .alerts()
+            .into_iter()
+            .filter(|alert| query.wants_alert(alert))
+            .collect(),
+    }))
+}
+
+/// The current matching statuses, then every matching status change and
+/// alert as `status` and `alert` events. A subscriber that falls too far
+/// behind skips what it missed.
+fn event_stream(
+    state: &AppState,
+    query: PatternsQuery,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let receiver = state.patterns.subscribe();
+    let current: Vec<Event> = state
+        .patterns
+        .statuses()
+        .iter()
+        .filter_map(|status| query.event(&PatternEvent::Status(Box::new(status.clone()))))
+        .collect();
+    let live = stream::unfold((receiver, query), |(mut receiver, query)| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    if let Some(event) = query.event(&event) {
+                        return Some((event, (receiver, query)));
+                    }
+                }
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+    Sse::new(stream::iter(current).chain(live).map(Ok)).keep_alive(KeepAlive::default())
+}
+
+#[utoipa::path(
+    get,
+    path = "/patterns/stream",
+    params(PatternsQuery),
+    responses(
+        (status = 200, description = "Server-sent `status` events (a PatternStatus) on every change and `alert` events (a PatternAlert), starting with the current statuses", body = String, content_type = "text/event-stream"),
+        (status = 400, description = "Unknown pattern", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn pattern_stream(
+    State(state): State<AppState>,
+    Query(query): Query<PatternsQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AppError> {
+    Ok(event_stream(&state, query.validated()?))
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct DoubleBottomQuery {
+    pub coin: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DoubleBottomResponse {
+    pub status: DoubleBottomStatus,
+    /// Recent double-bottom alerts on the coin, newest first.
+    pub alerts: Vec<PatternAlert>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/double-bottom",
+    params(DoubleBottomQuery),
+    responses(
+        (status = 200, description = "Where the coin's double bottom stands", body = DoubleBottomResponse),
+        (status = 404, description = "Coin not monitored, or no candles processed yet", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn double_bottom(
+    State(state): State<AppState>,
+    Query(query): Query<DoubleBottomQuery>,
+) -> Result<Json<DoubleBottomResponse>, AppError> {
+    let Some(PatternStatus::DoubleBottom(status)) =
+        state.patterns.status(&query.coin, "double_bottom")
+    else {
+        return Err(AppError::NotFound(format!(
+            "no double bottom status for {}",
+            query.coin
+        )));
+    };
+    let filter = PatternsQuery {
+        coin: Some(query.coin),
+        pattern: Some("double_bottom".to_string()),
+    };
+    Ok(Json(DoubleBottomResponse {
+        status,
+        alerts: state
+            .patterns
+            .alerts()
+            .into_iter()
+            .filter(|alert| filter.wants_alert(alert))
+            .collect(),
+    }))
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct DoubleBottomStreamQuery {
+    /// Only this coin.
+    pub coin: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/double-bottom/stream",
+    params(DoubleBottomStreamQuery),
+    responses(
+        (status = 200, description = "`/patterns/stream` narrowed to the double bottom detector", body = String, content_type = "text/event-stream")
+    )
+)]
+pub async fn double_bottom_stream(
+    State(state): State<AppState>,
+    Query(query): Query<DoubleBottomStreamQuery>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    event_stream(
+        &state,
+        PatternsQuery {
+            coin: query.coin,
+            pattern: Some("double_bottom".to_string()),
+        },
+    )
+}
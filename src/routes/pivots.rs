@@ -0,0 +1,68 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::business_logic::pivots::{utc_date, PivotLevels};
+use crate::error::AppError;
+use crate::state::AppState;
+
+/// Daily candles fetched, so a few missing days still leave one to use.
+const LOOKBACK_DAYS: usize = 7;
+
+#[derive(Deserialize, IntoParams)]
+pub struct PivotsQuery {
+    pub coin: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PivotsResponse {
+    pub coin: String,
+    /// UTC date the levels apply to (today), `YYYY-MM-DD`.
+    pub session_date: String,
+    /// UTC date of the daily candle the levels were computed from.
+    pub source_date: String,
+    /// False when yesterday's daily candle was unavailable and an older
+    /// complete day was used instead.
+    pub from_previous_day: bool,
+    pub levels: PivotLevels,
+}
+
+#[utoipa::path(
+    get,
+    path = "/pivots",
+    params(PivotsQuery),
+    responses(
+        (status = 200, description = "Classic pivot levels from the latest complete daily candle", body = PivotsResponse),
+        (status = 404, description = "No complete daily candle for this coin", body = crate::error::ErrorResponse),
+        (status = 500, description = "Market data unavailable", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn pivots(
+    State(state): State<AppState>,
+    Query(query): Query<PivotsQuery>,
+) -> Result<Json<PivotsResponse>, AppError> {
+    let now_ms = state.clock.now_ms();
+    let candles = state
+        .hyperliquid
+        .recent_closed_candles(&query.coin, "1d", LOOKBACK_DAYS, now_ms)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let source = candles.last().ok_or_else(|| {
+        AppError::NotFound(format!("no complete daily candle for {}", query.coin))
+    })?;
+
+    let date =
+        |ms| utc_date(ms).ok_or_else(|| AppError::Internal(format!("timestamp {ms} out of range")));
+    let session_date = date(now_ms)?;
+    let source_date = date(source.open_time)?;
+    Ok(Json(PivotsResponse {
+        coin: query.coin,
+        session_date: session_date.to_string(),
+        source_date: source_date.to_string(),
+        from_previous_day: session_date.pred_opt() == Some(source_date),
+        levels: PivotLevels::classic(source),
+    }))
+}
@@ -0,0 +1,78 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::business_logic::premium::{self, PremiumSample, PremiumStats};
+use crate::error::AppError;
+use crate::services::hyperliquid::AssetContext;
+use crate::state::AppState;
+
+#[derive(Deserialize, IntoParams)]
+pub struct PremiumQuery {
+    pub coin: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PremiumResponse {
+    pub coin: String,
+    pub mark_price: f64,
+    pub oracle_price: f64,
+    /// Mark over oracle in percent; negative is a discount.
+    pub premium_pct: f64,
+    /// Samples over the last 24h, oldest first.
+    pub history: Vec<PremiumSample>,
+    pub stats: Option<PremiumStats>,
+}
+
+/// Every perp's current context with its premium; the rolling history is
+/// recorded separately by the background sampler.
+pub(crate) async fn current_premiums(
+    state: &AppState,
+) -> Result<Vec<(AssetContext, f64)>, AppError> {
+    let contexts = state
+        .hyperliquid
+        .asset_contexts()
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    Ok(contexts
+        .into_iter()
+        .filter_map(|context| {
+            let premium_pct = premium::premium_pct(context.mark_price, context.oracle_price)?;
+            Some((context, premium_pct))
+        })
+        .collect())
+}
+
+#[utoipa::path(
+    get,
+    path = "/premium",
+    params(PremiumQuery),
+    responses(
+        (status = 200, description = "Current premium and recent history", body = PremiumResponse),
+        (status = 404, description = "Unknown coin", body = crate::error::ErrorResponse),
+        (status = 500, description = "Market data unavailable", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn premium(
+    State(state): State<AppState>,
+    Query(query): Query<PremiumQuery>,
+) -> Result<Json<PremiumResponse>, AppError> {
+    let (context, premium_pct) = current_premiums(&state)
+        .await?
+        .into_iter()
+        .find(|(context, _)| context.coin == query.coin)
+        .ok_or_else(|| AppError::NotFound(format!("unknown coin {}", query.coin)))?;
+    let history = state.premiums.history(&context.coin).unwrap_or_default();
+
+    Ok(Json(PremiumResponse {
+        coin: context.coin,
+        mark_price: context.mark_price,
+        oracle_price: context.oracle_price,
+        premium_pct,
+        history: history.samples().copied().collect(),
+        stats: history.stats(),
+    }))
+}
@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use utoipa::{OpenApi, ToSchema};
+
+use crate::naming::{self, ApiNaming};
+use crate::state::AppState;
+
+const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+const COMPONENT_REF_PREFIX: &str = "#/components/schemas/";
+const DEFS_REF_PREFIX: &str = "#/$defs/";
+
+#[derive(Serialize, ToSchema)]
+pub struct SchemaListResponse {
+    pub schemas: Vec<String>,
+}
+
+/// OpenAPI component schemas keyed by name, serialized once on first use.
+fn components() -> &'static BTreeMap<String, Value> {
+    static COMPONENTS: OnceLock<BTreeMap<String, Value>> = OnceLock::new();
+    COMPONENTS.get_or_init(|| {
+        crate::ApiDoc::openapi()
+            .components
+            .map(|components| {
+                components
+                    .schemas
+                    .into_iter()
+                    .map(|(name, schema)| {
+                        let value = serde_json::to_value(schema)
+                            .expect("OpenAPI component schema serializes to JSON");
+                        (name, value)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    })
+}
+
+/// Names of every schema that can be fetched from `/schemas/{name}`.
+pub fn schema_names() -> Vec<String> {
+    components().keys().cloned().collect()
+}
+
+/// Standalone JSON Schema (draft 2020-12) for an OpenAPI component.
+///
+/// References to other components are rewritten to point into `$defs`, and
+/// every transitively referenced component is embedded there, so the document
+/// validates on its own without the rest of the OpenAPI spec.
+pub fn json_schema(name: &str) -> Option<Value> {
+    let components = components();
+    let root = components.get(name)?;
+
+    let mut defs = Map::new();
+    let mut pending = Vec::new();
+    collect_refs(root, &mut pending);
+    while let Some(dep) = pending.pop() {
+        if dep == name || defs.contains_key(&dep) {
+            continue;
+        }
+        if let Some(schema) = components.get(&dep) {
+            collect_refs(schema, &mut pending);
+            defs.insert(dep, rewrite_refs(schema.clone(), name));
+        }
+    }
+
+    let mut schema = match rewrite_refs(root.clone(), name) {
+        Value::Object(map) => map,
+        other => return Some(other),
+    };
+    schema.insert("$schema".to_string(), Value::from(JSON_SCHEMA_DIALECT));
+    schema.entry("title").or_insert_with(|| Value::from(name));
+    if !defs.is_empty() {
+        schema.insert("$defs".to_string(), Value::Object(defs));
+    }
+    Some(Value::Object(schema))
+}
+
+fn collect_refs(value: &Value, out: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                match (key.as_str(), child) {
+                    ("$ref", Value::String(target)) => {
+                        if let Some(dep) = target.strip_prefix(COMPONENT_REF_PREFIX) {
+                            out.push(dep.to_string());
+                        }
+                    }
+                    _ => collect_refs(child, out),
+                }
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| collect_refs(item, out)),
+        _ => {}
+    }
+}
+
+fn rewrite_refs(value: Value, root: &str) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, child)| match (key.as_str(), child) {
+                    ("$ref", Value::String(target)) => {
+                        let target = match target.strip_prefix(COMPONENT_REF_PREFIX) {
+                            Some(dep) if dep == root => "#".to_string(),
+                            Some(dep) => format!("{DEFS_REF_PREFIX}{dep}"),
+                            None => target,
+                        };
+                        (key, Value::String(target))
+                    }
+                    (_, child) => (key, rewrite_refs(child, root)),
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(|item| rewrite_refs(item, root))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/schemas",
+    responses(
+        (status = 200, description = "Names of the published JSON Schemas", body = SchemaListResponse)
+    )
+)]
+pub async fn list_schemas() -> Json<SchemaListResponse> {
+    Json(SchemaListResponse {
+        schemas: schema_names(),
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/schemas/{name}",
+    params(
+        ("name" = String, Path, description = "Schema name as listed by GET /schemas")
+    ),
+    responses(
+        (status = 200, description = "JSON Schema (draft 2020-12) document", body = serde_json::Value),
+        (status = 404, description = "Unknown schema name")
+    )
+)]
+pub async fn get_schema(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    let schema = json_schema(&name).ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(match state.naming {
+        ApiNaming::Snake => schema,
+        ApiNaming::Camel => naming::rename_schema_properties(schema, naming::snake_to_camel),
+    }))
+}
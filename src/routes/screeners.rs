@@ -0,0 +1,219 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::business_logic::anomalies::CandleAnomaly;
+use crate::business_logic::funding::FundingAnomaly;
+use crate::business_logic::open_interest::OiMetrics;
+use crate::business_logic::premium::{self, DEFAULT_PREMIUM_BAND_PCT};
+use crate::business_logic::volume::VolumeSpike;
+use crate::error::AppError;
+use crate::routes::premium::current_premiums;
+use crate::state::{AppState, CandleEvent, RecentCandleEvents};
+
+#[derive(Deserialize, IntoParams)]
+pub struct RecentEventsQuery {
+    /// Only events for this coin.
+    pub coin: Option<String>,
+    /// Only events on candles opening at or after this time (epoch ms).
+    pub since_ms: Option<u64>,
+}
+
+impl RecentEventsQuery {
+    fn select<T: CandleEvent>(&self, events: &RecentCandleEvents<T>) -> Vec<T> {
+        events
+            .snapshot()
+            .into_iter()
+            .filter(|event| self.coin.as_deref().is_none_or(|coin| event.coin() == coin))
+            .filter(|event| self.since_ms.is_none_or(|since| event.open_time() >= since))
+            .collect()
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct VolumeSpikesResponse {
+    /// Newest candle first.
+    pub spikes: Vec<VolumeSpike>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/screeners/volume-spikes",
+    params(RecentEventsQuery),
+    responses(
+        (status = 200, description = "Recent volume spikes on monitored coins", body = VolumeSpikesResponse)
+    )
+)]
+pub async fn volume_spikes(
+    State(state): State<AppState>,
+    Query(query): Query<RecentEventsQuery>,
+) -> Json<VolumeSpikesResponse> {
+    Json(VolumeSpikesResponse {
+        spikes: query.select(&state.volume_spikes),
+    })
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AnomaliesResponse {
+    /// Newest candle first.
+    pub anomalies: Vec<CandleAnomaly>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/screeners/anomalies",
+    params(RecentEventsQuery),
+    responses(
+        (status = 200, description = "Recent wick and range anomalies on monitored coins", body = AnomaliesResponse)
+    )
+)]
+pub async fn anomalies(
+    State(state): State<AppState>,
+    Query(query): Query<RecentEventsQuery>,
+) -> Json<AnomaliesResponse> {
+    Json(AnomaliesResponse {
+        anomalies: query.select(&state.anomalies),
+    })
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct PremiumScreenerQuery {
+    /// List coins whose premium is beyond this many percent either way (default 0.5).
+    pub band_pct: Option<f64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PremiumOutlier {
+    pub coin: String,
+    pub mark_price: f64,
+    pub oracle_price: f64,
+    pub premium_pct: f64,
+    /// How long the premium has stayed beyond the band on this side (ms),
+    /// as far as the recorded history shows.
+    pub outside_band_for_ms: u64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PremiumScreenerResponse {
+    pub band_pct: f64,
+    /// Largest absolute premium first.
+    pub coins: Vec<PremiumOutlier>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/screeners/premium",
+    params(PremiumScreenerQuery),
+    responses(
+        (status = 200, description = "Perps trading outside the premium band", body = PremiumScreenerResponse),
+        (status = 400, description = "Invalid band", body = crate::error::ErrorResponse),
+        (status = 500, description = "Market data unavailable", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn premium_outliers(
+    State(state): State<AppState>,
+    Query(query): Query<PremiumScreenerQuery>,
+) -> Result<Json<PremiumScreenerResponse>, AppError> {
+    let band_pct = query.band_pct.unwrap_or(DEFAULT_PREMIUM_BAND_PCT);
+    if !band_pct.is_finite() || band_pct < 0.0 {
+        return Err(AppError::Validation(
+            "band_pct must be a non-negative number".to_string(),
+        ));
+    }
+
+    let mut coins: Vec<PremiumOutlier> = current_premiums(&state)
+        .await?
+        .into_iter()
+        .filter(|(_, premium_pct)| premium::outside_band(*premium_pct, band_pct))
+        .map(|(context, premium_pct)| PremiumOutlier {
+            outside_band_for_ms: state
+                .premiums
+                .history(&context.coin)
+                .map_or(0, |history| history.persistent_extreme_ms(band_pct)),
+            coin: context.coin,
+            mark_price: context.mark_price,
+            oracle_price: context.oracle_price,
+            premium_pct,
+        })
+        .collect();
+    coins.sort_by(|a, b| b.premium_pct.abs().total_cmp(&a.premium_pct.abs()));
+
+    Ok(Json(PremiumScreenerResponse { band_pct, coins }))
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct FundingScreenerResponse {
+    /// Time of the latest funding sample (epoch ms); absent before the first.
+    pub sampled_at_ms: Option<u64>,
+    /// Highest current rate first.
+    pub coins: Vec<FundingAnomaly>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/screeners/funding",
+    responses(
+        (status = 200, description = "Perps whose funding rate is anomalous against their own trailing 14 days, or above the absolute threshold", body = FundingScreenerResponse)
+    )
+)]
+pub async fn funding(State(state): State<AppState>) -> Json<FundingScreenerResponse> {
+    let config = state.settings.funding;
+    let histories = state.funding.snapshot();
+    let sampled_at_ms = histories
+        .iter()
+        .filter_map(|(_, history)| history.latest())
+        .map(|sample| sample.time_ms)
+        .max();
+    let mut coins: Vec<FundingAnomaly> = histories
+        .iter()
+        .filter_map(|(coin, history)| history.screen(coin, &config))
+        .collect();
+    coins.sort_by(|a, b| {
+        b.current_rate
+            .total_cmp(&a.current_rate)
+            .then_with(|| a.coin.cmp(&b.coin))
+    });
+    Json(FundingScreenerResponse {
+        sampled_at_ms,
+        coins,
+    })
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct OiScreenerResponse {
+    /// Window the deltas are measured over (`open_interest.window_ms`).
+    pub window_ms: u64,
+    /// Largest absolute OI change first.
+    pub coins: Vec<OiMetrics>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/screeners/open-interest",
+    responses(
+        (status = 200, description = "Perps whose open interest rose while price held flat or fell, or collapsed, over the configured window", body = OiScreenerResponse)
+    )
+)]
+pub async fn open_interest(State(state): State<AppState>) -> Json<OiScreenerResponse> {
+    let config = state.settings.open_interest;
+    let mut coins: Vec<OiMetrics> = state
+        .open_interest
+        .snapshot()
+        .iter()
+        .filter_map(|(coin, history)| history.metrics(coin, &config))
+        .filter(|metrics| !metrics.rules.is_empty())
+        .collect();
+    coins.sort_by(|a, b| {
+        b.oi_change_pct
+            .abs()
+            .total_cmp(&a.oi_change_pct.abs())
+            .then_with(|| a.coin.cmp(&b.coin))
+    });
+    Json(OiScreenerResponse {
+        window_ms: config.window_ms,
+        coins,
+    })
+}
@@ -0,0 +1,86 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::business_logic::intervals;
+use crate::business_logic::swing::{self, ZigzagPoint};
+use crate::error::AppError;
+use crate::state::AppState;
+
+const DEFAULT_INTERVAL: &str = "15m";
+const DEFAULT_LIMIT: usize = 500;
+/// Ten snapshots' worth; anything past one snapshot is fetched in pages.
+const MAX_LIMIT: usize = 5000;
+const ATR_PERIOD: usize = 14;
+const DEFAULT_REV_ATR: f64 = 1.0;
+
+#[derive(Deserialize, IntoParams)]
+pub struct SwingsQuery {
+    pub coin: String,
+    /// Candle interval (default `15m`).
+    pub interval: Option<String>,
+    /// Most recent candles to run the detector over, 1-5000 (default 500).
+    pub limit: Option<usize>,
+    /// Reversal in ATRs that confirms a swing (default 1.0).
+    pub rev_atr: Option<f64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SwingsResponse {
+    pub coin: String,
+    pub interval: String,
+    /// Closed candles the detector saw.
+    pub candles: usize,
+    /// Confirmed swings, oldest first; alternating peaks and troughs.
+    pub swings: Vec<ZigzagPoint>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/swings",
+    params(SwingsQuery),
+    responses(
+        (status = 200, description = "Confirmed swing highs and lows over recent candles", body = SwingsResponse),
+        (status = 400, description = "Invalid interval, limit or rev_atr", body = crate::error::ErrorResponse),
+        (status = 500, description = "Market data unavailable", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn swings(
+    State(state): State<AppState>,
+    Query(query): Query<SwingsQuery>,
+) -> Result<Json<SwingsResponse>, AppError> {
+    let interval = query
+        .interval
+        .unwrap_or_else(|| DEFAULT_INTERVAL.to_string());
+    if !intervals::is_supported(&interval) {
+        return Err(AppError::Validation(format!(
+            "unsupported interval {interval:?}"
+        )));
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(AppError::Validation(format!(
+            "limit must be between 1 and {MAX_LIMIT}"
+        )));
+    }
+    let rev_atr = query.rev_atr.unwrap_or(DEFAULT_REV_ATR);
+    if !(rev_atr.is_finite() && rev_atr > 0.0) {
+        return Err(AppError::Validation("rev_atr must be positive".to_string()));
+    }
+
+    let candles = state
+        .hyperliquid
+        .recent_closed_candles(&query.coin, &interval, limit, state.clock.now_ms())
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    Ok(Json(SwingsResponse {
+        coin: query.coin,
+        interval,
+        candles: candles.len(),
+        swings: swing::zigzag(&candles, ATR_PERIOD, rev_atr),
+    }))
+}
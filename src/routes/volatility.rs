@@ -0,0 +1,96 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::business_logic::intervals;
+use crate::business_logic::volatility::VolatilityEntry;
+use crate::error::AppError;
+use crate::services::volatility::MAX_ATR_PERIOD;
+use crate::state::AppState;
+
+#[derive(Deserialize, IntoParams)]
+pub struct VolatilityRankingQuery {
+    /// Candle interval the ATR is computed on; must match
+    /// `volatility.interval` when given.
+    pub interval: Option<String>,
+    /// ATR period; must match `volatility.period` when given.
+    pub period: Option<usize>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct VolatilityRankingResponse {
+    pub interval: String,
+    pub period: usize,
+    /// Monitor cycle this ranking was computed in (epoch ms); absent until
+    /// the first one.
+    pub computed_at_ms: Option<u64>,
+    /// Highest ATR% first.
+    pub coins: Vec<VolatilityEntry>,
+}
+
+impl VolatilityRankingResponse {
+    /// The ranking the monitor last published.
+    pub fn latest(state: &AppState) -> Self {
+        let settings = &state.settings.volatility;
+        match state.volatility.latest() {
+            Some(ranking) => Self {
+                interval: ranking.interval,
+                period: ranking.period,
+                computed_at_ms: Some(ranking.computed_at_ms),
+                coins: ranking.entries.to_vec(),
+            },
+            None => Self {
+                interval: settings.interval.clone(),
+                period: settings.period,
+                computed_at_ms: None,
+                coins: Vec::new(),
+            },
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/volatility-ranking",
+    params(VolatilityRankingQuery),
+    responses(
+        (status = 200, description = "Monitored coins ordered by ATR as a percentage of price", body = VolatilityRankingResponse),
+        (status = 400, description = "Invalid interval or period, or not the ones the ranking is computed on", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn volatility_ranking(
+    State(state): State<AppState>,
+    Query(query): Query<VolatilityRankingQuery>,
+) -> Result<Json<VolatilityRankingResponse>, AppError> {
+    let settings = &state.settings.volatility;
+    if let Some(interval) = &query.interval {
+        if !intervals::is_supported(interval) {
+            return Err(AppError::Validation(format!(
+                "unsupported interval {interval:?}"
+            )));
+        }
+        if *interval != settings.interval {
+            return Err(AppError::Validation(format!(
+                "the ranking is computed on {} candles; set volatility.interval to change it",
+                settings.interval
+            )));
+        }
+    }
+    if let Some(period) = query.period {
+        if !(1..=MAX_ATR_PERIOD).contains(&period) {
+            return Err(AppError::Validation(format!(
+                "period must be between 1 and {MAX_ATR_PERIOD}"
+            )));
+        }
+        if period != settings.period {
+            return Err(AppError::Validation(format!(
+                "the ranking uses period {}; set volatility.period to change it",
+                settings.period
+            )));
+        }
+    }
+    Ok(Json(VolatilityRankingResponse::latest(&state)))
+}
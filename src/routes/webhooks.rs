@@ -0,0 +1,219 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::business_logic::alerts::AlertSeverity;
+use crate::business_logic::quiet_hours::QuietHours;
+use crate::error::AppError;
+use crate::services::delivery_queue::{FailedDelivery, QueuedDelivery};
+use crate::services::webhooks::{NewWebhook, WebhookSubscription};
+use crate::state::AppState;
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateWebhookRequest {
+    /// http(s) URL that alerts are POSTed to.
+    pub url: String,
+    /// Only deliver alerts for these coins. Omit for every coin.
+    pub coins: Option<Vec<String>>,
+    /// Only deliver alerts at or above this severity. Omit for every severity.
+    pub min_severity: Option<AlertSeverity>,
+    /// When set, each body is signed with HMAC-SHA256 in the `X-Signature` header.
+    pub secret: Option<String>,
+    /// Hold back alerts below `bypass_severity` during a daily window.
+    pub quiet_hours: Option<QuietHours>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookStatus {
+    Active,
+    /// Disabled after too many consecutive delivery failures.
+    Disabled,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct WebhookResponse {
+    pub id: u64,
+    pub url: String,
+    pub coins: Option<Vec<String>>,
+    pub min_severity: Option<AlertSeverity>,
+    /// The secret itself is never returned.
+    pub has_secret: bool,
+    pub quiet_hours: Option<QuietHours>,
+    pub status: WebhookStatus,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    /// Alerts discarded because they fell in quiet hours.
+    pub quiet_dropped: u64,
+    /// Alerts held until the end of quiet hours.
+    pub quiet_deferred: u64,
+    pub created_at_ms: u64,
+}
+
+impl From<WebhookSubscription> for WebhookResponse {
+    fn from(subscription: WebhookSubscription) -> Self {
+        Self {
+            id: subscription.id,
+            url: subscription.url,
+            coins: subscription.coins,
+            min_severity: subscription.min_severity,
+            has_secret: subscription.secret.is_some(),
+            quiet_hours: subscription.quiet_hours,
+            status: if subscription.disabled {
+                WebhookStatus::Disabled
+            } else {
+                WebhookStatus::Active
+            },
+            consecutive_failures: subscription.consecutive_failures,
+            last_error: subscription.last_error,
+            quiet_dropped: subscription.quiet_dropped,
+            quiet_deferred: subscription.quiet_deferred,
+            created_at_ms: subscription.created_at_ms,
+        }
+    }
+}
+
+fn validate(request: CreateWebhookRequest) -> Result<NewWebhook, AppError> {
+    let url = reqwest::Url::parse(&request.url)
+        .map_err(|e| AppError::Validation(format!("invalid url: {e}")))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(AppError::Validation(
+            "url must use http or https".to_string(),
+        ));
+    }
+
+    let coins = match request.coins {
+        Some(coins) => {
+            let coins: Vec<String> = coins.into_iter().map(|c| c.trim().to_string()).collect();
+            if coins.is_empty() || coins.iter().any(String::is_empty) {
+                return Err(AppError::Validation(
+                    "coins must be omitted or a list of non-empty coin names".to_string(),
+                ));
+            }
+            Some(coins)
+        }
+        None => None,
+    };
+
+    if request.secret.as_deref() == Some("") {
+        return Err(AppError::Validation("secret must not be empty".to_string()));
+    }
+
+    if let Some(quiet_hours) = &request.quiet_hours {
+        quiet_hours
+            .validate()
+            .map_err(|e| AppError::Validation(format!("invalid quiet_hours: {e}")))?;
+    }
+
+    Ok(NewWebhook {
+        url: request.url,
+        coins,
+        min_severity: request.min_severity,
+        secret: request.secret,
+        quiet_hours: request.quiet_hours,
+    })
+}
+
+#[utoipa::path(
+    post,
+    path = "/webhooks",
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 201, description = "Subscription created", body = WebhookResponse),
+        (status = 400, description = "Invalid subscription", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    Json(request): Json<CreateWebhookRequest>,
+) -> Result<(StatusCode, Json<WebhookResponse>), AppError> {
+    let new = validate(request)?;
+    let subscription = state
+        .webhooks
+        .create(new, state.clock.now_ms())
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    Ok((StatusCode::CREATED, Json(subscription.into())))
+}
+
+#[utoipa::path(
+    get,
+    path = "/webhooks",
+    responses(
+        (status = 200, description = "All subscriptions with delivery status", body = Vec<WebhookResponse>)
+    )
+)]
+pub async fn list_webhooks(State(state): State<AppState>) -> Json<Vec<WebhookResponse>> {
+    Json(state.webhooks.list().into_iter().map(Into::into).collect())
+}
+
+#[utoipa::path(
+    delete,
+    path = "/webhooks/{id}",
+    params(
+        ("id" = u64, Path, description = "Subscription id")
+    ),
+    responses(
+        (status = 204, description = "Subscription deleted"),
+        (status = 404, description = "Unknown subscription", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<StatusCode, AppError> {
+    let deleted = state
+        .webhooks
+        .delete(id)
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    if !deleted {
+        return Err(AppError::NotFound(format!("webhook {id} not found")));
+    }
+    state
+        .webhook_queue
+        .forget_subscription(id)
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DeliveriesResponse {
+    /// Failed deliveries still being retried, oldest first.
+    pub pending: Vec<QueuedDelivery>,
+    /// Deliveries given up on, most recent last.
+    pub failed: Vec<FailedDelivery>,
+    /// Pending deliveries across every subscription.
+    pub queue_depth: usize,
+    /// Age of the oldest pending delivery across every subscription (ms).
+    pub oldest_pending_age_ms: Option<u64>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/webhooks/{id}/deliveries",
+    params(
+        ("id" = u64, Path, description = "Subscription id")
+    ),
+    responses(
+        (status = 200, description = "Queued and permanently failed deliveries", body = DeliveriesResponse),
+        (status = 404, description = "Unknown subscription", body = crate::error::ErrorResponse)
+    )
+)]
+pub async fn webhook_deliveries(
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<Json<DeliveriesResponse>, AppError> {
+    if state.webhooks.get(id).is_none() {
+        return Err(AppError::NotFound(format!("webhook {id} not found")));
+    }
+    let stats = state.webhook_queue.stats(state.clock.now_ms());
+    Ok(Json(DeliveriesResponse {
+        pending: state.webhook_queue.pending_for(id),
+        failed: state.webhook_queue.failed_for(id),
+        queue_depth: stats.depth,
+        oldest_pending_age_ms: stats.oldest_age_ms,
+    }))
+}
@@ -0,0 +1,80 @@
+//! Coalescing of snapshot events for slow stream consumers.
+
+use std::collections::VecDeque;
+
+use tokio::sync::broadcast::{self, error::RecvError, error::TryRecvError};
+
+/// Events that may be superseded by a newer one of the same kind.
+pub trait Coalesce {
+    /// `true` for full-state snapshots, where only the newest matters.
+    /// Alerts and state-change events must return `false`; they are never
+    /// dropped by [`CoalescingReceiver`].
+    fn is_snapshot(&self) -> bool;
+}
+
+/// Wraps a broadcast receiver so a consumer that falls behind skips straight
+/// to the newest snapshot instead of working through every intermediate one.
+///
+/// Each [`CoalescingReceiver::recv`] drains everything already buffered on
+/// the channel. Within that backlog, an older snapshot is discarded when a
+/// newer one arrives, while every other event is kept in order.
+#[derive(Debug)]
+pub struct CoalescingReceiver<T> {
+    inner: broadcast::Receiver<T>,
+    ready: VecDeque<T>,
+    coalesced: u64,
+    lagged: u64,
+}
+
+impl<T: Clone + Coalesce> CoalescingReceiver<T> {
+    pub fn new(inner: broadcast::Receiver<T>) -> Self {
+        Self {
+            inner,
+            ready: VecDeque::new(),
+            coalesced: 0,
+            lagged: 0,
+        }
+    }
+
+    /// Next event to send, or `None` once the channel is closed and drained.
+    pub async fn recv(&mut self) -> Option<T> {
+        while self.ready.is_empty() {
+            match self.inner.recv().await {
+                Ok(event) => self.push(event),
+                Err(RecvError::Lagged(missed)) => self.lagged += missed,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+        self.drain_buffered();
+        self.ready.pop_front()
+    }
+
+    /// Snapshots skipped because a newer one was already waiting.
+    pub fn coalesced(&self) -> u64 {
+        self.coalesced
+    }
+
+    /// Events lost because the channel overflowed before they were drained.
+    pub fn lagged(&self) -> u64 {
+        self.lagged
+    }
+
+    fn drain_buffered(&mut self) {
+        loop {
+            match self.inner.try_recv() {
+                Ok(event) => self.push(event),
+                Err(TryRecvError::Lagged(missed)) => self.lagged += missed,
+                Err(TryRecvError::Empty | TryRecvError::Closed) => return,
+            }
+        }
+    }
+
+    fn push(&mut self, event: T) {
+        if event.is_snapshot() {
+            let before = self.ready.len();
+            self.ready.retain(|queued| !queued.is_snapshot());
+            self.coalesced += (before - self.ready.len()) as u64;
+        }
+        self.ready.push_back(event);
+    }
+}
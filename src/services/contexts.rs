@@ -0,0 +1,116 @@
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use tokio_util::sync::CancellationToken;
+
+use crate::business_logic::funding::FundingSample;
+use crate::business_logic::open_interest::OiSample;
+use crate::business_logic::premium::{self, PremiumSample};
+use crate::services::hyperliquid::HyperliquidError;
+use crate::services::monitor::MonitorAlert;
+use crate::services::webhooks::WebhookDispatcher;
+use crate::state::AppState;
+
+/// Records every perp's premium, funding rate and open interest into
+/// [`AppState`] on a
+/// fixed schedule, from one asset-context fetch per tick, so the rolling
+/// histories grow at a steady rate however often they are read.
+///
+/// Monitored coins whose funding turns anomalous are sent to the webhook
+/// subscriptions as a [`MonitorAlert`], once per excursion.
+pub struct ContextSampler {
+    state: AppState,
+    dispatcher: WebhookDispatcher,
+    /// Monitored coins whose funding was anomalous at the last sample.
+    funding_flagged: BTreeSet<String>,
+}
+
+impl ContextSampler {
+    /// Alerts go out through [`AppState::webhook_dispatcher`].
+    pub fn new(state: AppState) -> Self {
+        Self {
+            dispatcher: state.webhook_dispatcher(),
+            state,
+            funding_flagged: BTreeSet::new(),
+        }
+    }
+
+    /// Fetch every perp's context once and record it. Returns how many
+    /// coins were sampled.
+    pub async fn sample(&mut self) -> Result<usize, HyperliquidError> {
+        let contexts = self.state.hyperliquid.asset_contexts().await?;
+        let now_ms = self.state.clock.now_ms();
+        let oi_window_ms = self.state.settings.open_interest.window_ms;
+        let mut sampled = 0;
+        for context in &contexts {
+            self.state.funding.record(
+                &context.coin,
+                FundingSample {
+                    time_ms: now_ms,
+                    rate: context.funding_rate,
+                },
+            );
+            self.state.open_interest.record(
+                &context.coin,
+                OiSample {
+                    time_ms: now_ms,
+                    open_interest: context.open_interest,
+                    price: context.mark_price,
+                },
+                oi_window_ms,
+            );
+            let Some(premium_pct) = premium::premium_pct(context.mark_price, context.oracle_price)
+            else {
+                continue;
+            };
+            self.state.premiums.record(
+                &context.coin,
+                PremiumSample {
+                    time_ms: now_ms,
+                    premium_pct,
+                },
+            );
+            sampled += 1;
+        }
+        self.alert_funding().await;
+        Ok(sampled)
+    }
+
+    async fn alert_funding(&mut self) {
+        let config = self.state.settings.funding;
+        let mut flagged = BTreeSet::new();
+        for coin in self.state.coins.get() {
+            let Some(anomaly) = self
+                .state
+                .funding
+                .history(&coin)
+                .and_then(|history| history.screen(&coin, &config))
+            else {
+                continue;
+            };
+            if !self.funding_flagged.contains(&coin) {
+                let alert = MonitorAlert::FundingAnomaly(anomaly);
+                self.dispatcher
+                    .dispatch(alert.coin(), alert.severity(), &alert)
+                    .await;
+            }
+            flagged.insert(coin);
+        }
+        self.funding_flagged = flagged;
+    }
+
+    /// Sample every `every` until `shutdown` is cancelled.
+    pub async fn run(mut self, every: Duration, shutdown: CancellationToken) {
+        let mut ticks = tokio::time::interval(every);
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => return,
+                _ = ticks.tick() => {
+                    if let Err(e) = self.sample().await {
+                        eprintln!("Asset context sampling failed: {e}");
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,307 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::services::webhooks::{write_atomically, WebhookStoreError};
+
+/// How long a failed delivery keeps being retried before it is given up on.
+pub const DEFAULT_MAX_DELIVERY_AGE_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// Delay before the first retry; doubles with every further attempt.
+pub const RETRY_BASE_DELAY_MS: u64 = 5_000;
+
+pub const MAX_RETRY_DELAY_MS: u64 = 10 * 60 * 1000;
+
+/// Permanently failed deliveries kept per subscription for debugging.
+pub const FAILED_DELIVERIES_KEPT: usize = 20;
+
+/// Delay after the `attempts`-th failed attempt: 5s, 10s, 20s, ... capped at 10 minutes.
+pub fn retry_delay_ms(attempts: u32) -> u64 {
+    let doublings = attempts.saturating_sub(1).min(16);
+    (RETRY_BASE_DELAY_MS << doublings).min(MAX_RETRY_DELAY_MS)
+}
+
+/// `last_error` of a delivery deferred by quiet hours before any attempt.
+pub const QUIET_HOURS_REASON: &str = "deferred by quiet hours";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct QueuedDelivery {
+    pub id: u64,
+    pub subscription_id: u64,
+    /// Exact body sent, so a retry carries the same signature.
+    pub body: String,
+    pub enqueued_at_ms: u64,
+    /// Zero for a delivery deferred by quiet hours that hasn't been tried yet.
+    pub attempts: u32,
+    pub next_attempt_ms: u64,
+    pub last_error: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct FailedDelivery {
+    pub id: u64,
+    pub subscription_id: u64,
+    pub body: String,
+    pub enqueued_at_ms: u64,
+    pub failed_at_ms: u64,
+    pub attempts: u32,
+    pub last_error: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueStats {
+    pub depth: usize,
+    /// Age of the oldest pending delivery; `None` when the queue is empty.
+    pub oldest_age_ms: Option<u64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct QueueState {
+    next_id: u64,
+    pending: Vec<QueuedDelivery>,
+    failed: Vec<FailedDelivery>,
+}
+
+/// Webhook deliveries waiting to be retried, optionally persisted so they
+/// survive a restart.
+///
+/// Like [`WebhookStore`](crate::services::webhooks::WebhookStore), every
+/// mutation rewrites the whole JSON file.
+#[derive(Debug)]
+pub struct DeliveryQueue {
+    path: Option<PathBuf>,
+    max_age_ms: u64,
+    state: Mutex<QueueState>,
+}
+
+impl DeliveryQueue {
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            max_age_ms: DEFAULT_MAX_DELIVERY_AGE_MS,
+            state: Mutex::new(QueueState::default()),
+        }
+    }
+
+    /// Queue backed by `path`, loading pending deliveries if the file exists.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, WebhookStoreError> {
+        let path = path.into();
+        let state = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(WebhookStoreError::Corrupt)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => QueueState::default(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            path: Some(path),
+            max_age_ms: DEFAULT_MAX_DELIVERY_AGE_MS,
+            state: Mutex::new(state),
+        })
+    }
+
+    pub fn with_max_age_ms(mut self, max_age_ms: u64) -> Self {
+        self.max_age_ms = max_age_ms;
+        self
+    }
+
+    /// Queue a delivery whose first attempt failed with `error`.
+    pub fn enqueue(
+        &self,
+        subscription_id: u64,
+        body: String,
+        error: String,
+        now_ms: u64,
+    ) -> Result<QueuedDelivery, WebhookStoreError> {
+        let mut state = self.state.lock().unwrap();
+        state.next_id += 1;
+        let delivery = QueuedDelivery {
+            id: state.next_id,
+            subscription_id,
+            body,
+            enqueued_at_ms: now_ms,
+            attempts: 1,
+            next_attempt_ms: now_ms + retry_delay_ms(1),
+            last_error: error,
+        };
+        state.pending.push(delivery.clone());
+        self.persist(&state)?;
+        Ok(delivery)
+    }
+
+    /// Queue a delivery that hasn't been attempted yet, to be sent at
+    /// `deliver_at_ms` (e.g. when quiet hours end).
+    pub fn defer(
+        &self,
+        subscription_id: u64,
+        body: String,
+        deliver_at_ms: u64,
+        now_ms: u64,
+    ) -> Result<QueuedDelivery, WebhookStoreError> {
+        let mut state = self.state.lock().unwrap();
+        state.next_id += 1;
+        let delivery = QueuedDelivery {
+            id: state.next_id,
+            subscription_id,
+            body,
+            enqueued_at_ms: now_ms,
+            attempts: 0,
+            next_attempt_ms: deliver_at_ms,
+            last_error: QUIET_HOURS_REASON.to_string(),
+        };
+        state.pending.push(delivery.clone());
+        self.persist(&state)?;
+        Ok(delivery)
+    }
+
+    /// Pending deliveries whose next attempt is due, oldest first.
+    pub fn due(&self, now_ms: u64) -> Vec<QueuedDelivery> {
+        self.state
+            .lock()
+            .unwrap()
+            .pending
+            .iter()
+            .filter(|d| d.next_attempt_ms <= now_ms)
+            .cloned()
+            .collect()
+    }
+
+    /// Record a retry. Success removes the delivery. Failure schedules the next
+    /// attempt with exponential backoff, or gives up once the delivery is older
+    /// than the max age.
+    pub fn record_retry(
+        &self,
+        id: u64,
+        outcome: Result<(), String>,
+        now_ms: u64,
+    ) -> Result<(), WebhookStoreError> {
+        let mut state = self.state.lock().unwrap();
+        let Some(index) = state.pending.iter().position(|d| d.id == id) else {
+            return Ok(());
+        };
+        match outcome {
+            Ok(()) => {
+                state.pending.remove(index);
+            }
+            Err(error) => {
+                let delivery = &mut state.pending[index];
+                delivery.attempts += 1;
+                delivery.last_error = error;
+                if now_ms.saturating_sub(delivery.enqueued_at_ms) >= self.max_age_ms {
+                    fail(&mut state, index, now_ms);
+                } else {
+                    delivery.next_attempt_ms = now_ms + retry_delay_ms(delivery.attempts);
+                }
+            }
+        }
+        self.persist(&state)
+    }
+
+    /// Stop retrying a delivery, e.g. because its subscription is gone.
+    pub fn give_up(&self, id: u64, reason: String, now_ms: u64) -> Result<(), WebhookStoreError> {
+        let mut state = self.state.lock().unwrap();
+        let Some(index) = state.pending.iter().position(|d| d.id == id) else {
+            return Ok(());
+        };
+        state.pending[index].last_error = reason;
+        fail(&mut state, index, now_ms);
+        self.persist(&state)
+    }
+
+    /// Drop every pending and failed delivery for a deleted subscription.
+    /// Returns how many entries were removed.
+    pub fn forget_subscription(&self, subscription_id: u64) -> Result<usize, WebhookStoreError> {
+        let mut state = self.state.lock().unwrap();
+        let before = state.pending.len() + state.failed.len();
+        state
+            .pending
+            .retain(|d| d.subscription_id != subscription_id);
+        state
+            .failed
+            .retain(|d| d.subscription_id != subscription_id);
+        let removed = before - state.pending.len() - state.failed.len();
+        if removed == 0 {
+            return Ok(0);
+        }
+        self.persist(&state)?;
+        Ok(removed)
+    }
+
+    pub fn pending_for(&self, subscription_id: u64) -> Vec<QueuedDelivery> {
+        self.state
+            .lock()
+            .unwrap()
+            .pending
+            .iter()
+            .filter(|d| d.subscription_id == subscription_id)
+            .cloned()
+            .collect()
+    }
+
+    /// Permanently failed deliveries for a subscription, most recent last.
+    pub fn failed_for(&self, subscription_id: u64) -> Vec<FailedDelivery> {
+        self.state
+            .lock()
+            .unwrap()
+            .failed
+            .iter()
+            .filter(|d| d.subscription_id == subscription_id)
+            .cloned()
+            .collect()
+    }
+
+    pub fn stats(&self, now_ms: u64) -> QueueStats {
+        let state = self.state.lock().unwrap();
+        QueueStats {
+            depth: state.pending.len(),
+            oldest_age_ms: state
+                .pending
+                .iter()
+                .map(|d| now_ms.saturating_sub(d.enqueued_at_ms))
+                .max(),
+        }
+    }
+
+    fn persist(&self, state: &QueueState) -> Result<(), WebhookStoreError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        write_atomically(
+            path,
+            &serde_json::to_vec_pretty(state).map_err(WebhookStoreError::Corrupt)?,
+        )
+    }
+}
+
+/// Move `state.pending[index]` to the failed list, trimming that
+/// subscription's history to [`FAILED_DELIVERIES_KEPT`].
+fn fail(state: &mut QueueState, index: usize, now_ms: u64) {
+    let delivery = state.pending.remove(index);
+    let subscription_id = delivery.subscription_id;
+    state.failed.push(FailedDelivery {
+        id: delivery.id,
+        subscription_id,
+        body: delivery.body,
+        enqueued_at_ms: delivery.enqueued_at_ms,
+        failed_at_ms: now_ms,
+        attempts: delivery.attempts,
+        last_error: delivery.last_error,
+    });
+    let kept = state
+        .failed
+        .iter()
+        .filter(|d| d.subscription_id == subscription_id)
+        .count();
+    if kept > FAILED_DELIVERIES_KEPT {
+        if let Some(oldest) = state
+            .failed
+            .iter()
+            .position(|d| d.subscription_id == subscription_id)
+        {
+            state.failed.remove(oldest);
+        }
+    }
+}
@@ -0,0 +1,364 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::sync::Semaphore;
+
+use crate::business_logic::intervals;
+use crate::clock::{Clock, SkewEstimator, SystemClock};
+use crate::models::candle::Candle;
+
+pub const DEFAULT_BASE_URL: &str = "https://api.hyperliquid.xyz";
+
+/// Requests allowed in flight at once across every caller sharing a client.
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 8;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Most candles one `candleSnapshot` response carries.
+pub const MAX_CANDLES_PER_REQUEST: usize = 500;
+
+#[derive(Debug)]
+pub enum HyperliquidError {
+    Http(reqwest::Error),
+    Status(reqwest::StatusCode),
+    /// The response body did not have the expected shape.
+    Parse(String),
+}
+
+impl fmt::Display for HyperliquidError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HyperliquidError::Http(e) => write!(f, "Hyperliquid request failed: {e}"),
+            HyperliquidError::Status(status) => write!(f, "Hyperliquid returned {status}"),
+            HyperliquidError::Parse(message) => {
+                write!(f, "unexpected Hyperliquid response: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for HyperliquidError {}
+
+impl From<reqwest::Error> for HyperliquidError {
+    fn from(e: reqwest::Error) -> Self {
+        HyperliquidError::Http(e)
+    }
+}
+
+/// Client for the Hyperliquid `info` endpoint.
+///
+/// Cloning is cheap and clones share the concurrency limit, so one client can
+/// be handed to every task that fetches market data.
+#[derive(Debug, Clone)]
+pub struct HyperliquidClient {
+    base_url: String,
+    http: reqwest::Client,
+    permits: Arc<Semaphore>,
+    clock_skew: Option<Arc<SkewEstimator>>,
+}
+
+/// Live per-perp market context from `metaAndAssetCtxs`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetContext {
+    pub coin: String,
+    pub mark_price: f64,
+    /// Oracle (index) price the mark is anchored to.
+    pub oracle_price: f64,
+    /// Absent when the book is empty.
+    pub mid_price: Option<f64>,
+    /// Current hourly funding rate.
+    pub funding_rate: f64,
+    /// Open interest in base units.
+    pub open_interest: f64,
+    pub prev_day_price: f64,
+    /// Traded volume over the last 24h, in base units.
+    pub day_base_volume: f64,
+    pub day_notional_volume: f64,
+}
+
+/// Static per-perp listing details from `meta`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerpMeta {
+    pub name: String,
+    pub sz_decimals: u32,
+    pub max_leverage: u32,
+    /// Tradable with isolated margin only.
+    #[serde(default)]
+    pub only_isolated: bool,
+    #[serde(default)]
+    pub is_delisted: bool,
+}
+
+#[derive(Deserialize)]
+struct RawMeta {
+    universe: Vec<PerpMeta>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawAssetContext {
+    mark_px: String,
+    oracle_px: String,
+    mid_px: Option<String>,
+    funding: String,
+    open_interest: String,
+    prev_day_px: String,
+    day_base_vlm: String,
+    day_ntl_vlm: String,
+}
+
+impl RawAssetContext {
+    fn parse(self, coin: String) -> Result<AssetContext, HyperliquidError> {
+        Ok(AssetContext {
+            coin,
+            mark_price: parse_decimal(&self.mark_px)?,
+            oracle_price: parse_decimal(&self.oracle_px)?,
+            mid_price: self.mid_px.as_deref().map(parse_decimal).transpose()?,
+            funding_rate: parse_decimal(&self.funding)?,
+            open_interest: parse_decimal(&self.open_interest)?,
+            prev_day_price: parse_decimal(&self.prev_day_px)?,
+            day_base_volume: parse_decimal(&self.day_base_vlm)?,
+            day_notional_volume: parse_decimal(&self.day_ntl_vlm)?,
+        })
+    }
+}
+
+/// Candle as returned by `candleSnapshot`, with prices as decimal strings.
+#[derive(Deserialize)]
+struct RawCandle {
+    t: u64,
+    #[serde(rename = "T")]
+    close_time: u64,
+    o: String,
+    h: String,
+    l: String,
+    c: String,
+    v: String,
+    n: u64,
+}
+
+impl TryFrom<RawCandle> for Candle {
+    type Error = HyperliquidError;
+
+    fn try_from(raw: RawCandle) -> Result<Self, Self::Error> {
+        Ok(Candle {
+            open_time: raw.t,
+            close_time: raw.close_time,
+            open: parse_decimal(&raw.o)?,
+            high: parse_decimal(&raw.h)?,
+            low: parse_decimal(&raw.l)?,
+            close: parse_decimal(&raw.c)?,
+            volume: parse_decimal(&raw.v)?,
+            num_trades: raw.n,
+        })
+    }
+}
+
+/// Upstream time from a `Date` header, taken as the middle of its
+/// whole second.
+fn date_header_ms(headers: &reqwest::header::HeaderMap) -> Option<u64> {
+    let value = headers.get(reqwest::header::DATE)?.to_str().ok()?;
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    u64::try_from(date.timestamp_millis())
+        .ok()
+        .map(|ms| ms + 500)
+}
+
+fn parse_decimal(value: &str) -> Result<f64, HyperliquidError> {
+    value
+        .parse()
+        .map_err(|_| HyperliquidError::Parse(format!("invalid number {value:?}")))
+}
+
+impl HyperliquidClient {
+    pub fn new() -> Self {
+        Self::with_base_url(DEFAULT_BASE_URL)
+    }
+
+    /// Client for another deployment (testnet, or a local stub in tests).
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .expect("failed to build Hyperliquid HTTP client");
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            http,
+            permits: Arc::new(Semaphore::new(DEFAULT_MAX_CONCURRENT_REQUESTS)),
+            clock_skew: None,
+        }
+    }
+
+    pub fn with_max_concurrent_requests(mut self, max: usize) -> Self {
+        self.permits = Arc::new(Semaphore::new(max.max(1)));
+        self
+    }
+
+    /// Feed `skew` from the `Date` header of every response.
+    pub fn with_clock_skew(mut self, skew: Arc<SkewEstimator>) -> Self {
+        self.clock_skew = Some(skew);
+        self
+    }
+
+    async fn info<T: for<'de> Deserialize<'de>>(&self, body: Value) -> Result<T, HyperliquidError> {
+        let _permit = self
+            .permits
+            .acquire()
+            .await
+            .expect("request semaphore is never closed");
+        let response = self
+            .http
+            .post(format!("{}/info", self.base_url))
+            .json(&body)
+            .send()
+            .await?;
+        if let Some(skew) = &self.clock_skew {
+            if let Some(upstream_ms) = date_header_ms(response.headers()) {
+                skew.observe(upstream_ms, SystemClock.now_ms());
+            }
+        }
+        if !response.status().is_success() {
+            return Err(HyperliquidError::Status(response.status()));
+        }
+        Ok(response.json().await?)
+    }
+
+    /// Mid price of every listed coin.
+    ///
+    /// Spot pairs (`PURR/USDC`, `@107`, ...) are included as Hyperliquid returns
+    /// them; see [`is_perp_coin`] to filter them out.
+    pub async fn all_mids(&self) -> Result<HashMap<String, f64>, HyperliquidError> {
+        let mids: HashMap<String, String> = self.info(json!({ "type": "allMids" })).await?;
+        mids.into_iter()
+            .map(|(coin, mid)| Ok((coin, parse_decimal(&mid)?)))
+            .collect()
+    }
+
+    /// Every perp in the universe, including delisted ones, in universe order.
+    pub async fn fetch_meta(&self) -> Result<Vec<PerpMeta>, HyperliquidError> {
+        let meta: RawMeta = self.info(json!({ "type": "meta" })).await?;
+        Ok(meta.universe)
+    }
+
+    /// Market context for every perp, in universe order.
+    pub async fn asset_contexts(&self) -> Result<Vec<AssetContext>, HyperliquidError> {
+        let (meta, contexts): (RawMeta, Vec<RawAssetContext>) =
+            self.info(json!({ "type": "metaAndAssetCtxs" })).await?;
+        if meta.universe.len() != contexts.len() {
+            return Err(HyperliquidError::Parse(format!(
+                "{} assets but {} contexts",
+                meta.universe.len(),
+                contexts.len()
+            )));
+        }
+        meta.universe
+            .into_iter()
+            .zip(contexts)
+            .map(|(asset, context)| context.parse(asset.name))
+            .collect()
+    }
+
+    /// Candles for `coin` opening in `[start_ms, end_ms]`, oldest first.
+    ///
+    /// Hyperliquid caps each response at [`MAX_CANDLES_PER_REQUEST`] candles;
+    /// see [`HyperliquidClient::closed_candles_since`] for longer ranges.
+    pub async fn candle_snapshot(
+        &self,
+        coin: &str,
+        interval: &str,
+        start_ms: u64,
+        end_ms: u64,
+    ) -> Result<Vec<Candle>, HyperliquidError> {
+        let raw: Vec<RawCandle> = self
+            .info(json!({
+                "type": "candleSnapshot",
+                "req": {
+                    "coin": coin,
+                    "interval": interval,
+                    "startTime": start_ms,
+                    "endTime": end_ms,
+                },
+            }))
+            .await?;
+        raw.into_iter().map(Candle::try_from).collect()
+    }
+
+    /// The last `limit` candles for `coin` that have closed by `now_ms`,
+    /// oldest first. `interval` must be supported.
+    ///
+    /// Windows longer than one snapshot are paged through
+    /// [`Self::closed_candles_since`].
+    pub async fn recent_closed_candles(
+        &self,
+        coin: &str,
+        interval: &str,
+        limit: usize,
+        now_ms: u64,
+    ) -> Result<Vec<Candle>, HyperliquidError> {
+        // One extra candle since the one still forming is dropped.
+        let start_ms =
+            intervals::window_start(interval, limit as u32 + 1, now_ms).unwrap_or_default();
+        let mut candles = if limit < MAX_CANDLES_PER_REQUEST {
+            let mut candles = self
+                .candle_snapshot(coin, interval, start_ms, now_ms)
+                .await?;
+            candles.retain(|c| c.is_closed_at(interval, now_ms));
+            candles
+        } else {
+            self.closed_candles_since(coin, interval, start_ms.saturating_sub(1), now_ms)
+                .await?
+        };
+        let skip = candles.len().saturating_sub(limit);
+        candles.drain(..skip);
+        Ok(candles)
+    }
+
+    /// Every candle for `coin` opening after `last_open_ms` that has closed
+    /// by `now_ms`, oldest first and each exactly once, fetched in as many
+    /// pages as the gap needs. `interval` must be supported.
+    pub async fn closed_candles_since(
+        &self,
+        coin: &str,
+        interval: &str,
+        last_open_ms: u64,
+        now_ms: u64,
+    ) -> Result<Vec<Candle>, HyperliquidError> {
+        let mut candles: Vec<Candle> = Vec::new();
+        let mut start_ms =
+            intervals::next_bucket_start(interval, last_open_ms).unwrap_or(last_open_ms + 1);
+        loop {
+            let page = self
+                .candle_snapshot(coin, interval, start_ms, now_ms)
+                .await?;
+            let full = page.len() >= MAX_CANDLES_PER_REQUEST;
+            let newest = candles.last().map(|c| c.open_time);
+            candles.extend(page.into_iter().filter(|c| {
+                c.open_time >= start_ms
+                    && newest.is_none_or(|t| c.open_time > t)
+                    && c.is_closed_at(interval, now_ms)
+            }));
+            match candles.last() {
+                Some(last) if full && Some(last.open_time) != newest => {
+                    start_ms = last.open_time + 1;
+                }
+                _ => return Ok(candles),
+            }
+        }
+    }
+}
+
+impl Default for HyperliquidClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether `coin` names a perp rather than a spot pair.
+pub fn is_perp_coin(coin: &str) -> bool {
+    !coin.starts_with('@') && !coin.contains('/')
+}
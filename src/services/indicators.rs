@@ -0,0 +1,49 @@
+use crate::business_logic::indicator_series::IndicatorSpec;
+use crate::services::hyperliquid::{HyperliquidClient, HyperliquidError};
+
+/// Indicator values aligned with the candles they were computed over.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndicatorSeries {
+    /// Open time of each candle, oldest first.
+    pub open_times: Vec<u64>,
+    /// One entry per requested spec, in request order; each has one value
+    /// per candle.
+    pub series: Vec<(IndicatorSpec, Vec<Option<f64>>)>,
+}
+
+/// Computes indicator series server-side over freshly fetched candles.
+#[derive(Debug, Clone)]
+pub struct IndicatorService {
+    client: HyperliquidClient,
+}
+
+impl IndicatorService {
+    pub fn new(client: HyperliquidClient) -> Self {
+        Self { client }
+    }
+
+    /// Fetch the last `limit` closed candles and run every spec over them.
+    ///
+    /// Values are `None` until an indicator has seen enough candles, so a
+    /// period close to `limit` leaves most of its series empty.
+    pub async fn compute(
+        &self,
+        coin: &str,
+        interval: &str,
+        limit: usize,
+        specs: &[IndicatorSpec],
+        now_ms: u64,
+    ) -> Result<IndicatorSeries, HyperliquidError> {
+        let candles = self
+            .client
+            .recent_closed_candles(coin, interval, limit, now_ms)
+            .await?;
+        Ok(IndicatorSeries {
+            open_times: candles.iter().map(|c| c.open_time).collect(),
+            series: specs
+                .iter()
+                .map(|spec| (*spec, spec.series(&candles)))
+                .collect(),
+        })
+    }
+}
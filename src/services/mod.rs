@@ -0,0 +1,12 @@
+pub mod coalesce;
+#[cfg(feature = "server")]
+pub mod contexts;
+pub mod delivery_queue;
+pub mod hyperliquid;
+pub mod indicators;
+#[cfg(feature = "server")]
+pub mod monitor;
+pub mod movers;
+pub mod universe;
+pub mod volatility;
+pub mod webhooks;
@@ -0,0 +1,522 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::task::JoinSet;
+use tokio::time::MissedTickBehavior;
+use tokio_util::sync::CancellationToken;
+use utoipa::ToSchema;
+
+use crate::business_logic::alerts::{AlertSeverity, PatternAlert, TrendDirection};
+use crate::business_logic::anomalies::{CandleAnomaly, CandleAnomalyDetector};
+use crate::business_logic::ascending_triangle::AscendingTriangleDetector;
+use crate::business_logic::cup_and_handle::CupAndHandleDetector;
+use crate::business_logic::descending_triangle::DescendingTriangleDetector;
+use crate::business_logic::double_bottom::DoubleBottomDetector;
+use crate::business_logic::double_top::{DoubleTopDetector, HigherTimeframeFilter};
+use crate::business_logic::funding::FundingAnomaly;
+use crate::business_logic::gaps::{GapPolicy, GapTracker};
+use crate::business_logic::head_and_shoulders::HeadAndShouldersDetector;
+use crate::business_logic::intervals;
+use crate::business_logic::patterns::{Confluence, PatternDetector};
+use crate::business_logic::range_breakout::RangeBreakoutDetector;
+use crate::business_logic::trendline::TrendlineDetector;
+use crate::business_logic::triple_bottom::TripleBottomDetector;
+use crate::business_logic::triple_top::TripleTopDetector;
+use crate::business_logic::volume::{VolumeMonitor, VolumeSpike};
+use crate::models::candle::Candle;
+use crate::services::hyperliquid::{HyperliquidClient, HyperliquidError};
+use crate::services::volatility::{self, VolatilityRanking};
+use crate::services::webhooks::WebhookDispatcher;
+use crate::settings::Settings;
+use crate::state::AppState;
+
+/// Closed candles fetched for a coin the first time the monitor sees it,
+/// enough to warm up every swing-based detector. Raised to a pattern
+/// detector's own [`PatternDetector::warmup_candles`] when that is longer.
+pub const WARMUP_CANDLES: usize = 100;
+
+/// Most holes refetched per coin per fetch; any beyond are left as found.
+pub const MAX_BACKFILLS_PER_FETCH: usize = 5;
+
+/// Webhook payload for something the monitor found, tagged by `type`.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MonitorAlert {
+    VolumeSpike(VolumeSpike),
+    CandleAnomaly(CandleAnomaly),
+    /// At the alert's own severity: `warning` for an early warning,
+    /// `critical` for a confirmation.
+    Pattern(PatternAlert),
+    /// Sent by [`crate::services::contexts::ContextSampler`].
+    FundingAnomaly(FundingAnomaly),
+}
+
+impl MonitorAlert {
+    pub fn coin(&self) -> &str {
+        match self {
+            MonitorAlert::VolumeSpike(spike) => &spike.coin,
+            MonitorAlert::CandleAnomaly(anomaly) => &anomaly.coin,
+            MonitorAlert::Pattern(alert) => &alert.coin,
+            MonitorAlert::FundingAnomaly(anomaly) => &anomaly.coin,
+        }
+    }
+
+    pub fn severity(&self) -> AlertSeverity {
+        match self {
+            MonitorAlert::VolumeSpike(_)
+            | MonitorAlert::CandleAnomaly(_)
+            | MonitorAlert::FundingAnomaly(_) => AlertSeverity::Info,
+            MonitorAlert::Pattern(alert) => alert.severity(),
+        }
+    }
+}
+
+/// Detector state for one monitored coin.
+#[derive(Debug)]
+struct CoinFeed {
+    /// Open time of the last candle fed to the detectors.
+    last_open_ms: Option<u64>,
+    gaps: GapTracker,
+    volume: VolumeMonitor,
+    anomalies: CandleAnomalyDetector,
+    patterns: Vec<Box<dyn PatternDetector>>,
+    /// Bucket of the higher-timeframe candle the trend was last read in.
+    htf_bucket: Option<u64>,
+    htf_trend: Option<TrendDirection>,
+}
+
+impl CoinFeed {
+    fn new(coin: &str, interval: &str, settings: &Settings) -> Self {
+        Self {
+            last_open_ms: None,
+            gaps: GapTracker::new(interval, settings.monitor.gaps),
+            volume: VolumeMonitor::new(coin, settings.volume_spike),
+            anomalies: CandleAnomalyDetector::new(coin, settings.anomalies),
+            patterns: pattern_detectors(coin, interval, settings),
+            htf_bucket: None,
+            htf_trend: None,
+        }
+    }
+
+    /// Start the detectors over, keeping the gap history.
+    fn reset_detectors(&mut self, coin: &str, interval: &str, settings: &Settings) {
+        self.volume = VolumeMonitor::new(coin, settings.volume_spike);
+        self.anomalies = CandleAnomalyDetector::new(coin, settings.anomalies);
+        self.patterns = pattern_detectors(coin, interval, settings);
+        if let Some(trend) = self.htf_trend {
+            self.set_htf_trend(trend);
+        }
+    }
+
+    fn set_htf_trend(&mut self, trend: TrendDirection) {
+        self.htf_trend = Some(trend);
+        for detector in &mut self.patterns {
+            detector.set_htf_trend(trend);
+        }
+    }
+}
+
+/// Every chart-pattern detector run on each monitored coin's `interval`
+/// candles.
+fn pattern_detectors(
+    coin: &str,
+    interval: &str,
+    settings: &Settings,
+) -> Vec<Box<dyn PatternDetector>> {
+    vec![
+        Box::new(DoubleBottomDetector::new(coin, settings.double_bottom)),
+        Box::new(DoubleTopDetector::new(
+            coin,
+            settings.double_top.clone(),
+            interval,
+        )),
+        Box::new(HeadAndShouldersDetector::new(
+            coin,
+            settings.head_and_shoulders,
+        )),
+        Box::new(TripleTopDetector::new(coin, settings.triple_top)),
+        Box::new(TripleBottomDetector::new(coin, settings.triple_bottom)),
+        Box::new(AscendingTriangleDetector::new(
+            coin,
+            settings.ascending_triangle,
+        )),
+        Box::new(DescendingTriangleDetector::new(
+            coin,
+            settings.descending_triangle,
+        )),
+        Box::new(CupAndHandleDetector::new(coin, settings.cup_and_handle)),
+        Box::new(RangeBreakoutDetector::new(coin, settings.range_breakout)),
+        Box::new(TrendlineDetector::new(coin, settings.trendline)),
+    ]
+}
+
+/// Closed candles to fetch for a coin the first time the monitor sees it.
+fn warmup_candles(interval: &str, settings: &Settings) -> usize {
+    pattern_detectors("", interval, settings)
+        .iter()
+        .map(|detector| detector.warmup_candles())
+        .fold(WARMUP_CANDLES, usize::max)
+}
+
+/// Polls closed candles for every monitored coin and runs the per-coin
+/// detectors over them, publishing what they find into [`AppState`] for the
+/// screener routes and pattern state, and recording each coin's data
+/// freshness for `/health`.
+/// Each cycle also keeps the volatility ranking current and, when
+/// `double_top.htf_filter` is set, reads each coin's higher-timeframe trend
+/// once per candle of that timeframe for the detectors to check alerts
+/// against.
+///
+/// Everything found is also sent as a [`MonitorAlert`] to the matching
+/// webhook subscriptions once the cycle's candles are processed.
+///
+/// The coin list is re-read from [`AppState::coins`] each cycle; coins that
+/// leave it lose their detector state and freshness entry. Paused coins
+/// stay on it and keep theirs.
+pub struct MarketMonitor {
+    state: AppState,
+    interval: String,
+    feeds: HashMap<String, CoinFeed>,
+    dispatcher: WebhookDispatcher,
+    /// Candle bucket and coin list the published volatility ranking covers.
+    ranked: Option<(u64, Vec<String>)>,
+    /// Candles fetched for a coin seen for the first time.
+    warmup: usize,
+}
+
+impl MarketMonitor {
+    /// `interval` must be supported. Alerts go out through
+    /// [`AppState::webhook_dispatcher`].
+    pub fn new(state: AppState, interval: impl Into<String>) -> Self {
+        let interval = interval.into();
+        Self {
+            dispatcher: state.webhook_dispatcher(),
+            warmup: warmup_candles(&interval, &state.settings),
+            state,
+            interval,
+            feeds: HashMap::new(),
+            ranked: None,
+        }
+    }
+
+    /// Fetch every monitored coin's newly closed candles and feed them to
+    /// its detectors. Returns how many candles were processed.
+    ///
+    /// A coin whose fetch fails is retried from the same point next cycle.
+    /// Paused coins are skipped but keep their detector state, so the
+    /// first cycle after they resume fetches everything they missed.
+    pub async fn run_cycle(&mut self) -> usize {
+        let coins = self.state.coins.get();
+        let freshness = &self.state.freshness;
+        let patterns = &self.state.patterns;
+        self.feeds.retain(|coin, _| {
+            let keep = coins.contains(coin);
+            if !keep {
+                freshness.remove(coin);
+                patterns.remove(coin);
+            }
+            keep
+        });
+        let now_ms = self.state.clock.now_ms();
+
+        let active: Vec<String> = coins
+            .iter()
+            .filter(|coin| !self.state.coins.is_paused(coin))
+            .cloned()
+            .collect();
+
+        let htf_filter = self.state.settings.double_top.htf_filter.clone();
+        let mut fetches = JoinSet::new();
+        for coin in active.clone() {
+            let (last_open_ms, gaps, htf_bucket) = match self.feeds.get(&coin) {
+                Some(feed) => (feed.last_open_ms, feed.gaps.clone(), feed.htf_bucket),
+                None => (
+                    None,
+                    GapTracker::new(&self.interval, self.state.settings.monitor.gaps),
+                    None,
+                ),
+            };
+            // The higher-timeframe trend is read once per candle of it.
+            let htf = htf_filter.clone().and_then(|filter| {
+                let bucket = intervals::bucket_start(&filter.interval, now_ms)?;
+                (htf_bucket != Some(bucket)).then_some((filter, bucket))
+            });
+            let client = self.state.hyperliquid.clone();
+            let interval = self.interval.clone();
+            let warmup = self.warmup;
+            fetches.spawn(async move {
+                let candles = fetch(
+                    &client,
+                    &coin,
+                    &interval,
+                    last_open_ms,
+                    warmup,
+                    gaps,
+                    now_ms,
+                )
+                .await;
+                let htf = match htf {
+                    Some((filter, bucket)) => htf_trend(&client, &coin, &filter, now_ms)
+                        .await
+                        .map(|trend| (bucket, trend)),
+                    None => None,
+                };
+                (coin, candles, htf)
+            });
+        }
+
+        let mut processed = 0;
+        let mut alerts = Vec::new();
+        while let Some(joined) = fetches.join_next().await {
+            let Ok((coin, candles, htf)) = joined else {
+                continue;
+            };
+            match candles {
+                Ok(candles) => processed += self.process(&coin, &candles, htf, &mut alerts),
+                Err(e) => eprintln!("Monitor fetch for {coin} failed: {e}"),
+            }
+        }
+        for alert in alerts {
+            self.dispatcher
+                .dispatch(alert.coin(), alert.severity(), &alert)
+                .await;
+        }
+        self.refresh_volatility(active, now_ms).await;
+        processed
+    }
+
+    /// Rebuild the published volatility ranking once a new candle of its
+    /// interval has closed or the coin list has changed.
+    async fn refresh_volatility(&mut self, coins: Vec<String>, now_ms: u64) {
+        let settings = &self.state.settings.volatility;
+        let Some(bucket) = intervals::bucket_start(&settings.interval, now_ms) else {
+            return;
+        };
+        let key = (bucket, coins);
+        if self.ranked.as_ref() == Some(&key) {
+            return;
+        }
+        let entries = volatility::compute(
+            &self.state.hyperliquid,
+            &key.1,
+            &settings.interval,
+            settings.period,
+            now_ms,
+        )
+        .await;
+        self.state.volatility.publish(VolatilityRanking {
+            interval: settings.interval.clone(),
+            period: settings.period,
+            computed_at_ms: now_ms,
+            entries: Arc::new(entries),
+        });
+        self.ranked = Some(key);
+    }
+
+    /// Run a cycle every `every` until `shutdown` is cancelled, and another
+    /// straight after coins are resumed so they catch up without waiting
+    /// for the next tick.
+    ///
+    /// A cycle that takes longer than `every` is logged, and the ticks it
+    /// overran are skipped rather than fired back to back.
+    pub async fn run(mut self, every: Duration, shutdown: CancellationToken) {
+        let mut ticks = tokio::time::interval(every);
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        let coins = self.state.coins.clone();
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => return,
+                _ = ticks.tick() => {}
+                _ = coins.resumed() => {}
+            }
+            let started = Instant::now();
+            let processed = self.run_cycle().await;
+            let took = started.elapsed();
+            if took > every {
+                eprintln!(
+                    "Monitor cycle took {took:?} for {processed} candles, \
+                     longer than the {every:?} poll interval"
+                );
+            }
+        }
+    }
+
+    /// Feed `candles` to `coin`'s detectors, pushing what they find onto
+    /// `alerts`, after handing them a newly read higher-timeframe trend and
+    /// the bucket it was read in. Returns how many candles were new.
+    fn process(
+        &mut self,
+        coin: &str,
+        candles: &[Candle],
+        htf: Option<(u64, TrendDirection)>,
+        alerts: &mut Vec<MonitorAlert>,
+    ) -> usize {
+        let interval = &self.interval;
+        let settings = &self.state.settings;
+        let feed = self
+            .feeds
+            .entry(coin.to_string())
+            .or_insert_with(|| CoinFeed::new(coin, interval, settings));
+        if let Some((bucket, trend)) = htf {
+            feed.htf_bucket = Some(bucket);
+            feed.set_htf_trend(trend);
+        }
+        let mut processed = 0;
+        for candle in candles {
+            // Anything at or before the last candle fed is a repeat, served
+            // again by an overlapping fetch or backfill.
+            if feed
+                .last_open_ms
+                .is_some_and(|last| candle.open_time <= last)
+            {
+                continue;
+            }
+            feed.last_open_ms = Some(candle.open_time);
+            processed += 1;
+            if let Some(gap) = feed.gaps.observe(candle) {
+                eprintln!(
+                    "{coin}: {} {interval} candles missing after {}",
+                    gap.missing_candles, gap.after_open_time
+                );
+                if feed.gaps.policy() == GapPolicy::ResetDetector {
+                    feed.reset_detectors(coin, interval, settings);
+                }
+            }
+            if let Some(spike) = feed.volume.update(candle) {
+                self.state.volume_spikes.record(spike.clone());
+                alerts.push(MonitorAlert::VolumeSpike(spike));
+            }
+            if let Some(anomaly) = feed.anomalies.update(candle) {
+                self.state.anomalies.record(anomaly.clone());
+                alerts.push(MonitorAlert::CandleAnomaly(anomaly));
+            }
+            for detector in &mut feed.patterns {
+                if let Some(alert) = detector.update(candle) {
+                    self.state.patterns.record_alert(alert.clone());
+                    alerts.push(MonitorAlert::Pattern(alert));
+                }
+            }
+            self.state.freshness.record(coin, candle.close_time);
+        }
+        if processed > 0 {
+            let confluence = confluence(&self.state, coin);
+            for detector in &mut feed.patterns {
+                detector.set_confluence(&confluence);
+                self.state.patterns.publish(detector.status());
+            }
+        }
+        let freshness = &self.state.freshness;
+        freshness.set_data_gap(coin, feed.gaps.has_data_gap());
+        freshness.set_gap_count(coin, feed.gaps.gap_count());
+        processed
+    }
+}
+
+/// Newly closed candles for `coin`: the last `warmup` on first sight,
+/// otherwise everything since `last_open_ms`. Holes that `gaps` (a copy of
+/// the feed's tracker) finds are refetched once, since upstream sometimes
+/// serves a candle late.
+///
+/// Returned oldest first with one candle per open time, whatever order and
+/// repeats upstream served them in.
+async fn fetch(
+    client: &HyperliquidClient,
+    coin: &str,
+    interval: &str,
+    last_open_ms: Option<u64>,
+    warmup: usize,
+    mut gaps: GapTracker,
+    now_ms: u64,
+) -> Result<Vec<Candle>, HyperliquidError> {
+    let mut candles = match last_open_ms {
+        Some(last_open_ms) => {
+            client
+                .closed_candles_since(coin, interval, last_open_ms, now_ms)
+                .await?
+        }
+        None => {
+            client
+                .recent_closed_candles(coin, interval, warmup, now_ms)
+                .await?
+        }
+    };
+    sort_unique(&mut candles);
+
+    let holes: Vec<_> = candles
+        .iter()
+        .filter_map(|candle| gaps.observe(candle))
+        .take(MAX_BACKFILLS_PER_FETCH)
+        .collect();
+    if holes.is_empty() {
+        return Ok(candles);
+    }
+    for hole in holes {
+        let Ok(backfill) = client
+            .candle_snapshot(
+                coin,
+                interval,
+                hole.after_open_time + 1,
+                hole.before_open_time - 1,
+            )
+            .await
+        else {
+            continue;
+        };
+        candles.extend(
+            backfill.into_iter().filter(|c| {
+                c.open_time > hole.after_open_time && c.open_time < hole.before_open_time
+            }),
+        );
+    }
+    sort_unique(&mut candles);
+    Ok(candles)
+}
+
+/// What the open-interest and premium screeners say about `coin` from the
+/// histories the context sampler keeps, judging the premium against
+/// `double_top.confluence.premium_band_pct`.
+fn confluence(state: &AppState, coin: &str) -> Confluence {
+    let oi_rules = state
+        .open_interest
+        .history(coin)
+        .and_then(|history| history.metrics(coin, &state.settings.open_interest))
+        .map(|metrics| metrics.rules)
+        .unwrap_or_default();
+    let premiums = state.premiums.history(coin).unwrap_or_default();
+    let band_pct = state.settings.double_top.confluence.premium_band_pct;
+    Confluence {
+        oi_rules,
+        premium_pct: premiums.latest().map(|sample| sample.premium_pct),
+        premium_extreme_ms: premiums.persistent_extreme_ms(band_pct),
+    }
+}
+
+/// `coin`'s trend on `filter`'s interval. `None` when the fetch fails or
+/// there aren't enough candles yet, so it is tried again next cycle.
+async fn htf_trend(
+    client: &HyperliquidClient,
+    coin: &str,
+    filter: &HigherTimeframeFilter,
+    now_ms: u64,
+) -> Option<TrendDirection> {
+    match client
+        .recent_closed_candles(coin, &filter.interval, filter.candles_needed(), now_ms)
+        .await
+    {
+        Ok(candles) => filter.classify(&candles),
+        Err(e) => {
+            eprintln!(
+                "Monitor {} trend fetch for {coin} failed: {e}",
+                filter.interval
+            );
+            None
+        }
+    }
+}
+
+fn sort_unique(candles: &mut Vec<Candle>) {
+    candles.sort_by_key(|c| c.open_time);
+    candles.dedup_by_key(|c| c.open_time);
+}
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tokio::task::JoinSet;
+
+use crate::business_logic::movers::{self, Mover, MoverWindow};
+use crate::services::hyperliquid::{is_perp_coin, HyperliquidClient, HyperliquidError};
+
+/// How long a computed ranking is served before it is rebuilt.
+pub const DEFAULT_MOVERS_TTL_MS: u64 = 30_000;
+
+#[derive(Debug, Clone)]
+struct CachedRanking {
+    computed_at_ms: u64,
+    movers: Arc<Vec<Mover>>,
+}
+
+/// Full per-window rankings, cached briefly so dashboard polling doesn't
+/// refetch market data on every request.
+#[derive(Debug)]
+pub struct MoversCache {
+    ttl_ms: u64,
+    rankings: Mutex<HashMap<MoverWindow, CachedRanking>>,
+}
+
+impl MoversCache {
+    pub fn new(ttl_ms: u64) -> Self {
+        Self {
+            ttl_ms,
+            rankings: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Ranking for `window` and when it was computed, rebuilt from `client`
+    /// if the cached one is older than the TTL.
+    ///
+    /// Concurrent misses may each rebuild; the last one to finish wins, which
+    /// is harmless for a read-only ranking.
+    pub async fn get_or_refresh(
+        &self,
+        client: &HyperliquidClient,
+        window: MoverWindow,
+        now_ms: u64,
+    ) -> Result<(u64, Arc<Vec<Mover>>), HyperliquidError> {
+        if let Some(cached) = self.rankings.lock().unwrap().get(&window) {
+            if now_ms.saturating_sub(cached.computed_at_ms) < self.ttl_ms {
+                return Ok((cached.computed_at_ms, cached.movers.clone()));
+            }
+        }
+
+        let movers = Arc::new(compute(client, window, now_ms).await?);
+        self.rankings.lock().unwrap().insert(
+            window,
+            CachedRanking {
+                computed_at_ms: now_ms,
+                movers: movers.clone(),
+            },
+        );
+        Ok((now_ms, movers))
+    }
+}
+
+impl Default for MoversCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_MOVERS_TTL_MS)
+    }
+}
+
+/// Rank every perp over `window`.
+///
+/// The 24h window comes from one `metaAndAssetCtxs` call. Shorter windows
+/// fetch each perp's candles concurrently, bounded by the client's request
+/// limit; a coin whose candles can't be fetched is left out rather than
+/// failing the ranking.
+async fn compute(
+    client: &HyperliquidClient,
+    window: MoverWindow,
+    now_ms: u64,
+) -> Result<Vec<Mover>, HyperliquidError> {
+    if window == MoverWindow::OneDay {
+        let ranked = client
+            .asset_contexts()
+            .await?
+            .into_iter()
+            .filter_map(|ctx| {
+                movers::mover_from_day_context(
+                    &ctx.coin,
+                    ctx.prev_day_price,
+                    ctx.mark_price,
+                    ctx.day_base_volume,
+                    ctx.day_notional_volume,
+                )
+            });
+        return Ok(movers::rank(ranked.collect()));
+    }
+
+    let coins = client.all_mids().await?;
+    let start_ms = window.fetch_start(now_ms);
+
+    let mut fetches = JoinSet::new();
+    for coin in coins.into_keys().filter(|coin| is_perp_coin(coin)) {
+        let client = client.clone();
+        fetches.spawn(async move {
+            let candles = client
+                .candle_snapshot(&coin, window.candle_interval(), start_ms, now_ms)
+                .await;
+            (coin, candles)
+        });
+    }
+
+    let mut ranked = Vec::new();
+    while let Some(joined) = fetches.join_next().await {
+        let Ok((coin, candles)) = joined else {
+            continue;
+        };
+        match candles {
+            Ok(candles) => ranked.extend(movers::mover_from_candles(&coin, &candles)),
+            Err(e) => eprintln!("Skipping {coin} in movers: {e}"),
+        }
+    }
+    Ok(movers::rank(ranked))
+}
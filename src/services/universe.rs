@@ -0,0 +1,156 @@
+//! Perp universe discovery, for monitoring every listed perp instead of a
+//! fixed coin list.
+
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "server")]
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "server")]
+use tokio_util::sync::CancellationToken;
+
+use crate::services::hyperliquid::{HyperliquidClient, HyperliquidError, PerpMeta};
+#[cfg(feature = "server")]
+use crate::state::MonitoredCoins;
+
+/// Which discovered perps are worth monitoring. Delisted perps never are.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct UniverseFilter {
+    /// Skip perps that only trade with isolated margin.
+    pub exclude_isolated_only: bool,
+    /// Min 24h notional volume (USD); perps without a volume are skipped.
+    pub min_day_volume: Option<f64>,
+}
+
+impl Default for UniverseFilter {
+    fn default() -> Self {
+        Self {
+            exclude_isolated_only: true,
+            min_day_volume: None,
+        }
+    }
+}
+
+/// Names of the perps in `meta` that pass `filter`, in universe order.
+/// `day_volumes` maps coin to 24h notional volume and is only consulted when
+/// the filter has a minimum.
+pub fn select(
+    meta: &[PerpMeta],
+    day_volumes: &HashMap<String, f64>,
+    filter: &UniverseFilter,
+) -> Vec<String> {
+    meta.iter()
+        .filter(|perp| !perp.is_delisted)
+        .filter(|perp| !(filter.exclude_isolated_only && perp.only_isolated))
+        .filter(|perp| match filter.min_day_volume {
+            Some(min) => day_volumes.get(&perp.name).is_some_and(|&v| v >= min),
+            None => true,
+        })
+        .map(|perp| perp.name.clone())
+        .collect()
+}
+
+/// Fetch the perp universe and keep what passes `filter`. Volumes are only
+/// fetched when the filter needs them.
+pub async fn discover(
+    client: &HyperliquidClient,
+    filter: &UniverseFilter,
+) -> Result<Vec<String>, HyperliquidError> {
+    let meta = client.fetch_meta().await?;
+    let day_volumes = match filter.min_day_volume {
+        Some(_) => client
+            .asset_contexts()
+            .await?
+            .into_iter()
+            .map(|ctx| (ctx.coin, ctx.day_notional_volume))
+            .collect(),
+        None => HashMap::new(),
+    };
+    Ok(select(&meta, &day_volumes, filter))
+}
+
+/// Coins to start and stop monitoring after a universe refresh.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UniverseChange {
+    /// Newly listed, in discovery order.
+    pub added: Vec<String>,
+    /// No longer listed (or filtered out), in monitored order.
+    pub removed: Vec<String>,
+}
+
+impl UniverseChange {
+    pub fn between(monitored: &[String], discovered: &[String]) -> Self {
+        let monitored_set: HashSet<&String> = monitored.iter().collect();
+        let discovered_set: HashSet<&String> = discovered.iter().collect();
+        Self {
+            added: discovered
+                .iter()
+                .filter(|c| !monitored_set.contains(c))
+                .cloned()
+                .collect(),
+            removed: monitored
+                .iter()
+                .filter(|c| !discovered_set.contains(c))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Re-discovers the perp universe for `coins = "all"`, swapping the result
+/// into the monitored coin list so new listings start being watched and
+/// delistings stop.
+#[cfg(feature = "server")]
+pub struct UniverseRefresher {
+    client: HyperliquidClient,
+    filter: UniverseFilter,
+    coins: MonitoredCoins,
+}
+
+#[cfg(feature = "server")]
+impl UniverseRefresher {
+    pub fn new(client: HyperliquidClient, filter: UniverseFilter, coins: MonitoredCoins) -> Self {
+        Self {
+            client,
+            filter,
+            coins,
+        }
+    }
+
+    /// Discover once and update the coin list if it changed. On failure the
+    /// list is left as it was.
+    pub async fn refresh(&self) -> Result<UniverseChange, HyperliquidError> {
+        let discovered = discover(&self.client, &self.filter).await?;
+        let change = UniverseChange::between(&self.coins.get(), &discovered);
+        if !change.is_empty() {
+            self.coins.set(discovered);
+        }
+        Ok(change)
+    }
+
+    /// Refresh every `every`, starting one period from now since the list
+    /// was just discovered, until `shutdown` is cancelled.
+    pub async fn run(self, every: Duration, shutdown: CancellationToken) {
+        let mut ticks = tokio::time::interval_at(tokio::time::Instant::now() + every, every);
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => return,
+                _ = ticks.tick() => match self.refresh().await {
+                    Ok(change) if !change.is_empty() => println!(
+                        "Perp universe changed: added [{}], removed [{}]",
+                        change.added.join(", "),
+                        change.removed.join(", ")
+                    ),
+                    Ok(_) => {}
+                    Err(e) => eprintln!("Failed to refresh perps: {e}"),
+                },
+            }
+        }
+    }
+}
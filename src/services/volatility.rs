@@ -0,0 +1,84 @@
+use std::sync::{Arc, RwLock};
+
+use tokio::task::JoinSet;
+
+use crate::business_logic::intervals;
+use crate::business_logic::volatility::{self, VolatilityEntry, DAY_MS};
+use crate::services::hyperliquid::HyperliquidClient;
+
+/// Longest ATR period a ranking may use. Windows are paged, so this only
+/// bounds how many candles are fetched per coin.
+pub const MAX_ATR_PERIOD: usize = 1_000;
+
+/// ATR% ranking of the monitored coins as of one monitor cycle.
+#[derive(Debug, Clone)]
+pub struct VolatilityRanking {
+    pub interval: String,
+    pub period: usize,
+    pub computed_at_ms: u64,
+    /// Highest ATR% first.
+    pub entries: Arc<Vec<VolatilityEntry>>,
+}
+
+/// The latest [`VolatilityRanking`], published by the monitor and read by
+/// the routes.
+#[derive(Debug, Default)]
+pub struct VolatilityRankings {
+    latest: RwLock<Option<VolatilityRanking>>,
+}
+
+impl VolatilityRankings {
+    pub fn publish(&self, ranking: VolatilityRanking) {
+        *self.latest.write().unwrap() = Some(ranking);
+    }
+
+    /// `None` until the monitor has completed a cycle.
+    pub fn latest(&self) -> Option<VolatilityRanking> {
+        self.latest.read().unwrap().clone()
+    }
+}
+
+/// Closed candles fetched per coin: enough to warm the ATR and look back a
+/// day for the 24h change.
+fn candles_needed(interval: &str, period: usize) -> usize {
+    let per_day = intervals::interval_ms(interval).map_or(0, |ms| (DAY_MS / ms) as usize);
+    period + per_day + 1
+}
+
+/// Fetch each coin's closed candles and rank them by ATR%. `interval` must
+/// be supported.
+///
+/// Fetches run concurrently, bounded by the client's request limit. A coin
+/// whose candles can't be fetched, or are too few for the ATR, is left out.
+pub async fn compute(
+    client: &HyperliquidClient,
+    coins: &[String],
+    interval: &str,
+    period: usize,
+    now_ms: u64,
+) -> Vec<VolatilityEntry> {
+    let limit = candles_needed(interval, period);
+    let mut fetches = JoinSet::new();
+    for coin in coins.iter().cloned() {
+        let client = client.clone();
+        let interval = interval.to_string();
+        fetches.spawn(async move {
+            let candles = client
+                .recent_closed_candles(&coin, &interval, limit, now_ms)
+                .await;
+            (coin, candles)
+        });
+    }
+
+    let mut entries = Vec::new();
+    while let Some(joined) = fetches.join_next().await {
+        let Ok((coin, candles)) = joined else {
+            continue;
+        };
+        match candles {
+            Ok(candles) => entries.extend(volatility::volatility_entry(&coin, &candles, period)),
+            Err(e) => eprintln!("Skipping {coin} in volatility ranking: {e}"),
+        }
+    }
+    volatility::rank(entries)
+}
@@ -0,0 +1,528 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tokio_util::sync::CancellationToken;
+
+use crate::business_logic::alerts::AlertSeverity;
+use crate::business_logic::quiet_hours::{QuietHours, QuietMode};
+use crate::clock::{Clock, SystemClock};
+use crate::services::delivery_queue::{DeliveryQueue, QueuedDelivery};
+
+/// Consecutive failed deliveries after which a subscription is disabled.
+pub const DEFAULT_MAX_CONSECUTIVE_FAILURES: u32 = 5;
+
+/// Header carrying `sha256=<hex HMAC>` of the request body.
+pub const SIGNATURE_HEADER: &str = "X-Signature";
+
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookSubscription {
+    pub id: u64,
+    pub url: String,
+    /// Only alerts for these coins are delivered; `None` means every coin.
+    pub coins: Option<Vec<String>>,
+    /// Alerts below this severity are skipped; `None` means every severity.
+    pub min_severity: Option<AlertSeverity>,
+    /// HMAC key used to sign delivered bodies.
+    pub secret: Option<String>,
+    /// Daily window during which lower-severity alerts are held back.
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
+    pub created_at_ms: u64,
+    pub consecutive_failures: u32,
+    pub disabled: bool,
+    pub last_error: Option<String>,
+    /// Alerts discarded because they fell in quiet hours.
+    #[serde(default)]
+    pub quiet_dropped: u64,
+    /// Alerts held until the end of quiet hours.
+    #[serde(default)]
+    pub quiet_deferred: u64,
+}
+
+impl WebhookSubscription {
+    /// Whether an alert for `coin` at `severity` should be sent to this subscription.
+    pub fn matches(&self, coin: &str, severity: AlertSeverity) -> bool {
+        if self.disabled {
+            return false;
+        }
+        if let Some(min) = self.min_severity {
+            if severity < min {
+                return false;
+            }
+        }
+        match &self.coins {
+            Some(coins) => coins.iter().any(|c| c == coin),
+            None => true,
+        }
+    }
+
+    /// If quiet hours hold back an alert at `severity` at `now_ms`, what to do
+    /// with it and when the window ends.
+    pub fn quiet_until(&self, severity: AlertSeverity, now_ms: u64) -> Option<(QuietMode, u64)> {
+        let quiet_hours = self.quiet_hours.as_ref()?;
+        let until_ms = quiet_hours.suppresses(severity, now_ms)?;
+        Some((quiet_hours.mode, until_ms))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewWebhook {
+    pub url: String,
+    pub coins: Option<Vec<String>>,
+    pub min_severity: Option<AlertSeverity>,
+    pub secret: Option<String>,
+    pub quiet_hours: Option<QuietHours>,
+}
+
+#[derive(Debug)]
+pub enum WebhookStoreError {
+    Io(io::Error),
+    Corrupt(serde_json::Error),
+}
+
+impl fmt::Display for WebhookStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WebhookStoreError::Io(e) => write!(f, "webhook store I/O error: {e}"),
+            WebhookStoreError::Corrupt(e) => write!(f, "webhook store file is invalid: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for WebhookStoreError {}
+
+impl From<io::Error> for WebhookStoreError {
+    fn from(e: io::Error) -> Self {
+        WebhookStoreError::Io(e)
+    }
+}
+
+/// Webhook subscriptions, optionally persisted to a JSON file.
+///
+/// Every mutation rewrites the whole file (via a temp file and rename), which
+/// is fine for the handful of subscriptions this is meant for. Ids come from a
+/// persisted counter, so a deleted subscription's id is never handed out again.
+#[derive(Debug)]
+pub struct WebhookStore {
+    path: Option<PathBuf>,
+    max_consecutive_failures: u32,
+    registry: Mutex<Registry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Registry {
+    next_id: u64,
+    subscriptions: Vec<WebhookSubscription>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self {
+            next_id: 1,
+            subscriptions: Vec::new(),
+        }
+    }
+}
+
+/// On-disk layouts: the current registry, or the bare array written before
+/// ids were counted.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredRegistry {
+    Current(Registry),
+    Legacy(Vec<WebhookSubscription>),
+}
+
+impl From<StoredRegistry> for Registry {
+    fn from(stored: StoredRegistry) -> Self {
+        match stored {
+            StoredRegistry::Current(registry) => registry,
+            StoredRegistry::Legacy(subscriptions) => Registry {
+                next_id: subscriptions.iter().map(|s| s.id).max().unwrap_or(0) + 1,
+                subscriptions,
+            },
+        }
+    }
+}
+
+impl WebhookStore {
+    /// Store that lives only as long as the process.
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            max_consecutive_failures: DEFAULT_MAX_CONSECUTIVE_FAILURES,
+            registry: Mutex::new(Registry::default()),
+        }
+    }
+
+    /// Store backed by `path`, loading existing subscriptions if the file exists.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, WebhookStoreError> {
+        let path = path.into();
+        let registry = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice::<StoredRegistry>(&bytes)
+                .map_err(WebhookStoreError::Corrupt)?
+                .into(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Registry::default(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            path: Some(path),
+            max_consecutive_failures: DEFAULT_MAX_CONSECUTIVE_FAILURES,
+            registry: Mutex::new(registry),
+        })
+    }
+
+    pub fn with_max_consecutive_failures(mut self, max: u32) -> Self {
+        self.max_consecutive_failures = max.max(1);
+        self
+    }
+
+    pub fn list(&self) -> Vec<WebhookSubscription> {
+        self.registry.lock().unwrap().subscriptions.clone()
+    }
+
+    pub fn get(&self, id: u64) -> Option<WebhookSubscription> {
+        self.registry
+            .lock()
+            .unwrap()
+            .subscriptions
+            .iter()
+            .find(|s| s.id == id)
+            .cloned()
+    }
+
+    pub fn create(
+        &self,
+        new: NewWebhook,
+        now_ms: u64,
+    ) -> Result<WebhookSubscription, WebhookStoreError> {
+        let mut registry = self.registry.lock().unwrap();
+        let id = registry.next_id;
+        registry.next_id += 1;
+        let subscription = WebhookSubscription {
+            id,
+            url: new.url,
+            coins: new.coins,
+            min_severity: new.min_severity,
+            secret: new.secret,
+            quiet_hours: new.quiet_hours,
+            created_at_ms: now_ms,
+            consecutive_failures: 0,
+            disabled: false,
+            last_error: None,
+            quiet_dropped: 0,
+            quiet_deferred: 0,
+        };
+        registry.subscriptions.push(subscription.clone());
+        self.persist(&registry)?;
+        Ok(subscription)
+    }
+
+    /// Remove a subscription. Returns `false` if no subscription had that id.
+    pub fn delete(&self, id: u64) -> Result<bool, WebhookStoreError> {
+        let mut registry = self.registry.lock().unwrap();
+        let before = registry.subscriptions.len();
+        registry.subscriptions.retain(|s| s.id != id);
+        if registry.subscriptions.len() == before {
+            return Ok(false);
+        }
+        self.persist(&registry)?;
+        Ok(true)
+    }
+
+    /// Active subscriptions whose filters accept an alert for `coin` at `severity`.
+    pub fn matching(&self, coin: &str, severity: AlertSeverity) -> Vec<WebhookSubscription> {
+        self.registry
+            .lock()
+            .unwrap()
+            .subscriptions
+            .iter()
+            .filter(|s| s.matches(coin, severity))
+            .cloned()
+            .collect()
+    }
+
+    /// Record a delivery outcome, disabling the subscription once it has failed
+    /// `max_consecutive_failures` times in a row. A success resets the count.
+    pub fn record_delivery(
+        &self,
+        id: u64,
+        outcome: Result<(), String>,
+    ) -> Result<(), WebhookStoreError> {
+        let mut registry = self.registry.lock().unwrap();
+        let Some(subscription) = registry.subscriptions.iter_mut().find(|s| s.id == id) else {
+            return Ok(());
+        };
+        match outcome {
+            Ok(()) => {
+                if subscription.consecutive_failures == 0 && subscription.last_error.is_none() {
+                    return Ok(());
+                }
+                subscription.consecutive_failures = 0;
+                subscription.last_error = None;
+            }
+            Err(error) => {
+                subscription.consecutive_failures += 1;
+                subscription.last_error = Some(error);
+                if subscription.consecutive_failures >= self.max_consecutive_failures {
+                    subscription.disabled = true;
+                }
+            }
+        }
+        self.persist(&registry)
+    }
+
+    /// Count an alert held back by quiet hours, as dropped or deferred.
+    pub fn record_quiet_suppression(
+        &self,
+        id: u64,
+        mode: QuietMode,
+    ) -> Result<(), WebhookStoreError> {
+        let mut registry = self.registry.lock().unwrap();
+        let Some(subscription) = registry.subscriptions.iter_mut().find(|s| s.id == id) else {
+            return Ok(());
+        };
+        match mode {
+            QuietMode::Drop => subscription.quiet_dropped += 1,
+            QuietMode::Defer => subscription.quiet_deferred += 1,
+        }
+        self.persist(&registry)
+    }
+
+    fn persist(&self, registry: &Registry) -> Result<(), WebhookStoreError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        write_atomically(
+            path,
+            &serde_json::to_vec_pretty(registry).map_err(WebhookStoreError::Corrupt)?,
+        )
+    }
+}
+
+pub(crate) fn write_atomically(path: &Path, bytes: &[u8]) -> Result<(), WebhookStoreError> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, bytes)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// `sha256=<hex>` HMAC-SHA256 of `body` keyed with `secret`.
+pub fn sign_payload(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    let digest = mac.finalize().into_bytes();
+    let hex: String = digest.iter().map(|byte| format!("{byte:02x}")).collect();
+    format!("sha256={hex}")
+}
+
+/// Delivers alert payloads to every matching subscription.
+///
+/// With a [`DeliveryQueue`] attached, failed deliveries are queued and
+/// retried by [`WebhookDispatcher::retry_due`] instead of being dropped.
+#[derive(Clone)]
+pub struct WebhookDispatcher {
+    store: Arc<WebhookStore>,
+    queue: Option<Arc<DeliveryQueue>>,
+    clock: Arc<dyn Clock>,
+    http: reqwest::Client,
+}
+
+impl WebhookDispatcher {
+    pub fn new(store: Arc<WebhookStore>) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(DELIVERY_TIMEOUT)
+            .build()
+            .expect("failed to build webhook HTTP client");
+        Self {
+            store,
+            queue: None,
+            clock: Arc::new(SystemClock),
+            http,
+        }
+    }
+
+    pub fn with_queue(mut self, queue: Arc<DeliveryQueue>) -> Self {
+        self.queue = Some(queue);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// POST `payload` to each subscription matching `coin` and `severity`.
+    ///
+    /// Returns the number of successful deliveries. Failures are recorded on the
+    /// subscription (and queued for retry when a queue is attached) rather than
+    /// returned, so one bad receiver never blocks others. Subscriptions in
+    /// quiet hours are skipped, with the alert dropped or deferred to the end
+    /// of the window per their settings.
+    pub async fn dispatch<T: Serialize>(
+        &self,
+        coin: &str,
+        severity: AlertSeverity,
+        payload: &T,
+    ) -> usize {
+        let body = match serde_json::to_string(payload) {
+            Ok(body) => body,
+            Err(e) => {
+                eprintln!("Failed to serialize webhook payload for {coin}: {e}");
+                return 0;
+            }
+        };
+
+        let now_ms = self.clock.now_ms();
+        let mut delivered = 0;
+        for subscription in self.store.matching(coin, severity) {
+            if let Some((mode, until_ms)) = subscription.quiet_until(severity, now_ms) {
+                self.hold(subscription.id, mode, &body, until_ms, now_ms);
+                continue;
+            }
+            let outcome = self.deliver(&subscription, body.as_bytes()).await;
+            match &outcome {
+                Ok(()) => delivered += 1,
+                Err(error) => self.enqueue(subscription.id, &body, error),
+            }
+            if let Err(e) = self.store.record_delivery(subscription.id, outcome) {
+                eprintln!(
+                    "Failed to record webhook delivery for {}: {e}",
+                    subscription.id
+                );
+            }
+        }
+        delivered
+    }
+
+    /// Drop or defer an alert that landed in quiet hours. Deferring needs a
+    /// queue; without one the alert is dropped.
+    fn hold(&self, subscription_id: u64, mode: QuietMode, body: &str, until_ms: u64, now_ms: u64) {
+        let mode = match (mode, &self.queue) {
+            (QuietMode::Defer, Some(queue)) => {
+                match queue.defer(subscription_id, body.to_string(), until_ms, now_ms) {
+                    Ok(_) => QuietMode::Defer,
+                    Err(e) => {
+                        eprintln!("Failed to defer webhook delivery for {subscription_id}: {e}");
+                        QuietMode::Drop
+                    }
+                }
+            }
+            _ => QuietMode::Drop,
+        };
+        if let Err(e) = self.store.record_quiet_suppression(subscription_id, mode) {
+            eprintln!("Failed to record quiet-hours suppression for {subscription_id}: {e}");
+        }
+    }
+
+    fn enqueue(&self, subscription_id: u64, body: &str, error: &str) {
+        let Some(queue) = &self.queue else {
+            return;
+        };
+        let now_ms = self.clock.now_ms();
+        if let Err(e) = queue.enqueue(subscription_id, body.to_string(), error.to_string(), now_ms)
+        {
+            eprintln!("Failed to queue webhook retry for {subscription_id}: {e}");
+        }
+    }
+
+    /// Retry every queued delivery that is due. Returns how many succeeded.
+    ///
+    /// Failed retries don't add to the subscription's consecutive failures
+    /// (the original attempt already did, or the first attempt of a delivery
+    /// deferred by quiet hours does), so a receiver that is down for a
+    /// deploy isn't disabled by its own backlog; a successful retry resets them.
+    /// Deliveries for deleted or disabled subscriptions are given up on.
+    pub async fn retry_due(&self) -> usize {
+        let Some(queue) = &self.queue else {
+            return 0;
+        };
+        let mut delivered = 0;
+        for queued in queue.due(self.clock.now_ms()) {
+            if let Err(e) = self.retry(queue, &queued, &mut delivered).await {
+                eprintln!("Failed to record webhook retry {}: {e}", queued.id);
+            }
+        }
+        delivered
+    }
+
+    async fn retry(
+        &self,
+        queue: &DeliveryQueue,
+        queued: &QueuedDelivery,
+        delivered: &mut usize,
+    ) -> Result<(), WebhookStoreError> {
+        let subscription = match self.store.get(queued.subscription_id) {
+            Some(subscription) if !subscription.disabled => subscription,
+            Some(_) => {
+                return queue.give_up(
+                    queued.id,
+                    "subscription disabled".to_string(),
+                    self.clock.now_ms(),
+                )
+            }
+            None => {
+                return queue.give_up(
+                    queued.id,
+                    "subscription deleted".to_string(),
+                    self.clock.now_ms(),
+                )
+            }
+        };
+
+        let outcome = self.deliver(&subscription, queued.body.as_bytes()).await;
+        if outcome.is_ok() {
+            *delivered += 1;
+            self.store.record_delivery(subscription.id, Ok(()))?;
+        } else if queued.attempts == 0 {
+            // First attempt of a deferred delivery counts like any other.
+            self.store
+                .record_delivery(subscription.id, outcome.clone())?;
+        }
+        queue.record_retry(queued.id, outcome, self.clock.now_ms())
+    }
+
+    /// Call [`WebhookDispatcher::retry_due`] every `every` until `shutdown`
+    /// is cancelled. A retry pass already under way is finished first.
+    pub async fn run_retry_worker(self, every: Duration, shutdown: CancellationToken) {
+        let mut ticks = tokio::time::interval(every);
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => return,
+                _ = ticks.tick() => {
+                    self.retry_due().await;
+                }
+            }
+        }
+    }
+
+    async fn deliver(&self, subscription: &WebhookSubscription, body: &[u8]) -> Result<(), String> {
+        let mut request = self
+            .http
+            .post(&subscription.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_vec());
+        if let Some(secret) = &subscription.secret {
+            request = request.header(SIGNATURE_HEADER, sign_payload(secret, body));
+        }
+        let response = request.send().await.map_err(|e| e.to_string())?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(format!("receiver returned {}", response.status()))
+        }
+    }
+}
@@ -0,0 +1,472 @@
+//! Startup configuration loaded from a TOML file.
+//!
+//! Every section and key is optional; anything left out keeps the built-in
+//! default. Unknown keys are rejected so a typo fails at startup instead of
+//! being silently ignored.
+//!
+//! Any key can also be set from the environment as
+//! `PERPSCREENER__<SECTION>__<KEY>`, e.g. `PERPSCREENER__MONITOR__COINS=BTC,ETH`.
+//! These win over the file. Lists are comma-separated.
+
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use toml::{Table, Value};
+use utoipa::openapi::schema::{ArrayBuilder, Object, ObjectBuilder, OneOfBuilder, Schema, Type};
+use utoipa::openapi::RefOr;
+use utoipa::ToSchema;
+
+use crate::business_logic::anomalies::AnomalyConfig;
+use crate::business_logic::ascending_triangle::AscendingTriangleConfig;
+use crate::business_logic::cup_and_handle::CupAndHandleConfig;
+use crate::business_logic::descending_triangle::DescendingTriangleConfig;
+use crate::business_logic::double_bottom::DoubleBottomConfig;
+use crate::business_logic::double_top::DoubleTopConfig;
+use crate::business_logic::funding::FundingScreenerConfig;
+use crate::business_logic::gaps::GapConfig;
+use crate::business_logic::head_and_shoulders::HeadAndShouldersConfig;
+use crate::business_logic::intervals;
+use crate::business_logic::open_interest::OiScreenerConfig;
+use crate::business_logic::range_breakout::RangeBreakoutConfig;
+use crate::business_logic::trendline::TrendlineConfig;
+use crate::business_logic::triple_bottom::TripleBottomConfig;
+use crate::business_logic::triple_top::TripleTopConfig;
+use crate::business_logic::volume::VolumeSpikeConfig;
+use crate::naming::ApiNaming;
+use crate::services::universe::UniverseFilter;
+use crate::services::volatility::MAX_ATR_PERIOD;
+
+/// Read when neither `--config` nor [`CONFIG_ENV`] names a file.
+pub const DEFAULT_CONFIG_PATH: &str = "config.toml";
+/// Environment variable naming the config file.
+pub const CONFIG_ENV: &str = "PERPSCREENER_CONFIG";
+/// Prefix of per-key override variables; path segments are joined by `__`.
+pub const ENV_PREFIX: &str = "PERPSCREENER__";
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    pub server: ServerSettings,
+    pub monitor: MonitorSettings,
+    pub double_bottom: DoubleBottomConfig,
+    pub double_top: DoubleTopConfig,
+    pub volume_spike: VolumeSpikeConfig,
+    pub anomalies: AnomalyConfig,
+    pub volatility: VolatilitySettings,
+    pub funding: FundingScreenerConfig,
+    pub open_interest: OiScreenerConfig,
+    pub head_and_shoulders: HeadAndShouldersConfig,
+    pub triple_top: TripleTopConfig,
+    pub triple_bottom: TripleBottomConfig,
+    pub ascending_triangle: AscendingTriangleConfig,
+    pub descending_triangle: DescendingTriangleConfig,
+    pub cup_and_handle: CupAndHandleConfig,
+    pub range_breakout: RangeBreakoutConfig,
+    pub trendline: TrendlineConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerSettings {
+    /// Socket address the HTTP server listens on.
+    pub bind: String,
+    pub api_naming: ApiNaming,
+    #[schema(value_type = String)]
+    pub webhook_store_path: PathBuf,
+    #[schema(value_type = String)]
+    pub webhook_queue_path: PathBuf,
+    /// Seconds between retries of failed webhook deliveries.
+    pub webhook_retry_secs: u64,
+}
+
+impl Default for ServerSettings {
+    fn default() -> Self {
+        Self {
+            bind: "0.0.0.0:3000".to_string(),
+            api_naming: ApiNaming::default(),
+            webhook_store_path: PathBuf::from("data/webhooks.json"),
+            webhook_queue_path: PathBuf::from("data/webhook_queue.json"),
+            webhook_retry_secs: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct MonitorSettings {
+    /// Coins the pattern detectors watch.
+    #[schema(schema_with = coin_selection_schema)]
+    pub coins: CoinSelection,
+    /// Candle interval the detectors run on.
+    pub detection_interval: String,
+    /// Seconds between market data polls.
+    pub poll_interval_secs: u64,
+    /// A coin's data is stale once its last processed candle is this many
+    /// detection intervals old.
+    pub stale_after_intervals: u32,
+    /// Which perps `coins = "all"` picks up.
+    pub discovery: UniverseFilter,
+    /// Seconds between re-fetches of the perp universe when `coins = "all"`.
+    pub discovery_refresh_secs: u64,
+    /// What to do when candles go missing from a coin's feed.
+    pub gaps: GapConfig,
+}
+
+impl Default for MonitorSettings {
+    fn default() -> Self {
+        Self {
+            coins: CoinSelection::Listed(["BTC", "ETH", "SOL"].map(String::from).to_vec()),
+            detection_interval: "1m".to_string(),
+            poll_interval_secs: 60,
+            stale_after_intervals: 5,
+            discovery: UniverseFilter::default(),
+            discovery_refresh_secs: 3600,
+            gaps: GapConfig::default(),
+        }
+    }
+}
+
+/// The ATR% ranking the monitor publishes for `/volatility-ranking`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct VolatilitySettings {
+    /// Candle interval the ATR is computed on.
+    pub interval: String,
+    /// ATR period, in candles.
+    pub period: usize,
+}
+
+impl Default for VolatilitySettings {
+    fn default() -> Self {
+        Self {
+            interval: "1h".to_string(),
+            period: 14,
+        }
+    }
+}
+
+/// A fixed coin list, or `"all"` for every listed perp, discovered from
+/// Hyperliquid at startup and re-discovered every `discovery_refresh_secs`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CoinSelection {
+    All,
+    Listed(Vec<String>),
+}
+
+impl CoinSelection {
+    /// A list whose only entry is `all` (any case) selects every perp, so
+    /// comma-separated sources can say `all` too.
+    pub fn from_list(coins: Vec<String>) -> Self {
+        match coins.as_slice() {
+            [only] if only.eq_ignore_ascii_case("all") => CoinSelection::All,
+            _ => CoinSelection::Listed(coins),
+        }
+    }
+}
+
+impl Serialize for CoinSelection {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            CoinSelection::All => serializer.serialize_str("all"),
+            CoinSelection::Listed(coins) => coins.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for CoinSelection {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Word(String),
+            List(Vec<String>),
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::Word(word) if word.eq_ignore_ascii_case("all") => Ok(CoinSelection::All),
+            Raw::Word(word) => Err(serde::de::Error::custom(format!(
+                "expected \"all\" or a list of coins, got {word:?}"
+            ))),
+            Raw::List(coins) => Ok(CoinSelection::from_list(coins)),
+        }
+    }
+}
+
+fn coin_selection_schema() -> impl Into<RefOr<Schema>> {
+    OneOfBuilder::new()
+        .item(ArrayBuilder::new().items(Object::with_type(Type::String)))
+        .item(
+            ObjectBuilder::new()
+                .schema_type(Type::String)
+                .enum_values(Some(["all"])),
+        )
+        .description(Some("Coin names, or \"all\" for every listed perp"))
+}
+
+#[derive(Debug)]
+pub enum SettingsError {
+    Io(PathBuf, io::Error),
+    Invalid(PathBuf, toml::de::Error),
+    Env { var: String, message: String },
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingsError::Io(path, e) => write!(f, "cannot read {}: {e}", path.display()),
+            SettingsError::Invalid(path, e) => write!(f, "invalid {}: {e}", path.display()),
+            SettingsError::Env { var, message } => write!(f, "invalid {var}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for SettingsError {}
+
+impl Settings {
+    /// Parse a config file's contents; `path` is only used in errors.
+    pub fn from_toml(text: &str, path: &Path) -> Result<Self, SettingsError> {
+        Self::from_sources(text, path, std::iter::empty())
+    }
+
+    /// Parse a config file's contents with `vars` layered on top. Variables
+    /// without [`ENV_PREFIX`] are ignored.
+    pub fn from_sources(
+        text: &str,
+        path: &Path,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self, SettingsError> {
+        let invalid = |e| SettingsError::Invalid(path.to_path_buf(), e);
+        let mut table: Table = toml::from_str(text).map_err(invalid)?;
+        let defaults = Table::try_from(Settings::default()).expect("defaults serialize");
+        let mut vars: Vec<_> = vars
+            .into_iter()
+            .filter(|(var, _)| var.starts_with(ENV_PREFIX))
+            .collect();
+        vars.sort();
+        for (var, raw) in vars {
+            apply_env(&mut table, &defaults, &var, &raw)
+                .map_err(|message| SettingsError::Env { var, message })?;
+        }
+        Settings::deserialize(table).map_err(invalid)
+    }
+
+    /// Load `explicit` if given, which must exist; otherwise
+    /// [`DEFAULT_CONFIG_PATH`] if it exists, else the defaults. `vars` are
+    /// applied on top either way.
+    pub fn load(
+        explicit: Option<&Path>,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self, SettingsError> {
+        let path = explicit.unwrap_or(Path::new(DEFAULT_CONFIG_PATH));
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound && explicit.is_none() => String::new(),
+            Err(e) => return Err(SettingsError::Io(path.to_path_buf(), e)),
+        };
+        Self::from_sources(&text, path, vars)
+    }
+
+    /// Problems that would make the settings unusable, one message each.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let monitor = &self.monitor;
+        if let CoinSelection::Listed(coins) = &monitor.coins {
+            if coins.is_empty() {
+                errors.push("monitor.coins: at least one coin is required".to_string());
+            }
+            if coins.iter().any(|c| c.trim().is_empty()) {
+                errors.push("monitor.coins: coin names must not be empty".to_string());
+            }
+            for (i, coin) in coins.iter().enumerate() {
+                if coins[..i].contains(coin) && !coins[i + 1..].contains(coin) {
+                    errors.push(format!("monitor.coins: `{coin}` is listed more than once"));
+                }
+            }
+        }
+        if monitor.discovery.min_day_volume.is_some_and(|v| v < 0.0) {
+            errors.push("monitor.discovery.min_day_volume must not be negative".to_string());
+        }
+        if monitor.poll_interval_secs == 0 {
+            errors.push("monitor.poll_interval_secs must be positive".to_string());
+        }
+        if monitor.stale_after_intervals == 0 {
+            errors.push("monitor.stale_after_intervals must be positive".to_string());
+        }
+        if monitor.discovery_refresh_secs == 0 {
+            errors.push("monitor.discovery_refresh_secs must be positive".to_string());
+        }
+        if monitor.gaps.clean_candles_to_clear == 0 {
+            errors.push("monitor.gaps.clean_candles_to_clear must be positive".to_string());
+        }
+        if !intervals::is_supported(&monitor.detection_interval) {
+            errors.push(format!(
+                "monitor.detection_interval: unsupported interval `{}`",
+                monitor.detection_interval
+            ));
+        }
+        if !intervals::is_supported(&self.volatility.interval) {
+            errors.push(format!(
+                "volatility.interval: unsupported interval `{}`",
+                self.volatility.interval
+            ));
+        }
+        if !(1..=MAX_ATR_PERIOD).contains(&self.volatility.period) {
+            errors.push(format!(
+                "volatility.period must be between 1 and {MAX_ATR_PERIOD}"
+            ));
+        }
+        errors.extend(
+            self.double_bottom
+                .validate()
+                .into_iter()
+                .map(|e| format!("double_bottom.{e}")),
+        );
+        errors.extend(
+            self.double_top
+                .validate()
+                .into_iter()
+                .map(|e| format!("double_top.{e}")),
+        );
+        errors.extend(
+            self.volume_spike
+                .validate()
+                .into_iter()
+                .map(|e| format!("volume_spike.{e}")),
+        );
+        errors.extend(
+            self.anomalies
+                .validate()
+                .into_iter()
+                .map(|e| format!("anomalies.{e}")),
+        );
+        errors.extend(
+            self.funding
+                .validate()
+                .into_iter()
+                .map(|e| format!("funding.{e}")),
+        );
+        errors.extend(
+            self.open_interest
+                .validate()
+                .into_iter()
+                .map(|e| format!("open_interest.{e}")),
+        );
+        errors.extend(
+            self.head_and_shoulders
+                .validate()
+                .into_iter()
+                .map(|e| format!("head_and_shoulders.{e}")),
+        );
+        errors.extend(
+            self.triple_top
+                .validate()
+                .into_iter()
+                .map(|e| format!("triple_top.{e}")),
+        );
+        errors.extend(
+            self.triple_bottom
+                .validate()
+                .into_iter()
+                .map(|e| format!("triple_bottom.{e}")),
+        );
+        errors.extend(
+            self.ascending_triangle
+                .validate()
+                .into_iter()
+                .map(|e| format!("ascending_triangle.{e}")),
+        );
+        errors.extend(
+            self.descending_triangle
+                .validate()
+                .into_iter()
+                .map(|e| format!("descending_triangle.{e}")),
+        );
+        errors.extend(
+            self.cup_and_handle
+                .validate()
+                .into_iter()
+                .map(|e| format!("cup_and_handle.{e}")),
+        );
+        errors.extend(
+            self.range_breakout
+                .validate()
+                .into_iter()
+                .map(|e| format!("range_breakout.{e}")),
+        );
+        errors.extend(
+            self.trendline
+                .validate()
+                .into_iter()
+                .map(|e| format!("trendline.{e}")),
+        );
+        errors
+    }
+
+    /// The resolved settings as TOML, for the startup log. Nothing here is
+    /// secret; webhook secrets live in the webhook store.
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("settings serialize")
+    }
+}
+
+/// Set the key named by `var` in `table`. The value is parsed as the type of
+/// the default at the same key, or inferred when there is no default.
+fn apply_env(table: &mut Table, defaults: &Table, var: &str, raw: &str) -> Result<(), String> {
+    let keys: Vec<String> = var[ENV_PREFIX.len()..]
+        .split("__")
+        .map(str::to_lowercase)
+        .collect();
+    if keys.iter().any(String::is_empty) {
+        return Err("empty key segment".to_string());
+    }
+    let (last, sections) = keys.split_last().expect("split yields one segment");
+
+    let mut like = Some(defaults);
+    let mut target = table;
+    for key in sections {
+        like = like.and_then(|t| t.get(key)).and_then(Value::as_table);
+        let entry = target
+            .entry(key.as_str())
+            .or_insert_with(|| Value::Table(Table::new()));
+        target = entry
+            .as_table_mut()
+            .ok_or_else(|| format!("`{key}` is not a section"))?;
+    }
+    let value = parse_value(raw, like.and_then(|t| t.get(last)))?;
+    target.insert(last.clone(), value);
+    Ok(())
+}
+
+fn parse_value(raw: &str, like: Option<&Value>) -> Result<Value, String> {
+    let raw = raw.trim();
+    match like {
+        Some(Value::Array(items)) => raw
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| parse_value(item, items.first()))
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        Some(Value::Integer(_)) => raw
+            .parse()
+            .map(Value::Integer)
+            .map_err(|_| format!("expected an integer, got `{raw}`")),
+        Some(Value::Float(_)) => raw
+            .parse()
+            .map(Value::Float)
+            .map_err(|_| format!("expected a number, got `{raw}`")),
+        Some(Value::Boolean(_)) => raw
+            .parse()
+            .map(Value::Boolean)
+            .map_err(|_| format!("expected true or false, got `{raw}`")),
+        Some(Value::Table(_)) => Err("names a section, not a key".to_string()),
+        Some(_) => Ok(Value::String(raw.to_string())),
+        None => Ok(raw
+            .parse()
+            .map(Value::Integer)
+            .or_else(|_| raw.parse().map(Value::Float))
+            .or_else(|_| raw.parse().map(Value::Boolean))
+            .unwrap_or_else(|_| Value::String(raw.to_string()))),
+    }
+}
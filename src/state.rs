@@ -0,0 +1,581 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
+
+use tokio::sync::{broadcast, Notify};
+
+use crate::business_logic::alerts::PatternAlert;
+use crate::business_logic::anomalies::CandleAnomaly;
+use crate::business_logic::funding::{FundingHistory, FundingSample};
+use crate::business_logic::intervals;
+use crate::business_logic::open_interest::{OiHistory, OiSample};
+use crate::business_logic::patterns::PatternStatus;
+use crate::business_logic::premium::{PremiumHistory, PremiumSample};
+use crate::business_logic::volume::VolumeSpike;
+use crate::clock::{Clock, SkewEstimator, SystemClock};
+use crate::naming::ApiNaming;
+use crate::services::delivery_queue::DeliveryQueue;
+use crate::services::hyperliquid::HyperliquidClient;
+use crate::services::movers::MoversCache;
+use crate::services::volatility::VolatilityRankings;
+use crate::services::webhooks::{WebhookDispatcher, WebhookStore};
+use crate::settings::Settings;
+
+/// Shared state handed to every route handler.
+#[derive(Clone)]
+pub struct AppState {
+    pub clock: Arc<dyn Clock>,
+    /// JSON field naming used on the wire.
+    pub naming: ApiNaming,
+    pub freshness: DataFreshness,
+    pub health: HealthConfig,
+    pub webhooks: Arc<WebhookStore>,
+    /// Failed webhook deliveries awaiting retry.
+    pub webhook_queue: Arc<DeliveryQueue>,
+    /// Estimated offset between upstream and local time, fed by the data client.
+    pub clock_skew: Arc<SkewEstimator>,
+    pub hyperliquid: HyperliquidClient,
+    pub movers: Arc<MoversCache>,
+    pub volume_spikes: RecentVolumeSpikes,
+    pub anomalies: RecentAnomalies,
+    /// Pattern detector progress and alerts, published by the monitor.
+    pub patterns: PatternStore,
+    pub premiums: PremiumTracker,
+    pub funding: FundingTracker,
+    pub open_interest: OiTracker,
+    /// ATR% ranking published by the monitor each cycle.
+    pub volatility: Arc<VolatilityRankings>,
+    /// Coins the monitor watches, as resolved at startup.
+    pub coins: MonitoredCoins,
+    /// Settings the server was started with, as served by `/config`.
+    pub settings: Arc<Settings>,
+}
+
+impl AppState {
+    /// State with an in-memory webhook store and retry queue; use
+    /// [`AppState::with_webhooks`] and [`AppState::with_webhook_queue`] to
+    /// persist them.
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            clock,
+            naming: ApiNaming::default(),
+            freshness: DataFreshness::default(),
+            health: HealthConfig::default(),
+            webhooks: Arc::new(WebhookStore::in_memory()),
+            webhook_queue: Arc::new(DeliveryQueue::in_memory()),
+            clock_skew: Arc::new(SkewEstimator::new(DEFAULT_MAX_CLOCK_SKEW_MS)),
+            hyperliquid: HyperliquidClient::new(),
+            movers: Arc::new(MoversCache::default()),
+            volume_spikes: RecentVolumeSpikes::default(),
+            anomalies: RecentAnomalies::default(),
+            patterns: PatternStore::default(),
+            premiums: PremiumTracker::default(),
+            funding: FundingTracker::default(),
+            open_interest: OiTracker::default(),
+            volatility: Arc::default(),
+            coins: MonitoredCoins::default(),
+            settings: Arc::new(Settings::default()),
+        }
+    }
+
+    pub fn with_naming(mut self, naming: ApiNaming) -> Self {
+        self.naming = naming;
+        self
+    }
+
+    /// Share `skew` with whatever feeds it and with a skew-corrected clock.
+    pub fn with_clock_skew(mut self, skew: Arc<SkewEstimator>) -> Self {
+        self.clock_skew = skew;
+        self
+    }
+
+    /// Fetch market data through `client` instead of the public mainnet API.
+    pub fn with_hyperliquid(mut self, client: HyperliquidClient) -> Self {
+        self.hyperliquid = client;
+        self
+    }
+
+    /// Also sets the `/health` staleness threshold from the monitor settings.
+    pub fn with_settings(mut self, settings: Settings) -> Self {
+        if let Some(ms) = intervals::interval_ms(&settings.monitor.detection_interval) {
+            self.health.detection_interval_ms = ms;
+        }
+        self.health.stale_after_intervals = settings.monitor.stale_after_intervals;
+        self.settings = Arc::new(settings);
+        self
+    }
+
+    pub fn with_monitored_coins(self, coins: Vec<String>) -> Self {
+        self.coins.set(coins);
+        self
+    }
+
+    pub fn with_webhooks(mut self, webhooks: Arc<WebhookStore>) -> Self {
+        self.webhooks = webhooks;
+        self
+    }
+
+    pub fn with_webhook_queue(mut self, queue: Arc<DeliveryQueue>) -> Self {
+        self.webhook_queue = queue;
+        self
+    }
+
+    /// Dispatcher delivering to the subscriptions in [`AppState::webhooks`],
+    /// with failures queued on [`AppState::webhook_queue`].
+    pub fn webhook_dispatcher(&self) -> WebhookDispatcher {
+        WebhookDispatcher::new(self.webhooks.clone())
+            .with_queue(self.webhook_queue.clone())
+            .with_clock(self.clock.clone())
+    }
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new(Arc::new(SystemClock))
+    }
+}
+
+/// Clock skew beyond which `/health` flags a warning.
+pub const DEFAULT_MAX_CLOCK_SKEW_MS: u64 = 2_000;
+
+/// Thresholds used by `/health` to judge data freshness.
+#[derive(Debug, Clone, Copy)]
+pub struct HealthConfig {
+    /// Candle interval the monitor runs detection on.
+    pub detection_interval_ms: u64,
+    /// A coin is stale once its last processed candle is older than this many intervals.
+    pub stale_after_intervals: u32,
+}
+
+impl HealthConfig {
+    pub fn max_age_ms(&self) -> u64 {
+        self.detection_interval_ms * u64::from(self.stale_after_intervals)
+    }
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            detection_interval_ms: 60_000,
+            stale_after_intervals: 5,
+        }
+    }
+}
+
+/// Close time (epoch ms) of the most recent successfully processed candle per
+/// coin, which coins' feeds are flagged for missing candles, and how many gaps
+/// each feed has had.
+///
+/// The monitor records into this after each processed candle; readers take a
+/// snapshot so the lock is never held across an await.
+#[derive(Debug, Clone, Default)]
+pub struct DataFreshness {
+    last_processed_ms: Arc<RwLock<HashMap<String, u64>>>,
+    data_gaps: Arc<RwLock<BTreeSet<String>>>,
+    gap_counts: Arc<RwLock<HashMap<String, u64>>>,
+}
+
+impl DataFreshness {
+    pub fn record(&self, coin: &str, candle_close_ms: u64) {
+        let mut last = self.last_processed_ms.write().unwrap();
+        let entry = last.entry(coin.to_string()).or_insert(candle_close_ms);
+        *entry = (*entry).max(candle_close_ms);
+    }
+
+    pub fn remove(&self, coin: &str) {
+        self.last_processed_ms.write().unwrap().remove(coin);
+        self.data_gaps.write().unwrap().remove(coin);
+        self.gap_counts.write().unwrap().remove(coin);
+    }
+
+    /// Flag or clear `coin`'s feed as having unrecovered missing candles.
+    pub fn set_data_gap(&self, coin: &str, flagged: bool) {
+        let mut gaps = self.data_gaps.write().unwrap();
+        if flagged {
+            gaps.insert(coin.to_string());
+        } else {
+            gaps.remove(coin);
+        }
+    }
+
+    pub fn has_data_gap(&self, coin: &str) -> bool {
+        self.data_gaps.read().unwrap().contains(coin)
+    }
+
+    /// Set how many gaps `coin`'s feed has had since the monitor picked it up.
+    pub fn set_gap_count(&self, coin: &str, count: u64) {
+        self.gap_counts
+            .write()
+            .unwrap()
+            .insert(coin.to_string(), count);
+    }
+
+    pub fn gap_count(&self, coin: &str) -> u64 {
+        self.gap_counts
+            .read()
+            .unwrap()
+            .get(coin)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<String, u64> {
+        self.last_processed_ms
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(coin, ms)| (coin.clone(), *ms))
+            .collect()
+    }
+}
+
+/// Events kept per coin by [`RecentCandleEvents`].
+pub const RECENT_EVENTS_PER_COIN: usize = 20;
+
+/// Something flagged on one coin's candle.
+pub trait CandleEvent: Clone {
+    fn coin(&self) -> &str;
+    /// Open time of the candle the event was raised on (epoch ms).
+    fn open_time(&self) -> u64;
+}
+
+impl CandleEvent for VolumeSpike {
+    fn coin(&self) -> &str {
+        &self.coin
+    }
+
+    fn open_time(&self) -> u64 {
+        self.open_time
+    }
+}
+
+impl CandleEvent for PatternAlert {
+    fn coin(&self) -> &str {
+        &self.coin
+    }
+
+    fn open_time(&self) -> u64 {
+        self.open_time
+    }
+}
+
+impl CandleEvent for CandleAnomaly {
+    fn coin(&self) -> &str {
+        &self.coin
+    }
+
+    fn open_time(&self) -> u64 {
+        self.open_time
+    }
+}
+
+/// Most recent candle events per coin, oldest dropped first.
+///
+/// Filled by whatever runs the per-coin detectors; read by the screener routes.
+#[derive(Debug, Clone)]
+pub struct RecentCandleEvents<T> {
+    events: Arc<RwLock<HashMap<String, VecDeque<T>>>>,
+}
+
+pub type RecentVolumeSpikes = RecentCandleEvents<VolumeSpike>;
+pub type RecentAnomalies = RecentCandleEvents<CandleAnomaly>;
+pub type RecentPatternAlerts = RecentCandleEvents<PatternAlert>;
+
+impl<T> Default for RecentCandleEvents<T> {
+    fn default() -> Self {
+        Self {
+            events: Arc::default(),
+        }
+    }
+}
+
+impl<T: CandleEvent> RecentCandleEvents<T> {
+    pub fn record(&self, event: T) {
+        let mut events = self.events.write().unwrap();
+        let coin_events = events.entry(event.coin().to_string()).or_default();
+        coin_events.push_back(event);
+        if coin_events.len() > RECENT_EVENTS_PER_COIN {
+            coin_events.pop_front();
+        }
+    }
+
+    /// Whether `coin` had an event on a candle opening at or after `since_ms`.
+    pub fn has_event_since(&self, coin: &str, since_ms: u64) -> bool {
+        self.events
+            .read()
+            .unwrap()
+            .get(coin)
+            .is_some_and(|events| events.iter().any(|e| e.open_time() >= since_ms))
+    }
+
+    /// The event raised on `coin`'s candle opening at `open_time`, if any.
+    pub fn at(&self, coin: &str, open_time: u64) -> Option<T> {
+        self.events
+            .read()
+            .unwrap()
+            .get(coin)?
+            .iter()
+            .find(|e| e.open_time() == open_time)
+            .cloned()
+    }
+
+    /// Every kept event, newest candle first.
+    pub fn snapshot(&self) -> Vec<T> {
+        let mut all: Vec<T> = self
+            .events
+            .read()
+            .unwrap()
+            .values()
+            .flatten()
+            .cloned()
+            .collect();
+        all.sort_by(|a, b| {
+            b.open_time()
+                .cmp(&a.open_time())
+                .then_with(|| a.coin().cmp(b.coin()))
+        });
+        all
+    }
+}
+
+/// Events buffered per pattern stream subscriber before it starts missing
+/// them.
+pub const PATTERN_EVENT_CAPACITY: usize = 256;
+
+/// A change in the pattern state, as sent to stream subscribers.
+#[derive(Debug, Clone)]
+pub enum PatternEvent {
+    /// Boxed, as statuses carry far more than alerts.
+    Status(Box<PatternStatus>),
+    Alert(PatternAlert),
+}
+
+/// Latest status of every detector on every monitored coin, plus their
+/// recent alerts. Changes are also broadcast for the pattern streams.
+#[derive(Debug, Clone)]
+pub struct PatternStore {
+    /// Keyed by coin, then detector name.
+    statuses: Arc<RwLock<BTreeMap<String, BTreeMap<&'static str, PatternStatus>>>>,
+    alerts: RecentPatternAlerts,
+    events: broadcast::Sender<PatternEvent>,
+}
+
+impl Default for PatternStore {
+    fn default() -> Self {
+        Self {
+            statuses: Arc::default(),
+            alerts: RecentPatternAlerts::default(),
+            events: broadcast::channel(PATTERN_EVENT_CAPACITY).0,
+        }
+    }
+}
+
+impl PatternStore {
+    /// Store `status`, broadcasting it if it differs from the last one for
+    /// its coin and detector.
+    pub fn publish(&self, status: PatternStatus) {
+        let mut statuses = self.statuses.write().unwrap();
+        let coin = statuses.entry(status.coin().to_string()).or_default();
+        if coin.get(status.name()) == Some(&status) {
+            return;
+        }
+        coin.insert(status.name(), status.clone());
+        let _ = self.events.send(PatternEvent::Status(Box::new(status)));
+    }
+
+    pub fn record_alert(&self, alert: PatternAlert) {
+        self.alerts.record(alert.clone());
+        let _ = self.events.send(PatternEvent::Alert(alert));
+    }
+
+    /// Drop `coin`'s statuses, e.g. once it is no longer monitored. Its
+    /// alerts age out on their own.
+    pub fn remove(&self, coin: &str) {
+        self.statuses.write().unwrap().remove(coin);
+    }
+
+    /// `coin`'s status from the detector called `name`.
+    pub fn status(&self, coin: &str, name: &str) -> Option<PatternStatus> {
+        self.statuses.read().unwrap().get(coin)?.get(name).cloned()
+    }
+
+    /// Every status, by coin then detector name.
+    pub fn statuses(&self) -> Vec<PatternStatus> {
+        self.statuses
+            .read()
+            .unwrap()
+            .values()
+            .flat_map(|coin| coin.values().cloned())
+            .collect()
+    }
+
+    /// Every kept alert, newest candle first.
+    pub fn alerts(&self) -> Vec<PatternAlert> {
+        self.alerts.snapshot()
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<PatternEvent> {
+        self.events.subscribe()
+    }
+}
+
+/// The coin list the monitor is working through, shared with routes that
+/// report across every monitored coin.
+///
+/// Coins can also be paused, all at once or one at a time: the monitor
+/// keeps their detector state but stops fetching and publishing for them
+/// until they are resumed.
+#[derive(Debug, Clone, Default)]
+pub struct MonitoredCoins {
+    coins: Arc<RwLock<Vec<String>>>,
+    paused: Arc<RwLock<PausedCoins>>,
+    resumed: Arc<Notify>,
+}
+
+/// What is paused: everything, and separately, individual coins.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PausedCoins {
+    pub all: bool,
+    pub coins: BTreeSet<String>,
+}
+
+impl MonitoredCoins {
+    pub fn set(&self, coins: Vec<String>) {
+        *self.coins.write().unwrap() = coins;
+    }
+
+    pub fn get(&self) -> Vec<String> {
+        self.coins.read().unwrap().clone()
+    }
+
+    /// Pause `coin`, or every coin when `None`.
+    pub fn pause(&self, coin: Option<&str>) {
+        let mut paused = self.paused.write().unwrap();
+        match coin {
+            Some(coin) => {
+                paused.coins.insert(coin.to_string());
+            }
+            None => paused.all = true,
+        }
+    }
+
+    /// Resume `coin`, or with `None` lift every pause. A coin resumed on its
+    /// own stays paused while everything is.
+    pub fn resume(&self, coin: Option<&str>) {
+        let mut paused = self.paused.write().unwrap();
+        match coin {
+            Some(coin) => {
+                paused.coins.remove(coin);
+            }
+            None => *paused = PausedCoins::default(),
+        }
+        self.resumed.notify_one();
+    }
+
+    pub fn is_paused(&self, coin: &str) -> bool {
+        let paused = self.paused.read().unwrap();
+        paused.all || paused.coins.contains(coin)
+    }
+
+    pub fn paused(&self) -> PausedCoins {
+        self.paused.read().unwrap().clone()
+    }
+
+    /// Completes after the next [`MonitoredCoins::resume`], or at once if
+    /// one happened since the last wait.
+    pub async fn resumed(&self) {
+        self.resumed.notified().await;
+    }
+}
+
+/// How far back [`PremiumTracker`] keeps samples.
+pub const PREMIUM_HISTORY_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// Rolling mark-vs-oracle premium history per coin, filled by
+/// [`crate::services::contexts::ContextSampler`].
+#[derive(Debug, Clone, Default)]
+pub struct PremiumTracker {
+    histories: Arc<RwLock<HashMap<String, PremiumHistory>>>,
+}
+
+impl PremiumTracker {
+    pub fn record(&self, coin: &str, sample: PremiumSample) {
+        self.histories
+            .write()
+            .unwrap()
+            .entry(coin.to_string())
+            .or_default()
+            .record(sample, PREMIUM_HISTORY_MS);
+    }
+
+    pub fn history(&self, coin: &str) -> Option<PremiumHistory> {
+        self.histories.read().unwrap().get(coin).cloned()
+    }
+}
+
+/// How far back [`FundingTracker`] keeps hourly rates.
+pub const FUNDING_HISTORY_MS: u64 = 14 * 24 * 60 * 60 * 1000;
+
+/// Trailing hourly funding rates per coin, filled by
+/// [`crate::services::contexts::ContextSampler`].
+#[derive(Debug, Clone, Default)]
+pub struct FundingTracker {
+    histories: Arc<RwLock<HashMap<String, FundingHistory>>>,
+}
+
+impl FundingTracker {
+    pub fn record(&self, coin: &str, sample: FundingSample) {
+        self.histories
+            .write()
+            .unwrap()
+            .entry(coin.to_string())
+            .or_default()
+            .record(sample, FUNDING_HISTORY_MS);
+    }
+
+    pub fn history(&self, coin: &str) -> Option<FundingHistory> {
+        self.histories.read().unwrap().get(coin).cloned()
+    }
+
+    /// Every coin with a recorded rate, with its history.
+    pub fn snapshot(&self) -> Vec<(String, FundingHistory)> {
+        self.histories
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(coin, history)| (coin.clone(), history.clone()))
+            .collect()
+    }
+}
+
+/// Trailing open-interest and price samples per coin, filled by
+/// [`crate::services::contexts::ContextSampler`].
+#[derive(Debug, Clone, Default)]
+pub struct OiTracker {
+    histories: Arc<RwLock<HashMap<String, OiHistory>>>,
+}
+
+impl OiTracker {
+    /// Record `sample`, keeping `retain_ms` of history before it.
+    pub fn record(&self, coin: &str, sample: OiSample, retain_ms: u64) {
+        self.histories
+            .write()
+            .unwrap()
+            .entry(coin.to_string())
+            .or_default()
+            .record(sample, retain_ms);
+    }
+
+    pub fn history(&self, coin: &str) -> Option<OiHistory> {
+        self.histories.read().unwrap().get(coin).cloned()
+    }
+
+    /// Every coin with a recorded sample, with its history.
+    pub fn snapshot(&self) -> Vec<(String, OiHistory)> {
+        self.histories
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(coin, history)| (coin.clone(), history.clone()))
+            .collect()
+    }
+}
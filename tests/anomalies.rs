@@ -0,0 +1,87 @@
+mod common;
+
+use common::candle;
+use perpscreener::business_logic::anomalies::{
+    wick_body_ratio, AnomalyConfig, AnomalyKind, CandleAnomalyDetector,
+};
+
+fn config() -> AnomalyConfig {
+    AnomalyConfig {
+        atr_period: 3,
+        wick_body_ratio: 3.0,
+        min_wick_range_atr: 1.0,
+        range_atr: 3.0,
+        envelope_candles: 3,
+        envelope_margin_atr: 1.0,
+    }
+}
+
+/// Detector fed three candles with a true range of 2 (ATR 2) inside a
+/// 99-101 envelope.
+fn warmed_up() -> CandleAnomalyDetector {
+    let mut detector = CandleAnomalyDetector::new("BTC", config());
+    for i in 0..3 {
+        assert_eq!(detector.update(&candle(i, 100.0, 101.0, 99.0, 100.5)), None);
+    }
+    detector
+}
+
+#[test]
+fn wick_ratio_floors_the_body() {
+    assert!((wick_body_ratio(&candle(0, 100.5, 103.0, 100.0, 100.8)) - 2.2 / 0.3).abs() < 1e-9);
+    // Doji: body floored at 5% of the range.
+    assert!((wick_body_ratio(&candle(0, 100.0, 102.0, 100.0, 100.0)) - 20.0).abs() < 1e-9);
+    assert_eq!(wick_body_ratio(&candle(0, 100.0, 100.0, 100.0, 100.0)), 0.0);
+}
+
+#[test]
+fn flags_long_wicks_on_meaningful_candles() {
+    let mut detector = warmed_up();
+    let anomaly = detector
+        .update(&candle(3, 100.5, 103.0, 100.0, 100.8))
+        .unwrap();
+    assert_eq!(anomaly.coin, "BTC");
+    assert_eq!(anomaly.open_time, common::T0 + 3 * common::MINUTE_MS);
+    assert_eq!(anomaly.kinds, vec![AnomalyKind::LongWick]);
+    assert_eq!(anomaly.atr, 2.0);
+    assert!((anomaly.range_atr - 1.5).abs() < 1e-9);
+
+    let mut quiet = warmed_up();
+    assert_eq!(quiet.update(&candle(3, 100.0, 100.3, 99.9, 100.0)), None);
+}
+
+#[test]
+fn flags_wide_ranges_closing_outside_the_envelope() {
+    let mut detector = warmed_up();
+    let anomaly = detector
+        .update(&candle(3, 100.0, 108.0, 99.5, 107.5))
+        .unwrap();
+    assert_eq!(
+        anomaly.kinds,
+        vec![AnomalyKind::WideRange, AnomalyKind::OutsideEnvelope]
+    );
+    assert!((anomaly.range_atr - 4.25).abs() < 1e-9);
+}
+
+#[test]
+fn close_must_clear_the_envelope_by_the_margin() {
+    let mut inside = warmed_up();
+    assert_eq!(inside.update(&candle(3, 100.5, 102.6, 100.4, 102.5)), None);
+
+    let mut outside = warmed_up();
+    let anomaly = outside.update(&candle(3, 99.5, 99.5, 96.4, 96.5)).unwrap();
+    assert_eq!(anomaly.kinds, vec![AnomalyKind::OutsideEnvelope]);
+}
+
+#[test]
+fn needs_warm_atr_and_full_envelope_and_skips_repeats() {
+    let mut detector = CandleAnomalyDetector::new("BTC", config());
+    detector.update(&candle(0, 100.0, 101.0, 99.0, 100.5));
+    detector.update(&candle(1, 100.0, 101.0, 99.0, 100.5));
+    assert_eq!(detector.update(&candle(2, 100.0, 130.0, 99.0, 129.0)), None);
+
+    let mut detector = warmed_up();
+    let spike = candle(3, 100.0, 108.0, 99.5, 107.5);
+    assert!(detector.update(&spike).is_some());
+    assert_eq!(detector.update(&spike), None);
+}
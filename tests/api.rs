@@ -0,0 +1,101 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use axum::http::StatusCode;
+use perpscreener::business_logic::alerts::{AlertStage, PatternAlert, PatternKind};
+use perpscreener::routes::health::{HealthResponse, HealthStatus};
+use perpscreener::state::AppState;
+use serde_json::Value;
+
+async fn get(uri: &str) -> (StatusCode, Value) {
+    common::get(perpscreener::app(AppState::default()), uri).await
+}
+
+#[tokio::test]
+async fn health_reports_healthy() {
+    let (status, body) = get("/health").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "healthy");
+}
+
+#[tokio::test]
+async fn schema_list_includes_registered_components() {
+    let (status, body) = get("/schemas").await;
+    assert_eq!(status, StatusCode::OK);
+    let names: Vec<&str> = body["schemas"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|name| name.as_str().unwrap())
+        .collect();
+    assert!(names.contains(&"HealthResponse"));
+    assert!(names.contains(&"SchemaListResponse"));
+    assert!(names.contains(&"PatternAlert"));
+}
+
+#[tokio::test]
+async fn unknown_schema_is_not_found() {
+    let (status, _) = get("/schemas/NoSuchType").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn every_published_schema_compiles() {
+    let (_, list) = get("/schemas").await;
+    for name in list["schemas"].as_array().unwrap() {
+        let (status, schema) = get(&format!("/schemas/{}", name.as_str().unwrap())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(
+            jsonschema::validator_for(&schema).is_ok(),
+            "schema {name} does not compile"
+        );
+    }
+}
+
+#[tokio::test]
+async fn health_payload_round_trips_through_its_schema() {
+    let (_, schema) = get("/schemas/HealthResponse").await;
+    let validator = jsonschema::validator_for(&schema).unwrap();
+
+    let instance = serde_json::to_value(HealthResponse {
+        status: HealthStatus::Degraded,
+        max_age_ms: 300_000,
+        coins: Vec::new(),
+        stale_coins: 0,
+        clock_skew_ms: Some(-1_250),
+        clock_skew_warning: false,
+    })
+    .unwrap();
+    assert!(validator.is_valid(&instance));
+    assert!(
+        !validator.is_valid(&serde_json::json!({ "status": "fine", "max_age_ms": 1, "coins": [], "clock_skew_warning": false }))
+    );
+    assert!(!validator.is_valid(&serde_json::json!({})));
+}
+
+#[tokio::test]
+async fn pattern_alert_round_trips_through_its_schema() {
+    let (status, schema) = get("/schemas/PatternAlert").await;
+    assert_eq!(status, StatusCode::OK);
+    let validator = jsonschema::validator_for(&schema).unwrap();
+
+    let instance = serde_json::to_value(PatternAlert {
+        coin: "BTC".to_string(),
+        pattern: PatternKind::DoubleBottom,
+        stage: AlertStage::Confirmation,
+        open_time: 1_700_000_040_000,
+        price: 101.5,
+        level: 100.0,
+        target_price: None,
+        pattern_height_pct: None,
+        retest_high: None,
+        confirmation_mode: None,
+        htf_trend: None,
+    })
+    .unwrap();
+    assert!(validator.is_valid(&instance));
+    let mut unknown_stage = instance.clone();
+    unknown_stage["stage"] = Value::from("maybe");
+    assert!(!validator.is_valid(&unknown_stage));
+}
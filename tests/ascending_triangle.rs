@@ -0,0 +1,100 @@
+mod common;
+
+use common::candles_from_closes;
+use perpscreener::business_logic::alerts::{AlertStage, PatternKind};
+use perpscreener::business_logic::ascending_triangle::{
+    AscendingTriangleConfig, AscendingTriangleDetector, AscendingTriangleState,
+};
+use perpscreener::models::candle::Candle;
+
+fn path(start: f64, steps: &[(usize, f64)]) -> Vec<f64> {
+    let mut closes = Vec::new();
+    let mut price = start;
+    for &(count, step) in steps {
+        for _ in 0..count {
+            price += step;
+            closes.push(price);
+        }
+    }
+    closes
+}
+
+/// Steady 0.5 moves make every candle a full ATR wide; a 2 ATR reversal keeps
+/// swings to the real turns.
+fn config() -> AscendingTriangleConfig {
+    AscendingTriangleConfig {
+        rev_atr: 2.0,
+        ..AscendingTriangleConfig::default()
+    }
+}
+
+/// Two tops at 100 with a pullback to 96 between them, then `rest`.
+fn two_touches_then(rest: &[(usize, f64)]) -> Vec<Candle> {
+    let mut steps = vec![(20, 0.5), (8, -0.5), (8, 0.5)];
+    steps.extend_from_slice(rest);
+    candles_from_closes(90.0, &path(90.0, &steps), 0.1)
+}
+
+fn run(
+    detector: &mut AscendingTriangleDetector,
+    candles: &[Candle],
+) -> Vec<AscendingTriangleState> {
+    candles
+        .iter()
+        .map(|c| {
+            detector.update(c);
+            detector.state()
+        })
+        .collect()
+}
+
+#[test]
+fn rising_lows_into_resistance_warn_then_breakout_confirms() {
+    // Lows at 96, 97 and 98 under a flat 100, then a rally through it.
+    let candles = two_touches_then(&[(6, -0.5), (6, 0.5), (4, -0.5), (8, 0.5)]);
+    let mut detector = AscendingTriangleDetector::new("SOL", config());
+
+    let alerts: Vec<_> = candles.iter().filter_map(|c| detector.update(c)).collect();
+    assert_eq!(alerts.len(), 2, "{alerts:?}");
+    assert!(alerts
+        .iter()
+        .all(|a| a.pattern == PatternKind::AscendingTriangle));
+    assert_eq!(alerts[0].stage, AlertStage::EarlyWarning);
+    assert_eq!(alerts[1].stage, AlertStage::Confirmation);
+    assert!((alerts[1].level - 100.1).abs() < 1e-9);
+    assert!(alerts[1].price > 100.1);
+
+    assert_eq!(detector.state(), AscendingTriangleState::Confirmed);
+    assert_eq!(detector.touches(), 3);
+    assert!((detector.resistance_price().unwrap() - 100.1).abs() < 1e-9);
+    assert!((detector.latest_higher_low().unwrap() - 97.9).abs() < 1e-9);
+}
+
+#[test]
+fn lower_low_invalidates() {
+    // Second pullback undercuts the first low at 96.
+    let candles = two_touches_then(&[(10, -0.5), (10, 0.5)]);
+    let mut detector = AscendingTriangleDetector::new("SOL", config());
+
+    let states = run(&mut detector, &candles);
+    assert!(states.contains(&AscendingTriangleState::Building));
+    assert!(states.contains(&AscendingTriangleState::Invalidated));
+    assert!(!states.contains(&AscendingTriangleState::Confirmed));
+}
+
+#[test]
+fn too_few_touches_never_qualifies() {
+    let candles = two_touches_then(&[(6, -0.5), (10, 0.5)]);
+    let mut detector = AscendingTriangleDetector::new(
+        "SOL",
+        AscendingTriangleConfig {
+            min_touches: 3,
+            ..config()
+        },
+    );
+
+    let alerts: Vec<_> = candles.iter().filter_map(|c| detector.update(c)).collect();
+    assert!(alerts.is_empty(), "{alerts:?}");
+    assert_eq!(detector.state(), AscendingTriangleState::Watching);
+    assert_eq!(AscendingTriangleState::Qualified.as_str(), "QUALIFIED");
+}
@@ -0,0 +1,120 @@
+#![cfg(feature = "server")]
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use axum::routing::post;
+use axum::{Json, Router};
+use perpscreener::business_logic::intervals::closed_since;
+use perpscreener::services::hyperliquid::{HyperliquidClient, MAX_CANDLES_PER_REQUEST};
+use serde_json::{json, Value};
+
+const MINUTE_MS: u64 = 60_000;
+const T0: u64 = 1_700_000_040_000;
+
+/// Stub `candleSnapshot` serving 1m candles opening in `[startTime, endTime]`,
+/// capped per response like the real API; counts the requests it answers.
+async fn spawn_hyperliquid(requests: Arc<AtomicUsize>) -> String {
+    let app = Router::new().route(
+        "/info",
+        post(move |Json(body): Json<Value>| {
+            let requests = requests.clone();
+            async move {
+                requests.fetch_add(1, Ordering::SeqCst);
+                let start = body["req"]["startTime"].as_u64().unwrap();
+                let end = body["req"]["endTime"].as_u64().unwrap();
+                let first = start.div_ceil(MINUTE_MS) * MINUTE_MS;
+                let candles: Vec<Value> = (0..)
+                    .map(|i| first + i * MINUTE_MS)
+                    .take_while(|&t| t <= end)
+                    .take(MAX_CANDLES_PER_REQUEST)
+                    .map(|t| {
+                        json!({
+                            "t": t, "T": t + MINUTE_MS - 1, "s": "BTC", "i": "1m",
+                            "o": "100.0", "h": "101.0", "l": "99.0", "c": "100.5",
+                            "v": "1.0", "n": 1
+                        })
+                    })
+                    .collect();
+                Json(Value::from(candles))
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn long_gaps_are_fetched_in_pages_without_repeats() {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let client = HyperliquidClient::with_base_url(spawn_hyperliquid(requests.clone()).await);
+    let last_open = T0;
+    // 1,200 candles closed, plus half of a forming one.
+    let now = T0 + 1_201 * MINUTE_MS + 30_000;
+
+    let candles = client
+        .closed_candles_since("BTC", "1m", last_open, now)
+        .await
+        .unwrap();
+    assert_eq!(
+        candles.len() as u64,
+        closed_since("1m", last_open, now).unwrap()
+    );
+    assert_eq!(candles.len(), 1_200);
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+
+    let opens: Vec<u64> = candles.iter().map(|c| c.open_time).collect();
+    let unique: HashSet<u64> = opens.iter().copied().collect();
+    assert_eq!(unique.len(), opens.len(), "a candle was returned twice");
+    assert_eq!(opens[0], T0 + MINUTE_MS);
+    assert!(opens.windows(2).all(|w| w[1] == w[0] + MINUTE_MS));
+    assert!(candles.iter().all(|c| c.is_closed_at("1m", now)));
+}
+
+#[tokio::test]
+async fn short_gaps_take_one_request() {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let client = HyperliquidClient::with_base_url(spawn_hyperliquid(requests.clone()).await);
+
+    let candles = client
+        .closed_candles_since("BTC", "1m", T0, T0 + 4 * MINUTE_MS)
+        .await
+        .unwrap();
+    assert_eq!(candles.len(), 3);
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+    let none = client
+        .closed_candles_since("BTC", "1m", T0, T0 + MINUTE_MS + 1)
+        .await
+        .unwrap();
+    assert!(none.is_empty());
+}
+
+#[tokio::test]
+async fn long_recent_windows_are_fetched_in_pages() {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let client = HyperliquidClient::with_base_url(spawn_hyperliquid(requests.clone()).await);
+    let now = T0 + 30_000;
+
+    let candles = client
+        .recent_closed_candles("BTC", "1m", 1_200, now)
+        .await
+        .unwrap();
+    assert_eq!(candles.len(), 1_200);
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+    assert_eq!(candles.last().unwrap().open_time, T0 - MINUTE_MS);
+    assert!(candles
+        .windows(2)
+        .all(|w| w[1].open_time == w[0].open_time + MINUTE_MS));
+
+    requests.store(0, Ordering::SeqCst);
+    let candles = client
+        .recent_closed_candles("BTC", "1m", 100, now)
+        .await
+        .unwrap();
+    assert_eq!(candles.len(), 100);
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+}
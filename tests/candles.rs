@@ -0,0 +1,63 @@
+use perpscreener::models::candle::{closed_only, Candle};
+
+const MINUTE: u64 = 60_000;
+const T0: u64 = 1_700_000_040_000 - 1_700_000_040_000 % (15 * MINUTE);
+
+fn candle(open_time: u64, interval_ms: u64) -> Candle {
+    Candle {
+        open_time,
+        close_time: open_time + interval_ms - 1,
+        open: 100.0,
+        high: 101.0,
+        low: 99.0,
+        close: 100.5,
+        volume: 10.0,
+        num_trades: 42,
+    }
+}
+
+#[test]
+fn candle_closing_exactly_now_is_closed() {
+    let c = candle(T0, MINUTE);
+    assert!(c.is_closed_at("1m", T0 + MINUTE));
+    assert!(!c.is_closed_at("1m", T0 + MINUTE - 1));
+}
+
+#[test]
+fn uses_the_candle_interval_not_a_fixed_minute() {
+    let c = candle(T0, 15 * MINUTE);
+    assert!(!c.is_closed_at("15m", T0 + 14 * MINUTE));
+    assert!(c.is_closed_at("15m", T0 + 15 * MINUTE));
+}
+
+#[test]
+fn unsupported_interval_falls_back_to_close_time() {
+    let c = candle(T0, MINUTE);
+    assert!(!c.is_closed_at("7m", c.close_time));
+    assert!(c.is_closed_at("7m", c.close_time + 1));
+}
+
+#[test]
+fn closed_only_drops_the_forming_candle() {
+    let candles: Vec<Candle> = (0..3).map(|i| candle(T0 + i * MINUTE, MINUTE)).collect();
+
+    let now = T0 + 2 * MINUTE + 30_000;
+    let closed = closed_only(candles.clone(), "1m", now);
+    assert_eq!(closed, candles[..2]);
+
+    let at_boundary = closed_only(candles.clone(), "1m", T0 + 3 * MINUTE);
+    assert_eq!(at_boundary, candles);
+}
+
+#[test]
+fn closed_only_on_a_five_minute_interval() {
+    let five = 5 * MINUTE;
+    let candles: Vec<Candle> = (0..3).map(|i| candle(T0 + i * five, five)).collect();
+
+    // Four minutes into the third candle only the first two have closed.
+    let closed = closed_only(candles.clone(), "5m", T0 + 2 * five + 4 * MINUTE);
+    assert_eq!(closed, candles[..2]);
+    // A 1m cutoff would wrongly count a 5m candle closed after one minute.
+    assert!(!candles[0].is_closed_at("5m", T0 + MINUTE));
+    assert!(candles[2].is_closed_at("5m", T0 + 3 * five));
+}
@@ -0,0 +1,103 @@
+#![cfg(feature = "server")]
+
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
+use perpscreener::cli::{Cli, Command, ServeArgs};
+use perpscreener::settings::{CoinSelection, Settings};
+
+fn serve_args(args: &[&str]) -> ServeArgs {
+    let Command::Serve(serve) = Cli::try_parse_from(args).unwrap().into_command();
+    serve
+}
+
+#[test]
+fn definition_is_consistent() {
+    Cli::command().debug_assert();
+}
+
+#[test]
+fn serve_is_the_default_subcommand() {
+    assert_eq!(serve_args(&["perpscreener"]), ServeArgs::default());
+    assert_eq!(
+        serve_args(&["perpscreener", "--port", "8080"]),
+        serve_args(&["perpscreener", "serve", "--port", "8080"])
+    );
+}
+
+#[test]
+fn parses_every_serve_flag() {
+    let args = serve_args(&[
+        "perpscreener",
+        "serve",
+        "--port",
+        "3001",
+        "--coins",
+        "BTC,ETH",
+        "--interval",
+        "15m",
+        "--config",
+        "prod.toml",
+    ]);
+    assert_eq!(args.port, Some(3001));
+    assert_eq!(
+        args.coins.as_deref(),
+        Some(&["BTC".to_string(), "ETH".to_string()][..])
+    );
+    assert_eq!(args.interval.as_deref(), Some("15m"));
+    assert_eq!(args.config.unwrap().to_str(), Some("prod.toml"));
+}
+
+#[test]
+fn rejects_bad_input() {
+    let matches = Cli::command().try_get_matches_from(["perpscreener", "--port", "http"]);
+    assert_eq!(matches.unwrap_err().kind(), ErrorKind::ValueValidation);
+    let matches = Cli::command().try_get_matches_from(["perpscreener", "scan"]);
+    assert!(matches.is_err());
+    let matches = Cli::command().try_get_matches_from(["perpscreener", "--help"]);
+    assert_eq!(matches.unwrap_err().kind(), ErrorKind::DisplayHelp);
+}
+
+#[test]
+fn flags_override_settings() {
+    let mut settings = Settings::default();
+    serve_args(&[
+        "perpscreener",
+        "--port",
+        "8080",
+        "--coins",
+        "DOGE",
+        "--interval",
+        "5m",
+    ])
+    .apply(&mut settings);
+    assert_eq!(settings.server.bind, "0.0.0.0:8080");
+    assert_eq!(
+        settings.monitor.coins,
+        CoinSelection::Listed(vec!["DOGE".to_string()])
+    );
+    assert_eq!(settings.monitor.detection_interval, "5m");
+
+    // Unset flags leave the settings alone.
+    let before = settings.clone();
+    serve_args(&["perpscreener"]).apply(&mut settings);
+    assert_eq!(settings.server, before.server);
+    assert_eq!(settings.monitor, before.monitor);
+}
+
+#[test]
+fn coins_all_switches_to_discovery() {
+    let mut settings = Settings::default();
+    serve_args(&["perpscreener", "--coins", "ALL"]).apply(&mut settings);
+    assert_eq!(settings.monitor.coins, CoinSelection::All);
+}
+
+#[test]
+fn empty_coin_list_fails_validation() {
+    let mut settings = Settings::default();
+    serve_args(&["perpscreener", "--coins", ""]).apply(&mut settings);
+    assert!(
+        !settings.validate().is_empty(),
+        "{:?}",
+        settings.monitor.coins
+    );
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::ManualClock;
+use perpscreener::clock::{Clock, SkewCorrectedClock, SkewEstimator};
+
+const NOW: u64 = 1_700_000_000_000;
+
+#[test]
+fn first_sample_sets_the_estimate() {
+    let skew = SkewEstimator::new(2_000);
+    assert_eq!(skew.estimate_ms(), None);
+    assert_eq!(skew.observe(NOW - 60_000, NOW), -60_000);
+    assert_eq!(skew.estimate_ms(), Some(-60_000));
+    assert!(skew.exceeds_threshold());
+}
+
+#[test]
+fn later_samples_are_smoothed() {
+    let skew = SkewEstimator::new(2_000);
+    skew.observe(NOW + 1_000, NOW);
+    // One outlier only moves the estimate a fifth of the way.
+    assert_eq!(skew.observe(NOW + 11_000, NOW), 3_000);
+    assert!(skew.exceeds_threshold());
+    for _ in 0..50 {
+        skew.observe(NOW + 500, NOW);
+    }
+    assert_eq!(skew.estimate_ms(), Some(500));
+    assert!(!skew.exceeds_threshold());
+}
+
+#[test]
+fn corrected_clock_applies_the_offset() {
+    let local = ManualClock::new(NOW);
+    let skew = Arc::new(SkewEstimator::new(2_000));
+    let clock = SkewCorrectedClock::new(local.clone(), skew.clone());
+
+    assert_eq!(clock.now_ms(), NOW);
+    // Host clock a minute fast: upstream reports a minute earlier.
+    skew.observe(NOW - 60_000, NOW);
+    assert_eq!(clock.now_ms(), NOW - 60_000);
+
+    local.advance(1_000);
+    assert_eq!(clock.now_ms(), NOW - 59_000);
+}
@@ -0,0 +1,108 @@
+#![cfg(feature = "server")]
+
+use std::time::Duration;
+
+use perpscreener::services::coalesce::{Coalesce, CoalescingReceiver};
+use tokio::sync::broadcast;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Event {
+    Snapshot(u32),
+    Alert(&'static str),
+}
+
+impl Coalesce for Event {
+    fn is_snapshot(&self) -> bool {
+        matches!(self, Event::Snapshot(_))
+    }
+}
+
+use Event::{Alert, Snapshot};
+
+#[tokio::test]
+async fn backlog_collapses_to_the_newest_snapshot_keeping_alerts() {
+    let (tx, rx) = broadcast::channel(64);
+    let mut rx = CoalescingReceiver::new(rx);
+
+    for event in [
+        Snapshot(1),
+        Snapshot(2),
+        Alert("BTC confirmed"),
+        Snapshot(3),
+        Alert("ETH warning"),
+        Snapshot(4),
+    ] {
+        tx.send(event).unwrap();
+    }
+    drop(tx);
+
+    let mut received = Vec::new();
+    while let Some(event) = rx.recv().await {
+        received.push(event);
+    }
+    assert_eq!(
+        received,
+        [Alert("BTC confirmed"), Alert("ETH warning"), Snapshot(4)]
+    );
+    assert_eq!(rx.coalesced(), 3);
+    assert_eq!(rx.lagged(), 0);
+}
+
+#[tokio::test]
+async fn keeping_up_delivers_every_snapshot() {
+    let (tx, rx) = broadcast::channel(4);
+    let mut rx = CoalescingReceiver::new(rx);
+    for i in 0..3 {
+        tx.send(Snapshot(i)).unwrap();
+        assert_eq!(rx.recv().await, Some(Snapshot(i)));
+    }
+    assert_eq!(rx.coalesced(), 0);
+}
+
+#[tokio::test]
+async fn slow_consumer_skips_intermediate_snapshots() {
+    let (tx, rx) = broadcast::channel(256);
+    let mut rx = CoalescingReceiver::new(rx);
+
+    let producer = tokio::spawn(async move {
+        for i in 0..50 {
+            tx.send(Snapshot(i)).unwrap();
+            if i == 25 {
+                tx.send(Alert("mid-burst")).unwrap();
+            }
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+    });
+
+    let mut received = Vec::new();
+    while let Some(event) = rx.recv().await {
+        received.push(event);
+        // Flushing to a slow client.
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    producer.await.unwrap();
+
+    assert_eq!(received.last(), Some(&Snapshot(49)));
+    assert!(received.contains(&Alert("mid-burst")));
+    assert!(received.len() < 20, "received {} events", received.len());
+    let snapshots: Vec<u32> = received
+        .iter()
+        .filter_map(|e| match e {
+            Snapshot(i) => Some(*i),
+            Alert(_) => None,
+        })
+        .collect();
+    assert!(snapshots.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(rx.lagged(), 0);
+}
+
+#[tokio::test]
+async fn overflow_is_counted_as_lag() {
+    let (tx, rx) = broadcast::channel(2);
+    let mut rx = CoalescingReceiver::new(rx);
+    for i in 0..5 {
+        tx.send(Snapshot(i)).unwrap();
+    }
+    assert_eq!(rx.recv().await, Some(Snapshot(4)));
+    assert_eq!(rx.lagged(), 3);
+}
@@ -0,0 +1,160 @@
+#![allow(dead_code, unused_imports)]
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use perpscreener::clock::Clock;
+use perpscreener::models::candle::Candle;
+
+#[cfg(feature = "server")]
+pub use http::*;
+
+/// Clock that only moves when told to.
+#[derive(Debug, Default)]
+pub struct ManualClock(AtomicU64);
+
+impl ManualClock {
+    pub fn new(now_ms: u64) -> Arc<Self> {
+        Arc::new(Self(AtomicU64::new(now_ms)))
+    }
+
+    pub fn set(&self, now_ms: u64) {
+        self.0.store(now_ms, Ordering::SeqCst);
+    }
+
+    pub fn advance(&self, ms: u64) {
+        self.0.fetch_add(ms, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_ms(&self) -> u64 {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+pub const MINUTE_MS: u64 = 60_000;
+/// Open time of the first candle built by [`candle`] (a whole minute).
+pub const T0: u64 = 1_700_000_040_000;
+
+/// 1m candle at position `index` after [`T0`] with unit volume.
+pub fn candle(index: u64, open: f64, high: f64, low: f64, close: f64) -> Candle {
+    let open_time = T0 + index * MINUTE_MS;
+    Candle {
+        open_time,
+        close_time: open_time + MINUTE_MS - 1,
+        open,
+        high,
+        low,
+        close,
+        volume: 1.0,
+        num_trades: 1,
+    }
+}
+
+/// 1m candles following `closes`, each opening at the previous close with a
+/// `wick` above and below the body.
+pub fn candles_from_closes(first_open: f64, closes: &[f64], wick: f64) -> Vec<Candle> {
+    let mut open = first_open;
+    closes
+        .iter()
+        .enumerate()
+        .map(|(i, &close)| {
+            let c = candle(
+                i as u64,
+                open,
+                open.max(close) + wick,
+                open.min(close) - wick,
+                close,
+            );
+            open = close;
+            c
+        })
+        .collect()
+}
+
+#[cfg(feature = "server")]
+mod http {
+    use std::sync::Arc;
+
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use http_body_util::BodyExt;
+    use perpscreener::models::candle::Candle;
+    use perpscreener::state::AppState;
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    use super::ManualClock;
+
+    /// App state driven by `clock`.
+    pub fn state_with_clock(clock: Arc<ManualClock>) -> AppState {
+        AppState::new(clock)
+    }
+
+    pub async fn send(app: Router, request: Request<Body>) -> (StatusCode, Value) {
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let body = if bytes.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&bytes).unwrap()
+        };
+        (status, body)
+    }
+
+    pub async fn get(app: Router, uri: &str) -> (StatusCode, Value) {
+        send(app, Request::get(uri).body(Body::empty()).unwrap()).await
+    }
+
+    /// Stub Hyperliquid `/info` serving `candleSnapshot` from fixed 1m series
+    /// per coin, honouring the requested time range and page size; unknown
+    /// coins have no candles. Returns its base URL.
+    pub async fn spawn_candle_server(series: Vec<(&str, Vec<Candle>)>) -> String {
+        let series: Arc<Vec<(String, Vec<Candle>)>> = Arc::new(
+            series
+                .into_iter()
+                .map(|(coin, candles)| (coin.to_string(), candles))
+                .collect(),
+        );
+        let app = Router::new().route(
+            "/info",
+            post(move |Json(body): Json<Value>| {
+                let series = series.clone();
+                async move {
+                    assert_eq!(body["type"], "candleSnapshot");
+                    let req = &body["req"];
+                    let start = req["startTime"].as_u64().unwrap();
+                    let end = req["endTime"].as_u64().unwrap();
+                    let candles: Vec<Value> = series
+                        .iter()
+                        .filter(|(coin, _)| req["coin"] == coin.as_str())
+                        .flat_map(|(coin, candles)| {
+                            candles
+                                .iter()
+                                .filter(|c| c.open_time >= start && c.open_time <= end)
+                                .take(500)
+                                .map(move |c| candle_json(coin, c))
+                        })
+                        .collect();
+                    Json(Value::from(candles))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}")
+    }
+
+    pub fn candle_json(coin: &str, c: &Candle) -> Value {
+        json!({
+            "t": c.open_time, "T": c.close_time, "s": coin, "i": "1m",
+            "o": c.open.to_string(), "h": c.high.to_string(), "l": c.low.to_string(),
+            "c": c.close.to_string(), "v": c.volume.to_string(), "n": c.num_trades
+        })
+    }
+}
@@ -0,0 +1,43 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::path::Path;
+
+use axum::http::StatusCode;
+use common::{ManualClock, T0};
+use perpscreener::naming::ApiNaming;
+use perpscreener::settings::Settings;
+
+#[tokio::test]
+async fn serves_the_effective_settings() {
+    let settings = Settings::from_sources(
+        "[monitor]\ncoins = [\"BTC\", \"DOGE\"]\n\n[double_bottom]\nmin_bounce_pct = 3.0\n",
+        Path::new("test.toml"),
+        [(
+            "PERPSCREENER__MONITOR__POLL_INTERVAL_SECS".to_string(),
+            "30".to_string(),
+        )],
+    )
+    .unwrap();
+    let state = common::state_with_clock(ManualClock::new(T0)).with_settings(settings);
+
+    let (status, body) = common::get(perpscreener::app(state), "/config").await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["server"]["bind"], "0.0.0.0:3000");
+    assert_eq!(body["server"]["api_naming"], "snake");
+    assert_eq!(body["monitor"]["coins"], serde_json::json!(["BTC", "DOGE"]));
+    assert_eq!(body["monitor"]["poll_interval_secs"], 30);
+    assert_eq!(body["monitor"]["detection_interval"], "1m");
+    assert_eq!(body["double_bottom"]["min_bounce_pct"], 3.0);
+    assert_eq!(body["double_bottom"]["atr_period"], 14);
+}
+
+#[tokio::test]
+async fn reports_the_naming_actually_in_use() {
+    let state = common::state_with_clock(ManualClock::new(T0)).with_naming(ApiNaming::Camel);
+    let (status, body) = common::get(perpscreener::app(state), "/config").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["server"]["apiNaming"], "camel");
+    assert_eq!(body["doubleBottom"]["atrPeriod"], 14);
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use common::candles_from_closes;
+use perpscreener::business_logic::alerts::{AlertStage, PatternKind};
+use perpscreener::business_logic::cup_and_handle::{
+    CupAndHandleConfig, CupAndHandleDetector, CupAndHandleState,
+};
+use perpscreener::models::candle::Candle;
+
+fn path(start: f64, steps: &[(usize, f64)]) -> Vec<f64> {
+    let mut closes = Vec::new();
+    let mut price = start;
+    for &(count, step) in steps {
+        for _ in 0..count {
+            price += step;
+            closes.push(price);
+        }
+    }
+    closes
+}
+
+/// Rim at 100, a rounded cup down to 90 with a flat base, back up to 100,
+/// then `rest`.
+fn cup_then(rest: &[(usize, f64)]) -> Vec<Candle> {
+    let mut steps = vec![(10, 1.0), (10, -1.0), (12, 0.0), (10, 1.0)];
+    steps.extend_from_slice(rest);
+    candles_from_closes(90.0, &path(90.0, &steps), 0.1)
+}
+
+fn run(detector: &mut CupAndHandleDetector, candles: &[Candle]) -> Vec<CupAndHandleState> {
+    candles
+        .iter()
+        .map(|c| {
+            detector.update(c);
+            detector.state()
+        })
+        .collect()
+}
+
+#[test]
+fn handle_warns_and_close_above_rim_confirms() {
+    let candles = cup_then(&[(3, -0.5), (5, 1.0)]);
+    let mut detector = CupAndHandleDetector::new("BTC", CupAndHandleConfig::default());
+
+    let alerts: Vec<_> = candles.iter().filter_map(|c| detector.update(c)).collect();
+    assert_eq!(alerts.len(), 2, "{alerts:?}");
+    assert!(alerts
+        .iter()
+        .all(|a| a.pattern == PatternKind::CupAndHandle));
+    assert_eq!(alerts[0].stage, AlertStage::EarlyWarning);
+    assert_eq!(alerts[1].stage, AlertStage::Confirmation);
+    assert!((alerts[1].level - 100.1).abs() < 1e-9);
+    assert!(alerts[1].price > 100.1);
+
+    assert_eq!(detector.state(), CupAndHandleState::Confirmed);
+    assert!((detector.rim_price().unwrap() - 100.1).abs() < 1e-9);
+    let depth = (100.1 - 89.9) / 100.1 * 100.0;
+    assert!((detector.cup_depth_pct().unwrap() - depth).abs() < 1e-9);
+    assert!((detector.handle_low().unwrap() - 98.4).abs() < 1e-9);
+}
+
+#[test]
+fn deep_handle_invalidates() {
+    // Handle gives back more than half the cup.
+    let candles = cup_then(&[(8, -1.0), (10, 1.0)]);
+    let mut detector = CupAndHandleDetector::new("BTC", CupAndHandleConfig::default());
+
+    let states = run(&mut detector, &candles);
+    assert!(states.contains(&CupAndHandleState::HandleForming));
+    assert!(states.contains(&CupAndHandleState::Invalidated));
+    assert!(!states.contains(&CupAndHandleState::Confirmed));
+}
+
+#[test]
+fn v_shaped_recovery_is_not_a_cup() {
+    let steps = [(10, 1.0), (10, -1.0), (10, 1.0), (3, -0.5), (5, 1.0)];
+    let candles = candles_from_closes(90.0, &path(90.0, &steps), 0.1);
+    let mut detector = CupAndHandleDetector::new(
+        "BTC",
+        CupAndHandleConfig {
+            min_cup_candles: 15,
+            ..CupAndHandleConfig::default()
+        },
+    );
+
+    let alerts: Vec<_> = candles.iter().filter_map(|c| detector.update(c)).collect();
+    assert!(alerts.is_empty(), "{alerts:?}");
+    assert_eq!(detector.warmup_candles(), 200);
+}
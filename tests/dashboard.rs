@@ -0,0 +1,35 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use axum::http::StatusCode;
+use common::{ManualClock, MINUTE_MS, T0};
+use perpscreener::business_logic::volume::{CandleDirection, VolumeSpike};
+
+#[tokio::test]
+async fn dashboard_batches_what_the_monitor_published() {
+    let state = common::state_with_clock(ManualClock::new(T0 + 10 * MINUTE_MS))
+        .with_monitored_coins(vec!["BTC".to_string()]);
+    state.freshness.record("BTC", T0 + 10 * MINUTE_MS - 1);
+    state.volume_spikes.record(VolumeSpike {
+        coin: "BTC".to_string(),
+        open_time: T0 + 9 * MINUTE_MS,
+        volume: 30.0,
+        average_volume: 10.0,
+        multiple: 3.0,
+        price_change_pct: 1.0,
+        direction: CandleDirection::Up,
+    });
+
+    let (status, body) = common::get(perpscreener::app(state), "/dashboard").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["health"]["status"], "healthy");
+    assert_eq!(body["health"]["coins"][0]["coin"], "BTC");
+    assert_eq!(body["volatility"]["interval"], "1h");
+    assert_eq!(
+        body["volatility"]["computed_at_ms"],
+        serde_json::Value::Null
+    );
+    assert_eq!(body["volume_spikes"][0]["multiple"], 3.0);
+    assert_eq!(body["anomalies"].as_array().unwrap().len(), 0);
+}
@@ -0,0 +1,300 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::sync::atomic::{AtomicU16, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::routing::post;
+use axum::Router;
+use common::ManualClock;
+use perpscreener::business_logic::alerts::AlertSeverity;
+use perpscreener::services::delivery_queue::{
+    retry_delay_ms, DeliveryQueue, FAILED_DELIVERIES_KEPT, MAX_RETRY_DELAY_MS,
+};
+use perpscreener::services::webhooks::{NewWebhook, WebhookDispatcher, WebhookStore};
+use serde_json::json;
+use tower::ServiceExt;
+
+const NOW: u64 = 1_700_000_000_000;
+const HOUR_MS: u64 = 3_600_000;
+
+fn new_webhook(url: &str) -> NewWebhook {
+    NewWebhook {
+        url: url.to_string(),
+        coins: None,
+        min_severity: None,
+        secret: None,
+        quiet_hours: None,
+    }
+}
+
+#[test]
+fn backoff_doubles_up_to_the_cap() {
+    assert_eq!(retry_delay_ms(1), 5_000);
+    assert_eq!(retry_delay_ms(2), 10_000);
+    assert_eq!(retry_delay_ms(4), 40_000);
+    assert_eq!(retry_delay_ms(9), MAX_RETRY_DELAY_MS);
+    assert_eq!(retry_delay_ms(u32::MAX), MAX_RETRY_DELAY_MS);
+}
+
+#[test]
+fn retries_are_scheduled_until_success() {
+    let queue = DeliveryQueue::in_memory();
+    let queued = queue
+        .enqueue(7, "{}".to_string(), "503".to_string(), NOW)
+        .unwrap();
+    assert_eq!(queued.attempts, 1);
+    assert_eq!(queued.next_attempt_ms, NOW + 5_000);
+    assert!(queue.due(NOW + 4_999).is_empty());
+    assert_eq!(queue.due(NOW + 5_000).len(), 1);
+
+    queue
+        .record_retry(queued.id, Err("timeout".to_string()), NOW + 5_000)
+        .unwrap();
+    let pending = queue.pending_for(7);
+    assert_eq!(pending[0].attempts, 2);
+    assert_eq!(pending[0].next_attempt_ms, NOW + 15_000);
+    assert_eq!(pending[0].last_error, "timeout");
+
+    let stats = queue.stats(NOW + 20_000);
+    assert_eq!(stats.depth, 1);
+    assert_eq!(stats.oldest_age_ms, Some(20_000));
+
+    queue.record_retry(queued.id, Ok(()), NOW + 15_000).unwrap();
+    assert!(queue.pending_for(7).is_empty());
+    assert!(queue.failed_for(7).is_empty());
+    assert_eq!(queue.stats(NOW).oldest_age_ms, None);
+}
+
+#[test]
+fn deliveries_past_the_max_age_fail_permanently() {
+    let queue = DeliveryQueue::in_memory().with_max_age_ms(HOUR_MS);
+    let id = queue
+        .enqueue(1, "{}".to_string(), "503".to_string(), NOW)
+        .unwrap()
+        .id;
+    queue
+        .record_retry(id, Err("still 503".to_string()), NOW + HOUR_MS - 1)
+        .unwrap();
+    assert_eq!(queue.pending_for(1).len(), 1);
+
+    queue
+        .record_retry(id, Err("still 503".to_string()), NOW + HOUR_MS)
+        .unwrap();
+    assert!(queue.pending_for(1).is_empty());
+    let failed = queue.failed_for(1);
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].attempts, 3);
+    assert_eq!(failed[0].failed_at_ms, NOW + HOUR_MS);
+    assert_eq!(failed[0].last_error, "still 503");
+}
+
+#[test]
+fn failed_history_is_trimmed_per_subscription() {
+    let queue = DeliveryQueue::in_memory();
+    for i in 0..FAILED_DELIVERIES_KEPT as u64 + 3 {
+        let id = queue
+            .enqueue(1, format!("{i}"), "boom".to_string(), NOW)
+            .unwrap()
+            .id;
+        queue.give_up(id, "gone".to_string(), NOW).unwrap();
+    }
+    let failed = queue.failed_for(1);
+    assert_eq!(failed.len(), FAILED_DELIVERIES_KEPT);
+    assert_eq!(failed[0].body, "3");
+    assert_eq!(failed[0].last_error, "gone");
+}
+
+#[test]
+fn queue_survives_reopening() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("webhook_queue.json");
+
+    let queue = DeliveryQueue::open(&path).unwrap();
+    queue
+        .enqueue(1, "{\"a\":1}".to_string(), "503".to_string(), NOW)
+        .unwrap();
+    let second = queue
+        .enqueue(2, "{}".to_string(), "503".to_string(), NOW)
+        .unwrap();
+    queue
+        .give_up(second.id, "deleted".to_string(), NOW)
+        .unwrap();
+
+    let reopened = DeliveryQueue::open(&path).unwrap();
+    assert_eq!(reopened.pending_for(1)[0].body, "{\"a\":1}");
+    assert_eq!(reopened.failed_for(2).len(), 1);
+    let third = reopened
+        .enqueue(3, "{}".to_string(), "503".to_string(), NOW)
+        .unwrap();
+    assert_eq!(third.id, 3);
+}
+
+/// Receiver answering with whatever status is currently in `status`.
+async fn spawn_receiver(status: Arc<AtomicU16>, hits: Arc<AtomicUsize>) -> String {
+    let app = Router::new().route(
+        "/hook",
+        post(move || {
+            let status = status.clone();
+            let hits = hits.clone();
+            async move {
+                hits.fetch_add(1, Ordering::SeqCst);
+                StatusCode::from_u16(status.load(Ordering::SeqCst)).unwrap()
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}/hook")
+}
+
+#[tokio::test]
+async fn failed_alerts_are_redelivered_once_the_receiver_recovers() {
+    let status = Arc::new(AtomicU16::new(503));
+    let hits = Arc::new(AtomicUsize::new(0));
+    let url = spawn_receiver(status.clone(), hits.clone()).await;
+
+    let clock = ManualClock::new(NOW);
+    let store = Arc::new(WebhookStore::in_memory().with_max_consecutive_failures(2));
+    let id = store.create(new_webhook(&url), NOW).unwrap().id;
+    let queue = Arc::new(DeliveryQueue::in_memory());
+    let dispatcher = WebhookDispatcher::new(store.clone())
+        .with_queue(queue.clone())
+        .with_clock(clock.clone());
+
+    let payload = json!({ "coin": "BTC" });
+    assert_eq!(
+        dispatcher
+            .dispatch("BTC", AlertSeverity::Critical, &payload)
+            .await,
+        0
+    );
+    assert_eq!(queue.pending_for(id).len(), 1);
+
+    // Not due yet.
+    assert_eq!(dispatcher.retry_due().await, 0);
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+    // Failed retries don't disable the subscription.
+    for _ in 0..3 {
+        clock.advance(MAX_RETRY_DELAY_MS);
+        assert_eq!(dispatcher.retry_due().await, 0);
+    }
+    assert!(!store.get(id).unwrap().disabled);
+    assert_eq!(queue.pending_for(id)[0].attempts, 4);
+
+    status.store(200, Ordering::SeqCst);
+    clock.advance(MAX_RETRY_DELAY_MS);
+    assert_eq!(dispatcher.retry_due().await, 1);
+    assert!(queue.pending_for(id).is_empty());
+    assert_eq!(store.get(id).unwrap().consecutive_failures, 0);
+    assert_eq!(hits.load(Ordering::SeqCst), 5);
+}
+
+#[tokio::test]
+async fn queued_deliveries_for_deleted_subscriptions_are_given_up() {
+    let clock = ManualClock::new(NOW);
+    let store = Arc::new(WebhookStore::in_memory());
+    let queue = Arc::new(DeliveryQueue::in_memory());
+    queue
+        .enqueue(42, "{}".to_string(), "503".to_string(), NOW)
+        .unwrap();
+    let dispatcher = WebhookDispatcher::new(store)
+        .with_queue(queue.clone())
+        .with_clock(clock.clone());
+
+    clock.advance(MAX_RETRY_DELAY_MS);
+    assert_eq!(dispatcher.retry_due().await, 0);
+    assert!(queue.pending_for(42).is_empty());
+    assert_eq!(queue.failed_for(42)[0].last_error, "subscription deleted");
+}
+
+#[tokio::test]
+async fn deliveries_endpoint_shows_the_queue() {
+    let clock = ManualClock::new(NOW);
+    let state = common::state_with_clock(clock.clone());
+    let id = state
+        .webhooks
+        .create(new_webhook("https://example.com/hook"), NOW)
+        .unwrap()
+        .id;
+    state
+        .webhook_queue
+        .enqueue(
+            id,
+            "{}".to_string(),
+            "receiver returned 502".to_string(),
+            NOW,
+        )
+        .unwrap();
+    state
+        .webhook_queue
+        .enqueue(id + 1, "{}".to_string(), "503".to_string(), NOW + 1_000)
+        .unwrap();
+    clock.advance(60_000);
+    let app = perpscreener::app(state);
+
+    let (status, body) = common::get(app.clone(), &format!("/webhooks/{id}/deliveries")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["pending"].as_array().unwrap().len(), 1);
+    assert_eq!(body["pending"][0]["last_error"], "receiver returned 502");
+    assert_eq!(body["failed"].as_array().unwrap().len(), 0);
+    assert_eq!(body["queue_depth"], 2);
+    assert_eq!(body["oldest_pending_age_ms"], 60_000);
+
+    let (status, _) = common::get(app, "/webhooks/99/deliveries").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn deleting_a_subscription_clears_its_queue_and_ids_stay_fresh() {
+    let clock = ManualClock::new(NOW);
+    let state = common::state_with_clock(clock.clone());
+    let app = perpscreener::app(state.clone());
+    let first = state
+        .webhooks
+        .create(new_webhook("https://a.example/hook"), NOW)
+        .unwrap()
+        .id;
+    state
+        .webhook_queue
+        .enqueue(first, "{}".to_string(), "503".to_string(), NOW)
+        .unwrap();
+    let failed = state
+        .webhook_queue
+        .enqueue(first, "{}".to_string(), "503".to_string(), NOW)
+        .unwrap();
+    state
+        .webhook_queue
+        .give_up(failed.id, "expired".to_string(), NOW)
+        .unwrap();
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::delete(format!("/webhooks/{first}"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert!(state.webhook_queue.pending_for(first).is_empty());
+    assert!(state.webhook_queue.failed_for(first).is_empty());
+
+    let second = state
+        .webhooks
+        .create(new_webhook("https://b.example/hook"), NOW)
+        .unwrap()
+        .id;
+    assert_ne!(second, first);
+    let (status, body) = common::get(app, &format!("/webhooks/{second}/deliveries")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["pending"].as_array().unwrap().len(), 0);
+    assert_eq!(body["failed"].as_array().unwrap().len(), 0);
+    assert_eq!(body["queue_depth"], 0);
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use common::candles_from_closes;
+use perpscreener::business_logic::alerts::{AlertStage, PatternKind};
+use perpscreener::business_logic::descending_triangle::{
+    DescendingTriangleConfig, DescendingTriangleDetector, DescendingTriangleState,
+};
+use perpscreener::models::candle::Candle;
+
+fn path(start: f64, steps: &[(usize, f64)]) -> Vec<f64> {
+    let mut closes = Vec::new();
+    let mut price = start;
+    for &(count, step) in steps {
+        for _ in 0..count {
+            price += step;
+            closes.push(price);
+        }
+    }
+    closes
+}
+
+/// Steady 0.5 moves make every candle a full ATR wide; a 2 ATR reversal keeps
+/// swings to the real turns.
+fn config() -> DescendingTriangleConfig {
+    DescendingTriangleConfig {
+        rev_atr: 2.0,
+        ..DescendingTriangleConfig::default()
+    }
+}
+
+/// Two lows at 100 with a bounce to 104 between them, then `rest`.
+fn two_touches_then(rest: &[(usize, f64)]) -> Vec<Candle> {
+    let mut steps = vec![(20, -0.5), (8, 0.5), (8, -0.5)];
+    steps.extend_from_slice(rest);
+    candles_from_closes(110.0, &path(110.0, &steps), 0.1)
+}
+
+fn run(
+    detector: &mut DescendingTriangleDetector,
+    candles: &[Candle],
+) -> Vec<DescendingTriangleState> {
+    candles
+        .iter()
+        .map(|c| {
+            detector.update(c);
+            detector.state()
+        })
+        .collect()
+}
+
+#[test]
+fn falling_highs_into_support_warn_then_breakdown_confirms() {
+    // Highs at 104, 103 and 102 over a flat 100, then a drop through it.
+    let candles = two_touches_then(&[(6, 0.5), (6, -0.5), (4, 0.5), (8, -0.5)]);
+    let mut detector = DescendingTriangleDetector::new("SOL", config());
+
+    let alerts: Vec<_> = candles.iter().filter_map(|c| detector.update(c)).collect();
+    assert_eq!(alerts.len(), 2, "{alerts:?}");
+    assert!(alerts
+        .iter()
+        .all(|a| a.pattern == PatternKind::DescendingTriangle));
+    assert_eq!(alerts[0].stage, AlertStage::EarlyWarning);
+    assert_eq!(alerts[1].stage, AlertStage::Confirmation);
+    assert!((alerts[1].level - 99.9).abs() < 1e-9);
+    assert!(alerts[1].price < 99.9);
+
+    assert_eq!(detector.state(), DescendingTriangleState::Confirmed);
+    assert_eq!(detector.touches(), 3);
+    assert!((detector.support_price().unwrap() - 99.9).abs() < 1e-9);
+    assert!((detector.latest_lower_high().unwrap() - 102.1).abs() < 1e-9);
+}
+
+#[test]
+fn higher_high_invalidates() {
+    // Second bounce clears the first high at 104.
+    let candles = two_touches_then(&[(10, 0.5), (10, -0.5)]);
+    let mut detector = DescendingTriangleDetector::new("SOL", config());
+
+    let states = run(&mut detector, &candles);
+    assert!(states.contains(&DescendingTriangleState::Building));
+    assert!(states.contains(&DescendingTriangleState::Invalidated));
+    assert!(!states.contains(&DescendingTriangleState::Confirmed));
+}
+
+#[test]
+fn too_few_touches_never_qualifies() {
+    let candles = two_touches_then(&[(6, 0.5), (10, -0.5)]);
+    let mut detector = DescendingTriangleDetector::new(
+        "SOL",
+        DescendingTriangleConfig {
+            min_touches: 3,
+            ..config()
+        },
+    );
+
+    let alerts: Vec<_> = candles.iter().filter_map(|c| detector.update(c)).collect();
+    assert!(alerts.is_empty(), "{alerts:?}");
+    assert_eq!(detector.state(), DescendingTriangleState::Watching);
+    assert_eq!(DescendingTriangleState::Building.as_str(), "BUILDING");
+}
@@ -0,0 +1,424 @@
+mod common;
+
+use common::candles_from_closes;
+use perpscreener::business_logic::alerts::{AlertSeverity, AlertStage, PatternKind};
+use perpscreener::business_logic::double_bottom::{
+    DoubleBottomConfig, DoubleBottomDetector, DoubleBottomState, EmaTrendFilter,
+};
+use perpscreener::models::candle::Candle;
+
+fn path(start: f64, steps: &[(usize, f64)]) -> Vec<f64> {
+    let mut closes = Vec::new();
+    let mut price = start;
+    for &(count, step) in steps {
+        for _ in 0..count {
+            price += step;
+            closes.push(price);
+        }
+    }
+    closes
+}
+
+/// Test candles move a steady 0.5 per candle, so every candle's range is a
+/// full ATR; a 2 ATR reversal keeps swings to the real turns.
+fn config() -> DoubleBottomConfig {
+    DoubleBottomConfig {
+        rev_atr: 2.0,
+        ..DoubleBottomConfig::default()
+    }
+}
+
+/// Decline to 90, bounce to 95, then back down by `second_leg`.
+fn v_v(second_leg: (usize, f64), finish: (usize, f64)) -> Vec<Candle> {
+    let closes = path(104.0, &[(28, -0.5), (10, 0.5), second_leg, finish]);
+    candles_from_closes(104.0, &closes, 0.1)
+}
+
+#[test]
+fn v_v_shape_warns_then_confirms_above_the_neckline() {
+    let candles = v_v((10, -0.48), (14, 0.5));
+    let mut detector = DoubleBottomDetector::new("BTC", config());
+
+    let alerts: Vec<_> = candles.iter().filter_map(|c| detector.update(c)).collect();
+    assert_eq!(alerts.len(), 2, "{alerts:?}");
+
+    let warning = &alerts[0];
+    assert_eq!(warning.pattern, PatternKind::DoubleBottom);
+    assert_eq!(warning.stage, AlertStage::EarlyWarning);
+    assert_eq!(warning.severity(), AlertSeverity::Warning);
+    assert!((warning.level - 89.9).abs() < 1e-9);
+
+    let confirmation = &alerts[1];
+    assert_eq!(confirmation.stage, AlertStage::Confirmation);
+    assert_eq!(confirmation.severity(), AlertSeverity::Critical);
+    assert!((confirmation.level - 95.1).abs() < 1e-9);
+    assert!(confirmation.price > confirmation.level);
+
+    assert_eq!(detector.state(), DoubleBottomState::Confirmed);
+    assert!((detector.trough1_price().unwrap() - 89.9).abs() < 1e-9);
+    assert!((detector.trough2_price().unwrap() - 90.1).abs() < 1e-9);
+}
+
+#[test]
+fn break_below_the_first_trough_invalidates() {
+    let candles = v_v((14, -0.6), (20, 0.6));
+    let mut detector = DoubleBottomDetector::new("BTC", config());
+
+    let mut invalidated = false;
+    let mut confirmed = false;
+    for candle in &candles {
+        if let Some(alert) = detector.update(candle) {
+            confirmed |= alert.stage == AlertStage::Confirmation;
+        }
+        invalidated |= detector.state() == DoubleBottomState::Invalidated;
+    }
+    assert!(invalidated);
+    assert!(!confirmed);
+}
+
+#[test]
+fn second_trough_far_above_the_first_starts_over() {
+    // The second leg only retraces to ~93, outside the trough tolerance.
+    let candles = v_v((4, -0.5), (14, 0.5));
+    let mut detector = DoubleBottomDetector::new("BTC", config());
+
+    let alerts: Vec<_> = candles.iter().filter_map(|c| detector.update(c)).collect();
+    assert!(alerts.is_empty(), "{alerts:?}");
+    assert_ne!(detector.state(), DoubleBottomState::Confirmed);
+}
+
+/// Gaps below the warning zone, sits there, then climbs back into it with a
+/// one-candle pop followed by small red candles.
+fn retest_from_below() -> Vec<Candle> {
+    let closes = path(
+        104.0,
+        &[
+            (28, -0.5),
+            (10, 0.5),
+            (1, -6.2),
+            (6, 0.0),
+            (2, 0.4),
+            (1, 0.6),
+            (3, -0.1),
+        ],
+    );
+    candles_from_closes(104.0, &closes, 0.1)
+}
+
+#[test]
+fn ema_filter_ignores_red_candles_in_a_recovery() {
+    let candles = retest_from_below();
+
+    // The close-vs-three-back check sees the dip after the pop as a decline.
+    let mut naive = DoubleBottomDetector::new("BTC", config());
+    let alerts: Vec<_> = candles.iter().filter_map(|c| naive.update(c)).collect();
+    assert_eq!(alerts.len(), 1, "{alerts:?}");
+    assert_eq!(alerts[0].stage, AlertStage::EarlyWarning);
+
+    // Price is back above a rising EMA, so the filter holds the warning.
+    let mut filtered = DoubleBottomDetector::new(
+        "BTC",
+        DoubleBottomConfig {
+            ema_filter: Some(EmaTrendFilter {
+                period: 5,
+                slope_lookback: 3,
+            }),
+            ..config()
+        },
+    );
+    let alerts: Vec<_> = candles.iter().filter_map(|c| filtered.update(c)).collect();
+    assert!(alerts.is_empty(), "{alerts:?}");
+}
+
+#[test]
+fn ema_filter_still_warns_on_a_real_second_leg() {
+    let candles = v_v((10, -0.48), (14, 0.5));
+    let mut detector = DoubleBottomDetector::new(
+        "BTC",
+        DoubleBottomConfig {
+            ema_filter: Some(EmaTrendFilter::default()),
+            ..config()
+        },
+    );
+
+    let alerts: Vec<_> = candles.iter().filter_map(|c| detector.update(c)).collect();
+    assert_eq!(alerts.len(), 2, "{alerts:?}");
+    assert_eq!(alerts[0].stage, AlertStage::EarlyWarning);
+    assert_eq!(alerts[1].stage, AlertStage::Confirmation);
+}
+
+#[test]
+fn slower_second_leg_shows_a_bullish_rsi_divergence() {
+    // Trough 1 ends 28 straight red candles; trough 2 follows a bounce.
+    let candles = v_v((10, -0.48), (14, 0.5));
+    let mut detector = DoubleBottomDetector::new("BTC", config());
+
+    let mut diverged_before_confirming = false;
+    for candle in &candles {
+        detector.update(candle);
+        if detector.state() == DoubleBottomState::Forming && detector.trough2_price().is_some() {
+            diverged_before_confirming |= detector.rsi_divergence();
+        }
+    }
+    assert!(diverged_before_confirming);
+    assert_eq!(detector.state(), DoubleBottomState::Confirmed);
+}
+
+#[test]
+fn required_rsi_divergence_holds_the_confirmation() {
+    let candles = v_v((10, -0.48), (14, 0.5));
+    let mut detector = DoubleBottomDetector::new(
+        "BTC",
+        DoubleBottomConfig {
+            rsi_divergence_delta: 101.0,
+            require_rsi_divergence: true,
+            ..config()
+        },
+    );
+
+    let alerts: Vec<_> = candles.iter().filter_map(|c| detector.update(c)).collect();
+    assert!(
+        alerts.iter().all(|a| a.stage == AlertStage::EarlyWarning),
+        "{alerts:?}"
+    );
+    assert!(!detector.rsi_divergence());
+    assert_ne!(detector.state(), DoubleBottomState::Confirmed);
+}
+
+#[test]
+fn adx_at_trough1_gates_alerts() {
+    let candles = v_v((10, -0.48), (14, 0.5));
+    let run = |min_adx_at_trough1| {
+        let mut detector = DoubleBottomDetector::new(
+            "BTC",
+            DoubleBottomConfig {
+                min_adx_at_trough1,
+                ..config()
+            },
+        );
+        let alerts: Vec<_> = candles.iter().filter_map(|c| detector.update(c)).collect();
+        (alerts.len(), detector.adx_at_trough1())
+    };
+
+    // 28 straight red candles: the first trough ends a strong trend.
+    let (alerts, adx) = run(Some(50.0));
+    assert_eq!(alerts, 2);
+    assert!(adx.unwrap() > 50.0, "{adx:?}");
+
+    let (alerts, _) = run(Some(adx.unwrap() + 1.0));
+    assert_eq!(alerts, 0);
+}
+
+#[test]
+fn shorter_second_leg_on_the_same_volume_shows_an_obv_divergence() {
+    // Every candle trades 1.0, so OBV counts up minus down candles: 10 up
+    // and 8 down between the troughs leaves trough 2 two candles higher.
+    let candles = v_v((8, -0.6), (14, 0.5));
+    let mut detector = DoubleBottomDetector::new(
+        "BTC",
+        DoubleBottomConfig {
+            obv_divergence_margin: 1.0,
+            require_obv_divergence: true,
+            ..config()
+        },
+    );
+    let alerts: Vec<_> = candles.iter().filter_map(|c| detector.update(c)).collect();
+    assert!(detector.obv_divergence());
+    assert_eq!(alerts.last().unwrap().stage, AlertStage::Confirmation);
+
+    // A mirror-image second leg retests on the same OBV.
+    let candles = v_v((10, -0.48), (14, 0.5));
+    let mut detector = DoubleBottomDetector::new(
+        "BTC",
+        DoubleBottomConfig {
+            require_obv_divergence: true,
+            ..config()
+        },
+    );
+    let alerts: Vec<_> = candles.iter().filter_map(|c| detector.update(c)).collect();
+    assert!(!detector.obv_divergence());
+    assert!(alerts.iter().all(|a| a.stage == AlertStage::EarlyWarning));
+}
+
+#[test]
+fn fib_levels_track_the_bounce_leg_until_the_pattern_resolves() {
+    let candles = v_v((10, -0.48), (14, 0.5));
+    let mut detector = DoubleBottomDetector::new("BTC", config());
+
+    let mut deepest = 0.0_f64;
+    for candle in &candles {
+        detector.update(candle);
+        if detector.state() == DoubleBottomState::Confirmed {
+            break;
+        }
+        if let Some(fib) = detector.fib_levels() {
+            assert!((fib.from - 89.9).abs() < 1e-9);
+            deepest = deepest.max(fib.retracement_pct);
+        }
+    }
+    assert_eq!(detector.fib_levels(), None, "cleared once confirmed");
+    // The second leg gave back nearly all of the 89.9 -> 95.1 bounce.
+    assert!((90.0..100.0).contains(&deepest), "{deepest}");
+}
+
+#[test]
+fn state_strings_match_the_double_top_vocabulary() {
+    assert_eq!(DoubleBottomState::TroughFound.as_str(), "TROUGH_FOUND");
+    assert_eq!(DoubleBottomState::Confirmed.as_str(), "CONFIRMED");
+}
+
+#[test]
+fn default_config_is_valid() {
+    assert_eq!(
+        DoubleBottomConfig::default().validate(),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn validate_reports_each_nonsensical_setting() {
+    let base = DoubleBottomConfig::default();
+    let cases: Vec<(DoubleBottomConfig, &str)> = vec![
+        (
+            DoubleBottomConfig {
+                atr_period: 0,
+                ..base
+            },
+            "atr_period",
+        ),
+        (
+            DoubleBottomConfig {
+                rsi_period: 0,
+                ..base
+            },
+            "rsi_period",
+        ),
+        (
+            DoubleBottomConfig {
+                adx_period: 0,
+                ..base
+            },
+            "adx_period",
+        ),
+        (
+            DoubleBottomConfig {
+                trend_lookback: 0,
+                ..base
+            },
+            "trend_lookback",
+        ),
+        (
+            DoubleBottomConfig {
+                max_trough_distance: 3,
+                trend_lookback: 3,
+                ..base
+            },
+            "max_trough_distance",
+        ),
+        (
+            DoubleBottomConfig {
+                rev_atr: 0.0,
+                ..base
+            },
+            "rev_atr",
+        ),
+        (
+            DoubleBottomConfig {
+                rev_atr: f64::NAN,
+                ..base
+            },
+            "rev_atr",
+        ),
+        (
+            DoubleBottomConfig {
+                breakout_buffer: -0.1,
+                ..base
+            },
+            "breakout_buffer",
+        ),
+        (
+            DoubleBottomConfig {
+                trough_tolerance_pct: -5.0,
+                ..base
+            },
+            "trough_tolerance_pct",
+        ),
+        (
+            DoubleBottomConfig {
+                min_bounce_pct: 0.0,
+                ..base
+            },
+            "min_bounce_pct",
+        ),
+        (
+            DoubleBottomConfig {
+                approach_threshold_pct: 150.0,
+                ..base
+            },
+            "approach_threshold_pct",
+        ),
+        (
+            DoubleBottomConfig {
+                trough_fail_pct: -1.0,
+                ..base
+            },
+            "trough_fail_pct",
+        ),
+        (
+            DoubleBottomConfig {
+                rsi_divergence_delta: -1.0,
+                ..base
+            },
+            "rsi_divergence_delta",
+        ),
+        (
+            DoubleBottomConfig {
+                min_adx_at_trough1: Some(120.0),
+                ..base
+            },
+            "min_adx_at_trough1",
+        ),
+        (
+            DoubleBottomConfig {
+                obv_divergence_margin: -1.0,
+                ..base
+            },
+            "obv_divergence_margin",
+        ),
+        (
+            DoubleBottomConfig {
+                ema_filter: Some(EmaTrendFilter {
+                    period: 0,
+                    slope_lookback: 3,
+                }),
+                ..base
+            },
+            "ema_filter.period",
+        ),
+        (
+            DoubleBottomConfig {
+                ema_filter: Some(EmaTrendFilter {
+                    period: 20,
+                    slope_lookback: 0,
+                }),
+                ..base
+            },
+            "ema_filter.slope_lookback",
+        ),
+    ];
+    for (config, field) in cases {
+        let errors = config.validate();
+        assert_eq!(errors.len(), 1, "{field}: {errors:?}");
+        assert!(errors[0].starts_with(field), "{field}: {errors:?}");
+    }
+}
+
+#[test]
+fn validate_lists_every_violation() {
+    let config = DoubleBottomConfig {
+        atr_period: 0,
+        trough_tolerance_pct: -5.0,
+        ..DoubleBottomConfig::default()
+    };
+    assert_eq!(config.validate().len(), 2);
+}
@@ -0,0 +1,879 @@
+mod common;
+
+use common::{candle, candles_from_closes, MINUTE_MS};
+use perpscreener::business_logic::alerts::{
+    AlertStage, ConfirmationMode, PatternKind, TrendDirection,
+};
+use perpscreener::business_logic::double_bottom::EmaTrendFilter;
+use perpscreener::business_logic::double_top::{
+    AtrRule, DoubleTopConfig, DoubleTopDetector, DoubleTopDiagnostics, DoubleTopState,
+    HigherTimeframeFilter, HigherTimeframeMode, InvalidationReason,
+};
+use perpscreener::business_logic::open_interest::OiRule;
+use perpscreener::business_logic::patterns::Confluence;
+use perpscreener::models::candle::Candle;
+
+fn path(start: f64, steps: &[(usize, f64)]) -> Vec<f64> {
+    let mut closes = Vec::new();
+    let mut price = start;
+    for &(count, step) in steps {
+        for _ in 0..count {
+            price += step;
+            closes.push(price);
+        }
+    }
+    closes
+}
+
+/// Steady 0.5 moves make every candle a full ATR wide; a 2 ATR reversal keeps
+/// swings to the real turns.
+fn config() -> DoubleTopConfig {
+    DoubleTopConfig {
+        rev_atr: 2.0,
+        ..DoubleTopConfig::default()
+    }
+}
+
+/// Peak at 98, pullback to 94, then `rest`.
+fn peak_and_pullback_then(rest: &[(usize, f64)]) -> Vec<Candle> {
+    let mut steps = vec![(20, 0.5), (8, -0.5)];
+    steps.extend_from_slice(rest);
+    candles_from_closes(88.0, &path(88.0, &steps), 0.1)
+}
+
+fn run(detector: &mut DoubleTopDetector, candles: &[Candle]) -> Vec<DoubleTopState> {
+    candles
+        .iter()
+        .map(|c| {
+            detector.update(c);
+            detector.state()
+        })
+        .collect()
+}
+
+#[test]
+fn retest_warns_and_neckline_break_confirms() {
+    let candles = peak_and_pullback_then(&[(8, 0.5), (12, -0.5)]);
+    let mut detector = DoubleTopDetector::new("SOL", config(), "1m");
+
+    let alerts: Vec<_> = candles.iter().filter_map(|c| detector.update(c)).collect();
+    assert_eq!(alerts.len(), 2, "{alerts:?}");
+    assert!(alerts.iter().all(|a| a.pattern == PatternKind::DoubleTop));
+    assert_eq!(alerts[0].stage, AlertStage::EarlyWarning);
+    assert!((alerts[0].level - 98.1).abs() < 1e-9);
+    assert_eq!(alerts[1].stage, AlertStage::Confirmation);
+    assert!((alerts[1].level - 93.9).abs() < 1e-9);
+    assert_eq!(alerts[1].confirmation_mode, Some(ConfirmationMode::Close));
+    assert_eq!(alerts[0].target_price, None);
+
+    // Measured move: the neckline less the height from the peak average.
+    let target = 93.9 - (98.1 - 93.9);
+    assert!((alerts[1].target_price.unwrap() - target).abs() < 1e-9);
+    let height_pct = (98.1 - 93.9) / 98.1 * 100.0;
+    assert!((alerts[1].pattern_height_pct.unwrap() - height_pct).abs() < 1e-9);
+    let status = detector.status();
+    assert_eq!(status.target_price, alerts[1].target_price);
+    assert_eq!(status.pattern_height_pct, alerts[1].pattern_height_pct);
+
+    assert_eq!(detector.state(), DoubleTopState::Confirmed);
+    assert!((detector.peak1_price().unwrap() - 98.1).abs() < 1e-9);
+    assert!((detector.peak2_price().unwrap() - 98.1).abs() < 1e-9);
+    assert!((detector.neckline_price().unwrap() - 93.9).abs() < 1e-9);
+
+    // Each swing is dated by the candle that made its extreme.
+    let status = detector.status();
+    assert_eq!(status.peak1_time_ms, Some(candles[19].open_time));
+    assert_eq!(status.trough_time_ms, Some(candles[27].open_time));
+    assert_eq!(status.peak2_time_ms, Some(candles[35].open_time));
+}
+
+#[test]
+fn retest_above_the_fail_level_invalidates() {
+    // The second rally runs to 100, past 98.1 * 1.015.
+    let candles = peak_and_pullback_then(&[(12, 0.5), (12, -0.5)]);
+    let mut detector = DoubleTopDetector::new("SOL", config(), "1m");
+
+    let states = run(&mut detector, &candles);
+    assert!(states.contains(&DoubleTopState::TroughFound));
+    assert!(states.contains(&DoubleTopState::Invalidated));
+    assert!(!states.contains(&DoubleTopState::Confirmed));
+}
+
+/// Why the pattern was invalidated, as of the candle that invalidated it.
+fn invalidation(config: DoubleTopConfig, candles: &[Candle]) -> Option<InvalidationReason> {
+    let mut detector = DoubleTopDetector::new("SOL", config, "1m");
+    candles.iter().find_map(|c| {
+        detector.update(c);
+        (detector.state() == DoubleTopState::Invalidated).then(|| {
+            let status = detector.status();
+            let reason = detector.invalidation_reason();
+            assert_eq!(status.invalidation, reason);
+            assert_eq!(
+                status.invalidation_reason.as_deref(),
+                reason.map(|r| r.as_str())
+            );
+            reason
+        })
+    })?
+}
+
+#[test]
+fn invalidation_says_why() {
+    let Some(InvalidationReason::PeakExceeded { fail_level, high }) =
+        invalidation(config(), &peak_and_pullback_then(&[(12, 0.5)]))
+    else {
+        panic!("expected peak_exceeded");
+    };
+    assert!((fail_level - 98.1 * 1.015).abs() < 1e-9);
+    assert!((high - 99.6).abs() < 1e-9);
+
+    let short = DoubleTopConfig {
+        max_peak_distance: 20,
+        ..config()
+    };
+    assert_eq!(
+        invalidation(short, &peak_and_pullback_then(&[(8, 0.5), (12, -0.5)])),
+        Some(InvalidationReason::TimedOut {
+            candles_since_peak1: 21
+        })
+    );
+
+    let candles = peak_and_pullback_then(&[(8, 0.5), (12, -0.5), (5, 0.5)]);
+    let reason = invalidation(config(), &candles);
+    assert!(
+        matches!(reason, Some(InvalidationReason::RetestFailed { close, .. }) if close > 93.9),
+        "{reason:?}"
+    );
+}
+
+#[test]
+fn manual_reset_is_reported_until_the_next_peak_1() {
+    let candles = peak_and_pullback_then(&[(8, 0.5), (12, -0.5)]);
+    let mut detector = DoubleTopDetector::new("SOL", config(), "1m");
+    run(&mut detector, &candles[..33]);
+    assert_eq!(detector.state(), DoubleTopState::TroughFound);
+
+    detector.reset();
+    assert_eq!(detector.state(), DoubleTopState::Invalidated);
+    assert_eq!(
+        detector.invalidation_reason(),
+        Some(InvalidationReason::ManualReset)
+    );
+    assert_eq!(
+        detector.status().invalidation_reason.as_deref(),
+        Some("manual_reset")
+    );
+
+    // The second rally's high starts over as peak 1.
+    run(&mut detector, &candles[33..]);
+    assert_ne!(detector.state(), DoubleTopState::Invalidated);
+    assert_eq!(detector.status().peak1_time_ms, Some(candles[35].open_time));
+    assert_eq!(detector.invalidation_reason(), None);
+    assert_eq!(detector.status().invalidation_reason, None);
+}
+
+#[test]
+fn gap_in_the_feed_still_ages_the_pattern() {
+    let config = DoubleTopConfig {
+        max_peak_distance: 40,
+        ..config()
+    };
+    let candles = peak_and_pullback_then(&[(8, 0.5), (12, -0.5)]);
+    let mut detector = DoubleTopDetector::new("SOL", config.clone(), "1m");
+    let states = run(&mut detector, &candles);
+    assert!(states.contains(&DoubleTopState::Confirmed));
+
+    // The same candles with the feed missing 30 minutes during the pullback:
+    // peak 1 is now more than 40 candles before the breakdown.
+    let gapped: Vec<Candle> = candles
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            let mut c = *c;
+            if i >= 25 {
+                c.open_time += 30 * MINUTE_MS;
+                c.close_time += 30 * MINUTE_MS;
+            }
+            c
+        })
+        .collect();
+    let mut detector = DoubleTopDetector::new("SOL", config, "1m");
+    let states = run(&mut detector, &gapped);
+    assert!(states.contains(&DoubleTopState::Invalidated));
+    assert!(!states.contains(&DoubleTopState::Confirmed));
+}
+
+#[test]
+fn repeated_candles_are_ignored() {
+    let candles = peak_and_pullback_then(&[(8, 0.5), (12, -0.5)]);
+    let mut detector = DoubleTopDetector::new("SOL", config(), "1m");
+
+    let mut alerts = Vec::new();
+    for (i, candle) in candles.iter().enumerate() {
+        alerts.extend(detector.update(candle));
+        // Re-delivering an earlier candle changes nothing.
+        alerts.extend(detector.update(&candles[i / 2]));
+    }
+    assert_eq!(alerts.len(), 2, "{alerts:?}");
+    assert_eq!(detector.state(), DoubleTopState::Confirmed);
+    assert_eq!(detector.status().candles_since_peak1, Some(28));
+}
+
+fn alert_stages(config: DoubleTopConfig, candles: &[Candle]) -> Vec<AlertStage> {
+    let mut detector = DoubleTopDetector::new("SOL", config, "1m");
+    candles
+        .iter()
+        .filter_map(|c| detector.update(c))
+        .map(|a| a.stage)
+        .collect()
+}
+
+#[test]
+fn atr_minimums_replace_the_percentage_ones() {
+    // The pullback and the height are both 4.2, about 4.3% and 6 ATRs.
+    let candles = peak_and_pullback_then(&[(8, 0.5), (12, -0.5)]);
+    let both = [AlertStage::EarlyWarning, AlertStage::Confirmation];
+
+    // Too shallow by %, deep enough in ATRs.
+    let height = DoubleTopConfig {
+        min_pattern_height_pct: 5.0,
+        min_pattern_height_atr: Some(5.0),
+        ..config()
+    };
+    assert_eq!(alert_stages(height, &candles), both);
+    let pullback = DoubleTopConfig {
+        min_pullback_pct: 5.0,
+        min_pullback_atr: Some(5.0),
+        ..config()
+    };
+    assert_eq!(alert_stages(pullback, &candles), both);
+
+    // Deep enough by %, too shallow in ATRs.
+    let height = DoubleTopConfig {
+        min_pattern_height_atr: Some(7.0),
+        ..config()
+    };
+    assert_eq!(alert_stages(height, &candles), []);
+    let pullback = DoubleTopConfig {
+        min_pullback_atr: Some(7.0),
+        ..config()
+    };
+    assert_eq!(alert_stages(pullback, &candles), []);
+}
+
+#[test]
+fn atr_minimums_can_apply_alongside_the_percentage_ones() {
+    let candles = peak_and_pullback_then(&[(8, 0.5), (12, -0.5)]);
+    let config = DoubleTopConfig {
+        atr_rule: AtrRule::Both,
+        min_pattern_height_atr: Some(5.0),
+        min_pullback_atr: Some(5.0),
+        ..config()
+    };
+    assert_eq!(
+        alert_stages(config.clone(), &candles),
+        [AlertStage::EarlyWarning, AlertStage::Confirmation]
+    );
+
+    // Passing the ATR rule no longer makes up for failing the % one.
+    for config in [
+        DoubleTopConfig {
+            min_pattern_height_pct: 5.0,
+            ..config.clone()
+        },
+        DoubleTopConfig {
+            min_pullback_pct: 5.0,
+            ..config
+        },
+    ] {
+        assert_eq!(alert_stages(config, &candles), []);
+    }
+}
+
+#[test]
+fn second_peak_needs_min_peak_distance_candles() {
+    // Peak 2 comes 16 candles after peak 1.
+    let candles = peak_and_pullback_then(&[(8, 0.5), (12, -0.5)]);
+    let at = |min_peak_distance| {
+        let config = DoubleTopConfig {
+            min_peak_distance,
+            ..config()
+        };
+        let mut detector = DoubleTopDetector::new("SOL", config, "1m");
+        run(&mut detector, &candles);
+        (detector.state(), detector.diagnostics())
+    };
+
+    let (state, diagnostics) = at(16);
+    assert_eq!(state, DoubleTopState::Confirmed);
+    assert_eq!(diagnostics, DoubleTopDiagnostics::default());
+
+    let (state, diagnostics) = at(17);
+    assert_ne!(state, DoubleTopState::Confirmed);
+    assert_eq!(diagnostics.too_close, 1);
+    assert_eq!(diagnostics.asymmetric, 0);
+}
+
+#[test]
+fn lopsided_legs_fail_the_symmetry_check() {
+    // 8 candles down to the neckline, then 12 back up to a 98.3 high.
+    let candles = peak_and_pullback_then(&[(12, 0.35), (16, -0.5)]);
+    let with_ratio = |max_leg_ratio| {
+        let config = DoubleTopConfig {
+            max_leg_ratio: Some(max_leg_ratio),
+            ..config()
+        };
+        let mut detector = DoubleTopDetector::new("SOL", config, "1m");
+        let stages = candles
+            .iter()
+            .filter_map(|c| detector.update(c))
+            .map(|a| a.stage)
+            .collect::<Vec<_>>();
+        (stages, detector.status().diagnostics)
+    };
+
+    let (stages, diagnostics) = with_ratio(1.5);
+    assert_eq!(stages, [AlertStage::EarlyWarning, AlertStage::Confirmation]);
+    assert_eq!(diagnostics.asymmetric, 0);
+
+    let (stages, diagnostics) = with_ratio(1.4);
+    assert_eq!(stages, [AlertStage::EarlyWarning]);
+    assert_eq!(diagnostics.asymmetric, 1);
+}
+
+#[test]
+fn ema_filter_ignores_a_single_spike_toward_peak_1() {
+    // A slide to 92, then one candle jumps back to 97.2, within 1% of peak 1.
+    let candles = peak_and_pullback_then(&[(10, -0.2), (1, 5.2)]);
+
+    // The close-vs-three-back check takes the spike for an uptrend.
+    assert_eq!(alert_stages(config(), &candles), [AlertStage::EarlyWarning]);
+
+    // The EMA is still lower than five candles back.
+    let filtered = DoubleTopConfig {
+        ema_filter: Some(EmaTrendFilter {
+            period: 20,
+            slope_lookback: 5,
+        }),
+        ..config()
+    };
+    assert_eq!(alert_stages(filtered, &candles), []);
+}
+
+#[test]
+fn ema_filter_still_warns_on_a_steady_rally() {
+    let candles = peak_and_pullback_then(&[(8, 0.5), (12, -0.5)]);
+    let config = DoubleTopConfig {
+        ema_filter: Some(EmaTrendFilter::default()),
+        ..config()
+    };
+    assert_eq!(
+        alert_stages(config, &candles),
+        [AlertStage::EarlyWarning, AlertStage::Confirmation]
+    );
+}
+
+#[test]
+fn zero_trend_lookback_skips_the_uptrend_check() {
+    // The retest stalls at 97.5 and 98: level with the closes 16 candles
+    // earlier, on the way up to peak 1.
+    let candles = peak_and_pullback_then(&[(8, 0.5), (12, -0.5)]);
+    let flat = DoubleTopConfig {
+        trend_lookback: 16,
+        ..config()
+    };
+    assert_eq!(
+        alert_stages(flat.clone(), &candles),
+        [AlertStage::Confirmation]
+    );
+
+    let skipped = DoubleTopConfig {
+        trend_lookback: 0,
+        ..flat
+    };
+    assert_eq!(
+        alert_stages(skipped, &candles),
+        [AlertStage::EarlyWarning, AlertStage::Confirmation]
+    );
+}
+
+#[test]
+fn smallest_window_still_looks_back_exactly() {
+    // One candle more than the lookback: the window is trimmed on every
+    // candle, yet the comparison stays trend_lookback closed candles back.
+    let candles = peak_and_pullback_then(&[(8, 0.5), (12, -0.5)]);
+    for trend_lookback in [3, 16] {
+        let full = DoubleTopConfig {
+            trend_lookback,
+            ..config()
+        };
+        let small = DoubleTopConfig {
+            history_window: trend_lookback + 1,
+            ..full.clone()
+        };
+        assert_eq!(
+            alert_stages(small, &candles),
+            alert_stages(full, &candles),
+            "trend_lookback {trend_lookback}"
+        );
+    }
+}
+
+#[test]
+fn confirmed_state_expires_after_its_ttl() {
+    let config = DoubleTopConfig {
+        confirmed_ttl_candles: 5,
+        ..config()
+    };
+    // A flat tail after the breakdown, so nothing new forms.
+    let candles = peak_and_pullback_then(&[(8, 0.5), (12, -0.5), (10, 0.0)]);
+    let mut detector = DoubleTopDetector::new("SOL", config, "1m");
+
+    let states = run(&mut detector, &candles);
+    let confirmed = states
+        .iter()
+        .position(|s| *s == DoubleTopState::Confirmed)
+        .unwrap();
+    assert!(states[confirmed..confirmed + 5]
+        .iter()
+        .all(|s| *s == DoubleTopState::Confirmed));
+    assert_eq!(states[confirmed + 5], DoubleTopState::Watching);
+
+    // Only the live state ages out; the pattern's prices stay.
+    let status = detector.status();
+    assert_eq!(status.state, "WATCHING");
+    assert!((status.peak2_price.unwrap() - 98.1).abs() < 1e-9);
+    assert!((status.neckline_price.unwrap() - 93.9).abs() < 1e-9);
+}
+
+/// The standard pattern confirmed at candle 44, then chop well below the
+/// neckline: a bounce to 91.5 that makes a swing high, and a flat tail.
+fn post_breakdown_chop() -> Vec<Candle> {
+    peak_and_pullback_then(&[
+        (8, 0.5),
+        (12, -0.5),
+        (6, -0.5),
+        (5, 0.5),
+        (6, -0.5),
+        (12, 0.0),
+    ])
+}
+
+#[test]
+fn cooldown_ignores_new_peaks_after_a_confirmation() {
+    let candles = post_breakdown_chop();
+    let mut detector = DoubleTopDetector::new("SOL", config(), "1m");
+    run(&mut detector, &candles[..65]);
+    // Without a cooldown the bounce starts a pattern of its own.
+    assert_eq!(detector.candidates().len(), 2);
+    assert_eq!(detector.status().cooldown_remaining_candles, None);
+
+    let config = DoubleTopConfig {
+        post_confirm_cooldown_candles: 30,
+        ..config()
+    };
+    let mut detector = DoubleTopDetector::new("SOL", config.clone(), "1m");
+    run(&mut detector, &candles[..65]);
+    assert_eq!(detector.candidates().len(), 1);
+    assert_eq!(detector.state(), DoubleTopState::Confirmed);
+    // 20 of the 30 candles have passed since the breakdown.
+    assert_eq!(detector.status().cooldown_remaining_candles, Some(10));
+
+    // Moving far enough from the neckline ends it early.
+    let config = DoubleTopConfig {
+        cooldown_exit_atr: Some(2.0),
+        ..config
+    };
+    let mut detector = DoubleTopDetector::new("SOL", config, "1m");
+    run(&mut detector, &candles[..65]);
+    assert_eq!(detector.candidates().len(), 2);
+    assert_eq!(detector.status().cooldown_remaining_candles, None);
+}
+
+#[test]
+fn confirmation_past_its_ttl_cools_down_before_watching() {
+    let config = DoubleTopConfig {
+        confirmed_ttl_candles: 10,
+        post_confirm_cooldown_candles: 30,
+        ..config()
+    };
+    let mut detector = DoubleTopDetector::new("SOL", config, "1m");
+    let states = run(&mut detector, &post_breakdown_chop());
+
+    assert_eq!(states[44], DoubleTopState::Confirmed);
+    assert_eq!(states[53], DoubleTopState::Confirmed);
+    assert!(states[54..74]
+        .iter()
+        .all(|s| *s == DoubleTopState::CoolingDown));
+    assert_eq!(states[74], DoubleTopState::Watching);
+    assert_eq!(DoubleTopState::CoolingDown.as_str(), "COOLING_DOWN");
+    assert_eq!(detector.status().cooldown_remaining_candles, None);
+}
+
+#[test]
+fn rejected_rally_back_to_the_neckline_raises_a_retest_alert() {
+    // Breakdown to 92, a rally back up to 93.5 (high 93.6, inside the band
+    // below the 93.9 neckline), then a drop back out of it.
+    let candles = peak_and_pullback_then(&[(8, 0.5), (12, -0.5), (3, 0.5), (3, -0.5)]);
+    let mut detector = DoubleTopDetector::new("SOL", config(), "1m");
+
+    let mut states = Vec::new();
+    let mut alerts = Vec::new();
+    for candle in &candles {
+        alerts.extend(detector.update(candle));
+        states.push(detector.state());
+    }
+    assert!(states.contains(&DoubleTopState::RetestWatch));
+    assert!(states.contains(&DoubleTopState::Retested));
+    let stages: Vec<_> = alerts.iter().map(|a| a.stage).collect();
+    assert_eq!(
+        stages,
+        [
+            AlertStage::EarlyWarning,
+            AlertStage::Confirmation,
+            AlertStage::Retest
+        ]
+    );
+    assert!((alerts[2].level - 93.9).abs() < 1e-9);
+    assert!((alerts[2].retest_high.unwrap() - 93.6).abs() < 1e-9);
+    assert_eq!(DoubleTopState::RetestWatch.as_str(), "RETEST_WATCH");
+    assert_eq!(DoubleTopState::Retested.as_str(), "RETESTED");
+}
+
+#[test]
+fn close_back_above_the_neckline_fails_the_retest() {
+    let candles = peak_and_pullback_then(&[(8, 0.5), (12, -0.5), (5, 0.5)]);
+    let mut detector = DoubleTopDetector::new("SOL", config(), "1m");
+
+    let states = run(&mut detector, &candles);
+    assert!(states.contains(&DoubleTopState::RetestWatch));
+    assert!(!states.contains(&DoubleTopState::Retested));
+    assert_eq!(detector.state(), DoubleTopState::Invalidated);
+}
+
+#[test]
+fn retest_outside_the_window_is_not_tracked() {
+    let config = DoubleTopConfig {
+        retest_window_candles: 3,
+        ..config()
+    };
+    let candles = peak_and_pullback_then(&[(8, 0.5), (12, -0.5), (3, 0.5), (3, -0.5)]);
+    let mut detector = DoubleTopDetector::new("SOL", config, "1m");
+
+    let states = run(&mut detector, &candles);
+    assert!(!states.contains(&DoubleTopState::RetestWatch));
+    assert!(states.contains(&DoubleTopState::Confirmed));
+}
+
+/// Confidence once `closes` have played out, with every candle from
+/// `from` on at `volume`.
+fn confidence_after(closes: &[f64], from: usize, volume: f64) -> f64 {
+    let mut candles = candles_from_closes(88.0, closes, 0.1);
+    for candle in &mut candles[from..] {
+        candle.volume = volume;
+    }
+    let mut detector = DoubleTopDetector::new("SOL", config(), "1m");
+    run(&mut detector, &candles);
+    assert_eq!(detector.state(), DoubleTopState::Confirmed);
+    detector.status().confidence.unwrap()
+}
+
+#[test]
+fn textbook_double_top_scores_well_above_a_marginal_one() {
+    // Equal peaks, even legs, a deep trough and a heavy breakdown.
+    let textbook = path(88.0, &[(20, 0.5), (8, -0.5), (8, 0.5), (12, -0.5)]);
+    let textbook = confidence_after(&textbook, 36, 3.0);
+    // A lower second peak after a slower, longer leg, a shallower trough
+    // and a breakdown on thin volume.
+    let marginal = path(88.0, &[(20, 0.5), (7, -0.5), (10, 0.3), (12, -0.5)]);
+    let marginal = confidence_after(&marginal, 37, 0.5);
+
+    assert!(textbook > 95.0, "{textbook}");
+    assert!(textbook - marginal > 20.0, "{textbook} vs {marginal}");
+}
+
+#[test]
+fn confidence_is_only_scored_while_forming_or_confirmed() {
+    let candles = peak_and_pullback_then(&[(8, 0.5), (12, -0.5)]);
+    let mut detector = DoubleTopDetector::new("SOL", config(), "1m");
+    for candle in &candles {
+        detector.update(candle);
+        let scored = detector.status().confidence.is_some();
+        let expected = matches!(
+            detector.state(),
+            DoubleTopState::Forming | DoubleTopState::Confirmed
+        );
+        assert_eq!(scored, expected, "{:?}", detector.state());
+    }
+}
+
+#[test]
+fn screener_confluence_adds_to_the_confidence_score() {
+    let closes = path(88.0, &[(20, 0.5), (7, -0.5), (10, 0.3), (12, -0.5)]);
+    let mut detector = DoubleTopDetector::new("SOL", config(), "1m");
+    run(&mut detector, &candles_from_closes(88.0, &closes, 0.1));
+    let base = detector.status().confidence.unwrap();
+    let with = |detector: &mut DoubleTopDetector, confluence: Confluence| {
+        detector.set_confluence(&confluence);
+        detector.status().confidence.unwrap()
+    };
+
+    let oi = Confluence {
+        oi_rules: vec![OiRule::SqueezeFuel],
+        ..Confluence::default()
+    };
+    assert!((with(&mut detector, oi.clone()) - base - 10.0).abs() < 1e-9);
+    // A premium beyond the band only counts once it has lasted.
+    let brief = Confluence {
+        premium_pct: Some(0.8),
+        premium_extreme_ms: 10 * MINUTE_MS,
+        ..oi.clone()
+    };
+    assert!((with(&mut detector, brief) - base - 10.0).abs() < 1e-9);
+    let persistent = Confluence {
+        premium_pct: Some(0.8),
+        premium_extreme_ms: 30 * MINUTE_MS,
+        ..oi
+    };
+    assert!((with(&mut detector, persistent.clone()) - base - 20.0).abs() < 1e-9);
+    // A discount is no sign of crowded longs.
+    let discount = Confluence {
+        premium_pct: Some(-0.8),
+        ..persistent.clone()
+    };
+    assert!((with(&mut detector, discount) - base - 10.0).abs() < 1e-9);
+
+    let status = detector.status();
+    assert_eq!(status.premium_pct, Some(-0.8));
+    assert_eq!(status.oi_rules, vec![OiRule::SqueezeFuel]);
+
+    // Never past 100.
+    let textbook = path(88.0, &[(20, 0.5), (8, -0.5), (8, 0.5), (12, -0.5)]);
+    let mut candles = candles_from_closes(88.0, &textbook, 0.1);
+    for candle in &mut candles[36..] {
+        candle.volume = 3.0;
+    }
+    let mut detector = DoubleTopDetector::new("SOL", config(), "1m");
+    run(&mut detector, &candles);
+    assert_eq!(with(&mut detector, persistent), 100.0);
+}
+
+#[test]
+fn volume_filter_withholds_a_thin_breakdown() {
+    let config = DoubleTopConfig {
+        min_breakdown_volume_mult: Some(1.5),
+        ..config()
+    };
+    let mut candles = peak_and_pullback_then(&[(8, 0.5), (12, -0.5)]);
+    let mut detector = DoubleTopDetector::new("SOL", config.clone(), "1m");
+    let states = run(&mut detector, &candles);
+    assert!(!states.contains(&DoubleTopState::Confirmed));
+    assert_eq!(detector.state(), DoubleTopState::Forming);
+
+    // The same breakdown on twice the usual volume confirms.
+    for candle in &mut candles[44..] {
+        candle.volume = 2.0;
+    }
+    let mut detector = DoubleTopDetector::new("SOL", config, "1m");
+    let alerts: Vec<_> = candles.iter().filter_map(|c| detector.update(c)).collect();
+    assert_eq!(detector.state(), DoubleTopState::Confirmed);
+    assert_eq!(alerts.last().unwrap().open_time, candles[44].open_time);
+}
+
+#[test]
+fn confirmation_mode_decides_whether_a_wick_confirms() {
+    // Back down to 94 after the second peak, then a candle that wicks to 93
+    // (below the break level) but closes back at 94.
+    let mut candles = peak_and_pullback_then(&[(8, 0.5), (8, -0.5)]);
+    candles.push(candle(candles.len() as u64, 94.0, 94.1, 93.0, 94.0));
+
+    for (mode, confirms) in [
+        (ConfirmationMode::Close, false),
+        (ConfirmationMode::Low, true),
+    ] {
+        let config = DoubleTopConfig {
+            confirmation_mode: mode,
+            ..config()
+        };
+        let mut detector = DoubleTopDetector::new("SOL", config, "1m");
+        let alert = candles
+            .iter()
+            .filter_map(|c| detector.update(c))
+            .find(|a| a.stage == AlertStage::Confirmation);
+        assert_eq!(alert.is_some(), confirms, "{mode:?}");
+        if let Some(alert) = alert {
+            assert_eq!(alert.confirmation_mode, Some(ConfirmationMode::Low));
+            assert_eq!(alert.price, 94.0);
+        }
+        let expected = if confirms {
+            DoubleTopState::Confirmed
+        } else {
+            DoubleTopState::Forming
+        };
+        assert_eq!(detector.state(), expected, "{mode:?}");
+    }
+}
+
+/// The standard pattern with volume 3 around peak 1 (candle 19) and
+/// `rally` on the way into and around peak 2 (candle 35).
+fn with_peak_volumes(rally: f64) -> Vec<Candle> {
+    let mut candles = peak_and_pullback_then(&[(8, 0.5), (12, -0.5)]);
+    for candle in &mut candles[17..=21] {
+        candle.volume = 3.0;
+    }
+    for candle in &mut candles[30..=37] {
+        candle.volume = rally;
+    }
+    candles
+}
+
+#[test]
+fn lower_volume_on_peak_2_is_a_divergence() {
+    let mut detector = DoubleTopDetector::new("SOL", config(), "1m");
+    run(&mut detector, &with_peak_volumes(1.0));
+    let status = detector.status();
+    assert!((status.peak_volume_ratio.unwrap() - 5.0 / 15.0).abs() < 1e-9);
+    assert_eq!(status.volume_divergence, Some(true));
+
+    let mut detector = DoubleTopDetector::new("SOL", config(), "1m");
+    run(&mut detector, &with_peak_volumes(3.0));
+    assert_eq!(detector.status().volume_divergence, Some(false));
+}
+
+#[test]
+fn required_divergence_gates_the_early_warning() {
+    let config = DoubleTopConfig {
+        require_volume_divergence: true,
+        ..config()
+    };
+    for (rally, warns) in [(1.0, true), (3.0, false)] {
+        let mut detector = DoubleTopDetector::new("SOL", config.clone(), "1m");
+        let stages: Vec<_> = with_peak_volumes(rally)
+            .iter()
+            .filter_map(|c| detector.update(c))
+            .map(|a| a.stage)
+            .collect();
+        assert_eq!(stages.contains(&AlertStage::EarlyWarning), warns, "{rally}");
+        assert!(stages.contains(&AlertStage::Confirmation));
+    }
+}
+
+/// Peak at 98, pullback to 94, a rally to 100 too far above peak 1 to be its
+/// second peak, then a deeper pullback to 93 and a rally off it.
+fn overlapping_setups() -> (DoubleTopConfig, Vec<Candle>) {
+    let config = DoubleTopConfig {
+        peak_fail_pct: 5.0,
+        min_pattern_height_pct: 5.0,
+        approach_threshold_pct: 10.0,
+        ..config()
+    };
+    let candles = peak_and_pullback_then(&[(12, 0.5), (14, -0.5), (6, 0.5)]);
+    (config, candles)
+}
+
+#[test]
+fn newer_setup_is_tracked_alongside_a_pending_one() {
+    let (config, candles) = overlapping_setups();
+    let mut detector = DoubleTopDetector::new("SOL", config.clone(), "1m");
+    run(&mut detector, &candles[..50]);
+
+    let candidates = detector.candidates();
+    assert_eq!(candidates.len(), 2, "{candidates:?}");
+    assert!((candidates[0].peak1_price - 98.1).abs() < 1e-9);
+    assert_eq!(candidates[0].state, "TROUGH_FOUND");
+    assert!((candidates[1].peak1_price - 100.1).abs() < 1e-9);
+    assert_eq!(candidates[1].state, "PEAK_FOUND");
+    // The getters follow the more advanced of the two.
+    assert_eq!(detector.state(), DoubleTopState::TroughFound);
+    assert!((detector.peak1_price().unwrap() - 98.1).abs() < 1e-9);
+    assert_eq!(detector.status().candidates, candidates);
+
+    let mut detector = DoubleTopDetector::new(
+        "SOL",
+        DoubleTopConfig {
+            max_candidates: 1,
+            ..config
+        },
+        "1m",
+    );
+    run(&mut detector, &candles[..50]);
+    let candidates = detector.candidates();
+    assert_eq!(candidates.len(), 1);
+    assert!((candidates[0].peak1_price - 100.1).abs() < 1e-9);
+}
+
+#[test]
+fn candidates_warning_on_the_same_candle_raise_one_alert() {
+    let (config, candles) = overlapping_setups();
+    let mut detector = DoubleTopDetector::new("SOL", config, "1m");
+    let stages: Vec<_> = candles
+        .iter()
+        .filter_map(|c| detector.update(c))
+        .map(|a| a.stage)
+        .collect();
+
+    assert_eq!(stages, [AlertStage::EarlyWarning]);
+    let candidates = detector.candidates();
+    assert_eq!(candidates.len(), 2, "{candidates:?}");
+    assert!(candidates.iter().all(|c| c.state == "FORMING"));
+}
+
+#[test]
+fn htf_filter_drops_alerts_against_an_up_trend() {
+    let candles = peak_and_pullback_then(&[(8, 0.5), (12, -0.5)]);
+    let with_trend = |htf_filter: Option<HigherTimeframeFilter>, trend| {
+        let config = DoubleTopConfig {
+            htf_filter,
+            ..config()
+        };
+        let mut detector = DoubleTopDetector::new("SOL", config, "1m");
+        detector.set_htf_trend(trend);
+        let alerts: Vec<_> = candles.iter().filter_map(|c| detector.update(c)).collect();
+        (alerts, detector.status())
+    };
+    let filter = HigherTimeframeFilter::default();
+
+    let (alerts, status) = with_trend(Some(filter.clone()), TrendDirection::Up);
+    assert_eq!(alerts, []);
+    assert_eq!(status.htf_trend, Some(TrendDirection::Up));
+    let suppressed = status.suppressed_alert.unwrap();
+    assert_eq!(suppressed.stage, AlertStage::Confirmation);
+    assert_eq!(suppressed.htf_trend, Some(TrendDirection::Up));
+
+    let (alerts, status) = with_trend(Some(filter.clone()), TrendDirection::Down);
+    assert_eq!(alerts.len(), 2);
+    assert!(alerts
+        .iter()
+        .all(|a| a.htf_trend == Some(TrendDirection::Down)));
+    assert_eq!(status.suppressed_alert, None);
+
+    let annotate = HigherTimeframeFilter {
+        mode: HigherTimeframeMode::Annotate,
+        ..filter
+    };
+    let (alerts, _) = with_trend(Some(annotate), TrendDirection::Up);
+    assert_eq!(alerts.len(), 2);
+    assert!(alerts
+        .iter()
+        .all(|a| a.htf_trend == Some(TrendDirection::Up)));
+
+    // Without the filter the trend is ignored.
+    let (alerts, status) = with_trend(None, TrendDirection::Up);
+    assert_eq!(alerts.len(), 2);
+    assert!(alerts.iter().all(|a| a.htf_trend.is_none()));
+    assert_eq!(status.htf_trend, None);
+}
+
+#[test]
+fn htf_trend_is_the_close_against_its_ema() {
+    let filter = HigherTimeframeFilter {
+        ema_period: 5,
+        ..HigherTimeframeFilter::default()
+    };
+    let rising = candles_from_closes(100.0, &path(100.0, &[(15, 0.5)]), 0.1);
+    assert_eq!(filter.classify(&rising), Some(TrendDirection::Up));
+    let falling = candles_from_closes(100.0, &path(100.0, &[(15, -0.5)]), 0.1);
+    assert_eq!(filter.classify(&falling), Some(TrendDirection::Down));
+    assert_eq!(filter.classify(&rising[..4]), None);
+    assert_eq!(filter.candles_needed(), 15);
+}
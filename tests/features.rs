@@ -0,0 +1,24 @@
+//! Build-configuration checks for the cargo feature split.
+
+use std::path::Path;
+use std::process::Command;
+
+/// The detection core must build without the web stack (`--no-default-features`).
+#[test]
+fn core_builds_without_default_features() {
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("no-default-features");
+    let output = Command::new(cargo)
+        .args(["check", "--lib", "--no-default-features", "--quiet"])
+        .arg("--manifest-path")
+        .arg(Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .output()
+        .expect("failed to run cargo");
+    assert!(
+        output.status.success(),
+        "core build failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,26 @@
+use perpscreener::business_logic::fibonacci::FibLevels;
+
+#[test]
+fn retracements_of_a_rally_are_measured_down_from_the_high() {
+    let fib = FibLevels::new(100.0, 200.0, 150.0).unwrap();
+    assert!((fib.r382 - 161.8).abs() < 1e-9);
+    assert_eq!(fib.r500, 150.0);
+    assert!((fib.r618 - 138.2).abs() < 1e-9);
+    assert!((fib.r786 - 121.4).abs() < 1e-9);
+    assert_eq!(fib.retracement_pct, 50.0);
+}
+
+#[test]
+fn retracements_of_a_decline_are_measured_up_from_the_low() {
+    let fib = FibLevels::new(200.0, 100.0, 110.0).unwrap();
+    assert!((fib.r382 - 138.2).abs() < 1e-9);
+    assert!((fib.r786 - 178.6).abs() < 1e-9);
+    assert!((fib.retracement_pct - 10.0).abs() < 1e-9);
+    // Past the start of the leg.
+    assert!(FibLevels::new(200.0, 100.0, 210.0).unwrap().retracement_pct > 100.0);
+}
+
+#[test]
+fn zero_length_leg_has_no_levels() {
+    assert_eq!(FibLevels::new(100.0, 100.0, 100.0), None);
+}
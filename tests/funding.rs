@@ -0,0 +1,119 @@
+use perpscreener::business_logic::funding::{
+    annualized, percentile, percentile_rank, screen, FundingHistory, FundingSample,
+    FundingScreenerConfig, FundingTrigger,
+};
+
+const HOUR_MS: u64 = 3_600_000;
+const NOW: u64 = 1_700_000_000_000 / HOUR_MS * HOUR_MS;
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-12, "{actual} != {expected}");
+}
+
+/// 0.00001, 0.00002, ... 0.00100, newest first.
+fn history() -> Vec<f64> {
+    (1..=100).rev().map(|i| i as f64 * 0.00001).collect()
+}
+
+fn config() -> FundingScreenerConfig {
+    FundingScreenerConfig {
+        percentile: 95.0,
+        absolute_threshold: None,
+        min_history: 10,
+    }
+}
+
+#[test]
+fn percentile_interpolates_between_ranks() {
+    assert_eq!(percentile(&[], 50.0), None);
+    assert_eq!(percentile(&[3.0], 95.0), Some(3.0));
+    assert_eq!(percentile(&[4.0, 1.0, 3.0, 2.0], 0.0), Some(1.0));
+    assert_eq!(percentile(&[4.0, 1.0, 3.0, 2.0], 100.0), Some(4.0));
+    assert_close(percentile(&[4.0, 1.0, 3.0, 2.0], 50.0).unwrap(), 2.5);
+    assert_close(percentile(&history(), 95.0).unwrap(), 0.0009505);
+}
+
+#[test]
+fn percentile_rank_counts_values_at_or_below() {
+    assert_eq!(percentile_rank(&[], 1.0), None);
+    assert_eq!(percentile_rank(&[1.0, 2.0, 3.0, 4.0], 2.0), Some(50.0));
+    assert_eq!(percentile_rank(&[1.0, 2.0, 3.0, 4.0], 0.5), Some(0.0));
+    assert_eq!(percentile_rank(&[1.0, 2.0, 3.0, 4.0], 9.0), Some(100.0));
+}
+
+#[test]
+fn annualizes_hourly_rates() {
+    assert_close(annualized(0.0001), 0.876);
+}
+
+#[test]
+fn flags_rates_above_the_trailing_percentile() {
+    let flagged = screen("BTC", 0.000965, &history(), &config()).unwrap();
+    assert_eq!(flagged.coin, "BTC");
+    assert_eq!(flagged.triggers, vec![FundingTrigger::Percentile]);
+    assert_eq!(flagged.percentile, Some(96.0));
+    assert_close(flagged.annualized_rate, 0.000965 * 8760.0);
+
+    assert_eq!(screen("BTC", 0.00095, &history(), &config()), None);
+}
+
+#[test]
+fn percentile_rule_needs_enough_history() {
+    let short: Vec<f64> = history().into_iter().take(5).collect();
+    assert_eq!(screen("BTC", 1.0, &short, &config()), None);
+}
+
+#[test]
+fn absolute_threshold_applies_without_history() {
+    let config = FundingScreenerConfig {
+        absolute_threshold: Some(0.0005),
+        ..config()
+    };
+    let flagged = screen("DOGE", 0.0006, &[], &config).unwrap();
+    assert_eq!(flagged.triggers, vec![FundingTrigger::Absolute]);
+    assert_eq!(flagged.percentile, None);
+
+    let both = screen("DOGE", 0.002, &history(), &config).unwrap();
+    assert_eq!(
+        both.triggers,
+        vec![FundingTrigger::Percentile, FundingTrigger::Absolute]
+    );
+    assert_eq!(screen("DOGE", 0.0005, &[], &config), None);
+}
+
+fn sample(time_ms: u64, rate: f64) -> FundingSample {
+    FundingSample { time_ms, rate }
+}
+
+#[test]
+fn history_keeps_one_rate_per_hour() {
+    let mut history = FundingHistory::default();
+    history.record(sample(NOW, 0.0001), 3 * HOUR_MS);
+    history.record(sample(NOW + 60_000, 0.0002), 3 * HOUR_MS);
+    history.record(sample(NOW + 30_000, 0.0009), 3 * HOUR_MS);
+    history.record(sample(NOW + HOUR_MS, 0.0003), 3 * HOUR_MS);
+    history.record(sample(NOW + HOUR_MS + 60_000, 0.0004), 3 * HOUR_MS);
+
+    assert_eq!(
+        history.latest(),
+        Some(sample(NOW + HOUR_MS + 60_000, 0.0004))
+    );
+    assert_eq!(history.trailing_rates(), [0.0002]);
+
+    history.record(sample(NOW + 5 * HOUR_MS, 0.0005), 3 * HOUR_MS);
+    assert_eq!(history.trailing_rates(), Vec::<f64>::new());
+}
+
+#[test]
+fn history_screens_the_latest_rate_against_earlier_hours() {
+    let mut hours = FundingHistory::default();
+    for (hour, rate) in history().into_iter().rev().enumerate() {
+        hours.record(sample(NOW + hour as u64 * HOUR_MS, rate), 14 * 24 * HOUR_MS);
+    }
+    assert_eq!(hours.trailing_rates().len(), 99);
+
+    hours.record(sample(NOW + 100 * HOUR_MS, 0.000965), 14 * 24 * HOUR_MS);
+    let flagged = hours.screen("BTC", &config()).unwrap();
+    assert_eq!(flagged.triggers, vec![FundingTrigger::Percentile]);
+    assert_eq!(flagged.percentile, Some(96.0));
+}
@@ -0,0 +1,115 @@
+use perpscreener::business_logic::gaps::{CandleGap, GapConfig, GapPolicy, GapTracker};
+use perpscreener::models::candle::Candle;
+
+const MINUTE: u64 = 60_000;
+const T0: u64 = 1_700_000_000_000 - 1_700_000_000_000 % MINUTE;
+
+fn candle(open_time: u64) -> Candle {
+    Candle {
+        open_time,
+        close_time: open_time + MINUTE - 1,
+        open: 1.0,
+        high: 1.0,
+        low: 1.0,
+        close: 1.0,
+        volume: 1.0,
+        num_trades: 1,
+    }
+}
+
+/// Feed `offsets` (in minutes from T0) and collect the reported gaps.
+fn feed(tracker: &mut GapTracker, offsets: &[u64]) -> Vec<CandleGap> {
+    offsets
+        .iter()
+        .filter_map(|&i| tracker.observe(&candle(T0 + i * MINUTE)))
+        .collect()
+}
+
+fn flagging(clean: u32) -> GapTracker {
+    GapTracker::new(
+        "1m",
+        GapConfig {
+            policy: GapPolicy::Flag,
+            clean_candles_to_clear: clean,
+        },
+    )
+}
+
+#[test]
+fn contiguous_feed_has_no_gaps() {
+    let mut tracker = flagging(3);
+    assert!(feed(&mut tracker, &[0, 1, 2, 3, 4]).is_empty());
+    assert_eq!(tracker.gap_count(), 0);
+    assert!(!tracker.has_data_gap());
+}
+
+#[test]
+fn missing_minute_is_reported_and_flagged() {
+    let mut tracker = flagging(3);
+    let gaps = feed(&mut tracker, &[0, 1, 2, 5, 6]);
+    assert_eq!(
+        gaps,
+        vec![CandleGap {
+            after_open_time: T0 + 2 * MINUTE,
+            before_open_time: T0 + 5 * MINUTE,
+            missing_candles: 2,
+        }]
+    );
+    assert_eq!(tracker.gap_count(), 1);
+    assert!(tracker.has_data_gap());
+}
+
+#[test]
+fn flag_clears_after_enough_clean_candles() {
+    let mut tracker = flagging(3);
+    feed(&mut tracker, &[0, 2, 3, 4]);
+    assert!(tracker.has_data_gap());
+    feed(&mut tracker, &[5]);
+    assert!(!tracker.has_data_gap());
+    assert_eq!(tracker.gap_count(), 1);
+}
+
+#[test]
+fn second_gap_restarts_the_clean_stretch() {
+    let mut tracker = flagging(3);
+    feed(&mut tracker, &[0, 2, 3, 4, 6, 7, 8]);
+    assert_eq!(tracker.gap_count(), 2);
+    assert!(tracker.has_data_gap());
+    feed(&mut tracker, &[9]);
+    assert!(!tracker.has_data_gap());
+}
+
+#[test]
+fn duplicates_and_stale_candles_are_ignored() {
+    let mut tracker = flagging(3);
+    assert!(feed(&mut tracker, &[0, 1, 1, 0, 2]).is_empty());
+    assert_eq!(tracker.gap_count(), 0);
+}
+
+#[test]
+fn reset_policy_counts_gaps_without_flagging() {
+    let mut tracker = GapTracker::new(
+        "1m",
+        GapConfig {
+            policy: GapPolicy::ResetDetector,
+            clean_candles_to_clear: 3,
+        },
+    );
+    assert_eq!(feed(&mut tracker, &[0, 4]).len(), 1);
+    assert_eq!(tracker.policy(), GapPolicy::ResetDetector);
+    assert_eq!(tracker.gap_count(), 1);
+    assert!(!tracker.has_data_gap());
+}
+
+#[test]
+fn monthly_gaps_count_calendar_months() {
+    // 2024-01-01, 2024-02-01 and 2024-05-01 00:00 UTC.
+    let jan = 1_704_067_200_000;
+    let feb = 1_706_745_600_000;
+    let may = 1_714_521_600_000;
+    let mut tracker = GapTracker::new("1M", GapConfig::default());
+    assert_eq!(tracker.observe(&candle(jan)), None);
+    assert_eq!(tracker.observe(&candle(feb)), None);
+    let gap = tracker.observe(&candle(may)).unwrap();
+    assert_eq!(gap.missing_candles, 2);
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use common::candles_from_closes;
+use perpscreener::business_logic::alerts::{AlertStage, PatternKind};
+use perpscreener::business_logic::head_and_shoulders::{
+    HeadAndShouldersConfig, HeadAndShouldersDetector, HeadAndShouldersState,
+};
+use perpscreener::models::candle::Candle;
+
+fn path(start: f64, steps: &[(usize, f64)]) -> Vec<f64> {
+    let mut closes = Vec::new();
+    let mut price = start;
+    for &(count, step) in steps {
+        for _ in 0..count {
+            price += step;
+            closes.push(price);
+        }
+    }
+    closes
+}
+
+/// Steady 0.5 moves make every candle a full ATR wide; a 2 ATR reversal keeps
+/// swings to the real turns.
+fn config() -> HeadAndShouldersConfig {
+    HeadAndShouldersConfig {
+        rev_atr: 2.0,
+        ..HeadAndShouldersConfig::default()
+    }
+}
+
+/// Left shoulder at 95, troughs at 92, head at 98, then `right_leg` up and
+/// `finish` after it.
+fn shoulders(right_leg: (usize, f64), finish: (usize, f64)) -> Vec<Candle> {
+    let closes = path(
+        85.0,
+        &[
+            (20, 0.5),
+            (6, -0.5),
+            (12, 0.5),
+            (12, -0.5),
+            right_leg,
+            finish,
+        ],
+    );
+    candles_from_closes(85.0, &closes, 0.1)
+}
+
+#[test]
+fn right_shoulder_warns_and_neckline_break_confirms() {
+    let candles = shoulders((6, 0.5), (14, -0.5));
+    let mut detector = HeadAndShouldersDetector::new("ETH", config());
+
+    let alerts: Vec<_> = candles.iter().filter_map(|c| detector.update(c)).collect();
+    assert_eq!(alerts.len(), 2, "{alerts:?}");
+    assert!(alerts
+        .iter()
+        .all(|a| a.pattern == PatternKind::HeadAndShoulders));
+    assert_eq!(alerts[0].stage, AlertStage::EarlyWarning);
+    assert_eq!(alerts[1].stage, AlertStage::Confirmation);
+    assert!((alerts[1].level - 91.9).abs() < 1e-9);
+    assert!(alerts[1].price < 91.9);
+
+    assert_eq!(detector.state(), HeadAndShouldersState::Confirmed);
+    assert!((detector.left_shoulder_price().unwrap() - 95.1).abs() < 1e-9);
+    assert!((detector.head_price().unwrap() - 98.1).abs() < 1e-9);
+    assert!((detector.right_shoulder_price().unwrap() - 95.1).abs() < 1e-9);
+}
+
+#[test]
+fn right_shoulder_above_the_head_invalidates() {
+    let candles = shoulders((14, 0.5), (14, -0.5));
+    let mut detector = HeadAndShouldersDetector::new("ETH", config());
+
+    let mut states = Vec::new();
+    let mut alerts = Vec::new();
+    for candle in &candles {
+        alerts.extend(detector.update(candle));
+        states.push(detector.state());
+    }
+    assert!(states.contains(&HeadAndShouldersState::HeadFound));
+    assert!(states.contains(&HeadAndShouldersState::Invalidated));
+    assert!(alerts.is_empty(), "{alerts:?}");
+}
+
+#[test]
+fn uneven_shoulders_do_not_form() {
+    // Right shoulder only reaches 93.5, 1.6 below the left.
+    let candles = shoulders((3, 0.5), (14, -0.5));
+    let mut detector = HeadAndShouldersDetector::new(
+        "ETH",
+        HeadAndShouldersConfig {
+            shoulder_tolerance_pct: 1.0,
+            ..config()
+        },
+    );
+
+    let alerts: Vec<_> = candles.iter().filter_map(|c| detector.update(c)).collect();
+    assert!(alerts.is_empty(), "{alerts:?}");
+}
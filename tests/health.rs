@@ -0,0 +1,249 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::sync::Arc;
+
+use axum::http::{header, StatusCode};
+use axum::routing::post;
+use axum::{Json, Router};
+use common::ManualClock;
+use perpscreener::clock::{Clock, SkewEstimator, SystemClock};
+use perpscreener::services::hyperliquid::HyperliquidClient;
+use perpscreener::state::AppState;
+use serde_json::{json, Value};
+
+const MINUTE: u64 = 60_000;
+const NOW: u64 = 1_700_000_000_000;
+
+fn monitoring(state: AppState, coins: &[&str]) -> AppState {
+    state.with_monitored_coins(coins.iter().map(|c| c.to_string()).collect())
+}
+
+#[tokio::test]
+async fn healthy_when_no_coins_are_monitored() {
+    let state = common::state_with_clock(ManualClock::new(NOW));
+    let (status, body) = common::get(perpscreener::app(state), "/health").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "healthy");
+    assert_eq!(body["coins"].as_array().unwrap().len(), 0);
+}
+
+#[tokio::test]
+async fn monitored_coins_without_data_are_stale() {
+    let state = monitoring(
+        common::state_with_clock(ManualClock::new(NOW)),
+        &["BTC", "ETH"],
+    );
+    let (status, body) = common::get(perpscreener::app(state.clone()), "/health").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["status"], "unhealthy");
+    assert_eq!(body["stale_coins"], 2);
+    assert_eq!(body["coins"][0]["coin"], "BTC");
+    assert_eq!(body["coins"][0]["stale"], true);
+    assert_eq!(body["coins"][0]["last_candle_close_ms"], Value::Null);
+
+    state.freshness.record("BTC", NOW - MINUTE);
+    let (status, body) = common::get(perpscreener::app(state), "/health").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "degraded");
+    assert_eq!(body["coins"][1]["coin"], "ETH");
+    assert_eq!(body["coins"][1]["stale"], true);
+}
+
+#[tokio::test]
+async fn healthy_when_every_coin_is_fresh() {
+    let state = monitoring(
+        common::state_with_clock(ManualClock::new(NOW)),
+        &["BTC", "ETH"],
+    );
+    state.freshness.record("BTC", NOW - MINUTE);
+    state.freshness.record("ETH", NOW - 2 * MINUTE);
+
+    let (status, body) = common::get(perpscreener::app(state), "/health").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "healthy");
+    assert_eq!(body["coins"][0]["coin"], "BTC");
+    assert_eq!(body["coins"][0]["age_ms"], MINUTE);
+    assert_eq!(body["coins"][0]["stale"], false);
+}
+
+#[tokio::test]
+async fn degraded_when_some_coins_are_stale() {
+    let state = monitoring(
+        common::state_with_clock(ManualClock::new(NOW)),
+        &["BTC", "ETH"],
+    );
+    let max_age = state.health.max_age_ms();
+    state.freshness.record("BTC", NOW - MINUTE);
+    state.freshness.record("ETH", NOW - max_age - 1);
+
+    let (status, body) = common::get(perpscreener::app(state), "/health").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "degraded");
+    assert_eq!(body["coins"][0]["stale"], false);
+    assert_eq!(body["coins"][1]["coin"], "ETH");
+    assert_eq!(body["coins"][1]["stale"], true);
+}
+
+#[tokio::test]
+async fn unhealthy_when_every_coin_is_stale() {
+    let state = monitoring(
+        common::state_with_clock(ManualClock::new(NOW)),
+        &["BTC", "ETH"],
+    );
+    let max_age = state.health.max_age_ms();
+    state.freshness.record("BTC", NOW - max_age - 1);
+    state.freshness.record("ETH", NOW - 10 * max_age);
+
+    let (status, body) = common::get(perpscreener::app(state), "/health").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["status"], "unhealthy");
+}
+
+#[tokio::test]
+async fn coin_exactly_at_the_threshold_is_not_stale() {
+    let clock = ManualClock::new(NOW);
+    let state = monitoring(common::state_with_clock(clock.clone()), &["BTC"]);
+    let max_age = state.health.max_age_ms();
+    state.freshness.record("BTC", NOW - max_age);
+
+    let (_, body) = common::get(perpscreener::app(state.clone()), "/health").await;
+    assert_eq!(body["status"], "healthy");
+
+    clock.advance(1);
+    let (status, body) = common::get(perpscreener::app(state), "/health").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["coins"][0]["stale"], true);
+}
+
+#[tokio::test]
+async fn threshold_scales_with_the_detection_interval() {
+    let mut state = monitoring(common::state_with_clock(ManualClock::new(NOW)), &["BTC"]);
+    state.health.detection_interval_ms = 15 * MINUTE;
+    state.health.stale_after_intervals = 2;
+    state.freshness.record("BTC", NOW - 29 * MINUTE);
+
+    let (_, body) = common::get(perpscreener::app(state), "/health").await;
+    assert_eq!(body["status"], "healthy");
+    assert_eq!(body["max_age_ms"], 30 * MINUTE);
+}
+
+#[tokio::test]
+async fn reports_clock_skew_once_measured() {
+    let state = common::state_with_clock(ManualClock::new(NOW));
+    let (_, body) = common::get(perpscreener::app(state.clone()), "/health").await;
+    assert_eq!(body["clock_skew_ms"], Value::Null);
+    assert_eq!(body["clock_skew_warning"], false);
+
+    state.clock_skew.observe(NOW + 500, NOW);
+    let (_, body) = common::get(perpscreener::app(state.clone()), "/health").await;
+    assert_eq!(body["clock_skew_ms"], 500);
+    assert_eq!(body["clock_skew_warning"], false);
+
+    let skewed = common::state_with_clock(ManualClock::new(NOW));
+    skewed.clock_skew.observe(NOW - 60_000, NOW);
+    let (status, body) = common::get(perpscreener::app(skewed), "/health").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["clock_skew_ms"], -60_000);
+    assert_eq!(body["clock_skew_warning"], true);
+}
+
+#[tokio::test]
+async fn stale_for_ms_counts_from_the_threshold() {
+    let clock = ManualClock::new(NOW);
+    let state = monitoring(common::state_with_clock(clock.clone()), &["BTC", "ETH"]);
+    let max_age = state.health.max_age_ms();
+    state.freshness.record("BTC", NOW - max_age);
+    state.freshness.record("ETH", NOW);
+
+    let (_, body) = common::get(perpscreener::app(state.clone()), "/health").await;
+    assert_eq!(body["stale_coins"], 0);
+    assert_eq!(body["coins"][0]["stale_for_ms"], 0);
+
+    clock.advance(3 * MINUTE);
+    let (status, body) = common::get(perpscreener::app(state), "/health").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "degraded");
+    assert_eq!(body["stale_coins"], 1);
+    assert_eq!(body["coins"][0]["coin"], "BTC");
+    assert_eq!(body["coins"][0]["stale_for_ms"], 3 * MINUTE);
+    assert_eq!(body["coins"][1]["stale_for_ms"], 0);
+}
+
+#[tokio::test]
+async fn staleness_scales_with_the_detection_interval() {
+    let settings = perpscreener::settings::Settings::from_toml(
+        "[monitor]\ndetection_interval = \"15m\"\n",
+        std::path::Path::new("test.toml"),
+    )
+    .unwrap();
+    let state = monitoring(
+        common::state_with_clock(ManualClock::new(NOW)).with_settings(settings),
+        &["BTC"],
+    );
+    assert_eq!(state.health.max_age_ms(), 5 * 15 * MINUTE);
+    // Ten minutes old is stale on 1m candles but fresh on 15m ones.
+    state.freshness.record("BTC", NOW - 10 * MINUTE);
+
+    let (status, body) = common::get(perpscreener::app(state), "/health").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["coins"][0]["stale"], false);
+}
+
+#[tokio::test]
+async fn stale_threshold_is_configurable() {
+    let settings = perpscreener::settings::Settings::from_toml(
+        "[monitor]\nstale_after_intervals = 2\n",
+        std::path::Path::new("test.toml"),
+    )
+    .unwrap();
+    let state = monitoring(
+        common::state_with_clock(ManualClock::new(NOW)).with_settings(settings),
+        &["BTC", "ETH"],
+    );
+    state.freshness.record("BTC", NOW - MINUTE);
+    state.freshness.record("ETH", NOW - 3 * MINUTE);
+
+    let (status, body) = common::get(perpscreener::app(state), "/health").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["max_age_ms"], 2 * MINUTE);
+    assert_eq!(body["coins"][0]["stale"], false);
+    assert_eq!(body["coins"][1]["stale"], true);
+    assert_eq!(body["coins"][1]["last_candle_close_ms"], NOW - 3 * MINUTE);
+    assert_eq!(body["coins"][1]["stale_for_ms"], MINUTE);
+}
+
+/// Stub `/info` whose `Date` header runs `ahead_ms` ahead of the host clock.
+async fn spawn_skewed_upstream(ahead_ms: u64) -> String {
+    let app = Router::new().route(
+        "/info",
+        post(move || async move {
+            let upstream_ms = SystemClock.now_ms() + ahead_ms;
+            let date = chrono::DateTime::from_timestamp_millis(upstream_ms as i64)
+                .unwrap()
+                .to_rfc2822();
+            ([(header::DATE, date)], Json(json!({ "BTC": "100.0" })))
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn client_responses_feed_the_skew_estimate() {
+    let skew = Arc::new(SkewEstimator::new(2_000));
+    let client = HyperliquidClient::with_base_url(spawn_skewed_upstream(3_600_000).await)
+        .with_clock_skew(skew.clone());
+    assert_eq!(skew.estimate_ms(), None);
+    client.all_mids().await.unwrap();
+
+    let estimate = skew.estimate_ms().unwrap();
+    assert!(
+        (3_598_000..=3_602_000).contains(&estimate),
+        "estimate {estimate}"
+    );
+    assert!(skew.exceeds_threshold());
+}
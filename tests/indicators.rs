@@ -0,0 +1,620 @@
+mod common;
+
+use common::{candle, candles_from_closes, MINUTE_MS, T0};
+use perpscreener::business_logic::indicator_series::{parse_set, IndicatorSpec};
+use perpscreener::business_logic::indicators::{
+    channel_break, is_squeeze, macd_crossovers, true_range, AdxCalculator, AtrCalculator, Bands,
+    BollingerCalculator, ChannelBreak, DonchianCalculator, DonchianChannel, EmaCalculator,
+    KeltnerCalculator, MacdCalculator, MacdConfig, MacdCross, ObvCalculator, RollingWindow,
+    RsiCalculator, SmaCalculator, VwapAnchor, VwapCalculator,
+};
+use perpscreener::models::candle::Candle;
+
+fn rsi_series(period: usize, closes: &[f64]) -> Vec<Option<f64>> {
+    RsiCalculator::new(period).apply_series(&candles_from_closes(closes[0], closes, 0.1))
+}
+
+#[test]
+fn rsi_seeds_from_simple_averages_then_wilder_smooths() {
+    // Changes +1, +1, -1: avg gain 2/3, avg loss 1/3, RS 2.
+    let values = rsi_series(3, &[10.0, 11.0, 12.0, 11.0, 13.0]);
+    assert_eq!(values[..3], [None, None, None]);
+    assert!((values[3].unwrap() - 200.0 / 3.0).abs() < 1e-9);
+    // +2: avg gain (2/3 * 2 + 2) / 3 = 10/9, avg loss (1/3 * 2) / 3 = 2/9, RS 5.
+    assert!((values[4].unwrap() - 250.0 / 3.0).abs() < 1e-9);
+}
+
+#[test]
+fn rsi_is_pinned_at_the_extremes_by_one_sided_moves() {
+    let rising = rsi_series(3, &[1.0, 2.0, 3.0, 4.0, 5.0]);
+    assert_eq!(rising[4], Some(100.0));
+    let falling = rsi_series(3, &[5.0, 4.0, 3.0, 2.0, 1.0]);
+    assert_eq!(falling[4], Some(0.0));
+}
+
+#[test]
+fn ema_is_seeded_with_the_sma_then_smoothed() {
+    let mut ema = EmaCalculator::new(3);
+    let closes = [10.0, 11.0, 12.0, 16.0, 16.0];
+    let values: Vec<_> = candles_from_closes(10.0, &closes, 0.1)
+        .iter()
+        .map(|c| ema.update(c))
+        .collect();
+    // SMA(10, 11, 12) = 11, then alpha = 2 / (3 + 1).
+    assert_eq!(values[..3], [None, None, Some(11.0)]);
+    assert_eq!(values[3], Some(13.5));
+    assert_eq!(values[4], Some(14.75));
+    assert_eq!(ema.value(), Some(14.75));
+    assert_eq!(ema.period(), 3);
+}
+
+#[test]
+fn ema_first_value_equals_the_sma_over_the_same_candles() {
+    let closes = [3.0, 8.0, 1.0, 6.0, 7.0, 2.0, 9.0];
+    let candles = candles_from_closes(3.0, &closes, 0.1);
+    let ema = EmaCalculator::new(4).apply_series(&candles);
+    let sma = SmaCalculator::new(4).apply_series(&candles);
+    assert_eq!(ema[3], Some(4.5));
+    assert_eq!(ema[3], sma[3]);
+    // 4.5 + (7 - 4.5) * 2 / 5
+    assert_eq!(ema[4], Some(5.5));
+}
+
+#[test]
+fn sma_rolls_over_the_last_period_closes() {
+    let candles = candles_from_closes(2.0, &[2.0, 4.0, 6.0, 8.0, 13.0], 0.1);
+    let mut sma = SmaCalculator::new(3);
+    assert_eq!(
+        sma.apply_series(&candles),
+        [None, None, Some(4.0), Some(6.0), Some(9.0)]
+    );
+    assert_eq!(sma.value(), Some(9.0));
+    assert_eq!(sma.period(), 3);
+}
+
+fn wave(count: usize) -> Vec<f64> {
+    (0..count)
+        .map(|i| 100.0 + (i as f64 / 8.0).sin() * 5.0)
+        .collect()
+}
+
+#[test]
+fn macd_is_the_fast_slow_spread_with_an_ema_signal() {
+    let closes = wave(80);
+    let candles = candles_from_closes(100.0, &closes, 0.1);
+    let values = MacdCalculator::new(MacdConfig::default()).apply_series(&candles);
+
+    // Warm once the 26 EMA and then 9 MACD values exist.
+    assert!(values[..33].iter().all(Option::is_none));
+    assert!(values[33..].iter().all(Option::is_some));
+
+    // Rebuild the lines from standalone EMAs.
+    let fast = EmaCalculator::new(12).apply_series(&candles);
+    let slow = EmaCalculator::new(26).apply_series(&candles);
+    let mut signal = EmaCalculator::new(9);
+    for i in 25..candles.len() {
+        let macd = fast[i].unwrap() - slow[i].unwrap();
+        let expected = signal.update_value(macd);
+        if let (Some(expected), Some(value)) = (expected, values[i]) {
+            assert!((value.macd - macd).abs() < 1e-9);
+            assert!((value.signal - expected).abs() < 1e-9);
+            assert!((value.histogram - (macd - expected)).abs() < 1e-9);
+        }
+    }
+}
+
+#[test]
+fn streaming_crossovers_match_the_batch_scan() {
+    let closes = wave(200);
+    let candles = candles_from_closes(100.0, &closes, 0.1);
+
+    let mut macd = MacdCalculator::new(MacdConfig::default());
+    let mut streamed = Vec::new();
+    let mut values = Vec::new();
+    for (i, candle) in candles.iter().enumerate() {
+        values.push(macd.update(candle));
+        if let Some(cross) = macd.crossover() {
+            streamed.push((i, cross));
+        }
+    }
+
+    let batch = MacdCalculator::new(MacdConfig::default()).apply_series(&candles);
+    for (a, b) in values.iter().zip(&batch) {
+        assert!(a
+            .zip(*b)
+            .is_none_or(|(a, b)| (a.histogram - b.histogram).abs() < 1e-12));
+    }
+    let crosses = macd_crossovers(&batch);
+    assert_eq!(crosses, streamed);
+    // A sine wave swings the histogram both ways and the crosses alternate.
+    assert!(crosses.len() >= 4, "{crosses:?}");
+    assert!(crosses.windows(2).all(|w| w[0].1 != w[1].1));
+    assert!(crosses.iter().any(|&(_, c)| c == MacdCross::Bullish));
+}
+
+/// 1m candle at `index` with a typical price of `price` and the given volume.
+fn traded(index: u64, price: f64, volume: f64) -> Candle {
+    Candle {
+        volume,
+        ..candle(index, price, price + 1.0, price - 1.0, price)
+    }
+}
+
+#[test]
+fn vwap_weights_typical_prices_by_volume() {
+    let candles = [
+        traded(0, 10.0, 1.0),
+        traded(1, 20.0, 3.0),
+        traded(2, 30.0, 0.0),
+    ];
+    let mut vwap = VwapCalculator::new(VwapAnchor::At(0));
+    let values = vwap.apply_series(&candles);
+    assert_eq!(values, [Some(10.0), Some(17.5), Some(17.5)]);
+
+    let mut empty = VwapCalculator::new(VwapAnchor::At(0));
+    assert_eq!(empty.update(&traded(0, 10.0, 0.0)), None);
+}
+
+#[test]
+fn anchored_vwap_ignores_candles_before_the_anchor() {
+    let candles: Vec<_> = (0..4)
+        .map(|i| traded(i, 10.0 * (i + 1) as f64, 1.0))
+        .collect();
+    let mut vwap = VwapCalculator::new(VwapAnchor::At(T0 + 2 * MINUTE_MS));
+    assert_eq!(
+        vwap.apply_series(&candles),
+        [None, None, Some(30.0), Some(35.0)]
+    );
+}
+
+#[test]
+fn session_vwap_restarts_at_the_session_start() {
+    // T0 is 22:14 UTC; a session opening at 22:15 restarts on the second candle.
+    let start_minute = ((T0 % (24 * 60 * MINUTE_MS)) / MINUTE_MS + 1) as u32;
+    let candles = [
+        traded(0, 10.0, 1.0),
+        traded(1, 20.0, 1.0),
+        traded(2, 30.0, 1.0),
+    ];
+    let mut vwap = VwapCalculator::new(VwapAnchor::Session { start_minute });
+    assert_eq!(
+        vwap.apply_series(&candles),
+        [Some(10.0), Some(20.0), Some(25.0)]
+    );
+}
+
+#[test]
+fn true_range_reaches_across_gaps_to_the_previous_close() {
+    let bar = candle(0, 12.0, 13.0, 11.0, 12.5);
+    assert_eq!(true_range(&bar, None), 2.0);
+    assert_eq!(true_range(&bar, Some(12.0)), 2.0);
+    assert_eq!(true_range(&bar, Some(9.0)), 4.0);
+    assert_eq!(true_range(&bar, Some(15.0)), 4.0);
+}
+
+#[test]
+fn adx_follows_wilder_directional_movement() {
+    let candles = [
+        candle(0, 9.0, 10.0, 8.0, 9.0),
+        candle(1, 9.0, 12.0, 9.0, 11.0),
+        candle(2, 11.0, 13.0, 10.0, 12.0),
+        candle(3, 12.0, 12.0, 9.0, 9.5),
+        candle(4, 9.5, 11.0, 8.0, 8.5),
+    ];
+    let values = AdxCalculator::new(2).apply_series(&candles);
+    assert_eq!(values[..3], [None, None, None]);
+
+    // True ranges are all 3. +DM 2, 1, 0, 0 and -DM 0, 0, 1, 1; the first DX
+    // is 100 (only +DM so far), the second 20: +DI 25, -DI 16.67.
+    let first = values[3].unwrap();
+    assert!((first.plus_di - 25.0).abs() < 1e-9);
+    assert!((first.minus_di - 50.0 / 3.0).abs() < 1e-9);
+    assert!((first.adx - 60.0).abs() < 1e-9);
+
+    // +DI 12.5, -DI 25: DX 33.3, smoothed with the 60 before it.
+    let next = values[4].unwrap();
+    assert!((next.plus_di - 12.5).abs() < 1e-9);
+    assert!((next.minus_di - 25.0).abs() < 1e-9);
+    assert!((next.adx - 140.0 / 3.0).abs() < 1e-9);
+}
+
+#[test]
+fn adx_is_high_in_a_steady_trend() {
+    let closes: Vec<f64> = (1..=40).map(|i| 100.0 + i as f64).collect();
+    let candles = candles_from_closes(100.0, &closes, 0.1);
+    let value = AdxCalculator::new(14)
+        .apply_series(&candles)
+        .pop()
+        .flatten()
+        .unwrap();
+    assert_eq!(value.minus_di, 0.0);
+    assert!(value.plus_di > 50.0);
+    assert!((value.adx - 100.0).abs() < 1e-9);
+}
+
+#[test]
+fn obv_adds_volume_on_up_closes_and_subtracts_it_on_down_closes() {
+    let candles = [
+        traded(0, 10.0, 5.0),
+        traded(1, 11.0, 2.0),
+        traded(2, 11.0, 7.0),
+        traded(3, 9.0, 3.0),
+        traded(4, 12.0, 4.0),
+    ];
+    let mut obv = ObvCalculator::new();
+    assert_eq!(obv.apply_series(&candles), [0.0, 2.0, 2.0, -1.0, 3.0]);
+    assert_eq!(obv.value(), 3.0);
+}
+
+#[test]
+fn donchian_evicts_the_extreme_once_it_leaves_the_window() {
+    // Highs/lows: the 20/1 extremes sit on candles 1 and 2.
+    let bars = [
+        (10.0, 5.0),
+        (20.0, 6.0),
+        (12.0, 1.0),
+        (11.0, 7.0),
+        (13.0, 8.0),
+        (9.0, 8.5),
+    ];
+    let candles: Vec<_> = bars
+        .iter()
+        .enumerate()
+        .map(|(i, &(high, low))| candle(i as u64, low, high, low, low))
+        .collect();
+    let channels = DonchianCalculator::new(3).apply_series(&candles);
+
+    let expected = [
+        None,
+        None,
+        Some((20.0, 1.0)),
+        Some((20.0, 1.0)),
+        // Candle 1's high drops out; candle 2's low is still in.
+        Some((13.0, 1.0)),
+        // Candle 2's low drops out too.
+        Some((13.0, 7.0)),
+    ];
+    let got: Vec<_> = channels
+        .iter()
+        .map(|c| c.map(|c| (c.upper, c.lower)))
+        .collect();
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn donchian_matches_a_naive_rescan() {
+    let closes: Vec<f64> = (0..120)
+        .map(|i| 100.0 + (i as f64 * 0.37).sin() * 4.0 + (i as f64 * 0.11).cos() * 2.0)
+        .collect();
+    let candles = candles_from_closes(100.0, &closes, 0.2);
+    let channels = DonchianCalculator::new(20).apply_series(&candles);
+    for (i, channel) in channels.iter().enumerate().skip(19) {
+        let window = &candles[i - 19..=i];
+        let upper = window.iter().map(|c| c.high).fold(f64::MIN, f64::max);
+        let lower = window.iter().map(|c| c.low).fold(f64::MAX, f64::min);
+        assert_eq!(
+            *channel,
+            Some(DonchianChannel { upper, lower }),
+            "candle {i}"
+        );
+    }
+}
+
+#[test]
+fn donchian_reports_closes_outside_the_prior_channel() {
+    let mut donchian = DonchianCalculator::new(3);
+    for i in 0..3 {
+        donchian.update(&candle(i, 10.0, 11.0, 9.0, 10.0));
+    }
+    let prior = donchian.value().unwrap();
+    assert_eq!(prior.middle(), 10.0);
+
+    // Closing at the old high is still inside.
+    let at_high = candle(3, 10.0, 11.5, 10.0, 11.0);
+    assert_eq!(channel_break(prior, &at_high), None);
+    donchian.update(&at_high);
+    assert_eq!(donchian.last_break(), None);
+
+    let breakout = candle(4, 11.0, 12.5, 11.0, 12.0);
+    donchian.update(&breakout);
+    assert_eq!(donchian.last_break(), Some(ChannelBreak::Above));
+
+    let breakdown = candle(5, 12.0, 12.0, 8.0, 8.5);
+    donchian.update(&breakdown);
+    assert_eq!(donchian.last_break(), Some(ChannelBreak::Below));
+}
+
+/// 24 OHLC bars and their ATR(14), worked through Wilder's recursion outside
+/// this crate.
+const ATR_BARS: [(f64, f64, f64, f64); 24] = [
+    (50.0, 50.4, 49.7, 50.0),
+    (50.0, 52.34, 49.6, 51.84),
+    (51.84, 53.7, 51.34, 53.1),
+    (53.1, 53.77, 52.5, 53.37),
+    (53.37, 53.87, 52.33, 52.63),
+    (52.63, 53.23, 50.77, 51.17),
+    (51.17, 51.57, 49.07, 49.57),
+    (49.57, 50.07, 47.84, 48.44),
+    (48.44, 49.04, 47.91, 48.21),
+    (48.21, 49.43, 47.81, 49.03),
+    (49.03, 51.16, 48.53, 50.66),
+    (50.66, 53.18, 50.06, 52.58),
+    (52.58, 54.58, 52.28, 54.18),
+    (54.18, 55.45, 53.78, 54.95),
+    (54.95, 55.55, 54.16, 54.66),
+    (54.66, 55.06, 52.89, 53.49),
+    (53.49, 53.99, 51.58, 51.88),
+    (51.88, 52.48, 50.05, 50.45),
+    (50.45, 50.85, 49.26, 49.76),
+    (49.76, 50.59, 49.16, 50.09),
+    (50.09, 51.99, 49.79, 51.39),
+    (51.39, 53.65, 50.99, 53.25),
+    (53.25, 55.58, 52.75, 55.08),
+    (55.08, 56.88, 54.48, 56.28),
+];
+const ATR_14: [f64; 11] = [
+    2.019286, 1.974337, 1.988313, 2.018433, 2.047831, 2.015129, 1.973334, 1.989524, 2.037415,
+    2.094028, 2.115884,
+];
+
+fn atr_bars() -> Vec<Candle> {
+    ATR_BARS
+        .iter()
+        .enumerate()
+        .map(|(i, &(o, h, l, c))| candle(i as u64, o, h, l, c))
+        .collect()
+}
+
+#[test]
+fn atr_matches_the_wilder_reference_sequence() {
+    let values = AtrCalculator::new(14).apply_series(&atr_bars());
+    assert!(values[..13].iter().all(Option::is_none));
+    for (value, expected) in values[13..].iter().zip(ATR_14) {
+        assert!(
+            (value.unwrap() - expected).abs() < 1e-6,
+            "{value:?} vs {expected}"
+        );
+    }
+}
+
+#[test]
+fn atr_is_not_a_rolling_average_of_true_ranges() {
+    // Averaging the last 14 true ranges agrees on the seed and then drifts.
+    let candles = atr_bars();
+    let values = AtrCalculator::new(14).apply_series(&candles);
+    let true_ranges: Vec<f64> = candles
+        .iter()
+        .enumerate()
+        .map(|(i, c)| true_range(c, i.checked_sub(1).map(|p| candles[p].close)))
+        .collect();
+    let rolling = |i: usize| true_ranges[i - 13..=i].iter().sum::<f64>() / 14.0;
+
+    assert!((values[13].unwrap() - rolling(13)).abs() < 1e-9);
+    assert!((values[23].unwrap() - rolling(23)).abs() > 0.1);
+}
+
+/// Deterministic pseudo-random walk of closes.
+fn random_walk(count: usize) -> Vec<f64> {
+    let mut seed: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut price = 1_000.0;
+    (0..count)
+        .map(|_| {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            let step = (seed >> 11) as f64 / (1u64 << 53) as f64 - 0.5;
+            price += step * 10.0;
+            price
+        })
+        .collect()
+}
+
+#[test]
+fn rolling_window_keeps_running_sums_and_evicts_oldest_first() {
+    let mut window = RollingWindow::new(3);
+    assert_eq!(window.mean(), None);
+    assert_eq!(window.push(1.0), None);
+    assert_eq!(window.push(2.0), None);
+    assert_eq!(window.push(6.0), None);
+    assert!(window.is_full());
+    assert_eq!(window.mean(), Some(3.0));
+    assert!((window.variance().unwrap() - 14.0 / 3.0).abs() < 1e-12);
+
+    assert_eq!(window.push(4.0), Some(1.0));
+    assert_eq!(window.iter().collect::<Vec<_>>(), [2.0, 6.0, 4.0]);
+    assert_eq!(window.sum(), 12.0);
+
+    window.clear();
+    assert!(window.is_empty());
+    assert_eq!(window.variance(), None);
+}
+
+#[test]
+fn windowed_sma_matches_a_rescan_over_a_long_random_series() {
+    let closes = random_walk(5_000);
+    let candles = candles_from_closes(1_000.0, &closes, 0.5);
+    let values = SmaCalculator::new(50).apply_series(&candles);
+    let mut window = RollingWindow::new(50);
+    for (i, value) in values.iter().enumerate() {
+        window.push(closes[i]);
+        if i < 49 {
+            assert_eq!(*value, None);
+            continue;
+        }
+        let slice = &closes[i - 49..=i];
+        let mean = slice.iter().sum::<f64>() / 50.0;
+        let variance = slice.iter().map(|c| (c - mean).powi(2)).sum::<f64>() / 50.0;
+        assert!((value.unwrap() - mean).abs() < 1e-6, "candle {i}");
+        assert!(
+            (window.variance().unwrap() - variance).abs() < 1e-4,
+            "candle {i}"
+        );
+    }
+}
+
+#[test]
+fn indicator_sets_parse_in_order_without_repeats() {
+    let specs = parse_set("ema20, EMA50,rsi14,atr14,ema20,").unwrap();
+    assert_eq!(
+        specs,
+        [
+            IndicatorSpec::Ema(20),
+            IndicatorSpec::Ema(50),
+            IndicatorSpec::Rsi(14),
+            IndicatorSpec::Atr(14),
+        ]
+    );
+    assert_eq!(specs[1].name(), "ema50");
+
+    for bad in [
+        "", " , ", "macd12", "ema", "ema0", "sma501", "rsi1.5", "20ema",
+    ] {
+        assert!(parse_set(bad).is_err(), "{bad:?}");
+    }
+    let err = parse_set("ema20,vwap").unwrap_err();
+    assert!(err.contains("vwap"), "{err}");
+}
+
+#[test]
+fn indicator_spec_series_match_their_calculators() {
+    let closes = random_walk(60);
+    let candles = candles_from_closes(1_000.0, &closes, 0.5);
+    assert_eq!(
+        IndicatorSpec::Rsi(14).series(&candles),
+        RsiCalculator::new(14).apply_series(&candles)
+    );
+    assert_eq!(
+        IndicatorSpec::Atr(5).series(&candles),
+        AtrCalculator::new(5).apply_series(&candles)
+    );
+}
+
+/// Candles closing at `closes` with a high and low 1 either side.
+fn bars(closes: &[f64]) -> Vec<Candle> {
+    closes
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| candle(i as u64, c, c + 1.0, c - 1.0, c))
+        .collect()
+}
+
+fn assert_bands(bands: Option<Bands>, upper: f64, middle: f64, lower: f64) {
+    let bands = bands.unwrap();
+    assert!((bands.upper - upper).abs() < 1e-3, "{bands:?}");
+    assert!((bands.middle - middle).abs() < 1e-3, "{bands:?}");
+    assert!((bands.lower - lower).abs() < 1e-3, "{bands:?}");
+}
+
+#[test]
+fn keltner_is_the_ema_plus_minus_atr_multiples() {
+    let values = KeltnerCalculator::new(3, 3, 2.0).apply_series(&bars(&[10.0, 11.0, 12.0, 16.0]));
+    assert_eq!(values[..2], [None, None]);
+    // EMA 11 (seed), ATR 2 (three ranges of 2).
+    assert_bands(values[2], 15.0, 11.0, 7.0);
+    // EMA 11 + (16 - 11) / 2; true range 5 (high 17 - prev close 12) gives ATR 3.
+    assert_bands(values[3], 19.5, 13.5, 7.5);
+}
+
+#[test]
+fn bollinger_uses_the_population_standard_deviation() {
+    let values = BollingerCalculator::new(3, 2.0).apply_series(&bars(&[10.0, 11.0, 12.0]));
+    // Mean 11, variance 2/3.
+    let width = 2.0 * (2.0_f64 / 3.0).sqrt();
+    assert_bands(values[2], 11.0 + width, 11.0, 11.0 - width);
+}
+
+#[test]
+fn squeeze_holds_while_bollinger_sits_inside_keltner() {
+    let candles = bars(&[10.0, 11.0, 12.0, 16.0, 30.0]);
+    let bollinger = BollingerCalculator::new(3, 2.0).apply_series(&candles);
+    let keltner = KeltnerCalculator::new(3, 3, 2.0).apply_series(&candles);
+    let squeeze: Vec<bool> = bollinger
+        .iter()
+        .zip(&keltner)
+        .map(|(b, k)| b.zip(*k).is_some_and(|(b, k)| is_squeeze(&b, &k)))
+        .collect();
+    // The jump to 30 blows the Bollinger lower band (3.9) below Keltner's (7.75).
+    assert_eq!(squeeze, [false, false, true, true, false]);
+}
+
+#[cfg(feature = "server")]
+mod endpoint {
+    use axum::http::StatusCode;
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use perpscreener::services::hyperliquid::HyperliquidClient;
+    use serde_json::{json, Value};
+
+    use super::common::{self, ManualClock, MINUTE_MS, T0};
+
+    /// Stub `info` endpoint serving flat 1m BTC candles at 1, 2, 3, ...
+    /// from `startTime`; the last one is still forming at the test clock.
+    async fn spawn_hyperliquid() -> String {
+        let app = Router::new().route(
+            "/info",
+            post(|Json(body): Json<Value>| async move {
+                if body["req"]["coin"] != "BTC" {
+                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                }
+                let start = body["req"]["startTime"].as_u64().unwrap();
+                let end = body["req"]["endTime"].as_u64().unwrap();
+                let candles: Vec<Value> = (0..)
+                    .map(|i| (i, start + i * MINUTE_MS))
+                    .take_while(|&(_, t)| t <= end)
+                    .map(|(i, t)| {
+                        let close = (i + 1) as f64;
+                        json!({
+                            "t": t, "T": t + MINUTE_MS - 1, "s": "BTC", "i": "1m",
+                            "o": close.to_string(), "h": close.to_string(),
+                            "l": close.to_string(), "c": close.to_string(),
+                            "v": "1.0", "n": 1
+                        })
+                    })
+                    .collect();
+                Ok(Json(Value::from(candles)))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn serves_aligned_series_for_the_requested_set() {
+        let url = spawn_hyperliquid().await;
+        // Mid-candle, so the latest candle is still forming.
+        let clock = ManualClock::new(T0 + 100 * MINUTE_MS + 30_000);
+        let state =
+            common::state_with_clock(clock).with_hyperliquid(HyperliquidClient::with_base_url(url));
+        let app = perpscreener::app(state);
+
+        let (status, body) = common::get(
+            app.clone(),
+            "/indicators?coin=BTC&interval=1m&limit=5&set=sma3,ema2",
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        let open_times = body["open_times"].as_array().unwrap();
+        assert_eq!(open_times.len(), 5);
+        assert_eq!(open_times[4], T0 + 99 * MINUTE_MS);
+        let indicators = body["indicators"].as_array().unwrap();
+        assert_eq!(indicators[0]["name"], "sma3");
+        assert_eq!(indicators[0]["values"], json!([null, null, 2.0, 3.0, 4.0]));
+        assert_eq!(indicators[1]["name"], "ema2");
+        assert_eq!(indicators[1]["values"], json!([null, 1.5, 2.5, 3.5, 4.5]));
+
+        let (status, _) = common::get(app.clone(), "/indicators?coin=ETH&set=ema20").await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+
+        for uri in [
+            "/indicators?coin=BTC",
+            "/indicators?coin=BTC&set=ema20,bogus5",
+            "/indicators?coin=BTC&set=ema0",
+            "/indicators?coin=BTC&set=ema20&interval=7m",
+            "/indicators?coin=BTC&set=ema20&limit=0",
+            "/indicators?coin=BTC&set=ema20&limit=5001",
+        ] {
+            let (status, _) = common::get(app.clone(), uri).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+        }
+    }
+}
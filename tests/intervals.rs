@@ -0,0 +1,165 @@
+use chrono::{NaiveDate, TimeZone, Utc};
+use perpscreener::business_logic::intervals::{
+    bucket_start, candles_between, closed_since, interval_ms, is_supported, ms_until_next_bucket,
+    next_bucket_start, window_start, SUPPORTED_INTERVALS,
+};
+
+const DAY: u64 = 86_400_000;
+
+fn ms(y: i32, m: u32, d: u32, h: u32, min: u32) -> u64 {
+    Utc.with_ymd_and_hms(y, m, d, h, min, 0)
+        .unwrap()
+        .timestamp_millis() as u64
+}
+
+fn date(ts_ms: u64) -> NaiveDate {
+    chrono::DateTime::from_timestamp_millis(ts_ms as i64)
+        .unwrap()
+        .date_naive()
+}
+
+#[test]
+fn every_supported_interval_has_bucket_math() {
+    for interval in SUPPORTED_INTERVALS {
+        assert!(is_supported(interval));
+        let now = ms(2024, 5, 17, 13, 37);
+        let start = bucket_start(interval, now).unwrap();
+        let next = next_bucket_start(interval, now).unwrap();
+        assert!(start <= now && now < next, "{interval}");
+    }
+    assert!(!is_supported("2m"));
+    assert_eq!(interval_ms("2m"), None);
+    assert_eq!(bucket_start("2m", 0), None);
+}
+
+#[test]
+fn monthly_interval_has_no_fixed_length() {
+    assert_eq!(interval_ms("1M"), None);
+    assert_eq!(interval_ms("1d"), Some(DAY));
+    assert_eq!(interval_ms("1w"), Some(7 * DAY));
+}
+
+#[test]
+fn fixed_intervals_walk_back_whole_candles() {
+    let now = ms(2024, 5, 17, 13, 37);
+    assert_eq!(window_start("15m", 4, now), Some(ms(2024, 5, 17, 12, 45)));
+    assert_eq!(window_start("1h", 1, now), Some(ms(2024, 5, 17, 13, 0)));
+    assert_eq!(window_start("1d", 0, now), Some(ms(2024, 5, 17, 0, 0)));
+}
+
+#[test]
+fn weekly_buckets_align_to_epoch_weeks() {
+    // 2024-05-16 is a Thursday, like 1970-01-01.
+    let now = ms(2024, 5, 19, 8, 0);
+    assert_eq!(bucket_start("1w", now), Some(ms(2024, 5, 16, 0, 0)));
+    assert_eq!(next_bucket_start("1w", now), Some(ms(2024, 5, 23, 0, 0)));
+}
+
+#[test]
+fn monthly_buckets_follow_calendar_months() {
+    assert_eq!(
+        bucket_start("1M", ms(2024, 1, 31, 23, 59)),
+        Some(ms(2024, 1, 1, 0, 0))
+    );
+    assert_eq!(
+        next_bucket_start("1M", ms(2024, 1, 31, 23, 59)),
+        Some(ms(2024, 2, 1, 0, 0))
+    );
+    assert_eq!(
+        next_bucket_start("1M", ms(2023, 12, 10, 0, 0)),
+        Some(ms(2024, 1, 1, 0, 0))
+    );
+    assert_eq!(
+        bucket_start("1M", ms(2024, 3, 1, 0, 0)),
+        Some(ms(2024, 3, 1, 0, 0))
+    );
+}
+
+#[test]
+fn month_lengths_vary_including_leap_february() {
+    let length = |y, m| next_bucket_start("1M", ms(y, m, 1, 0, 0)).unwrap() - ms(y, m, 1, 0, 0);
+    assert_eq!(length(2024, 1), 31 * DAY);
+    assert_eq!(length(2024, 2), 29 * DAY);
+    assert_eq!(length(2023, 2), 28 * DAY);
+    assert_eq!(length(2024, 4), 30 * DAY);
+}
+
+#[test]
+fn twelve_month_window_starts_on_a_month_boundary() {
+    let now = ms(2024, 3, 15, 12, 0);
+    let start = window_start("1M", 12, now).unwrap();
+    assert_eq!(date(start), NaiveDate::from_ymd_opt(2023, 4, 1).unwrap());
+
+    // A fixed 30-day month would have started in mid-April 2023 instead.
+    let naive = bucket_start("1d", now).unwrap() - 11 * 30 * DAY;
+    assert_ne!(start, naive);
+}
+
+#[test]
+fn monthly_window_crosses_leap_february() {
+    let now = ms(2024, 3, 31, 23, 0);
+    assert_eq!(window_start("1M", 2, now), Some(ms(2024, 2, 1, 0, 0)));
+    assert_eq!(window_start("1M", 3, now), Some(ms(2024, 1, 1, 0, 0)));
+    assert_eq!(window_start("1M", 14, now), Some(ms(2023, 2, 1, 0, 0)));
+}
+
+#[test]
+fn poll_delay_runs_to_the_next_month_boundary() {
+    assert_eq!(
+        ms_until_next_bucket("1M", ms(2024, 2, 1, 0, 0)),
+        Some(29 * DAY)
+    );
+    assert_eq!(ms_until_next_bucket("1M", ms(2023, 2, 28, 0, 0)), Some(DAY));
+    assert_eq!(
+        ms_until_next_bucket("1M", ms(2024, 1, 31, 12, 0)),
+        Some(DAY / 2)
+    );
+    assert_eq!(
+        ms_until_next_bucket("1m", ms(2024, 1, 31, 12, 0) + 15_000),
+        Some(45_000)
+    );
+}
+
+#[test]
+fn closed_since_counts_the_gap_to_backfill() {
+    let last = ms(2024, 5, 17, 12, 0);
+    // 12:01 through 13:36 have closed; 13:37 is still forming.
+    assert_eq!(
+        closed_since("1m", last, ms(2024, 5, 17, 13, 37) + 30_000),
+        Some(96)
+    );
+    // 12:05 through 13:30 on 5m candles.
+    assert_eq!(closed_since("5m", last, ms(2024, 5, 17, 13, 37)), Some(18));
+    // Nothing has closed since the last candle, or the next one is forming.
+    assert_eq!(closed_since("1m", last, last + 30_000), Some(0));
+    assert_eq!(closed_since("1m", last, last + 90_000), Some(0));
+    assert_eq!(closed_since("1m", last, last + 120_000), Some(1));
+    // February through April closed by mid-May.
+    assert_eq!(
+        closed_since("1M", ms(2024, 1, 1, 0, 0), ms(2024, 5, 17, 0, 0)),
+        Some(3)
+    );
+    assert_eq!(closed_since("2m", last, last), None);
+}
+
+#[test]
+fn candles_between_counts_intervals_not_candles_seen() {
+    let start = ms(2024, 5, 17, 12, 0);
+    assert_eq!(candles_between("1m", start, start + 59_999), Some(0));
+    assert_eq!(
+        candles_between("1m", start, ms(2024, 5, 17, 13, 30)),
+        Some(90)
+    );
+    assert_eq!(
+        candles_between("15m", start, ms(2024, 5, 17, 13, 30)),
+        Some(6)
+    );
+    // Backwards is no distance at all.
+    assert_eq!(candles_between("1m", start, start - 60_000), Some(0));
+    // Months of different lengths each count once.
+    assert_eq!(
+        candles_between("1M", ms(2024, 1, 15, 0, 0), ms(2024, 5, 2, 0, 0)),
+        Some(4)
+    );
+    assert_eq!(candles_between("2m", start, start), None);
+}
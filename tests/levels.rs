@@ -0,0 +1,129 @@
+mod common;
+
+use common::candles_from_closes;
+use perpscreener::business_logic::levels::{LevelConfig, LevelDetector, LevelSide};
+use perpscreener::models::candle::Candle;
+
+fn path(start: f64, steps: &[(usize, f64)]) -> Vec<f64> {
+    let mut closes = Vec::new();
+    let mut price = start;
+    for &(count, step) in steps {
+        for _ in 0..count {
+            price += step;
+            closes.push(price);
+        }
+    }
+    closes
+}
+
+/// Steady 0.5 moves make every candle a full ATR wide; a 2 ATR reversal keeps
+/// swings to the real turns.
+fn config() -> LevelConfig {
+    LevelConfig {
+        rev_atr: 2.0,
+        ..LevelConfig::default()
+    }
+}
+
+/// Four swings between 100 and 104, then `rest`.
+fn range_then(rest: &[(usize, f64)]) -> Vec<Candle> {
+    let mut steps = [(8, 0.5), (8, -0.5)].repeat(4);
+    steps.extend_from_slice(rest);
+    candles_from_closes(100.0, &path(100.0, &steps), 0.1)
+}
+
+fn detector_after(candles: &[Candle]) -> LevelDetector {
+    let mut detector = LevelDetector::new(config());
+    for candle in candles {
+        detector.update(candle);
+    }
+    detector
+}
+
+#[test]
+fn clusters_repeated_swings_into_levels() {
+    let detector = detector_after(&range_then(&[(4, 0.5)]));
+
+    let levels = detector.top(10);
+    assert_eq!(levels.len(), 2, "{levels:?}");
+    let resistance = levels
+        .iter()
+        .find(|l| l.side == LevelSide::Resistance)
+        .unwrap();
+    let support = levels
+        .iter()
+        .find(|l| l.side == LevelSide::Support)
+        .unwrap();
+    assert!((resistance.price - 104.1).abs() < 1e-9);
+    assert!((support.price - 99.9).abs() < 1e-9);
+    assert!(resistance.touches >= 2);
+    assert!(support.touches >= 2);
+    assert!(!resistance.flipped);
+
+    assert_eq!(detector.top(1).len(), 1);
+    assert!(detector.top(1)[0].touches >= detector.top(2)[1].touches);
+}
+
+#[test]
+fn broken_level_flips_side_then_drops() {
+    // Break out above 104 and hold...
+    let detector = detector_after(&range_then(&[(14, 0.5)]));
+    let levels = detector.top(10);
+    let old_resistance = levels
+        .iter()
+        .find(|l| (l.price - 104.1).abs() < 1e-9)
+        .unwrap();
+    assert_eq!(old_resistance.side, LevelSide::Support);
+    assert!(old_resistance.flipped);
+
+    // ...then collapse back through it.
+    let detector = detector_after(&range_then(&[(14, 0.5), (24, -0.5)]));
+    let levels = detector.top(10);
+    assert!(
+        !levels.iter().any(|l| (l.price - 104.1).abs() < 1e-9),
+        "{levels:?}"
+    );
+}
+
+#[cfg(feature = "server")]
+#[tokio::test]
+async fn endpoint_computes_levels_from_closed_candles() {
+    use axum::http::StatusCode;
+    use perpscreener::services::hyperliquid::HyperliquidClient;
+
+    let candles = range_then(&[(4, 0.5)]);
+    let now = candles.last().unwrap().close_time + 1;
+    let base_url = common::spawn_candle_server(vec![("BTC", candles.clone())]).await;
+    let state = common::state_with_clock(common::ManualClock::new(now))
+        .with_hyperliquid(HyperliquidClient::with_base_url(base_url));
+    let app = perpscreener::app(state);
+
+    let mut detector = LevelDetector::new(LevelConfig::default());
+    for candle in &candles {
+        detector.update(candle);
+    }
+    let expected = detector.top(10);
+    assert!(!expected.is_empty());
+
+    let (status, body) = common::get(app.clone(), "/levels?coin=BTC&interval=1m").await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["coin"], "BTC");
+    assert_eq!(body["candles"], candles.len());
+    let levels = body["levels"].as_array().unwrap();
+    assert_eq!(levels.len(), expected.len());
+    assert_eq!(levels[0]["touches"], expected[0].touches);
+    assert!(["support", "resistance"].contains(&levels[0]["side"].as_str().unwrap()));
+
+    let (_, body) = common::get(app.clone(), "/levels?coin=BTC&interval=1m&limit=1").await;
+    assert_eq!(body["levels"].as_array().unwrap().len(), 1);
+
+    let (status, body) = common::get(app.clone(), "/levels?coin=DOGE&interval=1m").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["candles"], 0);
+    assert_eq!(body["levels"].as_array().unwrap().len(), 0);
+
+    for uri in ["/levels?coin=BTC&interval=7m", "/levels?coin=BTC&limit=0"] {
+        let (status, _) = common::get(app.clone(), uri).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+    }
+}
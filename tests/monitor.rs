@@ -0,0 +1,496 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::routing::post;
+use axum::{Json, Router};
+use common::{ManualClock, MINUTE_MS, T0};
+use perpscreener::business_logic::intervals;
+use perpscreener::models::candle::Candle;
+use perpscreener::services::hyperliquid::HyperliquidClient;
+use perpscreener::services::monitor::MarketMonitor;
+use perpscreener::services::webhooks::NewWebhook;
+use perpscreener::settings::Settings;
+use perpscreener::state::AppState;
+use serde_json::Value;
+use tokio_util::sync::CancellationToken;
+
+/// Flat 1m candles with unit volume, except `spikes` as (index, volume).
+fn series(count: u64, spikes: &[(u64, f64)]) -> Vec<Candle> {
+    (0..count)
+        .map(|i| {
+            let mut candle = common::candle(i, 100.0, 100.5, 99.5, 100.2);
+            if let Some(&(_, volume)) = spikes.iter().find(|(index, _)| *index == i) {
+                candle.volume = volume;
+            }
+            candle
+        })
+        .collect()
+}
+
+/// Clock standing just after candle `index` closes.
+fn after(index: u64) -> u64 {
+    T0 + (index + 1) * MINUTE_MS
+}
+
+async fn monitored_state(clock: Arc<ManualClock>, series: Vec<(&str, Vec<Candle>)>) -> AppState {
+    let coins = series.iter().map(|(coin, _)| coin.to_string()).collect();
+    let base_url = common::spawn_candle_server(series).await;
+    common::state_with_clock(clock)
+        .with_hyperliquid(HyperliquidClient::with_base_url(base_url))
+        .with_monitored_coins(coins)
+}
+
+#[tokio::test]
+async fn volume_spikes_reach_the_screener() {
+    let clock = ManualClock::new(after(24));
+    let state = monitored_state(
+        clock.clone(),
+        vec![("BTC", series(40, &[(30, 10.0)])), ("ETH", series(40, &[]))],
+    )
+    .await;
+    let mut monitor = MarketMonitor::new(state.clone(), "1m");
+
+    assert_eq!(monitor.run_cycle().await, 50);
+    assert!(state.volume_spikes.snapshot().is_empty());
+
+    clock.set(after(34));
+    assert_eq!(monitor.run_cycle().await, 20);
+    // Nothing new has closed, so nothing is refed.
+    assert_eq!(monitor.run_cycle().await, 0);
+
+    let (status, body) = common::get(perpscreener::app(state), "/screeners/volume-spikes").await;
+    assert_eq!(status, StatusCode::OK);
+    let spikes = body["spikes"].as_array().unwrap();
+    assert_eq!(spikes.len(), 1, "{body}");
+    assert_eq!(spikes[0]["coin"], "BTC");
+    assert_eq!(spikes[0]["open_time"], T0 + 30 * MINUTE_MS);
+    assert_eq!(spikes[0]["multiple"], 10.0);
+}
+
+#[tokio::test]
+async fn volume_spikes_are_sent_to_webhooks() {
+    let received: Arc<Mutex<Vec<Value>>> = Arc::default();
+    let hook = Router::new().route(
+        "/hook",
+        post({
+            let received = received.clone();
+            move |Json(body): Json<Value>| async move {
+                received.lock().unwrap().push(body);
+                StatusCode::OK
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, hook).await.unwrap() });
+
+    let clock = ManualClock::new(after(39));
+    let state = monitored_state(clock, vec![("BTC", series(40, &[(30, 10.0)]))]).await;
+    state
+        .webhooks
+        .create(
+            NewWebhook {
+                url: format!("http://{addr}/hook"),
+                coins: None,
+                min_severity: None,
+                secret: None,
+                quiet_hours: None,
+            },
+            T0,
+        )
+        .unwrap();
+
+    MarketMonitor::new(state, "1m").run_cycle().await;
+    let received = received.lock().unwrap();
+    assert_eq!(received.len(), 1, "{received:?}");
+    assert_eq!(received[0]["type"], "volume_spike");
+    assert_eq!(received[0]["coin"], "BTC");
+    assert_eq!(received[0]["open_time"], T0 + 30 * MINUTE_MS);
+}
+
+#[tokio::test]
+async fn volume_spike_rule_comes_from_the_settings() {
+    let settings = Settings::from_toml(
+        "[volume_spike]\nmultiple = 20.0\n",
+        std::path::Path::new("test.toml"),
+    )
+    .unwrap();
+    let clock = ManualClock::new(after(39));
+    let state = monitored_state(clock, vec![("BTC", series(40, &[(30, 10.0)]))])
+        .await
+        .with_settings(settings);
+
+    MarketMonitor::new(state.clone(), "1m").run_cycle().await;
+    assert!(state.volume_spikes.snapshot().is_empty());
+}
+
+#[tokio::test]
+async fn coins_dropped_from_the_list_stop_being_fetched() {
+    let clock = ManualClock::new(after(24));
+    let state = monitored_state(
+        clock.clone(),
+        vec![("BTC", series(40, &[])), ("ETH", series(40, &[(30, 10.0)]))],
+    )
+    .await;
+    let mut monitor = MarketMonitor::new(state.clone(), "1m");
+    monitor.run_cycle().await;
+
+    state.coins.set(vec!["BTC".to_string()]);
+    clock.set(after(34));
+    assert_eq!(monitor.run_cycle().await, 10);
+    assert!(state.volume_spikes.snapshot().is_empty());
+}
+
+#[tokio::test]
+async fn candle_anomalies_reach_the_screener() {
+    let mut candles = series(40, &[]);
+    candles[32].high = 106.0;
+    candles[32].low = 94.0;
+    let clock = ManualClock::new(after(39));
+    let state = monitored_state(clock, vec![("SOL", candles)]).await;
+    MarketMonitor::new(state.clone(), "1m").run_cycle().await;
+
+    let (status, body) =
+        common::get(perpscreener::app(state), "/screeners/anomalies?coin=SOL").await;
+    assert_eq!(status, StatusCode::OK);
+    let anomalies = body["anomalies"].as_array().unwrap();
+    assert_eq!(anomalies.len(), 1, "{body}");
+    assert_eq!(anomalies[0]["open_time"], T0 + 32 * MINUTE_MS);
+    assert!(anomalies[0]["kinds"]
+        .as_array()
+        .unwrap()
+        .contains(&serde_json::json!("wide_range")));
+}
+
+#[tokio::test]
+async fn anomaly_thresholds_come_from_the_settings() {
+    let mut candles = series(40, &[]);
+    candles[32].high = 106.0;
+    candles[32].low = 94.0;
+    let settings = Settings::from_toml(
+        "[anomalies]\nrange_atr = 50.0\nwick_body_ratio = 500.0\nenvelope_margin_atr = 50.0\n",
+        std::path::Path::new("test.toml"),
+    )
+    .unwrap();
+    let clock = ManualClock::new(after(39));
+    let state = monitored_state(clock, vec![("SOL", candles)])
+        .await
+        .with_settings(settings);
+
+    MarketMonitor::new(state.clone(), "1m").run_cycle().await;
+    assert!(state.anomalies.snapshot().is_empty());
+}
+
+#[tokio::test]
+async fn processed_candles_drive_health() {
+    let clock = ManualClock::new(after(24));
+    let state = monitored_state(
+        clock.clone(),
+        vec![("BTC", series(40, &[])), ("ETH", series(25, &[]))],
+    )
+    .await;
+    let mut monitor = MarketMonitor::new(state.clone(), "1m");
+    let app = perpscreener::app(state.clone());
+
+    // Nothing processed yet, so every monitored coin is stale.
+    let (status, body) = common::get(app.clone(), "/health").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["status"], "unhealthy");
+    assert_eq!(body["coins"].as_array().unwrap().len(), 2);
+    assert_eq!(body["coins"][0]["last_candle_close_ms"], Value::Null);
+
+    monitor.run_cycle().await;
+    let (status, body) = common::get(app.clone(), "/health").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "healthy");
+    assert_eq!(body["coins"][0]["coin"], "BTC");
+    assert_eq!(
+        body["coins"][0]["last_candle_close_ms"],
+        T0 + 25 * MINUTE_MS - 1
+    );
+
+    // ETH's candles stop at index 24, so it goes stale while BTC keeps up.
+    clock.set(after(39));
+    monitor.run_cycle().await;
+    let (status, body) = common::get(app.clone(), "/health").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "degraded");
+    assert_eq!(body["coins"][1]["coin"], "ETH");
+    assert_eq!(body["coins"][1]["stale"], true);
+
+    state.coins.set(vec!["BTC".to_string()]);
+    monitor.run_cycle().await;
+    let (_, body) = common::get(app, "/health").await;
+    assert_eq!(body["status"], "healthy");
+    assert_eq!(body["coins"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn candles_that_never_arrive_flag_a_data_gap() {
+    let mut candles = series(40, &[]);
+    candles.remove(30);
+    let clock = ManualClock::new(after(39));
+    let state = monitored_state(clock, vec![("BTC", candles)]).await;
+
+    assert_eq!(
+        MarketMonitor::new(state.clone(), "1m").run_cycle().await,
+        39
+    );
+    let (_, body) = common::get(perpscreener::app(state), "/health").await;
+    assert_eq!(body["coins"][0]["coin"], "BTC");
+    assert_eq!(body["coins"][0]["data_gap"], true);
+    assert_eq!(body["coins"][0]["gap_count"], 1);
+}
+
+#[tokio::test]
+async fn reset_policy_restarts_the_detectors_after_a_gap() {
+    let mut candles = series(40, &[(35, 10.0)]);
+    candles.remove(30);
+    let settings = Settings::from_toml(
+        "[monitor.gaps]\npolicy = \"reset_detector\"\n",
+        std::path::Path::new("test.toml"),
+    )
+    .unwrap();
+    let clock = ManualClock::new(after(39));
+    let state = monitored_state(clock, vec![("BTC", candles)])
+        .await
+        .with_settings(settings);
+
+    MarketMonitor::new(state.clone(), "1m").run_cycle().await;
+    // The spike lands inside the restarted volume window, so it is not judged.
+    assert!(state.volume_spikes.snapshot().is_empty());
+    let (_, body) = common::get(perpscreener::app(state), "/health").await;
+    assert_eq!(body["coins"][0]["data_gap"], false);
+    assert_eq!(body["coins"][0]["gap_count"], 1);
+}
+
+#[tokio::test]
+async fn late_candles_are_backfilled() {
+    // Candle 30 is missing from any range that also covers candle 29, but a
+    // refetch of just its own slot finds it.
+    let candles = Arc::new(series(40, &[]));
+    let app = Router::new().route(
+        "/info",
+        post(move |Json(body): Json<Value>| {
+            let candles = candles.clone();
+            async move {
+                let start = body["req"]["startTime"].as_u64().unwrap();
+                let end = body["req"]["endTime"].as_u64().unwrap();
+                let late = T0 + 30 * MINUTE_MS;
+                let served: Vec<Value> = candles
+                    .iter()
+                    .filter(|c| c.open_time >= start && c.open_time <= end)
+                    .filter(|c| c.open_time != late || start > late - MINUTE_MS)
+                    .map(|c| common::candle_json("BTC", c))
+                    .collect();
+                Json(Value::from(served))
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let clock = ManualClock::new(after(24));
+    let state = common::state_with_clock(clock.clone())
+        .with_hyperliquid(HyperliquidClient::with_base_url(format!("http://{addr}")))
+        .with_monitored_coins(vec!["BTC".to_string()]);
+    let mut monitor = MarketMonitor::new(state.clone(), "1m");
+    assert_eq!(monitor.run_cycle().await, 25);
+
+    clock.set(after(39));
+    assert_eq!(monitor.run_cycle().await, 15);
+    let (_, body) = common::get(perpscreener::app(state), "/health").await;
+    assert_eq!(body["coins"][0]["data_gap"], false);
+}
+
+#[tokio::test]
+async fn repeated_and_unordered_candles_are_fed_once() {
+    // Upstream serves every candle twice, newest first.
+    let candles = Arc::new(series(40, &[(30, 10.0)]));
+    let app = Router::new().route(
+        "/info",
+        post(move |Json(body): Json<Value>| {
+            let candles = candles.clone();
+            async move {
+                let start = body["req"]["startTime"].as_u64().unwrap();
+                let end = body["req"]["endTime"].as_u64().unwrap();
+                let served: Vec<Value> = candles
+                    .iter()
+                    .rev()
+                    .filter(|c| c.open_time >= start && c.open_time <= end)
+                    .flat_map(|c| [common::candle_json("BTC", c), common::candle_json("BTC", c)])
+                    .collect();
+                Json(Value::from(served))
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let clock = ManualClock::new(after(24));
+    let state = common::state_with_clock(clock.clone())
+        .with_hyperliquid(HyperliquidClient::with_base_url(format!("http://{addr}")))
+        .with_monitored_coins(vec!["BTC".to_string()]);
+    let mut monitor = MarketMonitor::new(state.clone(), "1m");
+    assert_eq!(monitor.run_cycle().await, 25);
+
+    clock.set(after(34));
+    assert_eq!(monitor.run_cycle().await, 10);
+    assert_eq!(monitor.run_cycle().await, 0);
+    assert_eq!(state.volume_spikes.snapshot().len(), 1);
+    let (_, body) = common::get(perpscreener::app(state), "/health").await;
+    assert_eq!(body["coins"][0]["data_gap"], false);
+}
+
+#[tokio::test]
+async fn warmup_is_fetched_on_the_monitor_interval() {
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let recorded = requests.clone();
+    let app = Router::new().route(
+        "/info",
+        post(move |Json(body): Json<Value>| {
+            let recorded = recorded.clone();
+            async move {
+                recorded.lock().unwrap().push(body["req"].clone());
+                Json(Value::from(Vec::<Value>::new()))
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    // The cup and handle window sets the warmup to 120 candles.
+    let settings = Settings::from_toml(
+        "[cup_and_handle]\nwindow = 120\nmax_cup_candles = 100\n",
+        std::path::Path::new("test.toml"),
+    )
+    .unwrap();
+    let now = T0 + 1_000 * 15 * MINUTE_MS + 7 * MINUTE_MS;
+    let state = common::state_with_clock(ManualClock::new(now))
+        .with_hyperliquid(HyperliquidClient::with_base_url(format!("http://{addr}")))
+        .with_settings(settings)
+        .with_monitored_coins(vec!["BTC".to_string()]);
+    MarketMonitor::new(state, "15m").run_cycle().await;
+
+    let requests = requests.lock().unwrap();
+    let warmup = requests
+        .iter()
+        .find(|req| req["coin"] == "BTC")
+        .expect("a candle request for BTC");
+    assert_eq!(warmup["interval"], "15m");
+    let bucket = intervals::bucket_start("15m", now).unwrap();
+    assert_eq!(warmup["startTime"], bucket - 120 * 15 * MINUTE_MS);
+    assert_eq!(warmup["endTime"], now);
+}
+
+async fn post_to(app: Router, uri: &str) -> (StatusCode, Value) {
+    common::send(app, Request::post(uri).body(Body::empty()).unwrap()).await
+}
+
+#[tokio::test]
+async fn paused_coins_keep_their_state_and_catch_up_on_resume() {
+    let clock = ManualClock::new(after(24));
+    let state = monitored_state(
+        clock.clone(),
+        vec![("BTC", series(40, &[(30, 10.0)])), ("ETH", series(40, &[]))],
+    )
+    .await;
+    let mut monitor = MarketMonitor::new(state.clone(), "1m");
+    let app = perpscreener::app(state.clone());
+    assert_eq!(monitor.run_cycle().await, 50);
+
+    let (status, body) = post_to(app.clone(), "/admin/monitor/pause?coin=BTC").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["all"], false);
+    assert_eq!(body["coins"], serde_json::json!(["BTC"]));
+
+    // Only ETH is fetched, so BTC's spike at 30 goes unseen for now.
+    clock.set(after(34));
+    assert_eq!(monitor.run_cycle().await, 10);
+    assert!(state.volume_spikes.snapshot().is_empty());
+    let (_, body) = common::get(app.clone(), "/health").await;
+    assert_eq!(body["coins"][0]["coin"], "BTC");
+    assert_eq!(body["coins"][0]["paused"], true);
+    assert_eq!(
+        body["coins"][0]["last_candle_close_ms"],
+        T0 + 25 * MINUTE_MS - 1
+    );
+    assert_eq!(body["coins"][1]["paused"], false);
+
+    let (status, body) = post_to(app.clone(), "/admin/monitor/resume?coin=BTC").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["coins"], serde_json::json!([]));
+
+    // BTC picks up where it stopped, with the detectors it had.
+    assert_eq!(monitor.run_cycle().await, 10);
+    assert_eq!(state.volume_spikes.snapshot().len(), 1);
+    let (_, body) = common::get(app, "/health").await;
+    assert_eq!(body["coins"][0]["paused"], false);
+    assert_eq!(body["coins"][0]["data_gap"], false);
+}
+
+#[tokio::test]
+async fn pausing_everything_stops_every_fetch() {
+    let clock = ManualClock::new(after(24));
+    let state = monitored_state(clock.clone(), vec![("BTC", series(40, &[]))]).await;
+    let mut monitor = MarketMonitor::new(state.clone(), "1m");
+    let app = perpscreener::app(state.clone());
+    assert_eq!(monitor.run_cycle().await, 25);
+
+    let (_, body) = post_to(app.clone(), "/admin/monitor/pause").await;
+    assert_eq!(body["all"], true);
+    clock.set(after(34));
+    assert_eq!(monitor.run_cycle().await, 0);
+
+    // A coin resumed on its own stays paused while everything is.
+    post_to(app.clone(), "/admin/monitor/resume?coin=BTC").await;
+    assert_eq!(monitor.run_cycle().await, 0);
+
+    let (_, body) = post_to(app.clone(), "/admin/monitor/resume").await;
+    assert_eq!(body["all"], false);
+    assert_eq!(monitor.run_cycle().await, 10);
+
+    let (status, _) = post_to(app, "/admin/monitor/pause?coin=DOGE").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn resuming_runs_a_cycle_straight_away() {
+    let clock = ManualClock::new(after(24));
+    let state = monitored_state(clock.clone(), vec![("BTC", series(40, &[]))]).await;
+    let shutdown = CancellationToken::new();
+    // The first tick fires at once; the next is an hour away.
+    let monitor = tokio::spawn(
+        MarketMonitor::new(state.clone(), "1m").run(Duration::from_secs(3600), shutdown.clone()),
+    );
+    let caught_up = |index: u64| {
+        let state = state.clone();
+        async move {
+            while state.freshness.snapshot().get("BTC") != Some(&(after(index) - 1)) {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(5), caught_up(24))
+        .await
+        .unwrap();
+
+    state.coins.pause(None);
+    clock.set(after(34));
+    state.coins.resume(None);
+    tokio::time::timeout(Duration::from_secs(5), caught_up(34))
+        .await
+        .unwrap();
+
+    shutdown.cancel();
+    monitor.await.unwrap();
+}
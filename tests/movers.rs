@@ -0,0 +1,212 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use axum::http::StatusCode;
+use axum::routing::post;
+use axum::{Json, Router};
+use common::ManualClock;
+use perpscreener::business_logic::movers::{mover_from_candles, rank, MoverWindow};
+use perpscreener::services::hyperliquid::HyperliquidClient;
+use serde_json::{json, Value};
+
+const NOW: u64 = 1_700_000_030_000;
+const HOUR_MS: u64 = 3_600_000;
+
+#[test]
+fn parses_supported_windows_only() {
+    assert_eq!(MoverWindow::parse("1h"), Some(MoverWindow::OneHour));
+    assert_eq!(MoverWindow::parse("4h"), Some(MoverWindow::FourHours));
+    assert_eq!(MoverWindow::parse("24h"), Some(MoverWindow::OneDay));
+    assert_eq!(MoverWindow::parse("1d"), None);
+    assert_eq!(MoverWindow::parse(""), None);
+}
+
+#[test]
+fn fetch_start_covers_the_window_plus_the_forming_candle() {
+    let hour_start = NOW - NOW % HOUR_MS;
+    assert_eq!(
+        MoverWindow::OneDay.fetch_start(NOW),
+        hour_start - 24 * HOUR_MS
+    );
+    let five_min = 5 * 60_000;
+    assert_eq!(
+        MoverWindow::OneHour.fetch_start(NOW),
+        NOW - NOW % five_min - 12 * five_min
+    );
+}
+
+#[test]
+fn mover_measures_first_open_to_last_close() {
+    let candles = [
+        common::candle(0, 100.0, 101.0, 99.0, 100.5),
+        common::candle(1, 100.5, 111.0, 100.0, 110.0),
+    ];
+    let mover = mover_from_candles("BTC", &candles).unwrap();
+    assert_eq!(mover.open_price, 100.0);
+    assert_eq!(mover.last_price, 110.0);
+    assert!((mover.change_pct - 10.0).abs() < 1e-9);
+    assert_eq!(mover.volume, 2.0);
+    assert_eq!(mover.volume_usd, 210.5);
+    assert_eq!(mover_from_candles("BTC", &[]), None);
+}
+
+#[test]
+fn ranks_by_absolute_change() {
+    let mover = |coin: &str, close: f64| {
+        mover_from_candles(coin, &[common::candle(0, 100.0, 120.0, 80.0, close)]).unwrap()
+    };
+    let ranked = rank(vec![mover("A", 101.0), mover("B", 90.0), mover("C", 105.0)]);
+    let coins: Vec<&str> = ranked.iter().map(|m| m.coin.as_str()).collect();
+    assert_eq!(coins, ["B", "C", "A"]);
+}
+
+fn raw_candle(open_time: u64, open: &str, close: &str, volume: &str) -> Value {
+    json!({
+        "t": open_time, "T": open_time + HOUR_MS - 1, "s": "X", "i": "1h",
+        "o": open, "h": open, "l": close, "c": close, "v": volume, "n": 3
+    })
+}
+
+fn context(prev_day: &str, mark: &str, base_volume: &str) -> Value {
+    json!({
+        "dayBaseVlm": base_volume, "dayNtlVlm": "1000.0", "funding": "0.0",
+        "markPx": mark, "midPx": mark, "openInterest": "1.0", "oraclePx": mark,
+        "prevDayPx": prev_day
+    })
+}
+
+/// Stub `info` endpoint with three perps and one spot pair, plus day
+/// contexts for two perps and a delisted one; returns its base URL and a
+/// counter of candle requests served.
+async fn spawn_hyperliquid() -> (String, Arc<AtomicUsize>) {
+    let candle_requests = Arc::new(AtomicUsize::new(0));
+    let counter = candle_requests.clone();
+    let app = Router::new().route(
+        "/info",
+        post(move |Json(body): Json<Value>| {
+            let counter = counter.clone();
+            async move {
+                match body["type"].as_str() {
+                    Some("allMids") => Ok(Json(json!({
+                        "BTC": "110.0", "ETH": "95.0", "DOGE": "1.01", "@107": "40.0"
+                    }))),
+                    Some("metaAndAssetCtxs") => Ok(Json(json!([
+                        { "universe": [
+                            { "name": "BTC", "szDecimals": 5, "maxLeverage": 40 },
+                            { "name": "ETH", "szDecimals": 4, "maxLeverage": 25 },
+                            { "name": "OLD", "szDecimals": 0, "maxLeverage": 3, "isDelisted": true }
+                        ] },
+                        [
+                            context("100.0", "104.0", "30.0"),
+                            context("100.0", "88.0", "50.0"),
+                            context("0.0", "0.0", "0.0")
+                        ]
+                    ]))),
+                    Some("candleSnapshot") => {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        let start = body["req"]["startTime"].as_u64().unwrap();
+                        let candles = match body["req"]["coin"].as_str() {
+                            Some("BTC") => json!([
+                                raw_candle(start, "100.0", "105.0", "1.0"),
+                                raw_candle(start + HOUR_MS, "105.0", "110.0", "2.0")
+                            ]),
+                            Some("ETH") => json!([raw_candle(start, "100.0", "95.0", "4.0")]),
+                            Some("DOGE") => json!([raw_candle(start, "1.0", "1.01", "1000")]),
+                            _ => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+                        };
+                        Ok(Json(candles))
+                    }
+                    _ => Err(StatusCode::BAD_REQUEST),
+                }
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{addr}"), candle_requests)
+}
+
+#[tokio::test]
+async fn client_parses_candle_snapshots() {
+    let (url, _) = spawn_hyperliquid().await;
+    let client = HyperliquidClient::with_base_url(url);
+
+    let mids = client.all_mids().await.unwrap();
+    assert_eq!(mids["BTC"], 110.0);
+
+    let candles = client.candle_snapshot("BTC", "1h", NOW, NOW).await.unwrap();
+    assert_eq!(candles.len(), 2);
+    assert_eq!(candles[1].open_time, NOW + HOUR_MS);
+    assert_eq!(candles[1].close_time, NOW + 2 * HOUR_MS - 1);
+    assert_eq!(candles[1].close, 110.0);
+    assert_eq!(candles[1].num_trades, 3);
+}
+
+#[tokio::test]
+async fn movers_ranks_perps_and_caches_the_ranking() {
+    let (url, candle_requests) = spawn_hyperliquid().await;
+    let clock = ManualClock::new(NOW);
+    let state = common::state_with_clock(clock.clone())
+        .with_hyperliquid(HyperliquidClient::with_base_url(url));
+    let app = perpscreener::app(state);
+
+    let (status, body) = common::get(app.clone(), "/movers?window=1h&limit=2").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["window"], "1h");
+    assert_eq!(body["computed_at_ms"], NOW);
+    let movers = body["movers"].as_array().unwrap();
+    assert_eq!(movers.len(), 2);
+    assert_eq!(movers[0]["coin"], "BTC");
+    assert_eq!(movers[0]["change_pct"], 10.0);
+    assert_eq!(movers[0]["volume"], 3.0);
+    assert_eq!(movers[1]["coin"], "ETH");
+    assert_eq!(candle_requests.load(Ordering::SeqCst), 3);
+
+    clock.advance(10_000);
+    let (_, body) = common::get(app.clone(), "/movers?window=1h").await;
+    assert_eq!(body["computed_at_ms"], NOW);
+    assert_eq!(body["movers"].as_array().unwrap().len(), 3);
+    assert_eq!(candle_requests.load(Ordering::SeqCst), 3);
+
+    clock.advance(30_000);
+    let (_, body) = common::get(app, "/movers?window=1h").await;
+    assert_eq!(body["computed_at_ms"], NOW + 40_000);
+    assert_eq!(candle_requests.load(Ordering::SeqCst), 6);
+}
+
+#[tokio::test]
+async fn day_movers_come_from_one_asset_context_call() {
+    let (url, candle_requests) = spawn_hyperliquid().await;
+    let state = common::state_with_clock(ManualClock::new(NOW))
+        .with_hyperliquid(HyperliquidClient::with_base_url(url));
+
+    let (status, body) = common::get(perpscreener::app(state), "/movers").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["window"], "24h");
+    let movers = body["movers"].as_array().unwrap();
+    // OLD has no price a day ago and is left out.
+    assert_eq!(movers.len(), 2);
+    assert_eq!(movers[0]["coin"], "ETH");
+    assert_eq!(movers[0]["change_pct"], -12.0);
+    assert_eq!(movers[0]["open_price"], 100.0);
+    assert_eq!(movers[0]["last_price"], 88.0);
+    assert_eq!(movers[0]["volume"], 50.0);
+    assert_eq!(movers[0]["volume_usd"], 1000.0);
+    assert_eq!(movers[1]["coin"], "BTC");
+    assert_eq!(candle_requests.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn movers_rejects_invalid_parameters() {
+    let app = perpscreener::app(common::state_with_clock(ManualClock::new(NOW)));
+    for uri in ["/movers?window=2h", "/movers?limit=0", "/movers?limit=500"] {
+        let (status, body) = common::get(app.clone(), uri).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+        assert!(body["error"].is_string());
+    }
+}
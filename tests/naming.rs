@@ -0,0 +1,267 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::collections::{BTreeMap, HashMap};
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::routing::post;
+use axum::{middleware, Json, Router};
+use common::ManualClock;
+use perpscreener::naming::{
+    camel_to_snake, rename_json, snake_to_camel, ApiNaming, JsonNaming, MAX_RENAMED_BODY_BYTES,
+};
+use perpscreener::state::AppState;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use utoipa::{OpenApi, ToSchema};
+
+const NOW: u64 = 1_700_000_000_000;
+
+fn camel_state() -> AppState {
+    common::state_with_clock(ManualClock::new(NOW)).with_naming(ApiNaming::Camel)
+}
+
+fn post_json(uri: &str, body: Value) -> Request<Body> {
+    Request::post(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+#[test]
+fn converts_between_conventions() {
+    assert_eq!(snake_to_camel("as_of_ms"), "asOfMs");
+    assert_eq!(snake_to_camel("peak1_price"), "peak1Price");
+    assert_eq!(snake_to_camel("coin"), "coin");
+    assert_eq!(snake_to_camel("_private"), "_private");
+    assert_eq!(camel_to_snake("asOfMs"), "as_of_ms");
+    assert_eq!(camel_to_snake("peak1Price"), "peak1_price");
+    for key in ["last_candle_close_ms", "clock_skew_warning", "id"] {
+        assert_eq!(camel_to_snake(&snake_to_camel(key)), key);
+    }
+    assert_eq!("camel".parse(), Ok(ApiNaming::Camel));
+    assert_eq!("snake".parse(), Ok(ApiNaming::Snake));
+    assert!("kebab".parse::<ApiNaming>().is_err());
+}
+
+#[tokio::test]
+async fn camel_mode_round_trips_request_and_response_bodies() {
+    let state = camel_state();
+    let (status, created) = common::send(
+        perpscreener::app(state.clone()),
+        post_json(
+            "/webhooks",
+            json!({
+                "url": "https://example.com/hook",
+                "minSeverity": "critical",
+                "secret": "s3cret"
+            }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(created["minSeverity"], "critical");
+    assert_eq!(created["hasSecret"], true);
+    assert_eq!(created["createdAtMs"], NOW);
+    assert!(created.get("created_at_ms").is_none());
+
+    let (_, list) = common::get(perpscreener::app(state), "/webhooks").await;
+    assert_eq!(list[0]["minSeverity"], "critical");
+    assert_eq!(list[0]["consecutiveFailures"], 0);
+}
+
+#[tokio::test]
+async fn camel_mode_renames_nested_fields_and_errors_stay_readable() {
+    let state = camel_state().with_monitored_coins(vec!["BTC".to_string()]);
+    state.freshness.record("BTC", NOW - 60_000);
+    let app = perpscreener::app(state);
+
+    let (_, health) = common::get(app.clone(), "/health").await;
+    assert_eq!(health["maxAgeMs"], 300_000);
+    assert_eq!(health["clockSkewWarning"], false);
+    assert_eq!(health["coins"][0]["lastCandleCloseMs"], NOW - 60_000);
+    assert_eq!(health["coins"][0]["coin"], "BTC");
+
+    let (status, error) = common::get(app, "/webhooks/99/deliveries").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(error["error"].is_string());
+}
+
+#[tokio::test]
+async fn snake_mode_is_unchanged() {
+    let state = common::state_with_clock(ManualClock::new(NOW));
+    let (_, health) = common::get(perpscreener::app(state), "/health").await;
+    assert_eq!(health["max_age_ms"], 300_000);
+    assert!(health.get("maxAgeMs").is_none());
+}
+
+#[tokio::test]
+async fn schemas_follow_the_naming_and_validate_camel_payloads() {
+    let app = perpscreener::app(camel_state());
+    let (_, schema) = common::get(app.clone(), "/schemas/HealthResponse").await;
+    assert!(schema["properties"]["maxAgeMs"].is_object());
+    assert!(schema["required"]
+        .as_array()
+        .unwrap()
+        .contains(&json!("maxAgeMs")));
+    let coin = &schema["$defs"]["CoinFreshness"];
+    assert!(coin["properties"]["lastCandleCloseMs"].is_object());
+
+    let validator = jsonschema::validator_for(&schema).unwrap();
+    let (_, health) = common::get(app, "/health").await;
+    assert!(validator.is_valid(&health));
+    assert!(!validator.is_valid(&json!({
+        "status": "healthy", "max_age_ms": 1, "coins": [], "stale_coins": 0,
+        "clock_skew_warning": false
+    })));
+}
+
+#[test]
+fn openapi_document_follows_the_naming() {
+    let camel = serde_json::to_value(perpscreener::openapi(ApiNaming::Camel)).unwrap();
+    let health = &camel["components"]["schemas"]["HealthResponse"];
+    assert!(health["properties"]["maxAgeMs"].is_object());
+    assert!(health["properties"].get("max_age_ms").is_none());
+
+    let snake = serde_json::to_value(perpscreener::openapi(ApiNaming::Snake)).unwrap();
+    assert!(
+        snake["components"]["schemas"]["HealthResponse"]["properties"]["max_age_ms"].is_object()
+    );
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+struct Snapshot {
+    as_of_ms: u64,
+    quotes_by_coin: HashMap<String, Quote>,
+    indicators: BTreeMap<String, Vec<f64>>,
+    note: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+struct Quote {
+    mark_price: f64,
+    open_interest: f64,
+}
+
+#[utoipa::path(
+    post,
+    path = "/snapshots",
+    request_body = Snapshot,
+    responses((status = 200, description = "Echoed snapshot", body = Snapshot))
+)]
+async fn echo_snapshot(Json(snapshot): Json<Snapshot>) -> Json<Snapshot> {
+    Json(snapshot)
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(echo_snapshot), components(schemas(Snapshot, Quote)))]
+struct SnapshotDoc;
+
+fn snapshot_app() -> Router {
+    Router::new()
+        .route("/snapshots", post(echo_snapshot))
+        .layer(middleware::from_fn_with_state(
+            JsonNaming::new(ApiNaming::Camel, &SnapshotDoc::openapi()),
+            rename_json,
+        ))
+}
+
+#[tokio::test]
+async fn camel_mode_keeps_map_keys_while_renaming_their_values() {
+    let (status, body) = common::send(
+        snapshot_app(),
+        post_json(
+            "/snapshots",
+            json!({
+                "asOfMs": 7,
+                "quotesByCoin": {
+                    "kPEPE": { "markPrice": 0.01, "openInterest": 5.0 },
+                    "BTC": { "markPrice": 100.0, "openInterest": 2.5 }
+                },
+                "indicators": { "ema_20": [1.0, 2.0], "rsiFast": [50.0] },
+                "note": null
+            }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["asOfMs"], 7);
+    assert_eq!(body["quotesByCoin"]["kPEPE"]["markPrice"], 0.01);
+    assert_eq!(body["quotesByCoin"]["BTC"]["openInterest"], 2.5);
+    assert!(body["quotesByCoin"].get("k_pepe").is_none());
+    assert_eq!(body["indicators"]["ema_20"], json!([1.0, 2.0]));
+    assert_eq!(body["indicators"]["rsiFast"], json!([50.0]));
+    assert!(body["indicators"].get("ema20").is_none());
+}
+
+#[test]
+fn renaming_follows_the_schema_in_both_directions() {
+    let naming = JsonNaming::new(ApiNaming::Camel, &SnapshotDoc::openapi());
+    let schema = json!({ "$ref": "#/components/schemas/Snapshot" });
+    let snake = json!({
+        "as_of_ms": 1,
+        "quotes_by_coin": { "ETH_USD": { "mark_price": 1.0, "open_interest": 2.0 } },
+        "indicators": { "macd_signal": [] },
+        "note": "x"
+    });
+    let camel = naming.rename(snake.clone(), &schema, snake_to_camel);
+    assert_eq!(
+        camel,
+        json!({
+            "asOfMs": 1,
+            "quotesByCoin": { "ETH_USD": { "markPrice": 1.0, "openInterest": 2.0 } },
+            "indicators": { "macd_signal": [] },
+            "note": "x"
+        })
+    );
+    assert_eq!(naming.rename(camel, &schema, camel_to_snake), snake);
+}
+
+#[tokio::test]
+async fn oversized_requests_are_rejected() {
+    let body = format!("{{\"url\":\"{}\"}}", "a".repeat(MAX_RENAMED_BODY_BYTES));
+    let (status, error) = common::send(
+        perpscreener::app(camel_state()),
+        Request::post("/webhooks")
+            .header("content-type", "application/json")
+            .body(Body::from(body))
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert!(error["error"].is_string());
+}
+
+#[derive(Serialize, ToSchema)]
+struct Blob {
+    blob_data: String,
+}
+
+#[utoipa::path(get, path = "/blob", responses((status = 200, description = "Large body", body = Blob)))]
+async fn blob() -> Json<Blob> {
+    Json(Blob {
+        blob_data: "b".repeat(MAX_RENAMED_BODY_BYTES),
+    })
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(blob), components(schemas(Blob)))]
+struct BlobDoc;
+
+#[tokio::test]
+async fn oversized_responses_pass_through_unchanged() {
+    let app = Router::new()
+        .route("/blob", axum::routing::get(blob))
+        .layer(middleware::from_fn_with_state(
+            JsonNaming::new(ApiNaming::Camel, &BlobDoc::openapi()),
+            rename_json,
+        ));
+    let (status, body) = common::get(app, "/blob").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body["blob_data"].as_str().unwrap().len(),
+        MAX_RENAMED_BODY_BYTES
+    );
+}
@@ -0,0 +1,86 @@
+use perpscreener::business_logic::open_interest::{
+    classify, OiHistory, OiRule, OiSample, OiScreenerConfig,
+};
+
+const HOUR_MS: u64 = 3_600_000;
+const T0: u64 = 1_700_000_000_000;
+
+fn sample(hour: u64, open_interest: f64, price: f64) -> OiSample {
+    OiSample {
+        time_ms: T0 + hour * HOUR_MS,
+        open_interest,
+        price,
+    }
+}
+
+fn config() -> OiScreenerConfig {
+    OiScreenerConfig {
+        window_ms: 4 * HOUR_MS,
+        rise_pct: 15.0,
+        max_price_change_pct: 0.5,
+        collapse_pct: 20.0,
+    }
+}
+
+#[test]
+fn classifies_squeeze_fuel_and_collapse() {
+    assert_eq!(classify(20.0, -1.0, &config()), vec![OiRule::SqueezeFuel]);
+    assert_eq!(classify(20.0, 0.5, &config()), vec![OiRule::SqueezeFuel]);
+    assert!(classify(20.0, 3.0, &config()).is_empty());
+    assert!(classify(10.0, -1.0, &config()).is_empty());
+    assert_eq!(classify(-25.0, -4.0, &config()), vec![OiRule::Collapse]);
+    assert!(classify(-10.0, -4.0, &config()).is_empty());
+}
+
+#[test]
+fn measures_deltas_from_the_oldest_sample_in_the_window() {
+    let mut history = OiHistory::new();
+    for (hour, oi, price) in [
+        (0, 500.0, 90.0),
+        (1, 1000.0, 100.0),
+        (3, 1100.0, 99.0),
+        (5, 1200.0, 99.0),
+    ] {
+        history.record(sample(hour, oi, price), 24 * HOUR_MS);
+    }
+
+    let metrics = history.metrics("ETH", &config()).unwrap();
+    assert_eq!(metrics.coin, "ETH");
+    assert_eq!(metrics.open_interest, 1200.0);
+    assert!((metrics.oi_change_pct - 20.0).abs() < 1e-9);
+    assert!((metrics.price_change_pct + 1.0).abs() < 1e-9);
+    assert_eq!(metrics.window_ms, 4 * HOUR_MS);
+    assert_eq!(metrics.rules, vec![OiRule::SqueezeFuel]);
+}
+
+#[test]
+fn needs_half_a_window_of_history() {
+    let mut history = OiHistory::new();
+    history.record(sample(0, 1000.0, 100.0), 24 * HOUR_MS);
+    assert_eq!(history.metrics("BTC", &config()), None);
+    history.record(sample(1, 2000.0, 100.0), 24 * HOUR_MS);
+    assert_eq!(history.metrics("BTC", &config()), None);
+    history.record(sample(2, 2000.0, 100.0), 24 * HOUR_MS);
+    assert!(history.metrics("BTC", &config()).is_some());
+}
+
+#[test]
+fn record_ignores_stale_samples_and_prunes_old_ones() {
+    let mut history = OiHistory::new();
+    history.record(sample(0, 1000.0, 100.0), 2 * HOUR_MS);
+    history.record(sample(1, 1000.0, 100.0), 2 * HOUR_MS);
+    history.record(sample(1, 5000.0, 100.0), 2 * HOUR_MS);
+    assert_eq!(history.len(), 2);
+
+    history.record(sample(3, 700.0, 100.0), 2 * HOUR_MS);
+    assert_eq!(history.len(), 2);
+
+    let metrics = history.metrics(
+        "SOL",
+        &OiScreenerConfig {
+            window_ms: 2 * HOUR_MS,
+            ..config()
+        },
+    );
+    assert_eq!(metrics.unwrap().rules, vec![OiRule::Collapse]);
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use common::{candle, T0};
+use perpscreener::business_logic::paper::{
+    performance, BothTouched, ExitReason, PaperConfig, PaperPosition,
+};
+use perpscreener::business_logic::trade_plan::TradePlan;
+
+fn plan() -> TradePlan {
+    TradePlan {
+        entry: 100.0,
+        stop: 105.0,
+        target: 90.0,
+        risk_reward: 2.0,
+    }
+}
+
+fn position(config: &PaperConfig) -> PaperPosition {
+    PaperPosition::open("BTC", &plan(), T0, config).unwrap()
+}
+
+#[test]
+fn position_is_sized_to_the_risk_budget() {
+    let config = PaperConfig::default();
+    assert_eq!(position(&config).size, 20.0);
+
+    let riskless = TradePlan {
+        stop: 100.0,
+        ..plan()
+    };
+    assert_eq!(PaperPosition::open("BTC", &riskless, T0, &config), None);
+}
+
+#[test]
+fn confirming_candle_and_untouched_candles_keep_it_open() {
+    let config = PaperConfig::default();
+    let open = position(&config);
+    assert_eq!(
+        open.update(&candle(0, 100.0, 110.0, 80.0, 95.0), &config),
+        None
+    );
+    assert_eq!(
+        open.update(&candle(1, 100.0, 104.0, 91.0, 95.0), &config),
+        None
+    );
+}
+
+#[test]
+fn stop_and_target_hits_score_in_r() {
+    let config = PaperConfig::default();
+    let open = position(&config);
+
+    let stopped = open
+        .update(&candle(1, 100.0, 105.0, 99.0, 104.0), &config)
+        .unwrap();
+    assert_eq!(stopped.reason, ExitReason::Stop);
+    assert_eq!(stopped.exit, 105.0);
+    assert_eq!(stopped.r_multiple, -1.0);
+    assert_eq!(stopped.pnl, -100.0);
+
+    let won = open
+        .update(&candle(1, 99.0, 100.0, 90.0, 91.0), &config)
+        .unwrap();
+    assert_eq!(won.reason, ExitReason::Target);
+    assert_eq!(won.r_multiple, 2.0);
+    assert_eq!(won.pnl, 200.0);
+    assert_eq!(won.closed_at_ms, candle(1, 0.0, 0.0, 0.0, 0.0).open_time);
+}
+
+#[test]
+fn candle_touching_both_levels_follows_the_configured_assumption() {
+    let wide = candle(1, 100.0, 106.0, 89.0, 100.0);
+
+    let pessimistic = PaperConfig::default();
+    let trade = position(&pessimistic).update(&wide, &pessimistic).unwrap();
+    assert_eq!(trade.reason, ExitReason::Stop);
+
+    let optimistic = PaperConfig {
+        both_touched: BothTouched::TargetFirst,
+        ..PaperConfig::default()
+    };
+    let trade = position(&optimistic).update(&wide, &optimistic).unwrap();
+    assert_eq!(trade.reason, ExitReason::Target);
+}
+
+#[test]
+fn gaps_fill_at_the_open() {
+    let config = PaperConfig {
+        both_touched: BothTouched::TargetFirst,
+        ..PaperConfig::default()
+    };
+    let open = position(&config);
+
+    let gapped_up = open
+        .update(&candle(1, 108.0, 109.0, 85.0, 100.0), &config)
+        .unwrap();
+    assert_eq!(gapped_up.reason, ExitReason::Stop);
+    assert_eq!(gapped_up.exit, 108.0);
+    assert_eq!(gapped_up.r_multiple, -1.6);
+
+    let gapped_down = open
+        .update(&candle(1, 88.0, 89.0, 87.0, 88.0), &config)
+        .unwrap();
+    assert_eq!(gapped_down.reason, ExitReason::Target);
+    assert_eq!(gapped_down.r_multiple, 2.4);
+}
+
+#[test]
+fn performance_summarizes_closed_trades_in_close_order() {
+    assert_eq!(performance(&[]).win_rate, None);
+
+    let config = PaperConfig::default();
+    let open = position(&config);
+    let loss = open
+        .update(&candle(2, 100.0, 105.0, 99.0, 104.0), &config)
+        .unwrap();
+    let win = open
+        .update(&candle(1, 99.0, 100.0, 90.0, 91.0), &config)
+        .unwrap();
+
+    let summary = performance(&[loss, win]);
+    assert_eq!(summary.trades, 2);
+    assert_eq!(summary.wins, 1);
+    assert_eq!(summary.win_rate, Some(0.5));
+    assert_eq!(summary.average_r, Some(0.5));
+    assert_eq!(summary.total_pnl, 100.0);
+    let equity: Vec<f64> = summary.equity_curve.iter().map(|p| p.equity).collect();
+    assert_eq!(equity, vec![200.0, 100.0]);
+}
@@ -0,0 +1,550 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::routing::post;
+use axum::{Json, Router};
+use common::{candles_from_closes, ManualClock, MINUTE_MS, T0};
+use http_body_util::BodyExt;
+use perpscreener::business_logic::alerts::{
+    AlertSeverity, AlertStage, PatternAlert, PatternKind, TrendDirection,
+};
+use perpscreener::business_logic::open_interest::{OiRule, OiSample};
+use perpscreener::business_logic::patterns::{PatternStatus, PATTERN_NAMES};
+use perpscreener::business_logic::premium::PremiumSample;
+use perpscreener::models::candle::Candle;
+use perpscreener::services::hyperliquid::HyperliquidClient;
+use perpscreener::services::monitor::MarketMonitor;
+use perpscreener::services::webhooks::NewWebhook;
+use perpscreener::settings::Settings;
+use perpscreener::state::{AppState, PatternEvent};
+use serde_json::Value;
+use tower::ServiceExt;
+
+fn path(start: f64, steps: &[(usize, f64)]) -> Vec<f64> {
+    let mut closes = Vec::new();
+    let mut price = start;
+    for &(count, step) in steps {
+        for _ in 0..count {
+            price += step;
+            closes.push(price);
+        }
+    }
+    closes
+}
+
+/// Decline to 90, bounce to 95, retest 90 and break out above 95.
+fn double_bottom() -> Vec<Candle> {
+    let closes = path(104.0, &[(28, -0.5), (10, 0.5), (10, -0.48), (14, 0.5)]);
+    candles_from_closes(104.0, &closes, 0.1)
+}
+
+/// Every swing-based detector with swings sized for the test candles' steady 0.5 moves.
+const SETTINGS: &str = "
+[double_bottom]
+rev_atr = 2.0
+[double_top]
+rev_atr = 2.0
+[head_and_shoulders]
+rev_atr = 2.0
+[triple_top]
+rev_atr = 2.0
+[triple_bottom]
+rev_atr = 2.0
+[ascending_triangle]
+rev_atr = 2.0
+[descending_triangle]
+rev_atr = 2.0
+[trendline]
+rev_atr = 2.0
+";
+
+/// State monitoring BTC over `candles`.
+async fn monitored_state(candles: Vec<Candle>) -> AppState {
+    let clock = ManualClock::new(T0 + candles.len() as u64 * MINUTE_MS);
+    monitored_state_with_clock(clock, candles).await
+}
+
+async fn monitored_state_with_clock(clock: Arc<ManualClock>, candles: Vec<Candle>) -> AppState {
+    let base_url = common::spawn_candle_server(vec![("BTC", candles)]).await;
+    let settings = Settings::from_toml(SETTINGS, std::path::Path::new("test.toml")).unwrap();
+    common::state_with_clock(clock)
+        .with_hyperliquid(HyperliquidClient::with_base_url(base_url))
+        .with_settings(settings)
+        .with_monitored_coins(vec!["BTC".to_string()])
+}
+
+#[tokio::test]
+async fn monitor_publishes_double_bottom_status_and_alerts() {
+    let state = monitored_state(double_bottom()).await;
+    MarketMonitor::new(state.clone(), "1m").run_cycle().await;
+    let app = perpscreener::app(state);
+
+    let (status, body) = common::get(app.clone(), "/double-bottom?coin=BTC").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"]["coin"], "BTC");
+    assert_eq!(body["status"]["state"], "CONFIRMED");
+    assert!((body["status"]["trough1_price"].as_f64().unwrap() - 89.9).abs() < 1e-9);
+    let stages: Vec<&str> = body["alerts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| a["stage"].as_str().unwrap())
+        .collect();
+    assert_eq!(stages, ["confirmation", "early_warning"]);
+
+    let (status, body) = common::get(app.clone(), "/patterns?coin=BTC").await;
+    assert_eq!(status, StatusCode::OK);
+    let patterns: Vec<&str> = body["statuses"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["pattern"].as_str().unwrap())
+        .collect();
+    let mut every = PATTERN_NAMES.to_vec();
+    every.sort();
+    assert_eq!(patterns, every);
+    let (_, body) = common::get(app.clone(), "/patterns?coin=BTC&pattern=double_bottom").await;
+    assert_eq!(body["statuses"].as_array().unwrap().len(), 1);
+    assert_eq!(body["alerts"].as_array().unwrap().len(), 2);
+
+    let (status, _) = common::get(app.clone(), "/double-bottom?coin=ETH").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = common::get(app, "/patterns?pattern=flag").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+/// Status of `pattern` on BTC after one monitor cycle over `candles`.
+async fn status_after(candles: Vec<Candle>, pattern: &str) -> Value {
+    let state = monitored_state(candles).await;
+    MarketMonitor::new(state.clone(), "1m").run_cycle().await;
+    let (status, body) = common::get(
+        perpscreener::app(state),
+        &format!("/patterns?coin=BTC&pattern={pattern}"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["statuses"].as_array().unwrap().len(), 1, "{body}");
+    body["statuses"][0].clone()
+}
+
+#[tokio::test]
+async fn monitor_publishes_double_top_status() {
+    // Peaks at 98 with a pullback to 94 between them, then the neckline break.
+    let closes = path(88.0, &[(20, 0.5), (8, -0.5), (8, 0.5), (12, -0.5)]);
+    let status = status_after(candles_from_closes(88.0, &closes, 0.1), "double_top").await;
+    assert_eq!(status["state"], "CONFIRMED");
+    assert!((status["peak2_price"].as_f64().unwrap() - 98.1).abs() < 1e-9);
+    assert!((status["neckline_price"].as_f64().unwrap() - 93.9).abs() < 1e-9);
+    assert_eq!(status["candles_since_peak1"], 28);
+    assert_eq!(status["peak1_time_ms"], T0 + 19 * MINUTE_MS);
+    assert_eq!(status["trough_time_ms"], T0 + 27 * MINUTE_MS);
+    assert_eq!(status["peak2_time_ms"], T0 + 35 * MINUTE_MS);
+    assert!((status["target_price"].as_f64().unwrap() - 89.7).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn monitor_scores_double_top_with_the_open_interest_screener() {
+    let closes = path(88.0, &[(20, 0.5), (7, -0.5), (10, 0.3), (12, -0.5)]);
+    let candles = candles_from_closes(88.0, &closes, 0.1);
+    let confidence = |state: &AppState| {
+        let Some(PatternStatus::DoubleTop(status)) = state.patterns.status("BTC", "double_top")
+        else {
+            panic!("no double top status");
+        };
+        (status.confidence.unwrap(), status.oi_rules.clone())
+    };
+    let state = monitored_state(candles.clone()).await;
+    MarketMonitor::new(state.clone(), "1m").run_cycle().await;
+    let (base, rules) = confidence(&state);
+    assert!(rules.is_empty());
+
+    // OI up 20% over four hours on a flat price, as the context sampler
+    // records it.
+    let state = monitored_state(candles).await;
+    for (hours_ago, open_interest) in [(4, 1000.0), (0, 1200.0)] {
+        state.open_interest.record(
+            "BTC",
+            OiSample {
+                time_ms: T0 - hours_ago * 60 * MINUTE_MS,
+                open_interest,
+                price: 90.0,
+            },
+            state.settings.open_interest.window_ms,
+        );
+    }
+    MarketMonitor::new(state.clone(), "1m").run_cycle().await;
+    let (boosted, rules) = confidence(&state);
+    assert_eq!(rules, vec![OiRule::SqueezeFuel]);
+    assert!((boosted - base - 10.0).abs() < 1e-9, "{base} -> {boosted}");
+}
+
+#[tokio::test]
+async fn monitor_scores_double_top_with_the_sampled_premium() {
+    let closes = path(88.0, &[(20, 0.5), (7, -0.5), (10, 0.3), (12, -0.5)]);
+    let candles = candles_from_closes(88.0, &closes, 0.1);
+    let confidence = |state: &AppState| {
+        let Some(PatternStatus::DoubleTop(status)) = state.patterns.status("BTC", "double_top")
+        else {
+            panic!("no double top status");
+        };
+        (status.confidence.unwrap(), status.premium_pct)
+    };
+    let state = monitored_state(candles.clone()).await;
+    MarketMonitor::new(state.clone(), "1m").run_cycle().await;
+    let (base, premium_pct) = confidence(&state);
+    assert_eq!(premium_pct, None);
+
+    // An hour at a 0.8% premium, as the context sampler records it.
+    let state = monitored_state(candles).await;
+    for minute in (0..=60).step_by(10) {
+        state.premiums.record(
+            "BTC",
+            PremiumSample {
+                time_ms: T0 + minute * MINUTE_MS,
+                premium_pct: 0.8,
+            },
+        );
+    }
+    MarketMonitor::new(state.clone(), "1m").run_cycle().await;
+    let (boosted, premium_pct) = confidence(&state);
+    assert_eq!(premium_pct, Some(0.8));
+    assert!((boosted - base - 10.0).abs() < 1e-9, "{base} -> {boosted}");
+}
+
+#[tokio::test]
+async fn htf_filter_suppresses_double_top_alerts_in_an_up_trend() {
+    // Confirms on candle 44, then rallies, so the trend read with the fetch
+    // is up. The stub serves the same 1m candles whatever the interval.
+    let closes = path(
+        88.0,
+        &[(20, 0.5), (8, -0.5), (8, 0.5), (12, -0.5), (8, 0.5)],
+    );
+    let base_url =
+        common::spawn_candle_server(vec![("BTC", candles_from_closes(88.0, &closes, 0.1))]).await;
+    let toml = format!("{SETTINGS}[double_top.htf_filter]\ninterval = \"1m\"\nema_period = 5\n");
+    let settings = Settings::from_toml(&toml, std::path::Path::new("test.toml")).unwrap();
+    let state = common::state_with_clock(ManualClock::new(T0 + closes.len() as u64 * MINUTE_MS))
+        .with_hyperliquid(HyperliquidClient::with_base_url(base_url))
+        .with_settings(settings)
+        .with_monitored_coins(vec!["BTC".to_string()]);
+    MarketMonitor::new(state.clone(), "1m").run_cycle().await;
+
+    assert!(!state
+        .patterns
+        .alerts()
+        .iter()
+        .any(|a| a.pattern == PatternKind::DoubleTop));
+    let Some(PatternStatus::DoubleTop(status)) = state.patterns.status("BTC", "double_top") else {
+        panic!("no double top status");
+    };
+    assert_eq!(status.htf_trend, Some(TrendDirection::Up));
+    assert_eq!(
+        status.suppressed_alert.unwrap().stage,
+        AlertStage::Confirmation
+    );
+}
+
+#[tokio::test]
+async fn expired_double_top_confirmation_is_streamed_and_kept_in_alerts() {
+    // Confirms on candle 44, then stays flat.
+    let closes = path(
+        88.0,
+        &[(20, 0.5), (8, -0.5), (8, 0.5), (12, -0.5), (30, 0.0)],
+    );
+    let clock = ManualClock::new(T0 + 50 * MINUTE_MS);
+    let state =
+        monitored_state_with_clock(clock.clone(), candles_from_closes(88.0, &closes, 0.1)).await;
+    let mut monitor = MarketMonitor::new(state.clone(), "1m");
+    monitor.run_cycle().await;
+    let state_of = |state: &AppState| match state.patterns.status("BTC", "double_top") {
+        Some(PatternStatus::DoubleTop(status)) => status.state,
+        other => panic!("{other:?}"),
+    };
+    assert_eq!(state_of(&state), "CONFIRMED");
+
+    let mut events = state.patterns.subscribe();
+    clock.set(T0 + closes.len() as u64 * MINUTE_MS);
+    monitor.run_cycle().await;
+    assert_eq!(state_of(&state), "WATCHING");
+    let streamed = std::iter::from_fn(|| events.try_recv().ok()).any(|event| {
+        matches!(event, PatternEvent::Status(status)
+            if matches!(*status, PatternStatus::DoubleTop(ref s) if s.state == "WATCHING"))
+    });
+    assert!(streamed);
+    assert!(state
+        .patterns
+        .alerts()
+        .iter()
+        .any(|a| a.pattern == PatternKind::DoubleTop && a.stage == AlertStage::Confirmation));
+}
+
+#[tokio::test]
+async fn monitor_publishes_head_and_shoulders_status() {
+    // Left shoulder 95, troughs 92, head 98, right shoulder 95, then the break.
+    let closes = path(
+        85.0,
+        &[
+            (20, 0.5),
+            (6, -0.5),
+            (12, 0.5),
+            (12, -0.5),
+            (6, 0.5),
+            (14, -0.5),
+        ],
+    );
+    let status = status_after(
+        candles_from_closes(85.0, &closes, 0.1),
+        "head_and_shoulders",
+    )
+    .await;
+    assert_eq!(status["pattern"], "head_and_shoulders");
+    assert_eq!(status["state"], "CONFIRMED");
+    assert!((status["head_price"].as_f64().unwrap() - 98.1).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn monitor_publishes_triple_top_status() {
+    // Three peaks at 98 with pullbacks to 94, then the neckline break.
+    let closes = path(
+        88.0,
+        &[
+            (20, 0.5),
+            (8, -0.5),
+            (8, 0.5),
+            (8, -0.5),
+            (8, 0.5),
+            (12, -0.5),
+        ],
+    );
+    let status = status_after(candles_from_closes(88.0, &closes, 0.1), "triple_top").await;
+    assert_eq!(status["state"], "CONFIRMED");
+    assert!((status["peak3_price"].as_f64().unwrap() - 98.1).abs() < 1e-9);
+    assert!((status["neckline_price"].as_f64().unwrap() - 93.9).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn monitor_publishes_triple_bottom_status() {
+    // Troughs at 90, 89.5 and 90 under rallies to 94, then the breakout.
+    let closes = path(
+        100.0,
+        &[
+            (20, -0.5),
+            (8, 0.5),
+            (9, -0.5),
+            (8, 0.5),
+            (7, -0.5),
+            (14, 0.5),
+        ],
+    );
+    let status = status_after(candles_from_closes(100.0, &closes, 0.1), "triple_bottom").await;
+    assert_eq!(status["state"], "CONFIRMED");
+    assert!((status["trough2_price"].as_f64().unwrap() - 89.4).abs() < 1e-9);
+    assert!((status["resistance_price"].as_f64().unwrap() - 94.1).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn monitor_publishes_ascending_triangle_status() {
+    // Lows at 96, 97 and 98 under a flat 100, then a rally through it.
+    let closes = path(
+        90.0,
+        &[
+            (20, 0.5),
+            (8, -0.5),
+            (8, 0.5),
+            (6, -0.5),
+            (6, 0.5),
+            (4, -0.5),
+            (8, 0.5),
+        ],
+    );
+    let status = status_after(
+        candles_from_closes(90.0, &closes, 0.1),
+        "ascending_triangle",
+    )
+    .await;
+    assert_eq!(status["state"], "CONFIRMED");
+    assert_eq!(status["touches"], 3);
+    assert!((status["resistance_price"].as_f64().unwrap() - 100.1).abs() < 1e-9);
+    assert!((status["latest_higher_low"].as_f64().unwrap() - 97.9).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn monitor_publishes_descending_triangle_status() {
+    // Highs at 104, 103 and 102 over a flat 100, then a drop through it.
+    let closes = path(
+        110.0,
+        &[
+            (20, -0.5),
+            (8, 0.5),
+            (8, -0.5),
+            (6, 0.5),
+            (6, -0.5),
+            (4, 0.5),
+            (8, -0.5),
+        ],
+    );
+    let status = status_after(
+        candles_from_closes(110.0, &closes, 0.1),
+        "descending_triangle",
+    )
+    .await;
+    assert_eq!(status["state"], "CONFIRMED");
+    assert_eq!(status["touches"], 3);
+    assert!((status["support_price"].as_f64().unwrap() - 99.9).abs() < 1e-9);
+    assert!((status["latest_lower_high"].as_f64().unwrap() - 102.1).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn monitor_publishes_cup_and_handle_status() {
+    // Rim at 100, a rounded bottom at 90, back to the rim, a shallow handle
+    // and a breakout, then long enough flat that the cup is further back
+    // than the swing detectors' warmup.
+    let mut steps = vec![
+        (10, 1.0),
+        (10, -1.0),
+        (12, 0.0),
+        (10, 1.0),
+        (3, -0.5),
+        (5, 1.0),
+    ];
+    steps.push((100, 0.0));
+    let closes = path(90.0, &steps);
+    let status = status_after(candles_from_closes(90.0, &closes, 0.1), "cup_and_handle").await;
+    assert_eq!(status["state"], "CONFIRMED");
+    assert!((status["rim_price"].as_f64().unwrap() - 100.1).abs() < 1e-9);
+    assert!((status["handle_low"].as_f64().unwrap() - 98.4).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn monitor_publishes_range_breakout_status() {
+    // Flat at 100 long enough to form a range, then a close through the top.
+    let closes = path(100.0, &[(40, 0.0), (2, 1.0)]);
+    let status = status_after(candles_from_closes(100.0, &closes, 0.1), "range_breakout").await;
+    assert_eq!(status["state"], "BROKE_UP");
+    assert!((status["range_high"].as_f64().unwrap() - 100.1).abs() < 1e-9);
+    assert!((status["range_low"].as_f64().unwrap() - 99.9).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn monitor_publishes_trendline_status() {
+    // A rally and three higher lows, each a point above the last, with the
+    // line still intact.
+    let mut steps = vec![(18, 0.5)];
+    for _ in 0..3 {
+        steps.extend_from_slice(&[(6, -0.5), (8, 0.5)]);
+    }
+    let closes = path(95.0, &steps);
+    let status = status_after(candles_from_closes(95.0, &closes, 0.1), "trendline").await;
+    assert_eq!(status["downtrend"], Value::Null);
+    assert_eq!(status["uptrend"]["touches"], 3);
+    assert!((status["uptrend"]["slope"].as_f64().unwrap() - 1.0 / 14.0).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn coins_leaving_the_list_drop_their_pattern_status() {
+    let state = monitored_state(double_bottom()).await;
+    let mut monitor = MarketMonitor::new(state.clone(), "1m");
+    monitor.run_cycle().await;
+    assert!(state.patterns.status("BTC", "double_bottom").is_some());
+
+    state.coins.set(Vec::new());
+    monitor.run_cycle().await;
+    assert!(state.patterns.statuses().is_empty());
+}
+
+/// Next server-sent event on `body` as its raw text.
+async fn next_event(body: &mut Body) -> String {
+    let frame = tokio::time::timeout(Duration::from_secs(5), body.frame())
+        .await
+        .expect("no event within 5s")
+        .unwrap()
+        .unwrap();
+    String::from_utf8(frame.into_data().unwrap().to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn stream_sends_current_statuses_then_alerts() {
+    let state = monitored_state(double_bottom()).await;
+    MarketMonitor::new(state.clone(), "1m").run_cycle().await;
+    let app = perpscreener::app(state.clone());
+
+    let response = app
+        .oneshot(
+            Request::get("/double-bottom/stream?coin=BTC")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let mut body = response.into_body();
+
+    let first = next_event(&mut body).await;
+    assert!(first.starts_with("event: status\n"), "{first}");
+    assert!(first.contains("\"state\":\"CONFIRMED\""), "{first}");
+
+    for coin in ["ETH", "BTC"] {
+        state.patterns.record_alert(PatternAlert {
+            coin: coin.to_string(),
+            pattern: PatternKind::DoubleBottom,
+            stage: AlertStage::EarlyWarning,
+            open_time: T0,
+            price: 90.0,
+            level: 89.9,
+            target_price: None,
+            pattern_height_pct: None,
+            retest_high: None,
+            confirmation_mode: None,
+            htf_trend: None,
+        });
+    }
+    let next = next_event(&mut body).await;
+    assert!(next.starts_with("event: alert\n"), "{next}");
+    assert!(next.contains("\"coin\":\"BTC\""), "{next}");
+}
+
+#[tokio::test]
+async fn pattern_alerts_are_sent_to_webhooks_at_their_severity() {
+    let received: Arc<Mutex<Vec<Value>>> = Arc::default();
+    let hook = Router::new().route(
+        "/hook",
+        post({
+            let received = received.clone();
+            move |Json(body): Json<Value>| async move {
+                received.lock().unwrap().push(body);
+                StatusCode::OK
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, hook).await.unwrap() });
+
+    let state = monitored_state(double_bottom()).await;
+    state
+        .webhooks
+        .create(
+            NewWebhook {
+                url: format!("http://{addr}/hook"),
+                coins: None,
+                min_severity: Some(AlertSeverity::Critical),
+                secret: None,
+                quiet_hours: None,
+            },
+            T0,
+        )
+        .unwrap();
+
+    MarketMonitor::new(state, "1m").run_cycle().await;
+    let received = received.lock().unwrap();
+    assert_eq!(received.len(), 1, "{received:?}");
+    assert_eq!(received[0]["type"], "pattern");
+    assert_eq!(received[0]["pattern"], "double_bottom");
+    assert_eq!(received[0]["stage"], "confirmation");
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use common::candle;
+use perpscreener::business_logic::pivots::{utc_date, PivotLevels};
+
+#[test]
+fn classic_levels_from_high_low_close() {
+    // P = (110 + 90 + 100) / 3 = 100, range 20.
+    let levels = PivotLevels::classic(&candle(0, 95.0, 110.0, 90.0, 100.0));
+    assert_eq!(
+        levels,
+        PivotLevels {
+            pivot: 100.0,
+            r1: 110.0,
+            r2: 120.0,
+            r3: 130.0,
+            s1: 90.0,
+            s2: 80.0,
+            s3: 70.0,
+        }
+    );
+}
+
+#[test]
+fn utc_date_of_a_timestamp() {
+    assert_eq!(utc_date(0).unwrap().to_string(), "1970-01-01");
+    assert_eq!(
+        utc_date(1_700_000_040_000).unwrap().to_string(),
+        "2023-11-14"
+    );
+}
+
+#[cfg(feature = "server")]
+mod endpoint {
+    use axum::http::StatusCode;
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use perpscreener::services::hyperliquid::HyperliquidClient;
+    use serde_json::{json, Value};
+
+    use super::common::{self, ManualClock, T0};
+
+    const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+    /// 00:00 UTC on 2023-11-14, the day containing `T0`.
+    const TODAY: u64 = T0 / DAY_MS * DAY_MS;
+
+    /// Stub `info` endpoint serving 1d candles for the requested range. BTC
+    /// has every day including the one still forming; ETH is missing
+    /// yesterday's; SOL has none; anything else is an upstream error.
+    async fn spawn_hyperliquid() -> String {
+        let app = Router::new().route(
+            "/info",
+            post(|Json(body): Json<Value>| async move {
+                let skip_yesterday = match body["req"]["coin"].as_str() {
+                    Some("BTC") => false,
+                    Some("ETH") => true,
+                    Some("SOL") => return Ok(Json(json!([]))),
+                    _ => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+                };
+                let start = body["req"]["startTime"].as_u64().unwrap();
+                let candles: Vec<Value> = (0..)
+                    .map(|i| start + i * DAY_MS)
+                    .take_while(|&t| t <= TODAY)
+                    .filter(|&t| !(skip_yesterday && t == TODAY - DAY_MS))
+                    .map(|t| {
+                        // Each day trades 90-110 and closes at a day-dependent level.
+                        let close = 100.0 + ((t - start) / DAY_MS) as f64;
+                        json!({
+                            "t": t, "T": t + DAY_MS - 1, "s": "BTC", "i": "1d",
+                            "o": "100", "h": "110", "l": "90",
+                            "c": close.to_string(), "v": "1.0", "n": 1
+                        })
+                    })
+                    .collect();
+                Ok(Json(Value::from(candles)))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn serves_levels_from_the_latest_complete_day() {
+        let url = spawn_hyperliquid().await;
+        let clock = ManualClock::new(TODAY + 12 * 60 * 60 * 1000);
+        let state =
+            common::state_with_clock(clock).with_hyperliquid(HyperliquidClient::with_base_url(url));
+        let app = perpscreener::app(state);
+
+        let (status, body) = common::get(app.clone(), "/pivots?coin=BTC").await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        assert_eq!(body["session_date"], "2023-11-14");
+        assert_eq!(body["source_date"], "2023-11-13");
+        assert_eq!(body["from_previous_day"], true);
+        // Yesterday is the seventh of the eight days served from `startTime`
+        // and closed at 106: P = (110 + 90 + 106) / 3.
+        let pivot = body["levels"]["pivot"].as_f64().unwrap();
+        assert!((pivot - 102.0).abs() < 1e-9, "{body}");
+        assert_eq!(body["levels"]["r2"].as_f64().unwrap(), pivot + 20.0);
+
+        let (status, body) = common::get(app.clone(), "/pivots?coin=ETH").await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        assert_eq!(body["source_date"], "2023-11-12");
+        assert_eq!(body["from_previous_day"], false);
+
+        let (status, _) = common::get(app.clone(), "/pivots?coin=SOL").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = common::get(app.clone(), "/pivots?coin=DOGE").await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,263 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use axum::http::StatusCode;
+use axum::routing::post;
+use axum::{Json, Router};
+use common::ManualClock;
+use perpscreener::business_logic::premium::{
+    outside_band, premium_pct, PremiumHistory, PremiumSample,
+};
+use perpscreener::services::contexts::ContextSampler;
+use perpscreener::services::hyperliquid::HyperliquidClient;
+use perpscreener::services::webhooks::NewWebhook;
+use perpscreener::settings::Settings;
+use perpscreener::state::{PremiumTracker, PREMIUM_HISTORY_MS};
+use serde_json::{json, Value};
+
+const NOW: u64 = 1_700_000_000_000;
+const HOUR_MS: u64 = 3_600_000;
+
+fn sample(time_ms: u64, premium_pct: f64) -> PremiumSample {
+    PremiumSample {
+        time_ms,
+        premium_pct,
+    }
+}
+
+#[test]
+fn premium_is_relative_to_the_oracle() {
+    assert!((premium_pct(101.0, 100.0).unwrap() - 1.0).abs() < 1e-9);
+    assert!((premium_pct(99.5, 100.0).unwrap() + 0.5).abs() < 1e-9);
+    assert_eq!(premium_pct(1.0, 0.0), None);
+    assert!(outside_band(-0.6, 0.5));
+    assert!(!outside_band(0.5, 0.5));
+}
+
+#[test]
+fn history_keeps_a_rolling_window_with_stats() {
+    let mut history = PremiumHistory::default();
+    history.record(sample(NOW, 0.9), 2 * HOUR_MS);
+    history.record(sample(NOW + HOUR_MS, 0.1), 2 * HOUR_MS);
+    history.record(sample(NOW + HOUR_MS, 5.0), 2 * HOUR_MS);
+    history.record(sample(NOW + 3 * HOUR_MS, -0.4), 2 * HOUR_MS);
+
+    let kept: Vec<f64> = history.samples().map(|s| s.premium_pct).collect();
+    assert_eq!(kept, [0.1, -0.4]);
+    let stats = history.stats().unwrap();
+    assert!((stats.mean_pct + 0.15).abs() < 1e-9);
+    assert_eq!(stats.min_pct, -0.4);
+    assert_eq!(stats.max_pct, 0.1);
+    assert_eq!(PremiumHistory::default().stats(), None);
+}
+
+#[test]
+fn persistent_extreme_counts_same_side_samples_beyond_the_band() {
+    let mut history = PremiumHistory::default();
+    for (minute, premium) in [(0, -0.8), (1, 0.2), (2, 0.7), (3, 0.9), (4, 0.6)] {
+        history.record(sample(NOW + minute * 60_000, premium), HOUR_MS);
+    }
+    assert_eq!(history.persistent_extreme_ms(0.5), 2 * 60_000);
+    assert_eq!(history.persistent_extreme_ms(0.65), 0);
+
+    history.record(sample(NOW + 5 * 60_000, -0.9), HOUR_MS);
+    assert_eq!(history.persistent_extreme_ms(0.5), 0);
+}
+
+fn context(mark: &str, oracle: &str) -> Value {
+    json!({
+        "dayBaseVlm": "10000.0", "dayNtlVlm": "1000000.0", "funding": "0.0000125", "impactPxs": [mark, mark],
+        "markPx": mark, "midPx": mark, "openInterest": "250.5", "oraclePx": oracle,
+        "premium": "0.0", "prevDayPx": oracle
+    })
+}
+
+/// Stub `metaAndAssetCtxs` with BTC at a 1% premium, ETH at a 0.2% discount
+/// and SOL at a 2% discount; returns its base URL.
+async fn spawn_hyperliquid() -> String {
+    let app = Router::new().route(
+        "/info",
+        post(|Json(body): Json<Value>| async move {
+            assert_eq!(body["type"], "metaAndAssetCtxs");
+            Json(json!([
+                { "universe": [
+                    { "name": "BTC", "szDecimals": 5, "maxLeverage": 40 },
+                    { "name": "ETH", "szDecimals": 4, "maxLeverage": 25 },
+                    { "name": "SOL", "szDecimals": 2, "maxLeverage": 20 }
+                ] },
+                [context("101.0", "100.0"), context("99.8", "100.0"), context("98.0", "100.0")]
+            ]))
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn client_parses_asset_contexts() {
+    let client = HyperliquidClient::with_base_url(spawn_hyperliquid().await);
+    let contexts = client.asset_contexts().await.unwrap();
+    assert_eq!(contexts.len(), 3);
+    assert_eq!(contexts[0].coin, "BTC");
+    assert_eq!(contexts[0].mark_price, 101.0);
+    assert_eq!(contexts[0].oracle_price, 100.0);
+    assert_eq!(contexts[0].mid_price, Some(101.0));
+    assert_eq!(contexts[0].funding_rate, 0.0000125);
+    assert_eq!(contexts[0].open_interest, 250.5);
+}
+
+#[test]
+fn tracker_keeps_a_full_day_whatever_the_poll_rate() {
+    let tracker = PremiumTracker::default();
+    // A 15s poll for 25 hours.
+    let last = NOW + 25 * HOUR_MS;
+    for time_ms in (NOW..=last).step_by(15_000) {
+        tracker.record("BTC", sample(time_ms, 0.1));
+    }
+    let history = tracker.history("BTC").unwrap();
+    assert_eq!(
+        history.samples().next().unwrap().time_ms,
+        last - PREMIUM_HISTORY_MS
+    );
+    assert_eq!(history.samples().count(), 24 * 60 * 4 + 1);
+}
+
+#[tokio::test]
+async fn sampler_records_every_perp() {
+    let clock = ManualClock::new(NOW);
+    let state = common::state_with_clock(clock.clone())
+        .with_hyperliquid(HyperliquidClient::with_base_url(spawn_hyperliquid().await));
+    let mut sampler = ContextSampler::new(state.clone());
+    assert_eq!(sampler.sample().await.unwrap(), 3);
+    clock.advance(60_000);
+    sampler.sample().await.unwrap();
+
+    let history = state.premiums.history("SOL").unwrap();
+    let times: Vec<u64> = history.samples().map(|s| s.time_ms).collect();
+    assert_eq!(times, [NOW, NOW + 60_000]);
+    assert!((history.latest().unwrap().premium_pct + 2.0).abs() < 1e-9);
+    assert_eq!(
+        state.funding.history("SOL").unwrap().latest().unwrap().rate,
+        0.0000125
+    );
+    assert_eq!(state.open_interest.history("SOL").unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn anomalous_funding_on_monitored_coins_is_sent_to_webhooks_once() {
+    let received: Arc<Mutex<Vec<Value>>> = Arc::default();
+    let hook = Router::new().route(
+        "/hook",
+        post({
+            let received = received.clone();
+            move |Json(body): Json<Value>| async move {
+                received.lock().unwrap().push(body);
+                StatusCode::OK
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, hook).await.unwrap() });
+
+    let settings = Settings::from_toml(
+        "[funding]\nabsolute_threshold = 0.00001\n",
+        std::path::Path::new("test.toml"),
+    )
+    .unwrap();
+    let clock = ManualClock::new(NOW);
+    let state = common::state_with_clock(clock.clone())
+        .with_hyperliquid(HyperliquidClient::with_base_url(spawn_hyperliquid().await))
+        .with_settings(settings)
+        .with_monitored_coins(vec!["ETH".to_string()]);
+    state
+        .webhooks
+        .create(
+            NewWebhook {
+                url: format!("http://{addr}/hook"),
+                coins: None,
+                min_severity: None,
+                secret: None,
+                quiet_hours: None,
+            },
+            NOW,
+        )
+        .unwrap();
+
+    let mut sampler = ContextSampler::new(state);
+    sampler.sample().await.unwrap();
+    clock.advance(60_000);
+    sampler.sample().await.unwrap();
+
+    let received = received.lock().unwrap();
+    assert_eq!(received.len(), 1, "{received:?}");
+    assert_eq!(received[0]["type"], "funding_anomaly");
+    assert_eq!(received[0]["coin"], "ETH");
+    assert_eq!(received[0]["triggers"], json!(["absolute"]));
+}
+
+#[tokio::test]
+async fn premium_endpoint_reports_current_value_and_history() {
+    let clock = ManualClock::new(NOW);
+    let client = HyperliquidClient::with_base_url(spawn_hyperliquid().await);
+    let state = common::state_with_clock(clock.clone()).with_hyperliquid(client);
+    let mut sampler = ContextSampler::new(state.clone());
+    let app = perpscreener::app(state);
+
+    let (status, body) = common::get(app.clone(), "/premium?coin=BTC").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["coin"], "BTC");
+    assert_eq!(body["mark_price"], 101.0);
+    assert!((body["premium_pct"].as_f64().unwrap() - 1.0).abs() < 1e-9);
+    assert_eq!(body["history"].as_array().unwrap().len(), 0);
+    assert_eq!(body["stats"], Value::Null);
+
+    sampler.sample().await.unwrap();
+    clock.advance(60_000);
+    sampler.sample().await.unwrap();
+    for _ in 0..3 {
+        common::get(app.clone(), "/premium?coin=BTC").await;
+    }
+    let (_, body) = common::get(app.clone(), "/premium?coin=BTC").await;
+    assert_eq!(body["history"].as_array().unwrap().len(), 2);
+    assert_eq!(body["history"][1]["time_ms"], NOW + 60_000);
+
+    let (status, _) = common::get(app, "/premium?coin=DOGE").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn premium_screener_lists_coins_outside_the_band() {
+    let clock = ManualClock::new(NOW);
+    let client = HyperliquidClient::with_base_url(spawn_hyperliquid().await);
+    let state = common::state_with_clock(clock.clone()).with_hyperliquid(client);
+    let mut sampler = ContextSampler::new(state.clone());
+    let app = perpscreener::app(state);
+
+    let (status, body) = common::get(app.clone(), "/screeners/premium").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["band_pct"], 0.5);
+    let coins: Vec<&str> = body["coins"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["coin"].as_str().unwrap())
+        .collect();
+    assert_eq!(coins, ["SOL", "BTC"]);
+    assert_eq!(body["coins"][0]["outside_band_for_ms"], 0);
+
+    sampler.sample().await.unwrap();
+    clock.advance(60_000);
+    sampler.sample().await.unwrap();
+    let (_, body) = common::get(app.clone(), "/screeners/premium?band_pct=0.1").await;
+    assert_eq!(body["coins"].as_array().unwrap().len(), 3);
+    assert_eq!(body["coins"][0]["outside_band_for_ms"], 60_000);
+
+    let (status, _) = common::get(app, "/screeners/premium?band_pct=-1").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,215 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use axum::http::StatusCode;
+use axum::routing::post;
+use axum::Router;
+use chrono::{TimeZone, Utc};
+use common::ManualClock;
+use perpscreener::business_logic::alerts::AlertSeverity;
+use perpscreener::business_logic::quiet_hours::{QuietHours, QuietMode};
+use perpscreener::services::delivery_queue::DeliveryQueue;
+use perpscreener::services::webhooks::{NewWebhook, WebhookDispatcher, WebhookStore};
+use serde_json::json;
+
+/// Epoch ms of a UTC wall-clock time on 2024-01-15.
+fn utc(hour: u32, minute: u32) -> u64 {
+    Utc.with_ymd_and_hms(2024, 1, 15, hour, minute, 0)
+        .unwrap()
+        .timestamp_millis() as u64
+}
+
+fn quiet(start: &str, end: &str) -> QuietHours {
+    QuietHours {
+        start: start.to_string(),
+        end: end.to_string(),
+        timezone: "UTC".to_string(),
+        bypass_severity: None,
+        mode: QuietMode::Drop,
+    }
+}
+
+#[test]
+fn same_day_window_includes_start_and_excludes_end() {
+    let lunch = quiet("12:00", "13:00");
+    assert_eq!(lunch.active_until(utc(11, 59)), None);
+    assert_eq!(lunch.active_until(utc(12, 0)), Some(utc(13, 0)));
+    assert_eq!(lunch.active_until(utc(12, 59)), Some(utc(13, 0)));
+    assert_eq!(lunch.active_until(utc(13, 0)), None);
+}
+
+#[test]
+fn window_wraps_past_midnight() {
+    let night = quiet("22:00", "07:00");
+    let next_morning = utc(7, 0) + 24 * 3_600_000;
+    assert_eq!(night.active_until(utc(21, 59)), None);
+    assert_eq!(night.active_until(utc(22, 0)), Some(next_morning));
+    assert_eq!(night.active_until(utc(23, 59)), Some(next_morning));
+    assert_eq!(night.active_until(utc(0, 0)), Some(utc(7, 0)));
+    assert_eq!(night.active_until(utc(6, 59)), Some(utc(7, 0)));
+    assert_eq!(night.active_until(utc(7, 0)), None);
+    assert_eq!(night.active_until(utc(12, 0)), None);
+}
+
+#[test]
+fn window_is_evaluated_in_its_timezone() {
+    // 22:00-07:00 in New York is 03:00-12:00 UTC in January.
+    let night = QuietHours {
+        timezone: "America/New_York".to_string(),
+        ..quiet("22:00", "07:00")
+    };
+    assert_eq!(night.active_until(utc(2, 59)), None);
+    assert_eq!(night.active_until(utc(4, 0)), Some(utc(12, 0)));
+    assert_eq!(night.active_until(utc(12, 0)), None);
+}
+
+#[test]
+fn empty_and_invalid_windows_are_never_quiet() {
+    assert_eq!(quiet("03:00", "03:00").active_until(utc(3, 0)), None);
+
+    let bad_time = quiet("4am", "07:00");
+    assert!(bad_time.validate().is_err());
+    assert_eq!(bad_time.active_until(utc(5, 0)), None);
+
+    let bad_zone = QuietHours {
+        timezone: "Mars/Olympus".to_string(),
+        ..quiet("00:00", "23:59")
+    };
+    assert!(bad_zone.validate().is_err());
+    assert_eq!(bad_zone.active_until(utc(5, 0)), None);
+}
+
+#[test]
+fn bypass_severity_gets_through() {
+    let night = QuietHours {
+        bypass_severity: Some(AlertSeverity::Critical),
+        ..quiet("22:00", "07:00")
+    };
+    assert_eq!(
+        night.suppresses(AlertSeverity::Warning, utc(4, 0)),
+        Some(utc(7, 0))
+    );
+    assert_eq!(night.suppresses(AlertSeverity::Critical, utc(4, 0)), None);
+}
+
+async fn spawn_receiver(hits: Arc<AtomicUsize>) -> String {
+    let app = Router::new().route(
+        "/hook",
+        post(move || {
+            let hits = hits.clone();
+            async move {
+                hits.fetch_add(1, Ordering::SeqCst);
+                StatusCode::OK
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}/hook")
+}
+
+async fn dispatcher_in_quiet_hours(
+    mode: QuietMode,
+) -> (
+    WebhookDispatcher,
+    Arc<WebhookStore>,
+    Arc<DeliveryQueue>,
+    Arc<ManualClock>,
+    Arc<AtomicUsize>,
+    u64,
+) {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let url = spawn_receiver(hits.clone()).await;
+    let clock = ManualClock::new(utc(4, 0));
+    let store = Arc::new(WebhookStore::in_memory());
+    let id = store
+        .create(
+            NewWebhook {
+                url,
+                coins: None,
+                min_severity: None,
+                secret: None,
+                quiet_hours: Some(QuietHours {
+                    bypass_severity: Some(AlertSeverity::Critical),
+                    mode,
+                    ..quiet("22:00", "07:00")
+                }),
+            },
+            utc(4, 0),
+        )
+        .unwrap()
+        .id;
+    let queue = Arc::new(DeliveryQueue::in_memory());
+    let dispatcher = WebhookDispatcher::new(store.clone())
+        .with_queue(queue.clone())
+        .with_clock(clock.clone());
+    (dispatcher, store, queue, clock, hits, id)
+}
+
+#[tokio::test]
+async fn dropped_alerts_are_counted_and_critical_alerts_still_delivered() {
+    let (dispatcher, store, queue, _clock, hits, id) =
+        dispatcher_in_quiet_hours(QuietMode::Drop).await;
+    let payload = json!({ "coin": "BTC" });
+
+    assert_eq!(
+        dispatcher
+            .dispatch("BTC", AlertSeverity::Warning, &payload)
+            .await,
+        0
+    );
+    assert_eq!(hits.load(Ordering::SeqCst), 0);
+    assert!(queue.pending_for(id).is_empty());
+    assert_eq!(store.get(id).unwrap().quiet_dropped, 1);
+
+    assert_eq!(
+        dispatcher
+            .dispatch("BTC", AlertSeverity::Critical, &payload)
+            .await,
+        1
+    );
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn deferred_alerts_are_delivered_when_the_window_ends() {
+    let (dispatcher, store, queue, clock, hits, id) =
+        dispatcher_in_quiet_hours(QuietMode::Defer).await;
+
+    dispatcher
+        .dispatch("BTC", AlertSeverity::Warning, &json!({ "coin": "BTC" }))
+        .await;
+    assert_eq!(hits.load(Ordering::SeqCst), 0);
+    let pending = queue.pending_for(id);
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].attempts, 0);
+    assert_eq!(pending[0].next_attempt_ms, utc(7, 0));
+    assert_eq!(store.get(id).unwrap().quiet_deferred, 1);
+
+    clock.set(utc(6, 59));
+    assert_eq!(dispatcher.retry_due().await, 0);
+
+    clock.set(utc(7, 0));
+    assert_eq!(dispatcher.retry_due().await, 1);
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+    assert!(queue.pending_for(id).is_empty());
+}
+
+#[tokio::test]
+async fn deferring_without_a_queue_drops() {
+    let (_, store, _, clock, hits, id) = dispatcher_in_quiet_hours(QuietMode::Defer).await;
+    let dispatcher = WebhookDispatcher::new(store.clone()).with_clock(clock);
+
+    dispatcher
+        .dispatch("BTC", AlertSeverity::Info, &json!({}))
+        .await;
+    assert_eq!(hits.load(Ordering::SeqCst), 0);
+    let subscription = store.get(id).unwrap();
+    assert_eq!(subscription.quiet_dropped, 1);
+    assert_eq!(subscription.quiet_deferred, 0);
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use common::{candle, candles_from_closes};
+use perpscreener::business_logic::alerts::{AlertStage, PatternKind};
+use perpscreener::business_logic::range_breakout::{
+    RangeBreakoutConfig, RangeBreakoutDetector, RangeBreakoutState,
+};
+use perpscreener::models::candle::Candle;
+
+/// 40 candles chopping between 100 and 101, followed by `rest` closes.
+fn range_then(rest: &[f64]) -> Vec<Candle> {
+    let mut closes: Vec<f64> = (0..40)
+        .map(|i| if i % 2 == 0 { 101.0 } else { 100.0 })
+        .collect();
+    closes.extend_from_slice(rest);
+    candles_from_closes(100.0, &closes, 0.1)
+}
+
+fn feed(detector: &mut RangeBreakoutDetector, candles: &[Candle]) -> Vec<RangeBreakoutState> {
+    candles
+        .iter()
+        .map(|c| {
+            detector.update(c);
+            detector.state()
+        })
+        .collect()
+}
+
+#[test]
+fn close_above_the_band_breaks_up() {
+    let candles = range_then(&[102.0, 103.0]);
+    let mut detector = RangeBreakoutDetector::new("ETH", RangeBreakoutConfig::default());
+
+    let states = feed(&mut detector, &candles[..40]);
+    assert_eq!(states.last(), Some(&RangeBreakoutState::InRange));
+    assert!((detector.range_high().unwrap() - 101.1).abs() < 1e-9);
+    assert!((detector.range_low().unwrap() - 99.9).abs() < 1e-9);
+    assert!(detector.candles_in_range() >= 20);
+
+    let alerts: Vec<_> = candles[40..]
+        .iter()
+        .filter_map(|c| detector.update(c))
+        .collect();
+    assert_eq!(alerts.len(), 1, "{alerts:?}");
+    assert_eq!(alerts[0].pattern, PatternKind::RangeBreakoutUp);
+    assert_eq!(alerts[0].stage, AlertStage::Confirmation);
+    assert!((alerts[0].level - 101.1).abs() < 1e-9);
+    assert_eq!(detector.state(), RangeBreakoutState::BrokeUp);
+    assert_eq!(detector.state().as_str(), "BROKE_UP");
+}
+
+#[test]
+fn close_below_the_band_breaks_down() {
+    let candles = range_then(&[99.0, 98.0]);
+    let mut detector = RangeBreakoutDetector::new("ETH", RangeBreakoutConfig::default());
+
+    let alerts: Vec<_> = candles.iter().filter_map(|c| detector.update(c)).collect();
+    assert_eq!(alerts.len(), 1, "{alerts:?}");
+    assert_eq!(alerts[0].pattern, PatternKind::RangeBreakoutDown);
+    assert!((alerts[0].level - 99.9).abs() < 1e-9);
+    assert_eq!(detector.state(), RangeBreakoutState::BrokeDown);
+}
+
+#[test]
+fn wick_outside_the_band_without_a_close_is_not_a_breakout() {
+    let mut candles = range_then(&[]);
+    candles.push(candle(40, 101.0, 102.5, 100.9, 100.8));
+    candles.push(candle(41, 100.8, 101.0, 100.0, 100.2));
+    let mut detector = RangeBreakoutDetector::new("ETH", RangeBreakoutConfig::default());
+
+    let alerts: Vec<_> = candles.iter().filter_map(|c| detector.update(c)).collect();
+    assert!(alerts.is_empty(), "{alerts:?}");
+    assert_eq!(detector.state(), RangeBreakoutState::InRange);
+}
+
+#[test]
+fn resets_once_price_moves_away_from_the_broken_bound() {
+    let candles = range_then(&[102.0, 103.0, 104.0]);
+    let mut detector = RangeBreakoutDetector::new("ETH", RangeBreakoutConfig::default());
+
+    let states = feed(&mut detector, &candles);
+    assert!(states.contains(&RangeBreakoutState::BrokeUp));
+    assert_eq!(detector.state(), RangeBreakoutState::Watching);
+    assert_eq!(detector.candles_in_range(), 1);
+}
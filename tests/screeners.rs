@@ -0,0 +1,164 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use axum::http::StatusCode;
+use common::ManualClock;
+use perpscreener::business_logic::anomalies::{AnomalyKind, CandleAnomaly};
+use perpscreener::business_logic::funding::FundingSample;
+use perpscreener::business_logic::open_interest::OiSample;
+use perpscreener::business_logic::volume::{CandleDirection, VolumeSpike};
+use perpscreener::state::RECENT_EVENTS_PER_COIN;
+
+const NOW: u64 = 1_700_000_000_000;
+
+fn spike(coin: &str, open_time: u64) -> VolumeSpike {
+    VolumeSpike {
+        coin: coin.to_string(),
+        open_time,
+        volume: 40.0,
+        average_volume: 10.0,
+        multiple: 4.0,
+        price_change_pct: -1.5,
+        direction: CandleDirection::Down,
+    }
+}
+
+#[tokio::test]
+async fn volume_spikes_are_listed_newest_first_with_filters() {
+    let state = common::state_with_clock(ManualClock::new(NOW));
+    state.volume_spikes.record(spike("BTC", NOW - 120_000));
+    state.volume_spikes.record(spike("ETH", NOW - 60_000));
+    state.volume_spikes.record(spike("BTC", NOW - 30_000));
+    let app = perpscreener::app(state);
+
+    let (status, body) = common::get(app.clone(), "/screeners/volume-spikes").await;
+    assert_eq!(status, StatusCode::OK);
+    let spikes = body["spikes"].as_array().unwrap();
+    let order: Vec<(&str, u64)> = spikes
+        .iter()
+        .map(|s| {
+            (
+                s["coin"].as_str().unwrap(),
+                s["open_time"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        order,
+        [
+            ("BTC", NOW - 30_000),
+            ("ETH", NOW - 60_000),
+            ("BTC", NOW - 120_000)
+        ]
+    );
+    assert_eq!(spikes[0]["direction"], "down");
+    assert_eq!(spikes[0]["multiple"], 4.0);
+
+    let uri = format!(
+        "/screeners/volume-spikes?coin=BTC&since_ms={}",
+        NOW - 60_000
+    );
+    let (_, body) = common::get(app, &uri).await;
+    assert_eq!(body["spikes"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn recent_spikes_are_capped_per_coin() {
+    let state = common::state_with_clock(ManualClock::new(NOW));
+    for i in 0..RECENT_EVENTS_PER_COIN as u64 + 5 {
+        state.volume_spikes.record(spike("BTC", NOW + i));
+    }
+    let kept = state.volume_spikes.snapshot();
+    assert_eq!(kept.len(), RECENT_EVENTS_PER_COIN);
+    assert_eq!(kept.last().unwrap().open_time, NOW + 5);
+    assert!(state.volume_spikes.has_event_since("BTC", NOW + 20));
+    assert!(!state.volume_spikes.has_event_since("ETH", 0));
+}
+
+#[tokio::test]
+async fn anomalies_are_listed_with_filters() {
+    let state = common::state_with_clock(ManualClock::new(NOW));
+    for (coin, open_time) in [("BTC", NOW - 60_000), ("SOL", NOW)] {
+        state.anomalies.record(CandleAnomaly {
+            coin: coin.to_string(),
+            open_time,
+            kinds: vec![AnomalyKind::LongWick, AnomalyKind::WideRange],
+            wick_body_ratio: 6.0,
+            range_atr: 3.5,
+            atr: 2.0,
+        });
+    }
+    assert!(state.anomalies.at("BTC", NOW - 60_000).is_some());
+    assert!(state.anomalies.at("BTC", NOW).is_none());
+    let app = perpscreener::app(state);
+
+    let (status, body) = common::get(app.clone(), "/screeners/anomalies").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["anomalies"][0]["coin"], "SOL");
+    assert_eq!(body["anomalies"][0]["kinds"][0], "long_wick");
+    assert_eq!(body["anomalies"][1]["coin"], "BTC");
+
+    let (_, body) = common::get(app, "/screeners/anomalies?coin=BTC").await;
+    assert_eq!(body["anomalies"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn funding_screener_lists_anomalous_rates_highest_first() {
+    let state = common::state_with_clock(ManualClock::new(NOW));
+    let app = perpscreener::app(state.clone());
+    let (status, body) = common::get(app.clone(), "/screeners/funding").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["sampled_at_ms"], serde_json::Value::Null);
+    assert_eq!(body["coins"].as_array().unwrap().len(), 0);
+
+    for (coin, rate) in [("BTC", 0.0006), ("ETH", 0.0001), ("SOL", 0.002)] {
+        state
+            .funding
+            .record(coin, FundingSample { time_ms: NOW, rate });
+    }
+    let (_, body) = common::get(app, "/screeners/funding").await;
+    assert_eq!(body["sampled_at_ms"], NOW);
+    let coins = body["coins"].as_array().unwrap();
+    assert_eq!(coins.len(), 2);
+    assert_eq!(coins[0]["coin"], "SOL");
+    assert_eq!(coins[0]["triggers"], serde_json::json!(["absolute"]));
+    assert_eq!(coins[1]["coin"], "BTC");
+}
+
+#[tokio::test]
+async fn open_interest_screener_lists_coins_that_triggered_a_rule() {
+    const HOUR_MS: u64 = 3_600_000;
+    let state = common::state_with_clock(ManualClock::new(NOW));
+    for (coin, from_oi, to_oi, to_price) in [
+        ("BTC", 1000.0, 1200.0, 99.0),
+        ("ETH", 1000.0, 1100.0, 100.0),
+        ("SOL", 1000.0, 700.0, 95.0),
+        ("DOGE", 1000.0, 1500.0, 110.0),
+    ] {
+        for (time_ms, open_interest, price) in
+            [(NOW - 4 * HOUR_MS, from_oi, 100.0), (NOW, to_oi, to_price)]
+        {
+            state.open_interest.record(
+                coin,
+                OiSample {
+                    time_ms,
+                    open_interest,
+                    price,
+                },
+                4 * HOUR_MS,
+            );
+        }
+    }
+
+    let (status, body) = common::get(perpscreener::app(state), "/screeners/open-interest").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["window_ms"], 4 * HOUR_MS);
+    let coins = body["coins"].as_array().unwrap();
+    assert_eq!(coins.len(), 2, "{coins:?}");
+    assert_eq!(coins[0]["coin"], "SOL");
+    assert_eq!(coins[0]["rules"], serde_json::json!(["collapse"]));
+    assert_eq!(coins[1]["coin"], "BTC");
+    assert_eq!(coins[1]["rules"], serde_json::json!(["squeeze_fuel"]));
+    assert_eq!(coins[1]["oi_change_pct"], 20.0);
+}
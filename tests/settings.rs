@@ -0,0 +1,582 @@
+#![cfg(feature = "server")]
+
+use std::path::{Path, PathBuf};
+
+use perpscreener::business_logic::alerts::ConfirmationMode;
+use perpscreener::business_logic::double_top::{AtrRule, HigherTimeframeMode};
+use perpscreener::business_logic::gaps::GapPolicy;
+use perpscreener::naming::ApiNaming;
+use perpscreener::settings::{CoinSelection, MonitorSettings, ServerSettings, Settings};
+
+fn parse(text: &str) -> Settings {
+    Settings::from_toml(text, Path::new("test.toml")).unwrap()
+}
+
+fn listed(coins: &[&str]) -> CoinSelection {
+    CoinSelection::Listed(coins.iter().map(|c| c.to_string()).collect())
+}
+
+fn parse_err(text: &str) -> String {
+    Settings::from_toml(text, Path::new("test.toml"))
+        .unwrap_err()
+        .to_string()
+}
+
+#[test]
+fn example_config_parses() {
+    let text = std::fs::read_to_string("config.example.toml").unwrap();
+    let settings = parse(&text);
+    assert_eq!(settings.server, ServerSettings::default());
+    assert_eq!(settings.monitor.coins, listed(&["BTC", "ETH", "SOL"]));
+    let filter = settings.double_bottom.ema_filter.unwrap();
+    assert_eq!((filter.period, filter.slope_lookback), (20, 3));
+}
+
+#[test]
+fn empty_file_is_all_defaults() {
+    let settings = parse("");
+    assert_eq!(settings.server, ServerSettings::default());
+    assert_eq!(settings.monitor, MonitorSettings::default());
+    assert_eq!(settings.server.bind, "0.0.0.0:3000");
+    assert_eq!(settings.server.webhook_retry_secs, 5);
+    assert!(settings.double_bottom.ema_filter.is_none());
+    assert!(settings.validate().is_empty());
+}
+
+#[test]
+fn missing_keys_keep_their_defaults() {
+    let settings = parse(
+        r#"
+        [server]
+        bind = "127.0.0.1:8080"
+        api_naming = "camel"
+
+        [double_bottom]
+        min_bounce_pct = 3.5
+
+        [double_bottom.ema_filter]
+        period = 50
+        "#,
+    );
+    assert_eq!(settings.server.bind, "127.0.0.1:8080");
+    assert_eq!(settings.server.api_naming, ApiNaming::Camel);
+    assert_eq!(
+        settings.server.webhook_store_path,
+        PathBuf::from("data/webhooks.json")
+    );
+    assert_eq!(settings.monitor.poll_interval_secs, 60);
+    assert_eq!(settings.double_bottom.min_bounce_pct, 3.5);
+    assert_eq!(settings.double_bottom.atr_period, 14);
+    let filter = settings.double_bottom.ema_filter.unwrap();
+    assert_eq!((filter.period, filter.slope_lookback), (50, 3));
+}
+
+#[test]
+fn errors_name_the_offending_key() {
+    let err = parse_err("[server]\nbnid = \"0.0.0.0:1\"\n");
+    assert!(err.contains("bnid"), "{err}");
+    assert!(err.contains("test.toml"), "{err}");
+
+    let err = parse_err("[monitor]\npoll_interval_secs = \"often\"\n");
+    assert!(err.contains("poll_interval_secs"), "{err}");
+
+    let err = parse_err("[server]\napi_naming = \"kebab\"\n");
+    assert!(err.contains("api_naming"), "{err}");
+}
+
+#[test]
+fn explicit_missing_file_is_an_error_but_the_default_path_is_optional() {
+    assert!(Settings::load(Some(Path::new("does/not/exist.toml")), []).is_err());
+    // The tests run from the crate root, which has no config.toml.
+    assert!(Settings::load(None, []).is_ok());
+}
+
+fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn layered(text: &str, pairs: &[(&str, &str)]) -> Result<Settings, String> {
+    Settings::from_sources(text, Path::new("test.toml"), vars(pairs)).map_err(|e| e.to_string())
+}
+
+#[test]
+fn env_overrides_the_file_which_overrides_defaults() {
+    let settings = layered(
+        "[server]\nbind = \"127.0.0.1:8080\"\nwebhook_retry_secs = 9\n\n[monitor]\ncoins = [\"BTC\"]\n",
+        &[
+            ("PERPSCREENER__MONITOR__COINS", "BTC, ETH,DOGE"),
+            ("PERPSCREENER__SERVER__WEBHOOK_RETRY_SECS", "30"),
+            ("PERPSCREENER__DOUBLE_BOTTOM__MIN_BOUNCE_PCT", "2.5"),
+            ("PERPSCREENER__DOUBLE_BOTTOM__REQUIRE_RSI_DIVERGENCE", "true"),
+            ("PATH", "/usr/bin"),
+            ("PERPSCREENER_CONFIG", "ignored.toml"),
+        ],
+    )
+    .unwrap();
+    assert_eq!(settings.monitor.coins, listed(&["BTC", "ETH", "DOGE"]));
+    assert_eq!(settings.server.webhook_retry_secs, 30);
+    assert_eq!(settings.server.bind, "127.0.0.1:8080");
+    assert_eq!(settings.monitor.poll_interval_secs, 60);
+    assert_eq!(settings.double_bottom.min_bounce_pct, 2.5);
+    assert!(settings.double_bottom.require_rsi_divergence);
+}
+
+#[test]
+fn env_can_set_keys_without_a_default() {
+    let settings = layered(
+        "",
+        &[
+            ("PERPSCREENER__DOUBLE_BOTTOM__MIN_ADX_AT_TROUGH1", "25"),
+            ("PERPSCREENER__DOUBLE_BOTTOM__EMA_FILTER__PERIOD", "50"),
+            ("PERPSCREENER__SERVER__API_NAMING", "camel"),
+        ],
+    )
+    .unwrap();
+    assert_eq!(settings.double_bottom.min_adx_at_trough1, Some(25.0));
+    assert_eq!(settings.double_bottom.ema_filter.unwrap().period, 50);
+    assert_eq!(settings.server.api_naming, ApiNaming::Camel);
+}
+
+#[test]
+fn env_parse_failures_name_the_variable() {
+    let err = layered("", &[("PERPSCREENER__MONITOR__POLL_INTERVAL_SECS", "1m")]).unwrap_err();
+    assert!(
+        err.contains("PERPSCREENER__MONITOR__POLL_INTERVAL_SECS"),
+        "{err}"
+    );
+    assert!(err.contains("1m"), "{err}");
+
+    let err = layered("", &[("PERPSCREENER__DOUBLE_BOTTOM__REV_ATR", "wide")]).unwrap_err();
+    assert!(
+        err.contains("PERPSCREENER__DOUBLE_BOTTOM__REV_ATR"),
+        "{err}"
+    );
+
+    let err = layered("", &[("PERPSCREENER__SERVER", "x")]).unwrap_err();
+    assert!(err.contains("PERPSCREENER__SERVER"), "{err}");
+
+    let err = layered("", &[("PERPSCREENER__SERVER__BINDD", "x")]).unwrap_err();
+    assert!(err.contains("bindd"), "{err}");
+}
+
+#[test]
+fn effective_config_round_trips_through_toml() {
+    let settings = layered("", &[("PERPSCREENER__MONITOR__COINS", "SOL")]).unwrap();
+    let again = Settings::from_toml(&settings.to_toml(), Path::new("effective")).unwrap();
+    assert_eq!(again.monitor.coins, listed(&["SOL"]));
+    assert_eq!(again.server, settings.server);
+}
+
+#[test]
+fn validate_rejects_no_coins_and_unknown_intervals() {
+    let settings = parse("[monitor]\ncoins = []\ndetection_interval = \"7m\"\n");
+    let errors = settings.validate();
+    assert_eq!(errors.len(), 2, "{errors:?}");
+    assert!(errors[0].contains("monitor.coins"));
+    assert!(errors[1].contains("7m"));
+}
+
+#[test]
+fn validate_includes_detector_config() {
+    let settings = parse("[double_bottom]\natr_period = 0\n");
+    assert_eq!(
+        settings.validate(),
+        ["double_bottom.atr_period must be positive"]
+    );
+}
+
+#[test]
+fn validate_rejects_duplicate_and_blank_coins() {
+    let settings =
+        parse("[monitor]\ncoins = [\"BTC\", \"ETH\", \" \", \"BTC\", \"BTC\", \"ETH\"]\n");
+    assert_eq!(
+        settings.validate(),
+        [
+            "monitor.coins: coin names must not be empty",
+            "monitor.coins: `BTC` is listed more than once",
+            "monitor.coins: `ETH` is listed more than once",
+        ]
+    );
+}
+
+#[test]
+fn coin_list_comes_from_any_layer() {
+    let five = ["BTC", "ETH", "SOL", "DOGE", "HYPE"];
+    let from_file = parse("[monitor]\ncoins = [\"BTC\", \"ETH\", \"SOL\", \"DOGE\", \"HYPE\"]\n");
+    assert_eq!(from_file.monitor.coins, listed(&five));
+    let from_env = layered(
+        "",
+        &[("PERPSCREENER__MONITOR__COINS", "BTC,ETH,SOL,DOGE,HYPE")],
+    )
+    .unwrap();
+    assert_eq!(from_env.monitor.coins, listed(&five));
+    assert!(from_env.validate().is_empty());
+}
+
+#[test]
+fn coins_all_selects_every_perp() {
+    let settings =
+        parse("[monitor]\ncoins = \"all\"\n\n[monitor.discovery]\nmin_day_volume = 1e6\n");
+    assert_eq!(settings.monitor.coins, CoinSelection::All);
+    assert_eq!(settings.monitor.discovery.min_day_volume, Some(1e6));
+    assert!(settings.monitor.discovery.exclude_isolated_only);
+    assert!(settings.validate().is_empty());
+    assert!(settings.to_toml().contains("coins = \"all\""));
+
+    let from_env = layered("", &[("PERPSCREENER__MONITOR__COINS", "all")]).unwrap();
+    assert_eq!(from_env.monitor.coins, CoinSelection::All);
+
+    let err = parse_err("[monitor]\ncoins = \"some\"\n");
+    assert!(err.contains("coins"), "{err}");
+}
+
+#[test]
+fn validate_rejects_a_zero_stale_threshold() {
+    let settings = parse("[monitor]\nstale_after_intervals = 0\n");
+    assert_eq!(
+        settings.validate(),
+        ["monitor.stale_after_intervals must be positive"]
+    );
+}
+
+#[test]
+fn validate_rejects_a_zero_poll_interval() {
+    let settings = parse("[monitor]\npoll_interval_secs = 0\n");
+    assert_eq!(
+        settings.validate(),
+        ["monitor.poll_interval_secs must be positive"]
+    );
+}
+
+#[test]
+fn gap_handling_is_configurable() {
+    let settings = parse("[monitor.gaps]\npolicy = \"reset_detector\"\n");
+    assert_eq!(settings.monitor.gaps.policy, GapPolicy::ResetDetector);
+    assert_eq!(settings.monitor.gaps.clean_candles_to_clear, 20);
+
+    let settings = layered(
+        "",
+        &[("PERPSCREENER__MONITOR__GAPS__CLEAN_CANDLES_TO_CLEAR", "0")],
+    )
+    .unwrap();
+    assert_eq!(
+        settings.validate(),
+        ["monitor.gaps.clean_candles_to_clear must be positive"]
+    );
+    assert!(parse_err("[monitor.gaps]\npolicy = \"drop\"\n").contains("policy"));
+}
+
+#[test]
+fn volume_spike_rule_is_configurable() {
+    let settings = layered(
+        "[volume_spike]\nwindow = 30\n",
+        &[("PERPSCREENER__VOLUME_SPIKE__MIN_STDDEVS", "2.5")],
+    )
+    .unwrap();
+    assert_eq!(settings.volume_spike.window, 30);
+    assert_eq!(settings.volume_spike.multiple, 3.0);
+    assert_eq!(settings.volume_spike.min_stddevs, Some(2.5));
+    assert!(settings.validate().is_empty());
+
+    let settings = parse("[volume_spike]\nwindow = 0\nmin_stddevs = -1.0\nmin_stddev_pct = -5.0\n");
+    assert_eq!(
+        settings.validate(),
+        [
+            "volume_spike.window must be positive",
+            "volume_spike.min_stddevs must be positive",
+            "volume_spike.min_stddev_pct must not be negative",
+        ]
+    );
+}
+
+#[test]
+fn anomaly_thresholds_are_configurable() {
+    let settings = layered(
+        "[anomalies]\nrange_atr = 4.5\n",
+        &[("PERPSCREENER__ANOMALIES__ENVELOPE_CANDLES", "30")],
+    )
+    .unwrap();
+    assert_eq!(settings.anomalies.range_atr, 4.5);
+    assert_eq!(settings.anomalies.envelope_candles, 30);
+    assert_eq!(settings.anomalies.atr_period, 14);
+
+    let settings = parse("[anomalies]\natr_period = 0\nenvelope_margin_atr = -1.0\n");
+    assert_eq!(
+        settings.validate(),
+        [
+            "anomalies.atr_period must be positive",
+            "anomalies.envelope_margin_atr must not be negative",
+        ]
+    );
+}
+
+#[test]
+fn funding_screener_is_configurable() {
+    let settings = layered(
+        "[funding]\npercentile = 99.0\n",
+        &[("PERPSCREENER__FUNDING__ABSOLUTE_THRESHOLD", "0.001")],
+    )
+    .unwrap();
+    assert_eq!(settings.funding.percentile, 99.0);
+    assert_eq!(settings.funding.absolute_threshold, Some(0.001));
+    assert_eq!(settings.funding.min_history, 72);
+
+    let settings = parse("[funding]\npercentile = 101.0\nmin_history = 0\n");
+    assert_eq!(
+        settings.validate(),
+        [
+            "funding.percentile must be between 0 and 100",
+            "funding.min_history must be positive",
+        ]
+    );
+}
+
+#[test]
+fn open_interest_screener_is_configurable() {
+    let settings = layered(
+        "[open_interest]\nwindow_ms = 7200000\n",
+        &[("PERPSCREENER__OPEN_INTEREST__RISE_PCT", "25")],
+    )
+    .unwrap();
+    assert_eq!(settings.open_interest.window_ms, 7_200_000);
+    assert_eq!(settings.open_interest.rise_pct, 25.0);
+    assert_eq!(settings.open_interest.collapse_pct, 20.0);
+
+    let settings = parse("[open_interest]\nwindow_ms = 0\ncollapse_pct = -5.0\n");
+    assert_eq!(
+        settings.validate(),
+        [
+            "open_interest.window_ms must be positive",
+            "open_interest.collapse_pct must be positive",
+        ]
+    );
+}
+
+#[test]
+fn head_and_shoulders_detector_is_configurable() {
+    let settings = parse("[head_and_shoulders]\nshoulder_tolerance_pct = 3.0\n");
+    assert_eq!(settings.head_and_shoulders.shoulder_tolerance_pct, 3.0);
+    assert_eq!(settings.head_and_shoulders.max_head_distance, 60);
+
+    let settings = parse("[head_and_shoulders]\nrev_atr = 0.0\nmax_head_distance = 0\n");
+    assert_eq!(
+        settings.validate(),
+        [
+            "head_and_shoulders.rev_atr must be positive",
+            "head_and_shoulders.max_head_distance must be positive",
+        ]
+    );
+}
+
+#[test]
+fn double_top_detector_is_configurable() {
+    let settings = parse("[double_top]\nmax_peak_distance = 90\n");
+    assert_eq!(settings.double_top.max_peak_distance, 90);
+    assert_eq!(settings.double_top.trend_lookback, 3);
+    assert_eq!(settings.double_top.confirmed_ttl_candles, 24);
+
+    let settings = parse("[double_top]\nconfirmation_mode = \"low\"\n");
+    assert_eq!(settings.double_top.confirmation_mode, ConfirmationMode::Low);
+
+    let settings = parse("[double_top]\nmin_pattern_height_atr = 3.0\natr_rule = \"both\"\n");
+    assert_eq!(settings.double_top.min_pattern_height_atr, Some(3.0));
+    assert_eq!(settings.double_top.atr_rule, AtrRule::Both);
+
+    let settings = parse("[double_top.confidence]\nsymmetry = -1.0\n");
+    assert_eq!(
+        settings.validate(),
+        ["double_top.confidence weights must not be negative"]
+    );
+
+    let settings = parse(
+        "[double_top]\nmax_candidates = 0\ncooldown_exit_atr = 0.0\n\
+         min_peak_distance = 60\nmax_leg_ratio = 0.5\n",
+    );
+    assert_eq!(
+        settings.validate(),
+        [
+            "double_top.min_peak_distance must be less than max_peak_distance",
+            "double_top.max_leg_ratio must be at least 1",
+            "double_top.cooldown_exit_atr must be positive",
+            "double_top.max_candidates must be positive",
+        ]
+    );
+
+    let settings = parse("[double_top.ema_filter]\nslope_lookback = 0\n");
+    assert_eq!(settings.double_top.ema_filter.unwrap().period, 20);
+    assert_eq!(
+        settings.validate(),
+        ["double_top.ema_filter.slope_lookback must be positive"]
+    );
+
+    let settings = parse("[double_top.confluence]\npremium = 15.0\n");
+    assert_eq!(settings.double_top.confluence.premium, 15.0);
+    assert_eq!(settings.double_top.confluence.open_interest, 10.0);
+    let settings = parse("[double_top.confluence]\nopen_interest = -1.0\npremium_band_pct = 0.0\n");
+    assert_eq!(
+        settings.validate(),
+        [
+            "double_top.confluence bonuses must not be negative",
+            "double_top.confluence.premium_band_pct must be positive",
+        ]
+    );
+
+    let settings = parse("[double_top.htf_filter]\ninterval = \"4h\"\nmode = \"annotate\"\n");
+    let filter = settings.double_top.htf_filter.clone().unwrap();
+    assert_eq!(filter.interval, "4h");
+    assert_eq!(filter.ema_period, 20);
+    assert_eq!(filter.mode, HigherTimeframeMode::Annotate);
+    assert!(settings.validate().is_empty());
+
+    let settings = parse("[double_top.htf_filter]\ninterval = \"7m\"\nema_period = 0\n");
+    assert_eq!(
+        settings.validate(),
+        [
+            "double_top.htf_filter.interval must be a supported interval",
+            "double_top.htf_filter.ema_period must be positive",
+        ]
+    );
+
+    let settings = parse("[double_top]\ntrend_lookback = 0\n");
+    assert!(settings.validate().is_empty());
+
+    let settings =
+        parse("[double_top]\ntrend_lookback = 5\nhistory_window = 5\nretest_band_atr = 0.0\n");
+    assert_eq!(
+        settings.validate(),
+        [
+            "double_top.history_window must be greater than trend_lookback",
+            "double_top.retest_band_atr must be positive",
+            "double_top.volume_lookback must be positive and less than history_window",
+        ]
+    );
+}
+
+#[test]
+fn triple_top_detector_is_configurable() {
+    let settings = parse("[triple_top]\npeak_tolerance_pct = 1.0\n");
+    assert_eq!(settings.triple_top.peak_tolerance_pct, 1.0);
+    assert_eq!(settings.triple_top.max_pattern_candles, 120);
+
+    let settings = parse("[triple_top]\npeak_fail_pct = 0.0\nmax_pattern_candles = 0\n");
+    assert_eq!(
+        settings.validate(),
+        [
+            "triple_top.peak_fail_pct must be in (0, 100]",
+            "triple_top.max_pattern_candles must be positive",
+        ]
+    );
+}
+
+#[test]
+fn triple_bottom_detector_is_configurable() {
+    let settings = parse("[triple_bottom]\ntrough_tolerance_pct = 2.5\n");
+    assert_eq!(settings.triple_bottom.trough_tolerance_pct, 2.5);
+    assert_eq!(settings.triple_bottom.min_bounce_pct, 2.0);
+
+    let settings = parse("[triple_bottom]\nmin_bounce_pct = 0.0\nbreakout_buffer = -1.0\n");
+    assert_eq!(
+        settings.validate(),
+        [
+            "triple_bottom.min_bounce_pct must be in (0, 100]",
+            "triple_bottom.breakout_buffer must not be negative",
+        ]
+    );
+}
+
+#[test]
+fn ascending_triangle_detector_is_configurable() {
+    let settings = parse("[ascending_triangle]\nmin_touches = 3\n");
+    assert_eq!(settings.ascending_triangle.min_touches, 3);
+    assert_eq!(settings.ascending_triangle.apex_threshold_pct, 2.5);
+
+    let settings = parse("[ascending_triangle]\nmin_touches = 1\nlevel_tolerance_pct = 0.0\n");
+    assert_eq!(
+        settings.validate(),
+        [
+            "ascending_triangle.level_tolerance_pct must be in (0, 100]",
+            "ascending_triangle.min_touches must be at least 2",
+        ]
+    );
+}
+
+#[test]
+fn descending_triangle_detector_is_configurable() {
+    let settings = parse("[descending_triangle]\nmin_touches = 3\n");
+    assert_eq!(settings.descending_triangle.min_touches, 3);
+    assert_eq!(settings.descending_triangle.apex_threshold_pct, 2.5);
+
+    let settings =
+        parse("[descending_triangle]\nmin_high_drop_pct = -1.0\nbreakdown_buffer = -0.1\n");
+    assert_eq!(
+        settings.validate(),
+        [
+            "descending_triangle.min_high_drop_pct must be in [0, 100]",
+            "descending_triangle.breakdown_buffer must not be negative",
+        ]
+    );
+}
+
+#[test]
+fn cup_and_handle_detector_is_configurable() {
+    let settings = parse("[cup_and_handle]\nwindow = 120\nmax_cup_candles = 100\n");
+    assert_eq!(settings.cup_and_handle.window, 120);
+    assert_eq!(settings.cup_and_handle.max_cup_candles, 100);
+    assert_eq!(settings.cup_and_handle.min_depth_pct, 8.0);
+
+    let settings = parse("[cup_and_handle]\nwindow = 20\nhandle_max_retrace = 0.0\n");
+    assert_eq!(
+        settings.validate(),
+        [
+            "cup_and_handle.window must be more than min_cup_candles",
+            "cup_and_handle.handle_max_retrace must be in (0, 1]",
+        ]
+    );
+}
+
+#[test]
+fn range_breakout_detector_is_configurable() {
+    let settings = parse("[range_breakout]\nband_pct = 5.0\n");
+    assert_eq!(settings.range_breakout.band_pct, 5.0);
+    assert_eq!(settings.range_breakout.min_candles, 20);
+
+    let settings = parse("[range_breakout]\nband_pct = 0.0\nmin_candles = 1\n");
+    assert_eq!(
+        settings.validate(),
+        [
+            "range_breakout.band_pct must be in (0, 100]",
+            "range_breakout.min_candles must be at least 2",
+        ]
+    );
+}
+
+#[test]
+fn trendline_detector_is_configurable() {
+    let settings = parse("[trendline]\nanchor_swings = 4\n");
+    assert_eq!(settings.trendline.anchor_swings, 4);
+    assert_eq!(settings.trendline.min_touches, 3);
+
+    let settings = parse("[trendline]\nanchor_swings = 2\nmin_r_squared = 1.5\n");
+    assert_eq!(
+        settings.validate(),
+        [
+            "trendline.min_touches must be at most anchor_swings",
+            "trendline.min_r_squared must be in [0, 1]",
+        ]
+    );
+}
+
+#[test]
+fn validate_checks_the_volatility_ranking() {
+    let settings = parse("[volatility]\ninterval = \"7m\"\nperiod = 0\n");
+    assert_eq!(
+        settings.validate(),
+        [
+            "volatility.interval: unsupported interval `7m`",
+            "volatility.period must be between 1 and 1000",
+        ]
+    );
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::{ManualClock, T0};
+use perpscreener::services::webhooks::{WebhookDispatcher, WebhookStore};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_util::sync::CancellationToken;
+
+const DEADLINE: Duration = Duration::from_secs(5);
+
+#[tokio::test]
+async fn server_stops_when_shutdown_is_cancelled() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = perpscreener::app(common::state_with_clock(ManualClock::new(T0)));
+    let shutdown = CancellationToken::new();
+    let server = tokio::spawn(perpscreener::serve(listener, app, shutdown.clone()));
+
+    // The server answers before shutdown.
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+
+    shutdown.cancel();
+    let result = tokio::time::timeout(DEADLINE, server)
+        .await
+        .expect("server did not stop in time");
+    result.unwrap().unwrap();
+    assert!(TcpStream::connect(addr).await.is_err());
+}
+
+#[tokio::test]
+async fn retry_worker_stops_when_shutdown_is_cancelled() {
+    let dispatcher = WebhookDispatcher::new(Arc::new(WebhookStore::in_memory()));
+    let shutdown = CancellationToken::new();
+    let worker =
+        tokio::spawn(dispatcher.run_retry_worker(Duration::from_millis(10), shutdown.clone()));
+
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert!(!worker.is_finished());
+    shutdown.cancel();
+    tokio::time::timeout(DEADLINE, worker)
+        .await
+        .expect("retry worker did not stop in time")
+        .unwrap();
+}
@@ -0,0 +1,241 @@
+mod common;
+
+use common::{candle, candles_from_closes, MINUTE_MS, T0};
+use perpscreener::business_logic::indicators::AtrCalculator;
+use perpscreener::business_logic::swing::{zigzag, SwingDetector, SwingPoint, Trend};
+use perpscreener::models::candle::Candle;
+
+/// Run `candles` through a detector with a constant ATR of 1.0.
+fn swings(detector: &mut SwingDetector, candles: &[Candle]) -> Vec<(usize, SwingPoint)> {
+    candles
+        .iter()
+        .enumerate()
+        .filter_map(|(i, c)| detector.update(c, 1.0).map(|p| (i, p)))
+        .collect()
+}
+
+fn path(start: f64, steps: &[(usize, f64)]) -> Vec<f64> {
+    let mut closes = Vec::new();
+    let mut price = start;
+    for &(count, step) in steps {
+        for _ in 0..count {
+            price += step;
+            closes.push(price);
+        }
+    }
+    closes
+}
+
+#[test]
+fn series_starting_in_a_decline_emits_the_trough_first() {
+    // Falls 0.5 per candle for 8 candles, then rises.
+    let closes = path(110.0, &[(8, -0.5), (8, 0.5)]);
+    let candles = candles_from_closes(110.0, &closes, 0.1);
+    let mut detector = SwingDetector::new(1.0);
+
+    let found = swings(&mut detector, &candles);
+    let (_, first) = found[0];
+    assert!(!first.is_peak, "first swing was a phantom peak: {first:?}");
+    assert!((first.price - (106.0 - 0.1)).abs() < 1e-9);
+}
+
+#[test]
+fn series_starting_in_a_rally_emits_the_peak_first() {
+    let closes = path(100.0, &[(8, 0.5), (8, -0.5)]);
+    let candles = candles_from_closes(100.0, &closes, 0.1);
+    let mut detector = SwingDetector::new(1.0);
+
+    let found = swings(&mut detector, &candles);
+    let (_, first) = found[0];
+    assert!(first.is_peak);
+    assert!((first.price - (104.0 + 0.1)).abs() < 1e-9);
+}
+
+#[test]
+fn swing_points_carry_the_open_time_of_the_extreme_candle() {
+    let closes = path(100.0, &[(8, 0.5), (8, -0.5), (8, 0.5)]);
+    let candles = candles_from_closes(100.0, &closes, 0.1);
+    let mut detector = SwingDetector::new(1.0);
+
+    let found = swings(&mut detector, &candles);
+    let (confirmed_at, peak) = found[0];
+    // The high was made on the eighth candle and confirmed a few later.
+    assert_eq!(peak.open_time, T0 + 7 * MINUTE_MS);
+    assert!(confirmed_at > 7);
+    let (_, trough) = found[1];
+    assert_eq!(trough.open_time, T0 + 15 * MINUTE_MS);
+}
+
+#[test]
+fn zigzag_reports_each_swing_with_its_atr() {
+    let closes = path(100.0, &[(16, 0.5), (8, -0.5), (8, 0.5), (8, -0.5)]);
+    let candles = candles_from_closes(100.0, &closes, 0.1);
+
+    // A 2 ATR reversal: steady 0.5 moves make every candle a full ATR wide.
+    let points = zigzag(&candles, 14, 2.0);
+    let kinds: Vec<bool> = points.iter().map(|p| p.is_peak).collect();
+    assert_eq!(kinds, vec![true, false, true]);
+    assert_eq!(points[0].open_time, T0 + 15 * MINUTE_MS);
+    assert!((points[0].price - 108.1).abs() < 1e-9);
+    assert!(points.iter().all(|p| p.atr > 0.0));
+    assert!(zigzag(&candles[..10], 14, 2.0).is_empty());
+}
+
+#[test]
+fn nothing_is_emitted_while_seeding() {
+    // A full reversal inside the seed window is not reported as a swing.
+    let candles = vec![
+        candle(0, 100.0, 100.2, 99.9, 100.1),
+        candle(1, 100.1, 102.0, 100.0, 101.8),
+        candle(2, 101.8, 101.9, 99.0, 99.2),
+    ];
+    let mut detector = SwingDetector::new(1.0);
+    assert!(swings(&mut detector, &candles).is_empty());
+    assert_eq!(detector.trend(), None);
+}
+
+#[test]
+fn commits_only_after_the_seed_window_and_a_full_reversal() {
+    let flat: Vec<Candle> = (0..6)
+        .map(|i| candle(i, 100.0, 100.3, 99.8, 100.0))
+        .collect();
+    let mut detector = SwingDetector::new(1.0).with_seed_candles(3);
+    swings(&mut detector, &flat);
+    assert_eq!(
+        detector.trend(),
+        None,
+        "range never reached the reversal size"
+    );
+
+    detector.update(&candle(6, 99.8, 99.8, 99.0, 99.1), 1.0);
+    assert_eq!(detector.trend(), Some(Trend::Down));
+}
+
+#[test]
+fn alternates_peaks_and_troughs_once_trending() {
+    let closes = path(100.0, &[(6, 0.5), (6, -0.5), (6, 0.5), (6, -0.5)]);
+    let candles = candles_from_closes(100.0, &closes, 0.1);
+    let mut detector = SwingDetector::new(1.0);
+
+    let kinds: Vec<bool> = swings(&mut detector, &candles)
+        .into_iter()
+        .map(|(_, p)| p.is_peak)
+        .collect();
+    assert_eq!(kinds, vec![true, false, true]);
+}
+
+#[test]
+fn atr_is_none_until_the_period_fills_then_wilder_smoothed() {
+    let mut atr = AtrCalculator::new(3);
+    // True ranges: 2 (high-low), 3 (gap up to 13 from prev close 10), 1.
+    assert_eq!(atr.update(&candle(0, 9.0, 11.0, 9.0, 10.0)), None);
+    assert_eq!(atr.update(&candle(1, 11.0, 13.0, 12.0, 12.5)), None);
+    assert_eq!(atr.update(&candle(2, 12.5, 13.0, 12.0, 12.5)), Some(2.0));
+    // (2 * 2 + 4) / 3 with a true range of 4 (high 16.5 - prev close 12.5).
+    let next = atr.update(&candle(3, 14.0, 16.5, 14.0, 16.0)).unwrap();
+    assert!((next - 8.0 / 3.0).abs() < 1e-12);
+    assert_eq!(atr.value(), Some(next));
+}
+
+#[cfg(feature = "server")]
+mod endpoint {
+    use axum::http::StatusCode;
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use perpscreener::services::hyperliquid::HyperliquidClient;
+    use serde_json::{json, Value};
+
+    use super::common::{self, ManualClock, MINUTE_MS, T0};
+    use super::path;
+
+    /// Stub `info` endpoint serving 1m BTC candles that rally 24 candles from
+    /// `startTime` and then fall; any other coin is an upstream error.
+    async fn spawn_hyperliquid() -> String {
+        let app = Router::new().route(
+            "/info",
+            post(|Json(body): Json<Value>| async move {
+                if body["req"]["coin"] != "BTC" {
+                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                }
+                let start = body["req"]["startTime"].as_u64().unwrap();
+                let mut open = 100.0;
+                let candles: Vec<Value> = path(100.0, &[(24, 0.5), (16, -0.5)])
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, close)| {
+                        let t = start + i as u64 * MINUTE_MS;
+                        let candle = json!({
+                            "t": t, "T": t + MINUTE_MS - 1, "s": "BTC", "i": "1m",
+                            "o": open.to_string(),
+                            "h": (f64::max(open, close) + 0.1).to_string(),
+                            "l": (f64::min(open, close) - 0.1).to_string(),
+                            "c": close.to_string(), "v": "1.0", "n": 1
+                        });
+                        open = close;
+                        candle
+                    })
+                    .collect();
+                Ok(Json(Value::from(candles)))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn serves_the_zigzag_over_fetched_candles() {
+        let url = spawn_hyperliquid().await;
+        // Every stub candle has closed by now.
+        let clock = ManualClock::new(T0 + 100 * MINUTE_MS);
+        let state =
+            common::state_with_clock(clock).with_hyperliquid(HyperliquidClient::with_base_url(url));
+        let app = perpscreener::app(state);
+
+        let (status, body) =
+            common::get(app.clone(), "/swings?coin=BTC&interval=1m&rev_atr=2").await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        assert_eq!(body["coin"], "BTC");
+        assert_eq!(body["candles"], 40);
+        let swings = body["swings"].as_array().unwrap();
+        assert_eq!(swings.len(), 1, "{body}");
+        assert_eq!(swings[0]["is_peak"], true);
+        assert_eq!(swings[0]["price"], 112.1);
+        assert!(swings[0]["atr"].as_f64().unwrap() > 0.0);
+
+        let (status, _) = common::get(app.clone(), "/swings?coin=ETH").await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+
+        for uri in [
+            "/swings?coin=BTC&interval=7m",
+            "/swings?coin=BTC&limit=0",
+            "/swings?coin=BTC&limit=5001",
+            "/swings?coin=BTC&rev_atr=0",
+        ] {
+            let (status, _) = common::get(app.clone(), uri).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+        }
+    }
+
+    #[tokio::test]
+    async fn limits_past_one_snapshot_are_paged_in() {
+        let closes: Vec<f64> = (0..1_200).map(|i| 100.0 + (i % 40) as f64).collect();
+        let url = common::spawn_candle_server(vec![(
+            "BTC",
+            common::candles_from_closes(100.0, &closes, 0.5),
+        )])
+        .await;
+        let clock = ManualClock::new(T0 + 1_200 * MINUTE_MS);
+        let state =
+            common::state_with_clock(clock).with_hyperliquid(HyperliquidClient::with_base_url(url));
+
+        let (status, body) = common::get(
+            perpscreener::app(state),
+            "/swings?coin=BTC&interval=1m&limit=1200",
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        assert_eq!(body["candles"], 1_200);
+    }
+}
@@ -0,0 +1,71 @@
+use perpscreener::business_logic::trade_plan::{DoubleTopLevels, StopRule, TradePlan};
+
+fn levels() -> DoubleTopLevels {
+    DoubleTopLevels {
+        break_price: 99.0,
+        neckline: 100.0,
+        peak2: 110.0,
+        peak_fail: 108.0,
+        atr: Some(2.0),
+    }
+}
+
+#[test]
+fn stop_above_peak_is_padded_by_atr() {
+    let plan =
+        TradePlan::for_double_top(&levels(), StopRule::AbovePeak { atr_buffer: 0.5 }).unwrap();
+    assert_eq!(plan.entry, 99.0);
+    assert_eq!(plan.stop, 111.0);
+    assert_eq!(plan.target, 90.0);
+    assert_eq!(plan.risk_reward, 9.0 / 12.0);
+}
+
+#[test]
+fn stop_at_peak_fail_level() {
+    let plan = TradePlan::for_double_top(&levels(), StopRule::PeakFail).unwrap();
+    assert_eq!(plan.stop, 108.0);
+    assert_eq!(plan.risk_reward, 1.0);
+}
+
+#[test]
+fn missing_atr_pads_nothing() {
+    let plan = TradePlan::for_double_top(
+        &DoubleTopLevels {
+            atr: None,
+            ..levels()
+        },
+        StopRule::default(),
+    )
+    .unwrap();
+    assert_eq!(plan.stop, 110.0);
+}
+
+#[test]
+fn degenerate_levels_give_no_plan() {
+    // No pattern height.
+    let flat = DoubleTopLevels {
+        peak2: 100.0,
+        ..levels()
+    };
+    assert_eq!(TradePlan::for_double_top(&flat, StopRule::default()), None);
+
+    // Break already below the measured-move target.
+    let overshot = DoubleTopLevels {
+        break_price: 89.0,
+        ..levels()
+    };
+    assert_eq!(
+        TradePlan::for_double_top(&overshot, StopRule::PeakFail),
+        None
+    );
+
+    // Fail level at or below entry means no risk to measure against.
+    let no_risk = DoubleTopLevels {
+        peak_fail: 99.0,
+        ..levels()
+    };
+    assert_eq!(
+        TradePlan::for_double_top(&no_risk, StopRule::PeakFail),
+        None
+    );
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use common::candle;
+use perpscreener::business_logic::transforms::heikin_ashi;
+use perpscreener::models::candle::Candle;
+
+#[test]
+fn first_candle_opens_at_the_midpoint_of_its_raw_body() {
+    let raw = Candle {
+        volume: 7.5,
+        num_trades: 42,
+        ..candle(0, 10.0, 13.0, 9.0, 12.0)
+    };
+    let ha = heikin_ashi(&[raw]);
+    assert_eq!(ha.len(), 1);
+    assert_eq!(ha[0].open, 11.0);
+    assert_eq!(ha[0].close, 11.0);
+    assert_eq!((ha[0].high, ha[0].low), (13.0, 9.0));
+    assert_eq!(ha[0].open_time, raw.open_time);
+    assert_eq!(ha[0].close_time, raw.close_time);
+    assert_eq!((ha[0].volume, ha[0].num_trades), (7.5, 42));
+}
+
+#[test]
+fn later_opens_chain_from_the_previous_heikin_ashi_body() {
+    let raw = [
+        candle(0, 10.0, 13.0, 9.0, 12.0),
+        candle(1, 12.0, 14.0, 11.0, 13.0),
+        candle(2, 13.0, 13.5, 8.0, 8.5),
+    ];
+    let ha = heikin_ashi(&raw);
+    let ohlc: Vec<_> = ha
+        .iter()
+        .map(|c| (c.open, c.high, c.low, c.close))
+        .collect();
+    assert_eq!(
+        ohlc,
+        [
+            (11.0, 13.0, 9.0, 11.0),
+            // open (11 + 11) / 2, close (12 + 14 + 11 + 13) / 4.
+            (11.0, 14.0, 11.0, 12.5),
+            // open (11 + 12.5) / 2, close (13 + 13.5 + 8 + 8.5) / 4.
+            (11.75, 13.5, 8.0, 10.75),
+        ]
+    );
+
+    let gap = heikin_ashi(&[
+        candle(0, 10.0, 10.5, 9.5, 10.0),
+        candle(1, 20.0, 20.5, 19.5, 20.0),
+    ]);
+    assert_eq!(gap[1].open, 10.0);
+    assert_eq!(gap[1].low, 10.0, "low reaches down to the open");
+    assert!(heikin_ashi(&[]).is_empty());
+}
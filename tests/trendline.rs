@@ -0,0 +1,90 @@
+mod common;
+
+use common::candles_from_closes;
+use perpscreener::business_logic::alerts::{AlertStage, PatternKind};
+use perpscreener::business_logic::trendline::{TrendlineConfig, TrendlineDetector};
+use perpscreener::models::candle::Candle;
+
+fn path(start: f64, steps: &[(usize, f64)]) -> Vec<f64> {
+    let mut closes = Vec::new();
+    let mut price = start;
+    for &(count, step) in steps {
+        for _ in 0..count {
+            price += step;
+            closes.push(price);
+        }
+    }
+    closes
+}
+
+/// Steady 0.5 moves make every candle a full ATR wide; a 2 ATR reversal keeps
+/// swings to the real turns.
+fn config() -> TrendlineConfig {
+    TrendlineConfig {
+        rev_atr: 2.0,
+        ..TrendlineConfig::default()
+    }
+}
+
+/// A rally from 95 to 104 followed by `pullbacks` higher lows, each one
+/// point above the last, then a slide to well below the line.
+fn higher_lows(pullbacks: usize) -> Vec<Candle> {
+    let mut steps = vec![(18, 0.5)];
+    for _ in 0..pullbacks {
+        steps.extend_from_slice(&[(6, -0.5), (8, 0.5)]);
+    }
+    steps.push((24, -0.5));
+    candles_from_closes(95.0, &path(95.0, &steps), 0.1)
+}
+
+#[test]
+fn three_touch_uptrend_line_breaks() {
+    let candles = higher_lows(3);
+    let mut detector = TrendlineDetector::new("BTC", config());
+
+    let mut alerts = Vec::new();
+    let mut status = None;
+    for candle in &candles {
+        if let Some(alert) = detector.update(candle) {
+            alerts.push(alert);
+        }
+        status = status.or(detector.uptrend());
+    }
+
+    let status = status.expect("a qualifying uptrend line");
+    assert_eq!(status.touches, 3);
+    assert!((status.slope - 1.0 / 14.0).abs() < 1e-9, "{status:?}");
+    assert!(status.r_squared > 0.999);
+
+    assert_eq!(alerts.len(), 1, "{alerts:?}");
+    assert_eq!(alerts[0].pattern, PatternKind::TrendlineBreakDown);
+    assert_eq!(alerts[0].stage, AlertStage::Confirmation);
+    assert!(alerts[0].price < alerts[0].level);
+    assert_eq!(detector.uptrend(), None);
+}
+
+#[test]
+fn two_touch_line_does_not_qualify() {
+    let candles = higher_lows(2);
+    let mut detector = TrendlineDetector::new("BTC", config());
+
+    let alerts: Vec<_> = candles.iter().filter_map(|c| detector.update(c)).collect();
+    assert!(alerts.is_empty(), "{alerts:?}");
+    assert_eq!(detector.uptrend(), None);
+}
+
+#[test]
+fn downtrend_line_breaks_upward() {
+    let mut steps = vec![(18, -0.5)];
+    for _ in 0..3 {
+        steps.extend_from_slice(&[(6, 0.5), (8, -0.5)]);
+    }
+    steps.push((24, 0.5));
+    let candles = candles_from_closes(105.0, &path(105.0, &steps), 0.1);
+    let mut detector = TrendlineDetector::new("BTC", config());
+
+    let alerts: Vec<_> = candles.iter().filter_map(|c| detector.update(c)).collect();
+    assert_eq!(alerts.len(), 1, "{alerts:?}");
+    assert_eq!(alerts[0].pattern, PatternKind::TrendlineBreakUp);
+    assert!(alerts[0].price > alerts[0].level);
+}
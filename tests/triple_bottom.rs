@@ -0,0 +1,90 @@
+mod common;
+
+use common::candles_from_closes;
+use perpscreener::business_logic::alerts::{AlertStage, PatternKind};
+use perpscreener::business_logic::triple_bottom::{
+    TripleBottomConfig, TripleBottomDetector, TripleBottomState,
+};
+use perpscreener::models::candle::Candle;
+
+fn path(start: f64, steps: &[(usize, f64)]) -> Vec<f64> {
+    let mut closes = Vec::new();
+    let mut price = start;
+    for &(count, step) in steps {
+        for _ in 0..count {
+            price += step;
+            closes.push(price);
+        }
+    }
+    closes
+}
+
+/// Steady 0.5 moves make every candle a full ATR wide; a 2 ATR reversal keeps
+/// swings to the real turns.
+fn config() -> TripleBottomConfig {
+    TripleBottomConfig {
+        rev_atr: 2.0,
+        ..TripleBottomConfig::default()
+    }
+}
+
+/// Trough at 90, rally to 94, a second trough undercutting to 89.5, rally to
+/// 93.5, then `rest`.
+fn two_troughs_then(rest: &[(usize, f64)]) -> Vec<Candle> {
+    let mut steps = vec![(20, -0.5), (8, 0.5), (9, -0.5), (8, 0.5)];
+    steps.extend_from_slice(rest);
+    candles_from_closes(100.0, &path(100.0, &steps), 0.1)
+}
+
+fn run(detector: &mut TripleBottomDetector, candles: &[Candle]) -> Vec<TripleBottomState> {
+    candles
+        .iter()
+        .map(|c| {
+            detector.update(c);
+            detector.state()
+        })
+        .collect()
+}
+
+#[test]
+fn undercutting_troughs_still_confirm_above_resistance() {
+    let candles = two_troughs_then(&[(7, -0.5), (14, 0.5)]);
+    let mut detector = TripleBottomDetector::new("DOGE", config());
+
+    let alerts: Vec<_> = candles.iter().filter_map(|c| detector.update(c)).collect();
+    assert_eq!(alerts.len(), 2, "{alerts:?}");
+    assert!(alerts
+        .iter()
+        .all(|a| a.pattern == PatternKind::TripleBottom));
+    assert_eq!(alerts[0].stage, AlertStage::EarlyWarning);
+    assert_eq!(alerts[1].stage, AlertStage::Confirmation);
+
+    assert_eq!(detector.state(), TripleBottomState::Confirmed);
+    assert!((detector.trough1_price().unwrap() - 89.9).abs() < 1e-9);
+    assert!((detector.trough2_price().unwrap() - 89.4).abs() < 1e-9);
+    assert!((detector.trough3_price().unwrap() - 89.9).abs() < 1e-9);
+    // Highest of the two intermediate rallies.
+    assert!((detector.resistance_price().unwrap() - 94.1).abs() < 1e-9);
+    assert!((alerts[1].level - 94.1).abs() < 1e-9);
+}
+
+#[test]
+fn break_below_the_fail_level_invalidates() {
+    let candles = two_troughs_then(&[(16, -0.5), (20, 0.5)]);
+    let mut detector = TripleBottomDetector::new("DOGE", config());
+
+    let states = run(&mut detector, &candles);
+    assert!(states.contains(&TripleBottomState::DoubleForming));
+    assert!(states.contains(&TripleBottomState::Invalidated));
+    assert!(!states.contains(&TripleBottomState::Confirmed));
+}
+
+#[test]
+fn breakout_after_two_troughs_is_left_to_the_double_bottom() {
+    let candles = two_troughs_then(&[(2, -0.5), (10, 0.5)]);
+    let mut detector = TripleBottomDetector::new("DOGE", config());
+
+    let states = run(&mut detector, &candles);
+    assert!(states.contains(&TripleBottomState::DoubleForming));
+    assert!(!states.contains(&TripleBottomState::Confirmed));
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use common::candles_from_closes;
+use perpscreener::business_logic::alerts::{AlertStage, PatternKind};
+use perpscreener::business_logic::triple_top::{
+    TripleTopConfig, TripleTopDetector, TripleTopState,
+};
+use perpscreener::models::candle::Candle;
+
+fn path(start: f64, steps: &[(usize, f64)]) -> Vec<f64> {
+    let mut closes = Vec::new();
+    let mut price = start;
+    for &(count, step) in steps {
+        for _ in 0..count {
+            price += step;
+            closes.push(price);
+        }
+    }
+    closes
+}
+
+/// Steady 0.5 moves make every candle a full ATR wide; a 2 ATR reversal keeps
+/// swings to the real turns.
+fn config() -> TripleTopConfig {
+    TripleTopConfig {
+        rev_atr: 2.0,
+        ..TripleTopConfig::default()
+    }
+}
+
+/// Peaks at 98 with pullbacks to 94, then `rest`.
+fn two_peaks_then(rest: &[(usize, f64)]) -> Vec<Candle> {
+    let mut steps = vec![(20, 0.5), (8, -0.5), (8, 0.5), (8, -0.5)];
+    steps.extend_from_slice(rest);
+    candles_from_closes(88.0, &path(88.0, &steps), 0.1)
+}
+
+fn run(detector: &mut TripleTopDetector, candles: &[Candle]) -> Vec<TripleTopState> {
+    candles
+        .iter()
+        .map(|c| {
+            detector.update(c);
+            detector.state()
+        })
+        .collect()
+}
+
+#[test]
+fn third_peak_warns_and_neckline_break_confirms() {
+    let candles = two_peaks_then(&[(8, 0.5), (12, -0.5)]);
+    let mut detector = TripleTopDetector::new("SOL", config());
+
+    let alerts: Vec<_> = candles.iter().filter_map(|c| detector.update(c)).collect();
+    assert_eq!(alerts.len(), 2, "{alerts:?}");
+    assert!(alerts.iter().all(|a| a.pattern == PatternKind::TripleTop));
+    assert_eq!(alerts[0].stage, AlertStage::EarlyWarning);
+    assert_eq!(alerts[1].stage, AlertStage::Confirmation);
+    assert!((alerts[1].level - 93.9).abs() < 1e-9);
+
+    assert_eq!(detector.state(), TripleTopState::Confirmed);
+    for peak in [
+        detector.peak1_price(),
+        detector.peak2_price(),
+        detector.peak3_price(),
+    ] {
+        assert!((peak.unwrap() - 98.1).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn third_peak_above_the_fail_level_invalidates() {
+    // Third rally runs to 100, past 98.1 * 1.015.
+    let candles = two_peaks_then(&[(12, 0.5), (16, -0.5)]);
+    let mut detector = TripleTopDetector::new("SOL", config());
+
+    let states = run(&mut detector, &candles);
+    assert!(states.contains(&TripleTopState::DoubleForming));
+    assert!(states.contains(&TripleTopState::Invalidated));
+    assert!(!states.contains(&TripleTopState::TripleForming));
+    assert!(!states.contains(&TripleTopState::Confirmed));
+}
+
+#[test]
+fn break_after_two_peaks_is_left_to_the_double_top() {
+    let candles = two_peaks_then(&[(3, 0.5), (10, -0.5)]);
+    let mut detector = TripleTopDetector::new("SOL", config());
+
+    let states = run(&mut detector, &candles);
+    assert!(states.contains(&TripleTopState::DoubleForming));
+    assert!(!states.contains(&TripleTopState::Confirmed));
+    assert_eq!(TripleTopState::TripleForming.as_str(), "TRIPLE_FORMING");
+}
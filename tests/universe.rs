@@ -0,0 +1,152 @@
+#![cfg(feature = "server")]
+
+use std::collections::HashMap;
+
+use axum::routing::post;
+use axum::{Json, Router};
+use perpscreener::services::hyperliquid::{HyperliquidClient, PerpMeta};
+use perpscreener::services::universe::{
+    self, select, UniverseChange, UniverseFilter, UniverseRefresher,
+};
+use perpscreener::state::MonitoredCoins;
+use serde_json::{json, Value};
+
+fn perp(name: &str, only_isolated: bool, is_delisted: bool) -> PerpMeta {
+    PerpMeta {
+        name: name.to_string(),
+        sz_decimals: 2,
+        max_leverage: 10,
+        only_isolated,
+        is_delisted,
+    }
+}
+
+fn names(coins: &[&str]) -> Vec<String> {
+    coins.iter().map(|c| c.to_string()).collect()
+}
+
+#[test]
+fn select_skips_delisted_and_isolated_only_perps() {
+    let meta = [
+        perp("BTC", false, false),
+        perp("OLD", false, true),
+        perp("ISO", true, false),
+        perp("ETH", false, false),
+    ];
+    let none = HashMap::new();
+    assert_eq!(
+        select(&meta, &none, &UniverseFilter::default()),
+        ["BTC", "ETH"]
+    );
+
+    let keep_isolated = UniverseFilter {
+        exclude_isolated_only: false,
+        ..UniverseFilter::default()
+    };
+    assert_eq!(select(&meta, &none, &keep_isolated), ["BTC", "ISO", "ETH"]);
+}
+
+#[test]
+fn select_applies_the_volume_floor() {
+    let meta = [
+        perp("BTC", false, false),
+        perp("ETH", false, false),
+        perp("NEW", false, false),
+    ];
+    let volumes = HashMap::from([("BTC".to_string(), 5e9), ("ETH".to_string(), 5e5)]);
+    let filter = UniverseFilter {
+        min_day_volume: Some(1e6),
+        ..UniverseFilter::default()
+    };
+    // NEW has no volume yet, so it is left out too.
+    assert_eq!(select(&meta, &volumes, &filter), ["BTC"]);
+}
+
+#[test]
+fn change_lists_new_listings_and_delistings() {
+    let change = UniverseChange::between(
+        &names(&["BTC", "ETH", "OLD"]),
+        &names(&["NEW", "BTC", "ETH"]),
+    );
+    assert_eq!(change.added, ["NEW"]);
+    assert_eq!(change.removed, ["OLD"]);
+    assert!(UniverseChange::between(&names(&["BTC"]), &names(&["BTC"])).is_empty());
+}
+
+/// Stub `meta` and `metaAndAssetCtxs` with three perps: BTC, a delisted OLD
+/// and a low-volume ETH.
+async fn spawn_hyperliquid() -> String {
+    let app = Router::new().route(
+        "/info",
+        post(|Json(body): Json<Value>| async move {
+            let universe = json!([
+                { "name": "BTC", "szDecimals": 5, "maxLeverage": 40 },
+                { "name": "OLD", "szDecimals": 0, "maxLeverage": 3, "isDelisted": true },
+                { "name": "ETH", "szDecimals": 4, "maxLeverage": 25, "onlyIsolated": false }
+            ]);
+            let context = |volume: &str| {
+                json!({
+                    "dayBaseVlm": volume, "dayNtlVlm": volume, "funding": "0.0", "markPx": "1.0", "midPx": "1.0",
+                    "openInterest": "1.0", "oraclePx": "1.0", "prevDayPx": "1.0"
+                })
+            };
+            match body["type"].as_str() {
+                Some("meta") => Json(json!({ "universe": universe })),
+                Some("metaAndAssetCtxs") => Json(json!([
+                    { "universe": universe },
+                    [context("2000000.0"), context("0.0"), context("1000.0")]
+                ])),
+                other => panic!("unexpected request {other:?}"),
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn fetch_meta_parses_the_universe() {
+    let client = HyperliquidClient::with_base_url(spawn_hyperliquid().await);
+    let meta = client.fetch_meta().await.unwrap();
+    assert_eq!(meta.len(), 3);
+    assert_eq!(meta[0].name, "BTC");
+    assert_eq!(meta[0].max_leverage, 40);
+    assert!(!meta[0].only_isolated);
+    assert!(meta[1].is_delisted);
+}
+
+#[tokio::test]
+async fn discover_filters_the_fetched_universe() {
+    let client = HyperliquidClient::with_base_url(spawn_hyperliquid().await);
+    let all = universe::discover(&client, &UniverseFilter::default())
+        .await
+        .unwrap();
+    assert_eq!(all, ["BTC", "ETH"]);
+
+    let liquid = UniverseFilter {
+        min_day_volume: Some(1e6),
+        ..UniverseFilter::default()
+    };
+    assert_eq!(universe::discover(&client, &liquid).await.unwrap(), ["BTC"]);
+}
+
+#[tokio::test]
+async fn refresh_swaps_in_the_rediscovered_universe() {
+    let coins = MonitoredCoins::default();
+    coins.set(names(&["BTC", "OLD"]));
+    let refresher = UniverseRefresher::new(
+        HyperliquidClient::with_base_url(spawn_hyperliquid().await),
+        UniverseFilter::default(),
+        coins.clone(),
+    );
+
+    let change = refresher.refresh().await.unwrap();
+    assert_eq!(change.added, ["ETH"]);
+    assert_eq!(change.removed, ["OLD"]);
+    assert_eq!(coins.get(), ["BTC", "ETH"]);
+
+    assert!(refresher.refresh().await.unwrap().is_empty());
+    assert_eq!(coins.get(), ["BTC", "ETH"]);
+}
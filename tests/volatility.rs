@@ -0,0 +1,194 @@
+mod common;
+
+use perpscreener::business_logic::volatility::{
+    percentile_rank, rank, volatility_entry, RegimeConfig, RegimeTracker, VolatilityRegime,
+};
+use perpscreener::models::candle::Candle;
+
+const HOUR_MS: u64 = 3_600_000;
+
+/// Hourly candles closing at 100 with the given high-low ranges.
+fn hourly(ranges: &[f64]) -> Vec<Candle> {
+    ranges
+        .iter()
+        .enumerate()
+        .map(|(i, &range)| Candle {
+            open_time: common::T0 + i as u64 * HOUR_MS,
+            close_time: common::T0 + (i as u64 + 1) * HOUR_MS - 1,
+            ..common::candle(0, 100.0, 100.0 + range / 2.0, 100.0 - range / 2.0, 100.0)
+        })
+        .collect()
+}
+
+#[test]
+fn reports_atr_as_a_percentage_of_the_close() {
+    let entry = volatility_entry("BTC", &hourly(&[2.0; 3]), 3).unwrap();
+    assert_eq!(entry.coin, "BTC");
+    assert_eq!(entry.atr, 2.0);
+    assert_eq!(entry.atr_pct, 2.0);
+    assert_eq!(entry.atr_pct_change_24h, None);
+
+    assert_eq!(volatility_entry("BTC", &hourly(&[2.0; 2]), 3), None);
+}
+
+#[test]
+fn compares_against_the_value_a_day_earlier() {
+    let mut ranges = vec![2.0; 27];
+    ranges.push(6.0);
+    let entry = volatility_entry("ETH", &hourly(&ranges), 2).unwrap();
+    // Wilder: (2 * 1 + 6) / 2.
+    assert_eq!(entry.atr, 4.0);
+    assert!((entry.atr_pct_change_24h.unwrap() - 2.0).abs() < 1e-9);
+}
+
+#[test]
+fn ranks_most_volatile_first() {
+    let entries = vec![
+        volatility_entry("A", &hourly(&[1.0; 3]), 3).unwrap(),
+        volatility_entry("B", &hourly(&[5.0; 3]), 3).unwrap(),
+        volatility_entry("C", &hourly(&[3.0; 3]), 3).unwrap(),
+    ];
+    let coins: Vec<String> = rank(entries).into_iter().map(|e| e.coin).collect();
+    assert_eq!(coins, ["B", "C", "A"]);
+}
+
+#[cfg(feature = "server")]
+#[tokio::test]
+async fn monitor_publishes_the_ranking_each_new_candle() {
+    use axum::http::StatusCode;
+    use perpscreener::services::hyperliquid::HyperliquidClient;
+    use perpscreener::services::monitor::MarketMonitor;
+    use perpscreener::settings::Settings;
+
+    let minutes = |range: f64| -> Vec<Candle> {
+        (0..30)
+            .map(|i| common::candle(i, 100.0, 100.0 + range / 2.0, 100.0 - range / 2.0, 100.0))
+            .collect()
+    };
+    let base_url = common::spawn_candle_server(vec![
+        ("A", minutes(1.0)),
+        ("B", minutes(5.0)),
+        ("C", minutes(9.0)),
+    ])
+    .await;
+    let settings = Settings::from_toml(
+        "[volatility]\ninterval = \"1m\"\nperiod = 14\n",
+        std::path::Path::new("test.toml"),
+    )
+    .unwrap();
+    let clock = common::ManualClock::new(common::T0 + 20 * common::MINUTE_MS);
+    let state = common::state_with_clock(clock.clone())
+        .with_settings(settings)
+        .with_hyperliquid(HyperliquidClient::with_base_url(base_url))
+        .with_monitored_coins(["A", "B", "DOGE"].map(String::from).to_vec());
+    let app = perpscreener::app(state.clone());
+
+    let (status, body) = common::get(app.clone(), "/volatility-ranking").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["computed_at_ms"], serde_json::Value::Null);
+    assert_eq!(body["interval"], "1m");
+
+    let mut monitor = MarketMonitor::new(state.clone(), "1m");
+    monitor.run_cycle().await;
+    let (status, body) =
+        common::get(app.clone(), "/volatility-ranking?interval=1m&period=14").await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let computed_at = body["computed_at_ms"].as_u64().unwrap();
+    assert_eq!(computed_at, common::T0 + 20 * common::MINUTE_MS);
+    let coins: Vec<&str> = body["coins"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["coin"].as_str().unwrap())
+        .collect();
+    assert_eq!(coins, ["B", "A"]);
+    assert!((body["coins"][0]["atr_pct"].as_f64().unwrap() - 5.0).abs() < 1e-9);
+
+    // Not rebuilt until a new candle closes or the coins change.
+    clock.advance(10_000);
+    monitor.run_cycle().await;
+    let (_, body) = common::get(app.clone(), "/volatility-ranking").await;
+    assert_eq!(body["computed_at_ms"], computed_at);
+    state.coins.set(vec!["C".to_string()]);
+    monitor.run_cycle().await;
+    let (_, body) = common::get(app.clone(), "/volatility-ranking").await;
+    assert_eq!(body["coins"][0]["coin"], "C");
+    assert_eq!(body["computed_at_ms"], computed_at + 10_000);
+
+    for uri in [
+        "/volatility-ranking?interval=7m",
+        "/volatility-ranking?interval=1h",
+        "/volatility-ranking?period=0",
+        "/volatility-ranking?period=1001",
+        "/volatility-ranking?period=20",
+    ] {
+        let (status, _) = common::get(app.clone(), uri).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+    }
+}
+
+#[cfg(feature = "server")]
+#[tokio::test]
+async fn a_full_day_of_minute_candles_gives_the_24h_change() {
+    use perpscreener::services::hyperliquid::HyperliquidClient;
+    use perpscreener::services::volatility;
+
+    // 1m candles over a day and a bit: range 1 until the last hour, then 3.
+    let candles: Vec<Candle> = (0..1_500)
+        .map(|i| {
+            let range = if i >= 1_440 { 3.0 } else { 1.0 };
+            common::candle(i, 100.0, 100.0 + range / 2.0, 100.0 - range / 2.0, 100.0)
+        })
+        .collect();
+    let base_url = common::spawn_candle_server(vec![("BTC", candles)]).await;
+    let client = HyperliquidClient::with_base_url(base_url);
+    let now_ms = common::T0 + 1_500 * common::MINUTE_MS;
+
+    let entries = volatility::compute(&client, &["BTC".to_string()], "1m", 14, now_ms).await;
+    let change = entries[0].atr_pct_change_24h.expect("a day of candles");
+    // Wilder's ATR has nearly caught up with the wider range after an hour.
+    let expected = 2.0 - 2.0 * (13.0_f64 / 14.0).powi(60);
+    assert!((change - expected).abs() < 1e-9, "{change}");
+}
+
+#[test]
+fn regime_boundaries_are_inclusive_at_the_percentiles() {
+    let config = RegimeConfig::default();
+    let values: Vec<f64> = (1..=10).map(f64::from).collect();
+    // 2 of 10 values below 3, 3 below 4; 8 below 9, 7 below 8.
+    assert_eq!(percentile_rank(values.iter().copied(), 3.0), 20.0);
+    assert_eq!(config.classify(20.0), VolatilityRegime::Low);
+    assert_eq!(
+        config.classify(percentile_rank(values.iter().copied(), 4.0)),
+        VolatilityRegime::Normal
+    );
+    assert_eq!(percentile_rank(values.iter().copied(), 9.0), 80.0);
+    assert_eq!(config.classify(80.0), VolatilityRegime::High);
+    assert_eq!(
+        config.classify(percentile_rank(values.iter().copied(), 8.0)),
+        VolatilityRegime::Normal
+    );
+    assert_eq!(percentile_rank([], 1.0), 0.0);
+}
+
+#[test]
+fn regime_tracker_ranks_the_latest_atr_pct_within_its_window() {
+    // A 1-period ATR at a constant close of 100 makes ATR% the candle's range.
+    let mut tracker = RegimeTracker::new(RegimeConfig {
+        atr_period: 1,
+        window: 10,
+        ..RegimeConfig::default()
+    });
+    let ranges: Vec<f64> = (1..=10).map(f64::from).collect();
+    let regimes: Vec<_> = hourly(&ranges).iter().map(|c| tracker.update(c)).collect();
+    assert!(regimes[..9].iter().all(Option::is_none));
+    assert_eq!(regimes[9], Some(VolatilityRegime::High));
+    assert_eq!(tracker.atr_pct(), Some(10.0));
+
+    // The window slides: [2..=10, 3] then [3..=10, 3, 5].
+    let more = hourly(&[3.0, 5.0]);
+    assert_eq!(tracker.update(&more[0]), Some(VolatilityRegime::Low));
+    assert_eq!(tracker.update(&more[1]), Some(VolatilityRegime::Normal));
+    assert_eq!(tracker.regime(), Some(VolatilityRegime::Normal));
+    assert_eq!(VolatilityRegime::Normal.as_str(), "NORMAL");
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use perpscreener::business_logic::volume::{CandleDirection, VolumeMonitor, VolumeSpikeConfig};
+use perpscreener::models::candle::Candle;
+
+fn with_volume(index: u64, open: f64, close: f64, volume: f64) -> Candle {
+    Candle {
+        volume,
+        ..common::candle(index, open, open.max(close), open.min(close), close)
+    }
+}
+
+fn monitor() -> VolumeMonitor {
+    VolumeMonitor::new(
+        "BTC",
+        VolumeSpikeConfig {
+            window: 4,
+            multiple: 3.0,
+            min_stddevs: None,
+            ..VolumeSpikeConfig::default()
+        },
+    )
+}
+
+#[test]
+fn flags_volume_above_the_multiple_of_the_prior_average() {
+    let mut monitor = monitor();
+    for i in 0..4 {
+        assert_eq!(monitor.update(&with_volume(i, 100.0, 100.0, 10.0)), None);
+    }
+    assert_eq!(monitor.average(), Some(10.0));
+
+    let spike = monitor
+        .update(&with_volume(4, 100.0, 98.0, 35.0))
+        .expect("3.5x the average");
+    assert_eq!(spike.coin, "BTC");
+    assert_eq!(spike.open_time, common::T0 + 4 * common::MINUTE_MS);
+    assert_eq!(spike.average_volume, 10.0);
+    assert_eq!(spike.multiple, 3.5);
+    assert!((spike.price_change_pct + 2.0).abs() < 1e-9);
+    assert_eq!(spike.direction, CandleDirection::Down);
+
+    // The spike joins the baseline: (10 + 10 + 10 + 35) / 4.
+    assert_eq!(monitor.average(), Some(16.25));
+    assert_eq!(monitor.update(&with_volume(5, 100.0, 101.0, 45.0)), None);
+}
+
+#[test]
+fn stays_quiet_until_the_window_fills() {
+    let mut monitor = monitor();
+    for i in 0..3 {
+        monitor.update(&with_volume(i, 100.0, 100.0, 1.0));
+    }
+    assert_eq!(monitor.average(), None);
+    assert_eq!(monitor.update(&with_volume(3, 100.0, 100.0, 1_000.0)), None);
+}
+
+#[test]
+fn never_realerts_for_the_same_candle() {
+    let mut monitor = monitor();
+    for i in 0..4 {
+        monitor.update(&with_volume(i, 100.0, 100.0, 10.0));
+    }
+    let spiking = with_volume(4, 100.0, 103.0, 50.0);
+    let spike = monitor.update(&spiking).unwrap();
+    assert_eq!(spike.direction, CandleDirection::Up);
+    assert_eq!(monitor.update(&spiking), None);
+    assert_eq!(monitor.update(&with_volume(2, 100.0, 100.0, 90.0)), None);
+}
+
+/// Window of 4 over volumes 5, 15, 5, 15: mean 10, stddev 5.
+fn noisy_monitor(min_stddevs: Option<f64>) -> VolumeMonitor {
+    let mut monitor = VolumeMonitor::new(
+        "BTC",
+        VolumeSpikeConfig {
+            window: 4,
+            multiple: 3.0,
+            min_stddevs,
+            ..VolumeSpikeConfig::default()
+        },
+    );
+    for (i, volume) in [5.0, 15.0, 5.0, 15.0].into_iter().enumerate() {
+        monitor.update(&with_volume(i as u64, 100.0, 100.0, volume));
+    }
+    monitor
+}
+
+#[test]
+fn stddev_rule_needs_a_bigger_candle_on_a_noisy_baseline() {
+    // 5 stddevs puts the bar at 35 against 3x's 30.
+    let mut gated = noisy_monitor(Some(5.0));
+    assert_eq!(gated.stddev(), Some(5.0));
+    assert_eq!(gated.update(&with_volume(4, 100.0, 101.0, 32.0)), None);
+    let mut gated = noisy_monitor(Some(5.0));
+    assert!(gated.update(&with_volume(4, 100.0, 101.0, 40.0)).is_some());
+
+    let mut ungated = noisy_monitor(None);
+    assert!(ungated
+        .update(&with_volume(4, 100.0, 101.0, 32.0))
+        .is_some());
+}
+
+#[test]
+fn stddev_rule_replaces_the_multiple() {
+    // 2 stddevs puts the bar at 20, under 3x's 30.
+    let mut gated = noisy_monitor(Some(2.0));
+    let spike = gated
+        .update(&with_volume(4, 100.0, 101.0, 25.0))
+        .expect("past mean + 2 stddevs");
+    assert_eq!(spike.multiple, 2.5);
+
+    let mut ungated = noisy_monitor(None);
+    assert_eq!(ungated.update(&with_volume(4, 100.0, 101.0, 25.0)), None);
+}
+
+#[test]
+fn stddev_rule_on_a_flat_window_uses_the_floor() {
+    let flat = || {
+        let mut monitor = VolumeMonitor::new(
+            "BTC",
+            VolumeSpikeConfig {
+                window: 4,
+                min_stddevs: Some(3.0),
+                min_stddev_pct: 10.0,
+                ..VolumeSpikeConfig::default()
+            },
+        );
+        for i in 0..4 {
+            monitor.update(&with_volume(i, 100.0, 100.0, 10.0));
+        }
+        monitor
+    };
+    assert_eq!(flat().stddev(), Some(0.0));
+    // A hair above the mean is not a spike; the floor puts the bar at 13.
+    assert_eq!(flat().update(&with_volume(4, 100.0, 101.0, 10.01)), None);
+    assert_eq!(flat().update(&with_volume(4, 100.0, 101.0, 12.9)), None);
+    assert!(flat().update(&with_volume(4, 100.0, 101.0, 13.5)).is_some());
+}
@@ -0,0 +1,342 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use axum::body::{Body, Bytes};
+use axum::http::{HeaderMap, Request, StatusCode};
+use axum::routing::post;
+use axum::Router;
+use common::ManualClock;
+use perpscreener::business_logic::alerts::AlertSeverity;
+use perpscreener::services::webhooks::{
+    sign_payload, NewWebhook, WebhookDispatcher, WebhookStore, SIGNATURE_HEADER,
+};
+use perpscreener::state::AppState;
+use serde_json::{json, Value};
+
+const NOW: u64 = 1_700_000_000_000;
+
+fn post_json(uri: &str, body: Value) -> Request<Body> {
+    Request::post(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn new_webhook(url: &str) -> NewWebhook {
+    NewWebhook {
+        url: url.to_string(),
+        coins: None,
+        min_severity: None,
+        secret: None,
+        quiet_hours: None,
+    }
+}
+
+#[tokio::test]
+async fn create_list_and_delete_subscription() {
+    let state = common::state_with_clock(ManualClock::new(NOW));
+
+    let (status, created) = common::send(
+        perpscreener::app(state.clone()),
+        post_json(
+            "/webhooks",
+            json!({
+                "url": "https://example.com/hook",
+                "coins": ["BTC", "ETH"],
+                "min_severity": "critical",
+                "secret": "s3cret"
+            }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(created["id"], 1);
+    assert_eq!(created["has_secret"], true);
+    assert_eq!(created["status"], "active");
+    assert_eq!(created["created_at_ms"], NOW);
+    assert!(created.get("secret").is_none());
+
+    let (status, list) = common::get(perpscreener::app(state.clone()), "/webhooks").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(list.as_array().unwrap().len(), 1);
+    assert_eq!(list[0]["coins"], json!(["BTC", "ETH"]));
+    assert_eq!(list[0]["min_severity"], "critical");
+
+    let delete = || Request::delete("/webhooks/1").body(Body::empty()).unwrap();
+    let (status, _) = common::send(perpscreener::app(state.clone()), delete()).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, body) = common::send(perpscreener::app(state), delete()).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["error"], "webhook 1 not found");
+}
+
+#[tokio::test]
+async fn rejects_invalid_subscriptions() {
+    let state = AppState::default();
+    for body in [
+        json!({ "url": "not a url" }),
+        json!({ "url": "ftp://example.com/hook" }),
+        json!({ "url": "https://example.com", "coins": [] }),
+        json!({ "url": "https://example.com", "coins": ["BTC", " "] }),
+        json!({ "url": "https://example.com", "secret": "" }),
+        json!({ "url": "https://example.com", "quiet_hours": { "start": "25:00", "end": "07:00" } }),
+        json!({
+            "url": "https://example.com",
+            "quiet_hours": { "start": "22:00", "end": "07:00", "timezone": "Mars/Olympus" }
+        }),
+    ] {
+        let (status, response) = common::send(
+            perpscreener::app(state.clone()),
+            post_json("/webhooks", body.clone()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "accepted {body}");
+        assert!(response["error"].is_string());
+    }
+    assert!(state.webhooks.list().is_empty());
+}
+
+#[test]
+fn subscriptions_survive_reopening_the_store() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("nested").join("webhooks.json");
+
+    let store = WebhookStore::open(&path).unwrap();
+    let first = store
+        .create(new_webhook("https://a.example/hook"), NOW)
+        .unwrap();
+    let second = store
+        .create(new_webhook("https://b.example/hook"), NOW)
+        .unwrap();
+    store
+        .record_delivery(second.id, Err("boom".to_string()))
+        .unwrap();
+    store.delete(first.id).unwrap();
+
+    let reopened = WebhookStore::open(&path).unwrap();
+    let subscriptions = reopened.list();
+    assert_eq!(subscriptions.len(), 1);
+    assert_eq!(subscriptions[0].url, "https://b.example/hook");
+    assert_eq!(subscriptions[0].consecutive_failures, 1);
+    assert_eq!(subscriptions[0].last_error.as_deref(), Some("boom"));
+
+    let third = reopened
+        .create(new_webhook("https://c.example/hook"), NOW)
+        .unwrap();
+    assert_eq!(third.id, 3);
+}
+
+#[test]
+fn deleted_ids_are_not_reused_after_reopening() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("webhooks.json");
+
+    let store = WebhookStore::open(&path).unwrap();
+    store
+        .create(new_webhook("https://a.example/hook"), NOW)
+        .unwrap();
+    let newest = store
+        .create(new_webhook("https://b.example/hook"), NOW)
+        .unwrap();
+    store.delete(newest.id).unwrap();
+    let next = store
+        .create(new_webhook("https://c.example/hook"), NOW)
+        .unwrap();
+    assert_eq!(next.id, newest.id + 1);
+    store.delete(next.id).unwrap();
+
+    let reopened = WebhookStore::open(&path).unwrap();
+    let after_reopen = reopened
+        .create(new_webhook("https://d.example/hook"), NOW)
+        .unwrap();
+    assert_eq!(after_reopen.id, next.id + 1);
+}
+
+#[test]
+fn legacy_array_store_file_still_loads() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("webhooks.json");
+    let store = WebhookStore::in_memory();
+    store
+        .create(new_webhook("https://a.example/hook"), NOW)
+        .unwrap();
+    store
+        .create(new_webhook("https://b.example/hook"), NOW)
+        .unwrap();
+    std::fs::write(&path, serde_json::to_vec(&store.list()).unwrap()).unwrap();
+
+    let reopened = WebhookStore::open(&path).unwrap();
+    assert_eq!(reopened.list().len(), 2);
+    let created = reopened
+        .create(new_webhook("https://c.example/hook"), NOW)
+        .unwrap();
+    assert_eq!(created.id, 3);
+}
+
+#[test]
+fn corrupt_store_file_is_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("webhooks.json");
+    std::fs::write(&path, "{ not json").unwrap();
+    assert!(WebhookStore::open(&path).is_err());
+}
+
+#[test]
+fn filters_apply_coin_and_severity() {
+    let store = WebhookStore::in_memory();
+    let all = store
+        .create(new_webhook("https://all.example"), NOW)
+        .unwrap();
+    let btc_critical = store
+        .create(
+            NewWebhook {
+                coins: Some(vec!["BTC".to_string()]),
+                min_severity: Some(AlertSeverity::Critical),
+                ..new_webhook("https://btc.example")
+            },
+            NOW,
+        )
+        .unwrap();
+
+    let ids = |coin, severity| -> Vec<u64> {
+        store
+            .matching(coin, severity)
+            .into_iter()
+            .map(|s| s.id)
+            .collect()
+    };
+    assert_eq!(
+        ids("BTC", AlertSeverity::Critical),
+        vec![all.id, btc_critical.id]
+    );
+    assert_eq!(ids("BTC", AlertSeverity::Warning), vec![all.id]);
+    assert_eq!(ids("ETH", AlertSeverity::Critical), vec![all.id]);
+}
+
+#[test]
+fn consecutive_failures_disable_subscription_and_success_resets() {
+    let store = WebhookStore::in_memory().with_max_consecutive_failures(3);
+    let id = store
+        .create(new_webhook("https://x.example"), NOW)
+        .unwrap()
+        .id;
+
+    store
+        .record_delivery(id, Err("timeout".to_string()))
+        .unwrap();
+    store
+        .record_delivery(id, Err("timeout".to_string()))
+        .unwrap();
+    store.record_delivery(id, Ok(())).unwrap();
+    let subscription = store.get(id).unwrap();
+    assert_eq!(subscription.consecutive_failures, 0);
+    assert!(!subscription.disabled);
+
+    for _ in 0..3 {
+        store.record_delivery(id, Err("500".to_string())).unwrap();
+    }
+    let subscription = store.get(id).unwrap();
+    assert!(subscription.disabled);
+    assert_eq!(subscription.consecutive_failures, 3);
+    assert!(store.matching("BTC", AlertSeverity::Critical).is_empty());
+}
+
+#[test]
+fn signature_is_hex_hmac_sha256() {
+    // RFC 4231 test case 2.
+    assert_eq!(
+        sign_payload("Jefe", b"what do ya want for nothing?"),
+        "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+    );
+}
+
+type Received = Arc<Mutex<Vec<(HeaderMap, Bytes)>>>;
+
+/// Local receiver answering every POST with `status`; returns its URL.
+async fn spawn_receiver(status: StatusCode, received: Received) -> String {
+    let app = Router::new().route(
+        "/hook",
+        post(move |headers: HeaderMap, body: Bytes| {
+            let received = received.clone();
+            async move {
+                received.lock().unwrap().push((headers, body));
+                status
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}/hook")
+}
+
+#[tokio::test]
+async fn dispatcher_signs_matching_deliveries() {
+    let received = Received::default();
+    let url = spawn_receiver(StatusCode::OK, received.clone()).await;
+
+    let store = Arc::new(WebhookStore::in_memory());
+    store
+        .create(
+            NewWebhook {
+                secret: Some("s3cret".to_string()),
+                coins: Some(vec!["BTC".to_string()]),
+                ..new_webhook(&url)
+            },
+            NOW,
+        )
+        .unwrap();
+    let dispatcher = WebhookDispatcher::new(store);
+
+    let payload = json!({ "coin": "BTC", "message": "Double top CONFIRMED" });
+    assert_eq!(
+        dispatcher
+            .dispatch("BTC", AlertSeverity::Critical, &payload)
+            .await,
+        1
+    );
+    assert_eq!(
+        dispatcher
+            .dispatch("ETH", AlertSeverity::Critical, &payload)
+            .await,
+        0
+    );
+
+    let received = received.lock().unwrap();
+    assert_eq!(received.len(), 1);
+    let (headers, body) = &received[0];
+    assert_eq!(serde_json::from_slice::<Value>(body).unwrap(), payload);
+    assert_eq!(
+        headers[SIGNATURE_HEADER].to_str().unwrap(),
+        sign_payload("s3cret", body)
+    );
+}
+
+#[tokio::test]
+async fn dispatcher_disables_failing_receiver() {
+    let url = spawn_receiver(StatusCode::INTERNAL_SERVER_ERROR, Received::default()).await;
+    let store = Arc::new(WebhookStore::in_memory().with_max_consecutive_failures(2));
+    let id = store.create(new_webhook(&url), NOW).unwrap().id;
+    let dispatcher = WebhookDispatcher::new(store.clone());
+
+    let payload = json!({ "coin": "BTC" });
+    for _ in 0..3 {
+        assert_eq!(
+            dispatcher
+                .dispatch("BTC", AlertSeverity::Warning, &payload)
+                .await,
+            0
+        );
+    }
+
+    let subscription = store.get(id).unwrap();
+    assert!(subscription.disabled);
+    assert_eq!(subscription.consecutive_failures, 2);
+    assert_eq!(
+        subscription.last_error.as_deref(),
+        Some("receiver returned 500 Internal Server Error")
+    );
+}